@@ -155,6 +155,14 @@ pub trait AgentHandler: Send + Sync + Clone + 'static {
     async fn get_available_tools(&self) -> Result<Vec<gola_ag_ui_types::Tool>> {
         Ok(vec![])
     }
+
+    /// Get the models this agent's LLM backend can serve.
+    ///
+    /// The default implementation returns an empty list.
+    /// Override this method when the backend can enumerate its models.
+    async fn get_available_models(&self) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
 }
 
 /// Metadata about an agent.
@@ -336,6 +336,31 @@ async fn authorization_cancel_handler<T: AgentHandler + Clone>(
     }
 }
 
+/// Handler for the /models GET endpoint.
+async fn models_handler<T: AgentHandler + Clone>(
+    State(app_state): State<AppState<T>>,
+) -> std::result::Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    log::info!("Received models request");
+
+    match app_state.agent.get_available_models().await {
+        Ok(models) => {
+            log::info!("Successfully retrieved {} models", models.len());
+            Ok(Json(json!(models)))
+        }
+        Err(e) => {
+            log::error!("Failed to get available models: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "error": "Failed to retrieve available models",
+                    "details": e.to_string(),
+                    "timestamp": chrono::Utc::now()
+                })),
+            ))
+        }
+    }
+}
+
 /// Handler for the /tools GET endpoint.
 async fn tools_handler<T: AgentHandler + Clone>(
     State(app_state): State<AppState<T>>,
@@ -515,6 +540,7 @@ impl<T: AgentHandler + Clone + Send + Sync + 'static> AgUiServer<T> {
                 })
             }))
             .route("/tools", get(tools_handler::<T>))
+            .route("/models", get(models_handler::<T>))
             // Memory management endpoints
             .route("/memory/stats", get(memory_stats_handler::<T>))
             .route("/memory/clear", delete(memory_clear_handler::<T>))
@@ -545,6 +571,7 @@ impl<T: AgentHandler + Clone + Send + Sync + 'static> AgUiServer<T> {
             .route("/agents/stream", options(|| async { StatusCode::OK }))
             .route("/batch", options(|| async { StatusCode::OK }))
             .route("/tools", options(|| async { StatusCode::OK }))
+            .route("/models", options(|| async { StatusCode::OK }))
             .route("/memory/stats", options(|| async { StatusCode::OK }))
             .route("/memory/clear", options(|| async { StatusCode::OK }))
             .route("/agents/clear-memory", options(|| async { StatusCode::OK }))
@@ -72,6 +72,11 @@ impl SseEvent {
             Event::ToolCallArgs(_) => "TOOL_CALL_ARGS",
             Event::ToolCallEnd(_) => "TOOL_CALL_END",
             Event::ToolCallChunk(_) => "TOOL_CALL_CHUNK",
+            Event::ThinkingStart(_) => "THINKING_START",
+            Event::ThinkingEnd(_) => "THINKING_END",
+            Event::ThinkingTextMessageStart(_) => "THINKING_TEXT_MESSAGE_START",
+            Event::ThinkingTextMessageContent(_) => "THINKING_TEXT_MESSAGE_CONTENT",
+            Event::ThinkingTextMessageEnd(_) => "THINKING_TEXT_MESSAGE_END",
             Event::ToolAuthorizationRequest(_) => "TOOL_AUTHORIZATION_REQUEST",
             Event::ToolAuthorizationResponse(_) => "TOOL_AUTHORIZATION_RESPONSE",
             Event::AuthorizationStatus(_) => "AUTHORIZATION_STATUS",
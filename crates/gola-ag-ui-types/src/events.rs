@@ -26,6 +26,16 @@ pub enum EventType {
     ToolCallEnd,
     /// Chunk of a tool call.
     ToolCallChunk,
+    /// Start of a block of model reasoning.
+    ThinkingStart,
+    /// End of a block of model reasoning.
+    ThinkingEnd,
+    /// Start of a reasoning text message.
+    ThinkingTextMessageStart,
+    /// Content of a reasoning text message.
+    ThinkingTextMessageContent,
+    /// End of a reasoning text message.
+    ThinkingTextMessageEnd,
     /// Request for tool authorization.
     ToolAuthorizationRequest,
     /// Response to tool authorization request.
@@ -355,6 +365,66 @@ pub struct StepFinishedEvent {
     pub step_name: String,
 }
 
+/// Event indicating the start of a block of model reasoning.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThinkingStartEvent {
+    /// Timestamp when the event occurred (optional).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    /// Raw event data from the underlying system (optional).
+    #[serde(rename = "rawEvent", skip_serializing_if = "Option::is_none")]
+    pub raw_event: Option<serde_json::Value>,
+    /// A short title for the reasoning block (optional).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// Event indicating the end of a block of model reasoning.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThinkingEndEvent {
+    /// Timestamp when the event occurred (optional).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    /// Raw event data from the underlying system (optional).
+    #[serde(rename = "rawEvent", skip_serializing_if = "Option::is_none")]
+    pub raw_event: Option<serde_json::Value>,
+}
+
+/// Event indicating the start of a reasoning text message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThinkingTextMessageStartEvent {
+    /// Timestamp when the event occurred (optional).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    /// Raw event data from the underlying system (optional).
+    #[serde(rename = "rawEvent", skip_serializing_if = "Option::is_none")]
+    pub raw_event: Option<serde_json::Value>,
+}
+
+/// Event containing a piece of reasoning text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThinkingTextMessageContentEvent {
+    /// Timestamp when the event occurred (optional).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    /// Raw event data from the underlying system (optional).
+    #[serde(rename = "rawEvent", skip_serializing_if = "Option::is_none")]
+    pub raw_event: Option<serde_json::Value>,
+    /// The reasoning delta (must not be empty).
+    pub delta: String,
+}
+
+/// Event indicating the end of a reasoning text message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThinkingTextMessageEndEvent {
+    /// Timestamp when the event occurred (optional).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    /// Raw event data from the underlying system (optional).
+    #[serde(rename = "rawEvent", skip_serializing_if = "Option::is_none")]
+    pub raw_event: Option<serde_json::Value>,
+}
+
 /// An event in the ag-ui protocol.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
@@ -375,6 +445,16 @@ pub enum Event {
     ToolCallEnd(ToolCallEndEvent),
     /// Chunk of a tool call.
     ToolCallChunk(ToolCallChunkEvent),
+    /// Start of a block of model reasoning.
+    ThinkingStart(ThinkingStartEvent),
+    /// End of a block of model reasoning.
+    ThinkingEnd(ThinkingEndEvent),
+    /// Start of a reasoning text message.
+    ThinkingTextMessageStart(ThinkingTextMessageStartEvent),
+    /// Content of a reasoning text message.
+    ThinkingTextMessageContent(ThinkingTextMessageContentEvent),
+    /// End of a reasoning text message.
+    ThinkingTextMessageEnd(ThinkingTextMessageEndEvent),
     /// Request for tool authorization.
     ToolAuthorizationRequest(ToolAuthorizationRequestEvent),
     /// Response to tool authorization request.
@@ -415,6 +495,11 @@ impl Event {
             Event::ToolCallArgs(_) => EventType::ToolCallArgs,
             Event::ToolCallEnd(_) => EventType::ToolCallEnd,
             Event::ToolCallChunk(_) => EventType::ToolCallChunk,
+            Event::ThinkingStart(_) => EventType::ThinkingStart,
+            Event::ThinkingEnd(_) => EventType::ThinkingEnd,
+            Event::ThinkingTextMessageStart(_) => EventType::ThinkingTextMessageStart,
+            Event::ThinkingTextMessageContent(_) => EventType::ThinkingTextMessageContent,
+            Event::ThinkingTextMessageEnd(_) => EventType::ThinkingTextMessageEnd,
             Event::ToolAuthorizationRequest(_) => EventType::ToolAuthorizationRequest,
             Event::ToolAuthorizationResponse(_) => EventType::ToolAuthorizationResponse,
             Event::AuthorizationStatus(_) => EventType::AuthorizationStatus,
@@ -442,6 +527,11 @@ impl Event {
             Event::ToolCallArgs(e) => e.timestamp,
             Event::ToolCallEnd(e) => e.timestamp,
             Event::ToolCallChunk(e) => e.timestamp,
+            Event::ThinkingStart(e) => e.timestamp,
+            Event::ThinkingEnd(e) => e.timestamp,
+            Event::ThinkingTextMessageStart(e) => e.timestamp,
+            Event::ThinkingTextMessageContent(e) => e.timestamp,
+            Event::ThinkingTextMessageEnd(e) => e.timestamp,
             Event::ToolAuthorizationRequest(e) => e.timestamp,
             Event::ToolAuthorizationResponse(e) => e.timestamp,
             Event::AuthorizationStatus(e) => e.timestamp,
@@ -495,6 +585,21 @@ impl Event {
             "TOOL_CALL_CHUNK" => serde_json::from_str::<ToolCallChunkEvent>(data)
                 .map(Event::ToolCallChunk)
                 .map_err(|e| crate::error::AgUiError::serialization(format!("Failed to parse ToolCallChunkEvent: {}", e))),
+            "THINKING_START" => serde_json::from_str::<ThinkingStartEvent>(data)
+                .map(Event::ThinkingStart)
+                .map_err(|e| crate::error::AgUiError::serialization(format!("Failed to parse ThinkingStartEvent: {}", e))),
+            "THINKING_END" => serde_json::from_str::<ThinkingEndEvent>(data)
+                .map(Event::ThinkingEnd)
+                .map_err(|e| crate::error::AgUiError::serialization(format!("Failed to parse ThinkingEndEvent: {}", e))),
+            "THINKING_TEXT_MESSAGE_START" => serde_json::from_str::<ThinkingTextMessageStartEvent>(data)
+                .map(Event::ThinkingTextMessageStart)
+                .map_err(|e| crate::error::AgUiError::serialization(format!("Failed to parse ThinkingTextMessageStartEvent: {}", e))),
+            "THINKING_TEXT_MESSAGE_CONTENT" => serde_json::from_str::<ThinkingTextMessageContentEvent>(data)
+                .map(Event::ThinkingTextMessageContent)
+                .map_err(|e| crate::error::AgUiError::serialization(format!("Failed to parse ThinkingTextMessageContentEvent: {}", e))),
+            "THINKING_TEXT_MESSAGE_END" => serde_json::from_str::<ThinkingTextMessageEndEvent>(data)
+                .map(Event::ThinkingTextMessageEnd)
+                .map_err(|e| crate::error::AgUiError::serialization(format!("Failed to parse ThinkingTextMessageEndEvent: {}", e))),
             "TOOL_AUTHORIZATION_REQUEST" => serde_json::from_str::<ToolAuthorizationRequestEvent>(data)
                 .map(Event::ToolAuthorizationRequest)
                 .map_err(|e| crate::error::AgUiError::serialization(format!("Failed to parse ToolAuthorizationRequestEvent: {}", e))),
@@ -618,6 +723,91 @@ impl ToolCallEndEvent {
     }
 }
 
+impl ThinkingStartEvent {
+    /// Create a new thinking start event.
+    pub fn new() -> Self {
+        Self {
+            timestamp: None,
+            raw_event: None,
+            title: None,
+        }
+    }
+
+    /// Create a new thinking start event with a title.
+    pub fn with_title(title: String) -> Self {
+        Self {
+            timestamp: None,
+            raw_event: None,
+            title: Some(title),
+        }
+    }
+}
+
+impl Default for ThinkingStartEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThinkingEndEvent {
+    /// Create a new thinking end event.
+    pub fn new() -> Self {
+        Self {
+            timestamp: None,
+            raw_event: None,
+        }
+    }
+}
+
+impl Default for ThinkingEndEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThinkingTextMessageStartEvent {
+    /// Create a new thinking text message start event.
+    pub fn new() -> Self {
+        Self {
+            timestamp: None,
+            raw_event: None,
+        }
+    }
+}
+
+impl Default for ThinkingTextMessageStartEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThinkingTextMessageContentEvent {
+    /// Create a new thinking text message content event.
+    pub fn new(delta: String) -> Self {
+        Self {
+            timestamp: None,
+            raw_event: None,
+            delta,
+        }
+    }
+}
+
+impl ThinkingTextMessageEndEvent {
+    /// Create a new thinking text message end event.
+    pub fn new() -> Self {
+        Self {
+            timestamp: None,
+            raw_event: None,
+        }
+    }
+}
+
+impl Default for ThinkingTextMessageEndEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl StateSnapshotEvent {
     /// Create a new state snapshot event.
     pub fn new(snapshot: State) -> Self {
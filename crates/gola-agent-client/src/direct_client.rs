@@ -51,4 +51,11 @@ impl<T: AgentHandler> AgentClient for DirectAgentClient<T> {
             .map_err(|e| anyhow::anyhow!("Clear memory error: {}", e))?;
         Ok(())
     }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        self.agent_handler
+            .get_available_models()
+            .await
+            .map_err(|e| anyhow::anyhow!("List models error: {}", e))
+    }
 }
@@ -130,4 +130,20 @@ impl AgentClient for HttpAgentClient {
 
         Ok(())
     }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let models_url = format!("{}/models", self.base_url);
+        let response = self
+            .client
+            .get(&models_url)
+            .timeout(self.timeout)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            bail!("List models failed: {}", response.status());
+        }
+
+        Ok(response.json::<Vec<String>>().await?)
+    }
 }
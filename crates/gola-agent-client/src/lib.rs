@@ -32,6 +32,9 @@ pub trait AgentClient: Send + Sync {
 
     /// Clear the agent's memory
     async fn clear_memory(&self) -> Result<()>;
+
+    /// List the models the agent's LLM backend can serve
+    async fn list_models(&self) -> Result<Vec<String>>;
 }
 
 /// Factory for creating AgentClient instances
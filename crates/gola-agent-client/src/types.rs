@@ -56,6 +56,10 @@ pub enum StreamEvent {
     TextDelta(String),
     /// Tool call in progress
     ToolCall(String),
+    /// Reasoning content delta from the agent
+    ReasoningDelta(String),
+    /// End of a block of reasoning
+    ReasoningEnd,
     /// Agent run completed
     RunFinished,
     /// Agent run error
@@ -77,6 +81,10 @@ impl StreamEvent {
             GolaEvent::ToolCallStart(tool_event) => {
                 StreamEvent::ToolCall(tool_event.tool_call_name)
             }
+            GolaEvent::ThinkingTextMessageContent(thinking_event) => {
+                StreamEvent::ReasoningDelta(thinking_event.delta)
+            }
+            GolaEvent::ThinkingTextMessageEnd(_) => StreamEvent::ReasoningEnd,
             other => StreamEvent::Other(other),
         }
     }
@@ -63,6 +63,7 @@ impl TermAgentClient for DirectGolaTermClient {
         let mut stream = self.direct_client.stream_request(request).await?;
 
         let mut assistant_message = String::new();
+        let mut reasoning = String::new();
         let mut updated_messages = messages;
 
         // Process streaming events and convert to gola-term format
@@ -92,6 +93,15 @@ impl TermAgentClient for DirectGolaTermClient {
                     };
                     tx.send(Event::AgentPromptResponse(response))?;
                 }
+                StreamEvent::ReasoningDelta(text) => {
+                    reasoning.push_str(&text);
+                }
+                StreamEvent::ReasoningEnd => {
+                    if !reasoning.trim().is_empty() {
+                        tx.send(Event::AgentReasoning(reasoning.clone()))?;
+                    }
+                    reasoning.clear();
+                }
                 StreamEvent::RunFinished => {
                     // Add assistant response to message history
                     if !assistant_message.is_empty() {
@@ -128,4 +138,8 @@ impl TermAgentClient for DirectGolaTermClient {
     async fn clear_memory(&self) -> Result<()> {
         self.direct_client.clear_memory().await
     }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        self.direct_client.list_models().await
+    }
 }
@@ -3,7 +3,7 @@ use clap::{Parser, Subcommand};
 use gola_ag_ui_server::{
     shutdown_signal, AgUiServer, AgentHandler, ServerConfig, ToolAuthorizationMode,
 };
-use gola_core::{config::ConfigLoader, AgentFactory, GitHubConfigLoader};
+use gola_core::{cassette::CassetteConfig, config::ConfigLoader, AgentFactory, GitHubConfigLoader};
 use log::LevelFilter;
 use std::net::SocketAddr;
 
@@ -64,6 +64,23 @@ struct Cli {
 
     #[clap(long, help = "Execute a single task and output result to stdout")]
     task: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "CASSETTE",
+        help = "Record LLM and MCP interactions to a cassette file (task mode only)"
+    )]
+    record: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "CASSETTE",
+        help = "Replay LLM and MCP interactions from a cassette file instead of calling them (task mode only)"
+    )]
+    replay: Option<String>,
+
+    #[clap(long, help = "Simulate tool calls instead of executing them and write a report of what would have run")]
+    dry_run: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -106,12 +123,38 @@ enum Commands {
 
         #[clap(long, help = "Execute a single task and output result to stdout")]
         task: Option<String>,
+
+        #[clap(
+            long,
+            value_name = "CASSETTE",
+            help = "Record LLM and MCP interactions to a cassette file (task mode only)"
+        )]
+        record: Option<String>,
+
+        #[clap(
+            long,
+            value_name = "CASSETTE",
+            help = "Replay LLM and MCP interactions from a cassette file instead of calling them (task mode only)"
+        )]
+        replay: Option<String>,
+
+        #[clap(long, help = "Simulate tool calls instead of executing them and write a report of what would have run")]
+        dry_run: bool,
     },
     /// Manage GitHub repository cache
     Cache {
         #[clap(subcommand)]
         action: CacheCommands,
     },
+    /// Update the RAG index: embed new and changed documents and remove deleted ones
+    Reindex {
+        #[clap(
+            long,
+            short,
+            help = "Configuration source: file path, URL, or GitHub repository"
+        )]
+        config: Option<String>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -164,6 +207,8 @@ async fn main() -> Result<()> {
                 RunMode::Embedded
             }
         }
+        // Reindexing logs to the console like server-only mode
+        Some(Commands::Reindex { .. }) => RunMode::ServerOnly,
         _ => {
             // Check for conflicting flags first
             let task_specified = cli.task.is_some();
@@ -275,54 +320,98 @@ async fn main() -> Result<()> {
         Some(Commands::Run {
             config,
             bind_addr,
-            term_debug_panel,
+            term_debug_panel: _,
             embedded: _,
             server_only: _,
             terminal_only: _,
             server_url,
             task,
+            record,
+            replay,
+            dry_run,
         }) => {
             let config = config.unwrap_or(cli.config);
             let bind_addr = bind_addr.unwrap_or(cli.bind_addr);
             let server_url = server_url.unwrap_or(cli.server_url.clone());
             let task_prompt = task.or(cli.task.clone());
+            let cassette = resolve_cassette(record.or(cli.record), replay.or(cli.replay), &mode)?;
 
-            run_gola(
+            run_gola(RunOptions {
                 config,
                 bind_addr,
                 mode,
-                term_debug_panel || cli.term_debug_panel,
                 server_url,
                 task_prompt,
-            )
+                cassette,
+                dry_run: dry_run || cli.dry_run,
+            })
             .await
         }
         Some(Commands::Cache { action }) => handle_cache_command(action).await,
+        Some(Commands::Reindex { config }) => handle_reindex_command(config.unwrap_or(cli.config)).await,
         None => {
             // This case uses the mode already determined above
             let mode = mode;
+            let cassette = resolve_cassette(cli.record, cli.replay, &mode)?;
 
-            run_gola(
-                cli.config,
-                cli.bind_addr,
+            run_gola(RunOptions {
+                config: cli.config,
+                bind_addr: cli.bind_addr,
                 mode,
-                cli.term_debug_panel,
-                cli.server_url,
-                cli.task,
-            )
+                server_url: cli.server_url,
+                task_prompt: cli.task,
+                cassette,
+                dry_run: cli.dry_run,
+            })
             .await
         }
     }
 }
 
-async fn run_gola(
+/// Validate the --record/--replay flags and turn them into a cassette configuration
+fn resolve_cassette(
+    record: Option<String>,
+    replay: Option<String>,
+    mode: &RunMode,
+) -> Result<Option<CassetteConfig>> {
+    let cassette = match (record, replay) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!("--record and --replay cannot be used together.");
+        }
+        (Some(path), None) => Some(CassetteConfig::record(path)),
+        (None, Some(path)) => Some(CassetteConfig::replay(path)),
+        (None, None) => None,
+    };
+
+    if cassette.is_some() && !matches!(mode, RunMode::Task) {
+        anyhow::bail!("--record and --replay are only supported together with --task.");
+    }
+
+    Ok(cassette)
+}
+
+/// Command line settings for a run, merged from the top-level and `run` flags
+struct RunOptions {
     config: String,
     bind_addr: String,
     mode: RunMode,
-    _term_debug_panel: bool,
     server_url: String,
     task_prompt: Option<String>,
-) -> Result<()> {
+    cassette: Option<CassetteConfig>,
+    dry_run: bool,
+}
+
+async fn run_gola(options: RunOptions) -> Result<()> {
+    let RunOptions {
+        config,
+        bind_addr,
+        mode,
+        server_url,
+        task_prompt,
+        cassette,
+        dry_run,
+    } = options;
+
     // Terminal-only mode doesn't need configuration or agent setup
     if matches!(mode, RunMode::TerminalOnly) {
         log::info!("Terminal-only mode: skipping configuration loading");
@@ -332,7 +421,7 @@ async fn run_gola(
     // Task mode: execute single task and output result
     if matches!(mode, RunMode::Task) {
         if let Some(prompt) = task_prompt {
-            return run_task_mode(&config, prompt).await;
+            return run_task_mode(&config, prompt, cassette, dry_run).await;
         } else {
             anyhow::bail!("Task mode requires a task prompt. Use --task \"your task here\"");
         }
@@ -341,7 +430,7 @@ async fn run_gola(
     log::info!("Loading configuration from: {}", config);
 
     // Load configuration from various sources
-    let gola_config = if config.starts_with("github:") {
+    let mut gola_config = if config.starts_with("github:") {
         log::info!("Loading configuration from GitHub repository: {}", config);
         ConfigLoader::from_source(&config).await?
     } else if config.starts_with("http://") || config.starts_with("https://") {
@@ -355,6 +444,7 @@ async fn run_gola(
         "Configuration loaded successfully for agent: {}",
         gola_config.agent.name
     );
+    gola_config.dry_run.enabled |= dry_run;
 
     // Add dot after configuration loading in embedded mode
     if matches!(mode, RunMode::Embedded) {
@@ -368,6 +458,7 @@ async fn run_gola(
         gola_config,
         local_runtimes: true,
         non_interactive: matches!(mode, RunMode::ServerOnly),
+        cassette: None,
     };
     let agent_handler = AgentFactory::create_from_config(factory_config).await?;
     log::info!("GolaAgentHandler created.");
@@ -492,6 +583,18 @@ async fn handle_cache_command(action: CacheCommands) -> Result<()> {
     Ok(())
 }
 
+async fn handle_reindex_command(config: String) -> Result<()> {
+    let gola_config = ConfigLoader::from_source(&config).await?;
+    let report = AgentFactory::reindex_from_config(&gola_config).await?;
+
+    println!("Reindexed documents for agent {}: {}", gola_config.agent.name, report);
+    for (source, error) in &report.failed {
+        println!("  failed: {} ({})", source, error);
+    }
+
+    Ok(())
+}
+
 /// Run terminal-only UI connected to a remote gola server
 async fn run_terminal_only_ui(server_url: &str) -> Result<()> {
     
@@ -577,7 +680,12 @@ async fn run_terminal_only_ui(server_url: &str) -> Result<()> {
 }
 
 /// Run task mode - execute a single task and output result to stdout
-async fn run_task_mode(config: &str, task_prompt: String) -> Result<()> {
+async fn run_task_mode(
+    config: &str,
+    task_prompt: String,
+    cassette: Option<CassetteConfig>,
+    dry_run: bool,
+) -> Result<()> {
     use futures_util::StreamExt;
     use gola_ag_ui_types::{Message, RunAgentInput};
     use uuid::Uuid;
@@ -585,7 +693,7 @@ async fn run_task_mode(config: &str, task_prompt: String) -> Result<()> {
     log::info!("Loading configuration from: {}", config);
 
     // Load configuration from various sources
-    let gola_config = if config.starts_with("github:") {
+    let mut gola_config = if config.starts_with("github:") {
         log::info!("Loading configuration from GitHub repository: {}", config);
         ConfigLoader::from_source(config).await?
     } else if config.starts_with("http://") || config.starts_with("https://") {
@@ -599,12 +707,14 @@ async fn run_task_mode(config: &str, task_prompt: String) -> Result<()> {
         "Configuration loaded successfully for agent: {}",
         gola_config.agent.name
     );
+    gola_config.dry_run.enabled |= dry_run;
 
     // Create agent handler with headless settings
     let factory_config = gola_core::agent_factory::AgentFactoryConfig {
         gola_config,
         local_runtimes: true,
         non_interactive: true, // Task mode is non-interactive
+        cassette,
     };
     let agent_handler = AgentFactory::create_from_config(factory_config).await?;
     log::info!("Agent handler created for task execution");
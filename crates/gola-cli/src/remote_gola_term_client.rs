@@ -111,6 +111,7 @@ impl TermAgentClient for RemoteGolaTermClient {
         let mut lines_reader = StreamReader::new(stream).lines();
 
         let mut assistant_message = String::new();
+        let mut reasoning = String::new();
         let mut updated_messages = messages;
 
         while let Ok(line) = lines_reader.next_line().await {
@@ -138,6 +139,19 @@ impl TermAgentClient for RemoteGolaTermClient {
                                     tx.send(Event::AgentPromptResponse(response))?;
                                 }
                             }
+                            "THINKING_TEXT_MESSAGE_CONTENT" => {
+                                if let Some(delta) =
+                                    event_data.get("delta").and_then(|d| d.as_str())
+                                {
+                                    reasoning.push_str(delta);
+                                }
+                            }
+                            "THINKING_TEXT_MESSAGE_END" => {
+                                if !reasoning.trim().is_empty() {
+                                    tx.send(Event::AgentReasoning(reasoning.clone()))?;
+                                }
+                                reasoning.clear();
+                            }
                             "RUN_FINISHED" => {
                                 // Add assistant response to message history
                                 if !assistant_message.is_empty() {
@@ -188,4 +202,15 @@ impl TermAgentClient for RemoteGolaTermClient {
             anyhow::bail!("Clear memory request failed: {}", response.status())
         }
     }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let models_url = format!("{}/models", self.server_url);
+        let response = self.http_client.get(&models_url).send().await?;
+
+        if response.status().is_success() {
+            Ok(response.json::<Vec<String>>().await?)
+        } else {
+            anyhow::bail!("List models request failed: {}", response.status())
+        }
+    }
 }
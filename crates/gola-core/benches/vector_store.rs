@@ -0,0 +1,113 @@
+//! Search latency and recall of the HNSW index against the brute-force store
+//!
+//! Run with `cargo bench -p gola-core --bench vector_store`. Recall@10 of the HNSW
+//! index is printed for each `ef_search` before its timings, and the run fails if it
+//! drops below [`MIN_RECALL`] from `ef_search` 64 up.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use gola_core::rag::{HnswConfig, HnswVectorStore, InMemoryVectorStore, VectorStore};
+use std::collections::HashSet;
+use tokio::runtime::Runtime;
+
+const CORPUS: usize = 20_000;
+const DIMENSION: usize = 128;
+const QUERIES: usize = 100;
+const TOP_K: usize = 10;
+const CLUSTERS: usize = 256;
+/// Lowest acceptable recall@10 at the default `ef_search` and above
+const MIN_RECALL: f32 = 0.9;
+
+/// Vectors scattered around a few hundred centres, which is closer to the shape
+/// of real embeddings than uniform noise
+fn clustered_vectors(count: usize, seed: u64) -> Vec<Vec<f32>> {
+    let mut state = seed;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+    };
+    let centres: Vec<Vec<f32>> = (0..CLUSTERS)
+        .map(|_| (0..DIMENSION).map(|_| next()).collect())
+        .collect();
+    (0..count)
+        .map(|i| {
+            let centre = &centres[(i * 7919) % CLUSTERS];
+            centre.iter().map(|x| x + next()).collect()
+        })
+        .collect()
+}
+
+async fn recall(hnsw: &HnswVectorStore, exact: &InMemoryVectorStore, queries: &[Vec<f32>]) -> f32 {
+    let mut found = 0;
+    for query in queries {
+        let expected: HashSet<usize> = exact
+            .search(query, TOP_K)
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.document_id)
+            .collect();
+        found += hnsw
+            .search(query, TOP_K)
+            .await
+            .unwrap()
+            .iter()
+            .filter(|r| expected.contains(&r.document_id))
+            .count();
+    }
+    found as f32 / (queries.len() * TOP_K) as f32
+}
+
+fn search(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    // Queries come from the same clusters as the corpus but are not in it
+    let mut vectors = clustered_vectors(CORPUS + QUERIES, 1);
+    let queries = vectors.split_off(CORPUS);
+    let documents: Vec<(usize, Vec<f32>)> = vectors.into_iter().enumerate().collect();
+
+    let (exact, mut hnsw) = runtime.block_on(async {
+        let mut exact = InMemoryVectorStore::with_capacity(CORPUS);
+        exact.add_documents(documents.clone()).await.unwrap();
+        let mut hnsw = HnswVectorStore::new(HnswConfig::default());
+        hnsw.add_documents(documents).await.unwrap();
+        (exact, hnsw)
+    });
+
+    let mut group = c.benchmark_group("search_top10");
+    group.bench_function("brute_force", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            i = (i + 1) % QUERIES;
+            runtime.block_on(exact.search(black_box(&queries[i]), TOP_K)).unwrap()
+        })
+    });
+
+    for ef_search in [16, 64, 256] {
+        hnsw.set_ef_search(ef_search);
+        let recall = runtime.block_on(recall(&hnsw, &exact, &queries));
+        println!("hnsw ef_search={}: recall@{} = {:.3}", ef_search, TOP_K, recall);
+        if ef_search >= 64 {
+            assert!(
+                recall >= MIN_RECALL,
+                "recall@{} at ef_search={} was {:.3}, below {}",
+                TOP_K,
+                ef_search,
+                recall,
+                MIN_RECALL
+            );
+        }
+
+        group.bench_with_input(BenchmarkId::new("hnsw", ef_search), &ef_search, |b, _| {
+            let mut i = 0;
+            b.iter(|| {
+                i = (i + 1) % QUERIES;
+                runtime.block_on(hnsw.search(black_box(&queries[i]), TOP_K)).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, search);
+criterion_main!(benches);
@@ -11,6 +11,8 @@ use gola_ag_ui_server::error::ServerError;
 use gola_ag_ui_types::{
     Event, Role, RunAgentInput, RunErrorEvent, RunFinishedEvent,
     RunStartedEvent, TextMessageContentEvent, TextMessageEndEvent, TextMessageStartEvent,
+    ThinkingEndEvent, ThinkingStartEvent, ThinkingTextMessageContentEvent,
+    ThinkingTextMessageEndEvent, ThinkingTextMessageStartEvent,
     AuthorizationConfig, ToolAuthorizationResponseEvent, PendingAuthorization,
 };
 use async_trait::async_trait;
@@ -143,17 +145,21 @@ impl AgentHandler for GolaAgentHandler {
 
         // Clone the agent Arc to move into the stream
         let agent_clone = self.agent.clone();
+        let show_reasoning = self.config.agent.behavior.show_reasoning;
 
         let stream = async_stream::stream! {
             yield Event::RunStarted(RunStartedEvent::new(thread_id.clone(), run_id.clone()));
 
             let mut agent_guard = agent_clone.lock().await;
             let mut error_occurred = false;
+            let mut last_response: Option<String> = None;
+            let mut run_error: Option<String> = None;
 
             // Add the user's message to memory before starting the loop
             if let Err(e) = agent_guard.add_user_task_to_memory(&task_to_run).await {
                 let error_message = format!("Failed to add task to memory: {}", e);
                 log::error!("{}", error_message);
+                run_error = Some(error_message.clone());
                 yield Event::RunError(RunErrorEvent::new(error_message));
                 error_occurred = true;
             }
@@ -162,6 +168,10 @@ impl AgentHandler for GolaAgentHandler {
                 for step_num in 0..agent_guard.config().max_steps {
                     match agent_guard.run_step(step_num).await {
                         Ok((Some(agent_response_content), step)) => {
+                            for event in reasoning_events(show_reasoning, step.reasoning.as_deref()) {
+                                yield event;
+                            }
+
                             // Send tool observations first if any
                             if let Some(tool_results) = &step.tool_results {
                                 for observation in tool_results {
@@ -223,6 +233,7 @@ impl AgentHandler for GolaAgentHandler {
                             );
                             
                             // Send the main response as a separate message
+                            last_response = Some(agent_response_content.clone());
                             let message_id = Uuid::new_v4().to_string();
                             yield Event::TextMessageStart(TextMessageStartEvent::new(message_id.clone()));
                             yield Event::TextMessageContent(TextMessageContentEvent::new(message_id.clone(), agent_response_content));
@@ -238,6 +249,10 @@ impl AgentHandler for GolaAgentHandler {
                             }
                         }
                         Ok((None, step)) => {
+                            for event in reasoning_events(show_reasoning, step.reasoning.as_deref()) {
+                                yield event;
+                            }
+
                             // Send tool observations if any
                             if let Some(tool_results) = &step.tool_results {
                                 for observation in tool_results {
@@ -289,40 +304,17 @@ impl AgentHandler for GolaAgentHandler {
                             }
                         }
                         Err(GolaAgentError::LoopDetection(loop_msg)) => {
-                            log::warn!("Loop detected, attempting automated recovery: {}", loop_msg);
-                            
-                            // Reset the loop detector to prevent immediate re-detection
-                            agent_guard.clear_loop_detector();
-                            
-                            // Add recovery context to agent memory to guide different approach
-                            let recovery_context = format!(
-                                "Loop detected: {}. Try a different approach - avoid repeating the same tool call with identical parameters. \
-                                Consider alternative methods or skip problematic tools if possible.",
-                                loop_msg
-                            );
-                            
-                            // Add the recovery message directly to agent memory using core types
-                            let recovery_message = crate::core_types::Message {
-                                role: crate::core_types::Role::System,
-                                content: recovery_context,
-                                tool_call_id: None,
-                                tool_calls: None,
-                            };
-                            
-                            if let Err(e) = agent_guard.add_recovery_message(recovery_message).await {
-                                log::error!("Failed to add recovery context to memory: {}", e);
-                                // Continue anyway - the loop detector reset should still help
-                            }
-                            
-                            log::info!("Loop detector reset and recovery context added, continuing execution");
-                            
-                            // Continue the execution loop instead of breaking
-                            // This allows the agent to try a different approach
-                            continue;
+                            // Only raised when loop recovery is set to abort
+                            log::error!("Loop detected, stopping the run: {}", loop_msg);
+                            run_error = Some(loop_msg.clone());
+                            yield Event::RunError(RunErrorEvent::new(loop_msg));
+                            error_occurred = true;
+                            break;
                         }
                         Err(gola_err) => {
                             let error_message = format!("Agent execution failed: {}", gola_err);
                             log::error!("{}", error_message);
+                            run_error = Some(error_message.clone());
                             yield Event::RunError(RunErrorEvent::new(error_message));
                             error_occurred = true;
                             // Error occurred, break the loop.
@@ -332,6 +324,9 @@ impl AgentHandler for GolaAgentHandler {
                 }
             }
 
+            // Responses have already been streamed, so changes to the result are not sent
+            agent_guard.finish_run(last_response, run_error).await;
+
             if !error_occurred {
                 yield Event::RunFinished(RunFinishedEvent::new(thread_id.clone(), run_id.clone()));
             }
@@ -492,6 +487,32 @@ impl AgentHandler for GolaAgentHandler {
         Ok(metadata.available_tools)
     }
 
+    async fn get_available_models(&self) -> Result<Vec<String>, ServerError> {
+        match &self.config.llm {
+            Some(llm) if llm.provider == crate::config::LlmProvider::Ollama => {
+                crate::llm::providers::ollama::list_models(&crate::llm::providers::ollama::base_url_for(llm))
+                    .await
+                    .map_err(|e| ServerError::internal(e.to_string()))
+            }
+            Some(llm) => Ok(vec![llm.model.clone()]),
+            None => Ok(vec![]),
+        }
+    }
+
+}
+
+/// Builds the thinking events for a step's reasoning, if it should be shown
+fn reasoning_events(show_reasoning: bool, reasoning: Option<&str>) -> Vec<Event> {
+    match reasoning {
+        Some(text) if show_reasoning && !text.trim().is_empty() => vec![
+            Event::ThinkingStart(ThinkingStartEvent::new()),
+            Event::ThinkingTextMessageStart(ThinkingTextMessageStartEvent::new()),
+            Event::ThinkingTextMessageContent(ThinkingTextMessageContentEvent::new(text.to_string())),
+            Event::ThinkingTextMessageEnd(ThinkingTextMessageEndEvent::new()),
+            Event::ThinkingEnd(ThinkingEndEvent::new()),
+        ],
+        _ => Vec::new(),
+    }
 }
 
 #[cfg(test)]
@@ -584,6 +605,7 @@ mod tests {
                 tool_calls: None,
                 finish_reason: None,
                 usage: None,
+                reasoning: None,
             })
         })
         .await;
@@ -615,6 +637,59 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_handle_input_streams_reasoning_when_enabled() {
+        let mock_llm = Arc::new(MockLLM::new(|| {
+            Ok(CoreLLMResponse {
+                content: Some("Final Answer: 4".to_string()),
+                tool_calls: None,
+                finish_reason: None,
+                usage: None,
+                reasoning: Some(vec![crate::core_types::ReasoningBlock::text("2 + 2 is 4")]),
+            })
+        }));
+        let agent_instance = crate::agent::Agent::new(
+            mock_llm,
+            Default::default(),
+            None,
+            crate::agent::AgentConfig::default(),
+        );
+        let mut gola_config = create_test_gola_config_for_handler();
+        gola_config.agent.behavior.show_reasoning = true;
+        let handler =
+            GolaAgentHandler::new(Arc::new(Mutex::new(agent_instance)), Arc::new(gola_config));
+
+        let run_input = RunAgentInput::new(
+            "thread-r".to_string(),
+            "run-r".to_string(),
+            serde_json::json!({}),
+            vec![Message::new_user("msg-r".to_string(), "What is 2 + 2?".to_string())],
+            vec![],
+            vec![],
+            serde_json::json!({}),
+        );
+
+        let stream = handler.handle_input(run_input).await.unwrap();
+        let events: Vec<Event> = stream.collect().await;
+
+        assert_eq!(events.len(), 10);
+        assert!(matches!(events[1], Event::ThinkingStart(_)));
+        assert!(matches!(events[2], Event::ThinkingTextMessageStart(_)));
+        assert!(
+            matches!(events[3], Event::ThinkingTextMessageContent(ref c) if c.delta == "2 + 2 is 4")
+        );
+        assert!(matches!(events[4], Event::ThinkingTextMessageEnd(_)));
+        assert!(matches!(events[5], Event::ThinkingEnd(_)));
+        assert!(matches!(events[6], Event::TextMessageStart(_)));
+    }
+
+    #[test]
+    fn test_reasoning_events_hidden_by_default() {
+        assert!(reasoning_events(false, Some("private thoughts")).is_empty());
+        assert!(reasoning_events(true, None).is_empty());
+        assert_eq!(reasoning_events(true, Some("thoughts")).len(), 5);
+    }
+
     #[tokio::test]
     async fn test_authorization_config() {
         let handler = create_handler_with_mock_llm_behavior(|| {
@@ -623,6 +698,7 @@ mod tests {
                 tool_calls: None,
                     finish_reason: None,
                     usage: None,
+                    reasoning: None,
             })
         })
         .await;
@@ -647,6 +723,7 @@ mod tests {
                 tool_calls: None,
                     finish_reason: None,
                     usage: None,
+                    reasoning: None,
             })
         })
         .await;
@@ -663,6 +740,7 @@ mod tests {
                 tool_calls: None,
                     finish_reason: None,
                     usage: None,
+                    reasoning: None,
             })
         })
         .await;
@@ -719,6 +797,7 @@ mod tests {
                     tool_calls: None,
                     finish_reason: None,
                     usage: None,
+                    reasoning: None,
                 })
             })),
             Default::default(),
@@ -750,6 +829,7 @@ mod tests {
                     tool_calls: None,
                     finish_reason: None,
                     usage: None,
+                    reasoning: None,
                 })
             })),
             Default::default(),
@@ -785,6 +865,7 @@ mod tests {
                     tool_calls: None,
                     finish_reason: None,
                     usage: None,
+                    reasoning: None,
                 })
             })),
             Default::default(),
@@ -832,6 +913,7 @@ mod tests {
                     tool_calls: None,
                     finish_reason: None,
                     usage: None,
+                    reasoning: None,
                 })
             })),
             Default::default(),
@@ -869,6 +951,7 @@ mod tests {
                 tool_calls: None,
                     finish_reason: None,
                     usage: None,
+                    reasoning: None,
             })
             })),
             Default::default(),
@@ -911,6 +994,7 @@ mod tests {
                 tool_calls: None,
                     finish_reason: None,
                     usage: None,
+                    reasoning: None,
             })
             })),
             Default::default(),
@@ -10,7 +10,9 @@ use std::sync::Arc;
 
 use serde_json::Value;
 
-use crate::config::types::{MemoryConfig, MemoryEvictionStrategy};
+use crate::config::types::{ContextBudgetConfig, MemoryConfig, MemoryEvictionStrategy};
+use crate::context_builder::{ContextBuilder, ContextInputs};
+use crate::hooks::{AgentHook, HookChain, HookEvent};
 use crate::core_types::{HistoryStep, Message, Observation, Role};
 use crate::errors::AgentError;
 use crate::executors::CodeExecutor;
@@ -22,10 +24,12 @@ use crate::llm::{ToolMetadata, LLM};
 use crate::memory::{
     AgentMemory, ConversationMemory, ConversationSummaryBufferMemory, MemoryStats, ConversationSummaryMemory
 };
-use crate::rag::{Rag, RagConfig, RetrievedContext};
+use crate::rag::{MetadataFilter, Rag, RagConfig, RetrievedContext};
+use crate::review::{AnswerReviewer, ReviewRecord};
 use crate::memory::SlidingWindowMemory;
 use crate::tools::{Tool, ControlPlaneServer};
-use crate::loop_detection::{PatternDetector, LoopDetectionConfig, LoopPattern};
+use crate::tools::dry_run::ToolSimulator;
+use crate::loop_detection::{PatternDetector, LoopDetectionConfig, LoopPattern, LoopRecoveryStrategy};
 use crate::trace::{AgentTraceHandler, AgentStep, AgentExecution};
 use async_trait::async_trait;
 
@@ -43,6 +47,9 @@ pub struct AgentConfig {
     pub system_prompt: Option<String>,
     pub memory_config: Option<MemoryConfig>,
     pub authorization_mode: AuthorizationMode,
+    /// Assemble requests within this budget; without one the full history is sent
+    pub context_budget: Option<ContextBudgetConfig>,
+    pub loop_detection: LoopDetectionConfig,
 }
 
 impl Default for AgentConfig {
@@ -54,6 +61,8 @@ impl Default for AgentConfig {
             system_prompt: None,
             memory_config: None,
             authorization_mode: AuthorizationMode::Allow,
+            context_budget: None,
+            loop_detection: LoopDetectionConfig::default(),
         }
     }
 }
@@ -66,6 +75,8 @@ pub struct Agent {
     history: AgentMemory,
     config: AgentConfig,
     rag_system: Option<Box<dyn Rag>>,
+    // Retrieved for the latest user task, sent as a budgeted section
+    rag_context: Option<RetrievedContext>,
     authorization_handler: Option<Arc<dyn AuthorizationHandler>>,
     all_tools_approved: bool,
     consecutive_tool_failures: HashMap<String, u32>,
@@ -75,6 +86,17 @@ pub struct Agent {
     trace_handles: Vec<tokio::task::JoinHandle<()>>,
     control_plane: ControlPlaneServer,
     loop_detector: PatternDetector,
+    hooks: HookChain,
+    // Tool call being executed, for the after_tool hook
+    active_tool_call: Option<crate::core_types::ToolCall>,
+    // Set in dry-run mode, answers tool calls instead of the tools
+    tool_simulator: Option<ToolSimulator>,
+    reviewer: Option<AnswerReviewer>,
+    // Task the current final answer is reviewed against
+    current_task: Option<String>,
+    review_rounds: usize,
+    // Set when a loop was detected and the user should decide how to go on
+    loop_question: Option<String>,
 }
 
 #[async_trait]
@@ -109,6 +131,7 @@ impl Agent {
             } else {
                 Box::new(SlidingWindowMemory::new(10))
             };
+        let loop_detector = PatternDetector::new(config.loop_detection.clone());
 
         Agent {
             llm,
@@ -118,6 +141,7 @@ impl Agent {
             history: AgentMemory::new(),
             config,
             rag_system: None,
+            rag_context: None,
             authorization_handler: None,
             all_tools_approved: false,
             consecutive_tool_failures: HashMap::new(),
@@ -126,7 +150,14 @@ impl Agent {
             trace_handler: None,
             trace_handles: Vec::new(),
             control_plane: ControlPlaneServer::new(),
-            loop_detector: PatternDetector::new(LoopDetectionConfig::default()),
+            loop_detector,
+            hooks: HookChain::new(),
+            active_tool_call: None,
+            tool_simulator: None,
+            reviewer: None,
+            current_task: None,
+            review_rounds: 0,
+            loop_question: None,
         }
     }
 
@@ -174,6 +205,26 @@ impl Agent {
         self.authorization_handler = Some(handler);
     }
 
+    /// Switches to dry-run mode, where `simulator` answers every tool call except
+    /// the control plane ones
+    pub fn set_tool_simulator(&mut self, simulator: ToolSimulator) {
+        self.tool_simulator = Some(simulator);
+    }
+
+    pub fn tool_simulator(&self) -> Option<&ToolSimulator> {
+        self.tool_simulator.as_ref()
+    }
+
+    /// Has `reviewer` check final answers before they are returned
+    pub fn set_reviewer(&mut self, reviewer: AnswerReviewer) {
+        self.reviewer = Some(reviewer);
+    }
+
+    /// Adds a hook that runs after the ones already registered
+    pub fn add_hook(&mut self, hook: Arc<dyn AgentHook>) {
+        self.hooks.push(hook);
+    }
+
     pub fn set_trace_handler(&mut self, handler: Box<dyn AgentTraceHandler>) {
         self.trace_handler = Some(handler);
     }
@@ -307,6 +358,7 @@ impl Agent {
     async fn retrieve_rag_context(
         &self,
         query: &str,
+        filter: &MetadataFilter,
     ) -> Result<Option<RetrievedContext>, AgentError> {
         if !self.is_rag_enabled() {
             return Ok(None);
@@ -314,7 +366,7 @@ impl Agent {
 
         if let Some(rag) = &self.rag_system {
             log::info!("Retrieving RAG context for query: {}", query);
-            let context = rag.retrieve(query, None).await?;
+            let context = rag.retrieve_filtered(query, None, filter).await?;
 
             if context.is_empty() {
                 log::info!("No relevant context found in RAG system");
@@ -329,7 +381,7 @@ impl Agent {
     }
 
     async fn format_task_with_rag_context(&self, task: &str) -> Result<String, AgentError> {
-        if let Some(context) = self.retrieve_rag_context(task).await? {
+        if let Some(context) = self.retrieve_rag_context(task, &MetadataFilter::default()).await? {
             let formatted_context = context.format_for_llm();
             Ok(format!(
                 "Task: {}\n\n{}\n\nPlease use the above context to help answer the task if relevant.",
@@ -352,6 +404,10 @@ impl Agent {
             match self.run_step(step_num).await {
                 Ok((Some(final_answer), step)) => {
                     steps.push(step);
+                    let final_answer = self
+                        .finish_run(Some(final_answer.clone()), None)
+                        .await
+                        .unwrap_or(final_answer);
                     if let Some(handler) = &mut self.trace_handler {
                         let execution = AgentExecution {
                             steps,
@@ -371,6 +427,7 @@ impl Agent {
                     
                     // Propagate LoopDetection error to UI handler for graceful recovery
                     // Don't attempt recovery here - let the UI handler manage it
+                    self.finish_run(None, Some(loop_msg.clone())).await;
                     if let Some(handler) = &mut self.trace_handler {
                         let execution = AgentExecution {
                             steps,
@@ -384,6 +441,7 @@ impl Agent {
                 Err(e) => {
                     let err_msg = format!("Agent step failed: {}", e);
                     log::error!("{}", err_msg);
+                    self.finish_run(None, Some(err_msg.clone())).await;
                     if let Some(handler) = &mut self.trace_handler {
                         let execution = AgentExecution {
                             steps,
@@ -401,6 +459,7 @@ impl Agent {
             "Agent reached max_steps ({}) without a final answer.",
             self.config.max_steps
         );
+        self.finish_run(None, Some("Max steps reached".to_string())).await;
         if let Some(handler) = &mut self.trace_handler {
             let execution = AgentExecution {
                 steps,
@@ -415,40 +474,117 @@ impl Agent {
         Err(AgentError::MaxStepsReached)
     }
 
-    /// Executes a single step of the agent's reasoning loop.
-    /// This is suitable for turn-by-turn conversational interactions.
-    /// It returns `Ok((Some(final_answer), step))` if the agent provides a final answer,
-    pub async fn run_step(&mut self, step_number: usize) -> Result<(Option<String>, AgentStep), AgentError> {
-        let conversation_messages = self.memory.get_context();
-        let mut messages_for_llm = Vec::new();
-
-        // Prepend system prompt if it exists
-        if let Some(system_prompt) = &self.config.system_prompt {
-            if !system_prompt.is_empty() {
-                messages_for_llm.push(Message {
-                    role: Role::System,
-                    content: system_prompt.clone(),
-                    tool_call_id: None,
-                    tool_calls: None,
-                });
+    /// Runs the run_end hooks and returns the result as they left it
+    ///
+    /// In dry-run mode this also writes the report of intended tool calls.
+    ///
+    /// [`Agent::run`] calls this itself; callers driving [`Agent::run_step`]
+    /// directly call it once the run is over.
+    pub async fn finish_run(&self, result: Option<String>, error: Option<String>) -> Option<String> {
+        if let Some(simulator) = &self.tool_simulator {
+            if let Err(e) = simulator.write_report() {
+                log::error!("{}", e);
             }
         }
 
-        messages_for_llm.extend(conversation_messages);
+        if self.hooks.is_empty() {
+            return result;
+        }
+        match self.hooks.dispatch(HookEvent::RunEnd { result: result.clone(), error }).await {
+            Ok(HookEvent::RunEnd { result, .. }) => result,
+            Ok(_) => unreachable!("hooks keep the event kind"),
+            Err(e) => {
+                log::warn!("run_end hook failed: {}", e);
+                result
+            }
+        }
+    }
 
+    /// Has the reviewer, if any, check a proposed final answer
+    ///
+    /// Returns the answer when it is accepted. A rejected answer gives `None`
+    /// and the critique is added to memory for the next step. The review is
+    /// recorded on `step`.
+    async fn review_final_answer(
+        &mut self,
+        answer: String,
+        step: &mut AgentStep,
+    ) -> Result<Option<String>, AgentError> {
+        let Some(reviewer) = &self.reviewer else {
+            return Ok(Some(answer));
+        };
+        if self.review_rounds >= reviewer.max_rounds() {
+            log::info!("Review rounds used up, accepting the answer");
+            return Ok(Some(answer));
+        }
+
+        let task = self.current_task.clone().unwrap_or_default();
+        let verdict = reviewer.review(&task, &answer).await;
+        self.review_rounds += 1;
+        step.review = Some(ReviewRecord {
+            round: self.review_rounds,
+            approved: verdict.approved,
+            critique: verdict.critique.clone(),
+        });
+        if verdict.approved {
+            log::info!("Reviewer approved the answer");
+            return Ok(Some(answer));
+        }
+
+        log::info!("Reviewer rejected the answer: {}", verdict.critique);
+        self.history.add_step(HistoryStep::Thought(format!(
+            "Reviewer rejected the answer: {}",
+            verdict.critique
+        )));
+        self.memory
+            .add_message(Message {
+                role: Role::User,
+                content: format!(
+                    "A reviewer did not accept your answer:\n{}\n\nAddress this and then give your final answer again.",
+                    verdict.critique
+                ),
+                tool_call_id: None,
+                tool_calls: None,
+                reasoning: None,
+            })
+            .await?;
+        Ok(None)
+    }
+
+    /// Executes a single step of the agent's reasoning loop.
+    /// This is suitable for turn-by-turn conversational interactions.
+    /// It returns `Ok((Some(final_answer), step))` if the agent provides a final answer,
+    pub async fn run_step(&mut self, step_number: usize) -> Result<(Option<String>, AgentStep), AgentError> {
         let mut tool_metadata: Vec<ToolMetadata> =
             self.tools.values().map(|t| t.metadata()).collect();
         
-        // Add control plane tools to the available tools
-        // Add all control plane tools to the available tools
+        // Add the control plane tools that were not registered with the agent's tools
         let control_tools = self.control_plane.list_tools();
         log::info!("Control plane tools available: {:?}", control_tools);
         for tool_name in control_tools {
+            if self.tools.contains_key(&tool_name) {
+                continue;
+            }
             if let Some(control_tool) = self.control_plane.get_tool(&tool_name) {
                 tool_metadata.push(control_tool.metadata());
                 log::info!("Added control plane tool to LLM: {}", tool_name);
             }
         }
+
+        let assembled = ContextBuilder::new(self.config.context_budget.clone()).assemble(ContextInputs {
+            system_prompt: self.config.system_prompt.as_deref(),
+            tools: tool_metadata,
+            rag: self.rag_context.as_ref(),
+            conversation: self.memory.get_context(),
+        });
+        let context_report = assembled.report;
+        let HookEvent::BeforeLlm { messages: messages_for_llm, tools: tool_metadata } = self
+            .hooks
+            .dispatch(HookEvent::BeforeLlm { messages: assembled.messages, tools: assembled.tools })
+            .await?
+        else {
+            unreachable!("hooks keep the event kind")
+        };
         log::info!("Total tools available to LLM: {}", tool_metadata.len());
 
         log::info!("Generating LLM response");
@@ -466,21 +602,34 @@ impl Agent {
             }
         };
         log::info!("LLM response generated");
+        let HookEvent::AfterLlm { response: llm_response } =
+            self.hooks.dispatch(HookEvent::AfterLlm { response: llm_response }).await?
+        else {
+            unreachable!("hooks keep the event kind")
+        };
 
-        let mut thought = None;
-        if let Some(content) = &llm_response.content {
+        let reasoning = llm_response.reasoning_text();
+        if let Some(r) = &reasoning {
+            log::debug!("Reasoning: {}", r);
+        }
+
+        // Tool calls and reasoning are kept with the assistant turn so the tool
+        // results that follow stay paired with it on the next request
+        let has_tool_calls = llm_response.tool_calls.as_ref().is_some_and(|c| !c.is_empty());
+        if llm_response.content.is_some() || has_tool_calls {
             log::info!("Adding assistant message to memory");
             self.memory
                 .add_message(Message {
                     role: Role::Assistant,
-                    content: content.clone(),
+                    content: llm_response.content.clone().unwrap_or_default(),
                     tool_call_id: None,
-                    tool_calls: None,
+                    tool_calls: llm_response.tool_calls.clone(),
+                    reasoning: llm_response.reasoning.clone(),
                 })
                 .await?;
             log::info!("Assistant message added to memory");
-            thought = Some(content.clone());
         }
+        let thought = llm_response.content.clone();
 
         if let Some(t) = &thought {
             if !t.trim().is_empty() {
@@ -496,24 +645,32 @@ impl Agent {
                             .unwrap_or("")
                             .trim()
                             .to_string();
-                        let step = AgentStep {
+                        let mut step = AgentStep {
                             step_number,
                             thought: thought.clone(),
                             tool_calls: llm_response.tool_calls.clone(),
                             tool_results: None,
+                            reasoning: reasoning.clone(),
+                            context: context_report.clone(),
+                            review: None,
                         };
+                        let final_answer = self.review_final_answer(final_answer, &mut step).await?;
                         if let Some(handler) = &mut self.trace_handler {
                             handler.on_step_complete(&step);
                         }
-                        return Ok((Some(final_answer), step));
+                        return Ok((final_answer, step));
                     }
                     // If there are no tool calls and no "Final Answer",
                     // we assume it's a conversational response and yield control.
+                    // Only explicit final answers go to the reviewer.
                     let step = AgentStep {
                         step_number,
                         thought: thought.clone(),
                         tool_calls: llm_response.tool_calls.clone(),
                         tool_results: None,
+                        reasoning: reasoning.clone(),
+                        context: context_report.clone(),
+                        review: None,
                     };
                     if let Some(handler) = &mut self.trace_handler {
                         handler.on_step_complete(&step);
@@ -557,12 +714,16 @@ impl Agent {
                             if let Some(summary) = response.get("summary").and_then(|s| s.as_str()) {
                                 log::info!("Agent execution completed with summary: {}", summary);
                                 
-                                let step = AgentStep {
+                                let mut step = AgentStep {
                                     step_number,
                                     thought: thought.clone(),
                                     tool_calls: llm_response.tool_calls.clone(),
                                     tool_results: Some(tool_results),
+                                    reasoning: reasoning.clone(),
+                                    context: context_report.clone(),
+                                    review: None,
                                 };
+                                let summary = self.review_final_answer(summary.to_string(), &mut step).await?;
                                 if let Some(handler) = &mut self.trace_handler {
                                     if let Some(handle) = handler.on_step_complete(&step) {
                                         self.trace_handles.push(handle);
@@ -570,7 +731,7 @@ impl Agent {
                                 }
                                 
                                 // Return the summary as the final answer
-                                return Ok((Some(summary.to_string()), step));
+                                return Ok((summary, step));
                             }
                         }
                         
@@ -581,6 +742,9 @@ impl Agent {
                             thought: thought.clone(),
                             tool_calls: llm_response.tool_calls.clone(),
                             tool_results: Some(tool_results),
+                            reasoning: reasoning.clone(),
+                            context: context_report.clone(),
+                            review: None,
                         };
                         if let Some(handler) = &mut self.trace_handler {
                             if let Some(handle) = handler.on_step_complete(&step) {
@@ -614,6 +778,9 @@ impl Agent {
                                 thought: thought.clone(),
                                 tool_calls: llm_response.tool_calls.clone(),
                                 tool_results: Some(tool_results),
+                                reasoning: reasoning.clone(),
+                                context: context_report.clone(),
+                                review: None,
                             };
                             if let Some(handler) = &mut self.trace_handler {
                                 if let Some(handle) = handler.on_step_complete(&step) {
@@ -632,6 +799,9 @@ impl Agent {
             thought: thought.clone(),
             tool_calls: llm_response.tool_calls.clone(),
             tool_results: Some(tool_results),
+            reasoning: reasoning.clone(),
+            context: context_report.clone(),
+            review: None,
         };
         if let Some(handler) = &mut self.trace_handler {
             if let Some(handle) = handler.on_step_complete(&step) {
@@ -639,15 +809,42 @@ impl Agent {
             }
         }
 
+        if let Some(question) = self.loop_question.take() {
+            // Ends the turn so the user can redirect the agent
+            self.memory
+                .add_message(Message {
+                    role: Role::Assistant,
+                    content: question.clone(),
+                    tool_call_id: None,
+                    tool_calls: None,
+                    reasoning: None,
+                })
+                .await?;
+            return Ok((Some(question), step));
+        }
+
         // No final answer yet in this step
         Ok((None, step))
     }
 
     pub async fn add_user_task_to_memory(&mut self, task: &str) -> Result<(), AgentError> {
-        log::info!("Formatting task with RAG context");
-        let enhanced_task = self
-            .format_task_with_rag_context(task)
-            .await?;
+        let HookEvent::RunStart { task } =
+            self.hooks.dispatch(HookEvent::RunStart { task: task.to_string() }).await?
+        else {
+            unreachable!("hooks keep the event kind")
+        };
+        let task = task.as_str();
+        self.current_task = Some(task.to_string());
+        self.review_rounds = 0;
+        self.loop_question = None;
+        let enhanced_task = if self.config.context_budget.is_some() {
+            // Kept out of memory so that each request can fit it to the budget
+            self.rag_context = self.retrieve_rag_context(task, &MetadataFilter::default()).await?;
+            task.to_string()
+        } else {
+            log::info!("Formatting task with RAG context");
+            self.format_task_with_rag_context(task).await?
+        };
         log::info!("Task formatted");
 
         self.history
@@ -659,6 +856,7 @@ impl Agent {
                 content: enhanced_task,
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             })
             .await?;
         log::info!("User message added to memory");
@@ -666,6 +864,24 @@ impl Agent {
     }
 
     async fn execute_tool(&mut self, tool_call: crate::core_types::ToolCall, step_num: usize) -> Result<Observation, AgentError> {
+        let result = self.dispatch_tool(tool_call, step_num).await;
+        // Recording the observation consumes the call; an error return must not leave
+        // it behind for the AfterTool hooks of a later, unrelated observation
+        self.active_tool_call.take();
+        result
+    }
+
+    async fn dispatch_tool(&mut self, tool_call: crate::core_types::ToolCall, step_num: usize) -> Result<Observation, AgentError> {
+        let tool_call_id = tool_call.id.clone();
+        let tool_call = match self.hooks.dispatch(HookEvent::BeforeTool { tool_call }).await {
+            Ok(HookEvent::BeforeTool { tool_call }) => tool_call,
+            Ok(_) => unreachable!("hooks keep the event kind"),
+            Err(AgentError::HookVeto(reason)) => {
+                let content = format!("Tool call was vetoed: {}", reason);
+                return self.add_tool_observation(tool_call_id, content, false).await;
+            }
+            Err(e) => return Err(e),
+        };
         let loop_pattern = self.loop_detector.add_tool_call(
             tool_call.name.clone(),
             tool_call.arguments.clone(),
@@ -674,9 +890,10 @@ impl Agent {
         
         if loop_pattern.is_problematic() {
             log::warn!("Loop pattern detected: {:?}", loop_pattern);
-            return self.handle_loop_detection(loop_pattern, tool_call, step_num).await;
+            return self.handle_loop_detection(loop_pattern, tool_call).await;
         }
-        
+        self.active_tool_call = Some(tool_call.clone());
+
         // Check if this is a control plane tool first
         if ControlPlaneServer::is_control_tool(&tool_call.name) {
             self.execute_control_plane_tool(tool_call, step_num).await
@@ -689,6 +906,29 @@ impl Agent {
         }
     }
 
+    /// Answers an authorized call from the simulator during a dry run
+    ///
+    /// Arguments are validated and authorization is asked for as usual, and only
+    /// the execution itself is simulated. `execute_code` and `rag_search` are
+    /// simulated too, so a dry run neither runs code nor depends on the index.
+    /// Returns `None` outside dry runs.
+    async fn simulate_execution(
+        &mut self,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        step_num: usize,
+    ) -> Option<(String, bool)> {
+        let metadata = self.tools.get(tool_name).map(|tool| tool.metadata());
+        let simulator = self.tool_simulator.as_mut()?;
+        let tool_call = crate::core_types::ToolCall {
+            id: None,
+            name: tool_name.to_string(),
+            arguments: arguments.clone(),
+        };
+        let simulated = simulator.simulate(step_num, &tool_call, metadata.as_ref()).await;
+        Some((simulated.result, simulated.success))
+    }
+
     async fn execute_code_tool(&mut self, tool_call: crate::core_types::ToolCall, step_num: usize) -> Result<Observation, AgentError> {
         const MAX_CONSECUTIVE_FAILURES: u32 = 2;
         if let Some(failures) = self.consecutive_tool_failures.get("execute_code") {
@@ -725,6 +965,9 @@ impl Agent {
             ).await.unwrap_or(false);
 
             if is_authorized {
+                if let Some((content, success)) = self.simulate_execution("execute_code", &tool_args, step_num).await {
+                    return self.add_tool_observation(tool_id, content, success).await;
+                }
                 match executor.execute_code(&lang, &code).await {
                     Ok(exec_result) => {
                         self.consecutive_tool_failures.remove("execute_code");
@@ -776,6 +1019,10 @@ impl Agent {
                 .to_string();
             let tool_args = tool_call.arguments.clone();
             let tool_id = tool_call.id.clone();
+            let filter = match MetadataFilter::from_arguments(&tool_args) {
+                Ok(filter) => filter,
+                Err(e) => return self.add_tool_observation(tool_id, e.to_string(), false).await,
+            };
 
             let is_authorized = self.check_tool_authorization(
                 "rag_search",
@@ -786,7 +1033,10 @@ impl Agent {
             ).await.unwrap_or(false);
 
             if is_authorized {
-                match self.retrieve_rag_context(&query).await {
+                if let Some((content, success)) = self.simulate_execution("rag_search", &tool_args, step_num).await {
+                    return self.add_tool_observation(tool_id, content, success).await;
+                }
+                match self.retrieve_rag_context(&query, &filter).await {
                     Ok(Some(context)) => {
                         self.consecutive_tool_failures.remove("rag_search");
                         let formatted_context = context.format_for_llm();
@@ -831,6 +1081,17 @@ impl Agent {
 
         if let Some(tool) = self.tools.get(&tool_name).cloned() {
             let tool_metadata = tool.metadata();
+
+            // Malformed arguments go back to the model instead of reaching the tool
+            let tool_args = match crate::tools::arguments::prepare_arguments(&tool_metadata.input_schema, tool_args) {
+                Ok(args) => args,
+                Err(violations) => {
+                    log::warn!("Rejected arguments for tool '{}': {:?}", tool_name, violations);
+                    let err_msg = crate::tools::arguments::describe_violations(&tool_name, &violations, &tool_metadata.input_schema);
+                    return self.add_tool_observation(tool_id, err_msg, false).await;
+                }
+            };
+
             let is_authorized = self.check_tool_authorization(
                 &tool_name,
                 &tool_metadata.description,
@@ -840,6 +1101,9 @@ impl Agent {
             ).await.unwrap_or(false);
 
             if is_authorized {
+                if let Some((content, success)) = self.simulate_execution(&tool_name, &tool_args, step_num).await {
+                    return self.add_tool_observation(tool_id, content, success).await;
+                }
                 match tool.execute(tool_args).await {
                     Ok(content) => {
                         self.consecutive_tool_failures.remove(&tool_name);
@@ -862,38 +1126,43 @@ impl Agent {
         }
     }
 
-    async fn handle_loop_detection(&mut self, pattern: LoopPattern, tool_call: crate::core_types::ToolCall, step_num: usize) -> Result<Observation, AgentError> {
-        match pattern {
-            LoopPattern::ExactLoop { tool_name, count, .. } => {
-                log::error!("TERMINATING: Exact loop detected - {} called {} times", tool_name, count);
-                
-                // Don't attempt recovery - terminate execution immediately
-                let error_msg = format!(
-                    "Agent execution terminated due to infinite loop: '{}' called {} times consecutively. \
+    async fn handle_loop_detection(&mut self, pattern: LoopPattern, tool_call: crate::core_types::ToolCall) -> Result<Observation, AgentError> {
+        let note = self.loop_recovery_note(&pattern)?;
+        self.add_tool_observation(tool_call.id, format!("Not executed. {}", note), false).await
+    }
+
+    /// Applies the configured recovery strategy to a detected loop
+    ///
+    /// Returns the note to show the model, or the error that ends the run when
+    /// the strategy is to abort.
+    fn loop_recovery_note(&mut self, pattern: &LoopPattern) -> Result<String, AgentError> {
+        let description = pattern.describe();
+        match self.config.loop_detection.recovery {
+            LoopRecoveryStrategy::Abort => {
+                log::error!("TERMINATING: Loop detected - {}", description);
+                Err(AgentError::LoopDetection(format!(
+                    "Agent execution terminated due to a loop: {}. \
                     This indicates the agent is stuck and cannot make progress.",
-                    tool_name, count
-                );
-                
-                return Err(AgentError::LoopDetection(error_msg));
+                    description
+                )))
             }
-            LoopPattern::SimilarLoop { tool_name, count, similarity_score, .. } => {
-                log::error!("TERMINATING: Similar loop detected - {} called {} times with {:.1}% similarity", 
-                           tool_name, count, similarity_score * 100.0);
-                
-                let error_msg = format!(
-                    "Agent execution terminated due to similar loop pattern: '{}' called {} times with {:.1}% argument similarity. \
-                    This indicates the agent is stuck in a repetitive pattern.",
-                    tool_name, count, similarity_score * 100.0
-                );
-                
-                return Err(AgentError::LoopDetection(error_msg));
+            LoopRecoveryStrategy::InjectGuidance => {
+                log::warn!("Loop detected, asking the model to change approach: {}", description);
+                self.loop_detector.clear();
+                Ok(format!(
+                    "Loop detected: {}. Repeating these calls will not help. \
+                    Try a different approach, or give your final answer with what you have.",
+                    description
+                ))
             }
-            _ => {
-                // This shouldn't happen since we check is_problematic() before calling this method
-                log::warn!("Non-problematic pattern passed to handle_loop_detection: {:?}", pattern);
-                
-                // Execute normally as fallback
-                self.execute_generic_tool(tool_call, step_num).await
+            LoopRecoveryStrategy::AskUser => {
+                log::warn!("Loop detected, asking the user how to proceed: {}", description);
+                self.loop_detector.clear();
+                self.loop_question = Some(format!(
+                    "I seem to be stuck: {}. How would you like me to proceed?",
+                    description
+                ));
+                Ok(format!("Loop detected: {}. Waiting for the user's direction.", description))
             }
         }
     }
@@ -960,6 +1229,37 @@ impl Agent {
     }
 
     async fn add_tool_observation(&mut self, tool_call_id: Option<String>, content: String, success: bool) -> Result<Observation, AgentError> {
+        let active_tool_call = self.active_tool_call.take();
+        let (mut content, success) = match active_tool_call.clone() {
+            Some(tool_call) => {
+                let observation = Observation {
+                    tool_call_id: tool_call_id.clone(),
+                    content,
+                    success,
+                };
+                match self.hooks.dispatch(HookEvent::AfterTool { tool_call, observation }).await {
+                    Ok(HookEvent::AfterTool { observation, .. }) => (observation.content, observation.success),
+                    Ok(_) => unreachable!("hooks keep the event kind"),
+                    Err(AgentError::HookVeto(reason)) => (format!("Tool result was withheld: {}", reason), false),
+                    Err(e) => return Err(e),
+                }
+            }
+            None => (content, success),
+        };
+
+        let mut loop_error = None;
+        if let Some(tool_call) = active_tool_call.filter(|call| !ControlPlaneServer::is_control_tool(&call.name)) {
+            let pattern = self.loop_detector.add_observation(&tool_call.name, &tool_call.arguments, &content);
+            if pattern.is_problematic() {
+                log::warn!("Loop pattern detected in tool results: {:?}", pattern);
+                match self.loop_recovery_note(&pattern) {
+                    Ok(note) => content = format!("{}\n\n{}", content, note),
+                    // Stored first so the tool call keeps its result
+                    Err(e) => loop_error = Some(e),
+                }
+            }
+        }
+
         if !success {
             if self.last_tool_error.as_deref() == Some(&content) {
                 self.consecutive_error_count += 1;
@@ -985,8 +1285,12 @@ impl Agent {
                 content,
                 tool_call_id,
                 tool_calls: None,
+                reasoning: None,
             })
             .await?;
+        if let Some(e) = loop_error {
+            return Err(e);
+        }
         Ok(observation)
     }
 
@@ -1023,6 +1327,7 @@ impl Agent {
             ),
             tool_call_id: None,
             tool_calls: None,
+            reasoning: None,
         }).await?;
         
         log::info!("Recovery context added, returning explanation to user");
@@ -1060,6 +1365,7 @@ impl Agent {
     pub fn clear_memory(&mut self) {
         self.memory.clear();
         self.history.clear();
+        self.rag_context = None;
     }
 
     /// Get the tools available to this agent
@@ -1097,6 +1403,7 @@ mod tests {
                 usage: None,
                 content: Some("Final Answer: Test complete".to_string()),
                 tool_calls: None,
+                reasoning: None,
             })
         }
     }
@@ -1160,6 +1467,7 @@ mod tests {
                         name: "get_current_time".to_string(),
                         arguments: serde_json::json!({"timezone": "UTC"}),
                     }]),
+                    reasoning: None,
                 })
             } else {
                 // After loop recovery, should provide final answer
@@ -1168,6 +1476,7 @@ mod tests {
                     usage: None,
                     content: Some("Final Answer: Recovery successful".to_string()),
                     tool_calls: None,
+                    reasoning: None,
                 })
             }
         }
@@ -1236,4 +1545,324 @@ mod tests {
         let stats = agent.loop_detector.get_statistics();
         assert_eq!(stats.total_calls, 0, "Loop detector should be cleared at start of steps");
     }
-}
\ No newline at end of file
+
+    // Tool that records the arguments it was called with
+    struct RecordingTool {
+        calls: Arc<std::sync::Mutex<Vec<serde_json::Value>>>,
+    }
+
+    #[async_trait]
+    impl Tool for RecordingTool {
+        fn metadata(&self) -> ToolMetadata {
+            ToolMetadata {
+                name: "repeat".to_string(),
+                description: "Repeats a word".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": { "word": { "type": "string" }, "count": { "type": "integer" } },
+                    "required": ["word", "count"]
+                }),
+            }
+        }
+
+        async fn execute(&self, args: serde_json::Value) -> Result<String, AgentError> {
+            self.calls.lock().unwrap().push(args);
+            Ok("done".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_arguments_are_validated_before_execution() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("repeat".to_string(), Arc::new(RecordingTool { calls: calls.clone() }));
+        let mut agent = Agent::new(Arc::new(MockLLM), tools, None, AgentConfig::default());
+
+        let call = |arguments| crate::core_types::ToolCall {
+            id: Some("call_1".to_string()),
+            name: "repeat".to_string(),
+            arguments,
+        };
+
+        let observation = agent
+            .execute_generic_tool(call(serde_json::json!({ "word": "hi", "count": "3" })), 1)
+            .await
+            .unwrap();
+        assert!(observation.success);
+        assert_eq!(calls.lock().unwrap()[0], serde_json::json!({ "word": "hi", "count": 3 }));
+
+        let observation = agent
+            .execute_generic_tool(call(serde_json::json!({ "count": "many" })), 2)
+            .await
+            .unwrap();
+        assert!(!observation.success);
+        assert!(observation.content.starts_with("Invalid arguments for tool 'repeat'"));
+        assert!(observation.content.contains("\"word\" is a required property"));
+        assert!(observation.content.contains("At '/count'"));
+        assert_eq!(calls.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_context_budget_is_reported_in_step_trace() {
+        let mut budget = ContextBudgetConfig::new(8000);
+        budget.reserved_output_tokens = 0;
+        budget.system.max_tokens = Some(50);
+        let config = AgentConfig {
+            system_prompt: Some("Be thorough. ".repeat(100)),
+            context_budget: Some(budget),
+            ..AgentConfig::default()
+        };
+        let mut agent = Agent::new(Arc::new(MockLLM), HashMap::new(), None, config);
+
+        agent.add_user_task_to_memory("test task").await.unwrap();
+        let (answer, step) = agent.run_step(0).await.unwrap();
+        assert_eq!(answer.as_deref(), Some("Test complete"));
+
+        let report = step.context.unwrap();
+        assert_eq!(report.budget_tokens, 8000);
+        assert_eq!(report.dropped.len(), 1);
+        assert_eq!(report.dropped[0].description, "system prompt truncated");
+    }
+
+    struct GuardHook;
+
+    #[async_trait]
+    impl AgentHook for GuardHook {
+        async fn on_event(&self, event: &mut HookEvent) -> Result<crate::hooks::HookAction, AgentError> {
+            match event {
+                HookEvent::BeforeTool { tool_call } if tool_call.arguments["word"] == "secret" => {
+                    return Ok(crate::hooks::HookAction::Veto("secret words are not allowed".to_string()));
+                }
+                HookEvent::BeforeTool { tool_call } => tool_call.arguments["count"] = serde_json::json!(2),
+                HookEvent::AfterTool { observation, .. } => observation.content.push_str(" (checked)"),
+                _ => {}
+            }
+            Ok(crate::hooks::HookAction::Continue)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hooks_modify_and_veto_tool_calls() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("repeat".to_string(), Arc::new(RecordingTool { calls: calls.clone() }));
+        let mut agent = Agent::new(Arc::new(MockLLM), tools, None, AgentConfig::default());
+        agent.add_hook(Arc::new(GuardHook));
+
+        let call = |word: &str| crate::core_types::ToolCall {
+            id: Some("call_1".to_string()),
+            name: "repeat".to_string(),
+            arguments: serde_json::json!({ "word": word }),
+        };
+
+        let observation = agent.execute_tool(call("hi"), 1).await.unwrap();
+        assert!(observation.success);
+        assert_eq!(observation.content, "done (checked)");
+        assert_eq!(calls.lock().unwrap()[0], serde_json::json!({ "word": "hi", "count": 2 }));
+
+        let observation = agent.execute_tool(call("secret"), 2).await.unwrap();
+        assert!(!observation.success);
+        assert_eq!(observation.content, "Tool call was vetoed: secret words are not allowed");
+        assert_eq!(calls.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_simulates_tools_without_calling_them() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("repeat".to_string(), Arc::new(RecordingTool { calls: calls.clone() }));
+        let mut agent = Agent::new(Arc::new(MockLLM), tools, None, AgentConfig::default());
+        agent.set_tool_simulator(ToolSimulator::new(Vec::new(), None));
+
+        let call = |arguments| crate::core_types::ToolCall {
+            id: Some("call_1".to_string()),
+            name: "repeat".to_string(),
+            arguments,
+        };
+        let observation = agent.execute_tool(call(serde_json::json!({ "word": "hi", "count": "2" })), 4).await.unwrap();
+
+        assert!(observation.success);
+        assert_eq!(observation.content, "[dry run] 'repeat' was not executed");
+        assert!(calls.lock().unwrap().is_empty());
+        let simulated = agent.tool_simulator().unwrap().calls();
+        assert_eq!(simulated.len(), 1);
+        assert_eq!(simulated[0].step, 4);
+        assert_eq!(simulated[0].arguments, serde_json::json!({ "word": "hi", "count": 2 }));
+
+        // Invalid arguments are rejected as in a real run, before simulating
+        let observation = agent.execute_tool(call(serde_json::json!({ "count": 2 })), 5).await.unwrap();
+        assert!(!observation.success);
+        assert!(observation.content.starts_with("Invalid arguments for tool 'repeat'"));
+        assert_eq!(agent.tool_simulator().unwrap().calls().len(), 1);
+    }
+
+    // Rejects the first answer it sees and approves the rest
+    struct StrictReviewerLLM {
+        reviews: std::sync::Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl LLM for StrictReviewerLLM {
+        async fn generate(
+            &self,
+            messages: Vec<Message>,
+            _tools: Option<Vec<ToolMetadata>>,
+        ) -> Result<LLMResponse, AgentError> {
+            assert!(messages[1].content.starts_with("Task:\nsummarize and translate"));
+            let mut reviews = self.reviews.lock().unwrap();
+            *reviews += 1;
+            let verdict = if *reviews == 1 {
+                r#"{"approved": false, "critique": "The translation is missing"}"#
+            } else {
+                r#"{"approved": true}"#
+            };
+            Ok(LLMResponse {
+                finish_reason: None,
+                usage: None,
+                content: Some(verdict.to_string()),
+                tool_calls: None,
+                reasoning: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reviewer_sends_rejected_answers_back() {
+        let mut agent = Agent::new(Arc::new(MockLLM), HashMap::new(), None, AgentConfig::default());
+        let reviewer_llm = Arc::new(StrictReviewerLLM { reviews: std::sync::Mutex::new(0) });
+        let config = crate::config::ReviewConfig {
+            max_rounds: 1,
+            ..Default::default()
+        };
+        agent.set_reviewer(AnswerReviewer::new(reviewer_llm.clone(), &config));
+
+        agent.add_user_task_to_memory("summarize and translate").await.unwrap();
+        let (answer, step) = agent.run_step(0).await.unwrap();
+        assert_eq!(answer, None);
+        let review = step.review.unwrap();
+        assert_eq!((review.round, review.approved), (1, false));
+        let feedback = agent.memory().get_context().last().unwrap().clone();
+        assert_eq!(feedback.role, Role::User);
+        assert!(feedback.content.contains("The translation is missing"));
+
+        // Out of review rounds, the next answer goes through unreviewed
+        let (answer, step) = agent.run_step(1).await.unwrap();
+        assert_eq!(answer.as_deref(), Some("Test complete"));
+        assert!(step.review.is_none());
+        assert_eq!(*reviewer_llm.reviews.lock().unwrap(), 1);
+
+        // A new task gets a fresh round, which approves
+        let answer = agent.run("summarize and translate again".to_string()).await.unwrap();
+        assert_eq!(answer, "Test complete");
+        assert_eq!(*reviewer_llm.reviews.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reviewer_skips_conversational_replies() {
+        let mut agent = Agent::new(Arc::new(ChattyLLM), HashMap::new(), None, AgentConfig::default());
+        let reviewer_llm = Arc::new(StrictReviewerLLM { reviews: std::sync::Mutex::new(0) });
+        agent.set_reviewer(AnswerReviewer::new(reviewer_llm.clone(), &crate::config::ReviewConfig::default()));
+
+        agent.add_user_task_to_memory("hello").await.unwrap();
+        let (answer, step) = agent.run_step(0).await.unwrap();
+        assert_eq!(answer.as_deref(), Some("Hi! What should I work on?"));
+        assert!(step.review.is_none());
+        assert_eq!(*reviewer_llm.reviews.lock().unwrap(), 0);
+    }
+
+    // Replies without a final answer or tool calls
+    struct ChattyLLM;
+
+    #[async_trait]
+    impl LLM for ChattyLLM {
+        async fn generate(
+            &self,
+            _messages: Vec<Message>,
+            _tools: Option<Vec<ToolMetadata>>,
+        ) -> Result<LLMResponse, AgentError> {
+            Ok(LLMResponse {
+                finish_reason: None,
+                usage: None,
+                content: Some("Hi! What should I work on?".to_string()),
+                tool_calls: None,
+                reasoning: None,
+            })
+        }
+    }
+
+    // Calls `repeat` with a new word every time
+    struct RepeatCallingLLM {
+        turns: std::sync::Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl LLM for RepeatCallingLLM {
+        async fn generate(
+            &self,
+            _messages: Vec<Message>,
+            _tools: Option<Vec<ToolMetadata>>,
+        ) -> Result<LLMResponse, AgentError> {
+            let mut turns = self.turns.lock().unwrap();
+            *turns += 1;
+            Ok(LLMResponse {
+                finish_reason: None,
+                usage: None,
+                content: None,
+                tool_calls: Some(vec![crate::core_types::ToolCall {
+                    id: Some(format!("call_{}", *turns)),
+                    name: "repeat".to_string(),
+                    arguments: serde_json::json!({ "word": "again", "count": 1 }),
+                }]),
+                reasoning: None,
+            })
+        }
+    }
+
+    fn looping_agent(recovery: LoopRecoveryStrategy) -> Agent {
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("repeat".to_string(), Arc::new(RecordingTool { calls: Default::default() }));
+        let config = AgentConfig {
+            loop_detection: LoopDetectionConfig {
+                observation_repeat_threshold: 2,
+                recovery,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        Agent::new(Arc::new(RepeatCallingLLM { turns: Default::default() }), tools, None, config)
+    }
+
+    #[tokio::test]
+    async fn test_loop_recovery_strategies() {
+        let call = |word: &str| crate::core_types::ToolCall {
+            id: Some(format!("call_{}", word)),
+            name: "repeat".to_string(),
+            arguments: serde_json::json!({ "word": word, "count": 1 }),
+        };
+
+        // Different calls getting the same acknowledgement are progress
+        let mut agent = looping_agent(LoopRecoveryStrategy::InjectGuidance);
+        assert_eq!(agent.execute_tool(call("a"), 0).await.unwrap().content, "done");
+        assert_eq!(agent.execute_tool(call("b"), 1).await.unwrap().content, "done");
+        let observation = agent.execute_tool(call("a"), 2).await.unwrap();
+        assert!(observation.content.starts_with("done\n\nLoop detected: 'repeat' returned the same result 2 times."));
+        assert_eq!(agent.execute_tool(call("c"), 3).await.unwrap().content, "done");
+
+        let mut agent = looping_agent(LoopRecoveryStrategy::Abort);
+        agent.execute_tool(call("a"), 0).await.unwrap();
+        agent.execute_tool(call("b"), 1).await.unwrap();
+        let err = agent.execute_tool(call("a"), 2).await.unwrap_err();
+        assert!(matches!(err, AgentError::LoopDetection(_)));
+        // The tool call still got its result
+        assert_eq!(agent.memory().get_context().last().unwrap().tool_call_id.as_deref(), Some("call_a"));
+
+        let mut agent = looping_agent(LoopRecoveryStrategy::AskUser);
+        agent.add_user_task_to_memory("repeat words").await.unwrap();
+        assert_eq!(agent.run_step(0).await.unwrap().0, None);
+        let (answer, _) = agent.run_step(1).await.unwrap();
+        assert_eq!(
+            answer.as_deref(),
+            Some("I seem to be stuck: 'repeat' returned the same result 2 times. How would you like me to proceed?")
+        );
+    }
+}
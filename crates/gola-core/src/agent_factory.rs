@@ -4,23 +4,28 @@ use crate::executors::runtime_manager::RuntimeManager;
 use crate::tracing::TracingTraceHandler;
 use crate::ag_ui_handler::GolaAgentHandler; // Added import
 use crate::agent::{Agent, AgentConfig};
+use crate::cassette::{Cassette, CassetteConfig, CassetteLLM, CassetteMCPClient};
 use crate::config::GolaConfig;
 use crate::errors::AgentError;
 use crate::executors::{docker::DockerCodeExecutor, CodeExecutor};
 use crate::guardrails::AuthorizationMode;
+use crate::hooks::ScriptHook;
 use crate::llm::{LLM, utils::LLMFactory};
 use crate::rag::{
     embeddings::{
         EmbeddingGenerator, EmbeddingProvider as RagEmbeddingProvider, RestEmbeddingConfig,
         RestEmbeddingFactory,
     },
-    splitter::TextSplitter,
+    splitter::{ChunkSizeUnit, LanguageTextSplitter, SplitStrategy, TextSplitter, TiktokenTokenizer},
     vector_store::{InMemoryVectorStore, PersistentVectorStore, VectorStore},
-    Rag, RagConfig, RagDocument, RagSystem,
+    HnswVectorStore, HttpReranker, LexicalReranker, LlmReranker, LoadOptions, Rag, RagConfig,
+    RagDocument, RagSystem, ReindexReport, Reranker,
 };
-use crate::tools::{CalculatorTool, MCPToolFactory, RMCPClient, Tool, WebSearchTool};
+use crate::tools::{CalculatorTool, MCPClientTrait, MCPToolFactory, RMCPClient, Tool, WebSearchTool};
 use crate::tools::control_plane::ControlPlaneServer;
-use std::collections::HashMap;
+use crate::tools::dry_run::ToolSimulator;
+use crate::review::AnswerReviewer;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::Mutex; // Added import
 
@@ -31,6 +36,8 @@ pub struct AgentFactoryConfig {
     pub gola_config: GolaConfig,
     pub local_runtimes: bool,
     pub non_interactive: bool,
+    /// Record LLM and MCP traffic to, or replay it from, a cassette file
+    pub cassette: Option<CassetteConfig>,
 }
 
 impl AgentFactory {
@@ -39,11 +46,19 @@ impl AgentFactory {
         factory_config: AgentFactoryConfig,
     ) -> Result<GolaAgentHandler, AgentError> {
         let config = factory_config.gola_config;
+        // Before anything below opens a connection
+        crate::http::configure(&config.http)?;
         let local_runtimes = factory_config.local_runtimes;
         let non_interactive = factory_config.non_interactive;
-
-        let llm = Self::configure_llm(&config)?;
-        let tools = Self::configure_tools(&config, local_runtimes, non_interactive).await?;
+        let cassette = factory_config
+            .cassette
+            .as_ref()
+            .map(Cassette::open)
+            .transpose()?
+            .map(Arc::new);
+
+        let llm = Self::configure_llm(&config, cassette.as_ref())?;
+        let tools = Self::configure_tools(&config, local_runtimes, non_interactive, cassette.as_ref()).await?;
         let code_executor = Self::configure_code_executor(&config).await?;
         let mut agent_core_config = Self::configure_agent_config(&config); // Renamed for clarity
                                                                        //
@@ -52,12 +67,34 @@ impl AgentFactory {
         agent_core_config.authorization_mode = AuthorizationMode::Ask;
 
         let mut agent_instance = if config.rag.as_ref().map_or(false, |r| r.enabled) {
-            let rag_system = Self::configure_rag_system(&config).await?;
+            let (rag_system, _) = Self::configure_rag_system(&config, llm.clone()).await?;
             Agent::with_rag(llm.clone(), tools, code_executor, agent_core_config, rag_system)
         } else {
             Agent::new(llm.clone(), tools, code_executor, agent_core_config)
         };
 
+        if config.dry_run.enabled {
+            log::warn!("Dry run: tools will be simulated, not executed");
+            agent_instance.set_tool_simulator(ToolSimulator::from_config(&config.dry_run, llm.clone())?);
+        }
+
+        if let Some(review) = &config.agent.behavior.review {
+            let review_llm = match &review.llm {
+                Some(llm_config) => crate::llm::providers::create_llm_client(llm_config)?,
+                None => llm.clone(),
+            };
+            let mut reviewer = AnswerReviewer::new(review_llm, review);
+            if let Some(output) = config.agent.schema.output.as_ref().filter(|_| config.agent.schema.enabled) {
+                reviewer = reviewer.with_output_schema(output.schema.clone());
+            }
+            agent_instance.set_reviewer(reviewer);
+        }
+
+        for hook_config in &config.hooks {
+            log::info!("Registering hook '{}' ({})", hook_config.name, hook_config.run);
+            agent_instance.add_hook(Arc::new(ScriptHook::new(hook_config.clone())));
+        }
+
         if config.tracing.enabled {
             let tracing_llm = if config.tracing.model_provider == "default" {
                 llm
@@ -77,11 +114,32 @@ impl AgentFactory {
         Ok(handler)
     }
 
-    fn configure_llm(config: &GolaConfig) -> Result<Arc<dyn LLM>, AgentError> {
+    fn configure_llm(
+        config: &GolaConfig,
+        cassette: Option<&Arc<Cassette>>,
+    ) -> Result<Arc<dyn LLM>, AgentError> {
+        let Some(cassette) = cassette else {
+            return Self::configure_base_llm(config).map(LLMFactory::wrap_with_defaults);
+        };
+
+        // Cassettes sit directly on the provider client so that replayed responses
+        // still flow through the same recovery and truncation wrappers
+        let base_client: Arc<dyn LLM> = if cassette.is_replaying() {
+            log::info!("Replaying LLM responses from cassette {}", cassette.path().display());
+            Arc::new(CassetteLLM::replaying(cassette.clone()))
+        } else {
+            log::info!("Recording LLM responses to cassette {}", cassette.path().display());
+            Arc::new(CassetteLLM::recording(Self::configure_base_llm(config)?, cassette.clone()))
+        };
+
+        Ok(LLMFactory::wrap_with_defaults(base_client))
+    }
+
+    fn configure_base_llm(config: &GolaConfig) -> Result<Arc<dyn LLM>, AgentError> {
         match &config.llm {
             Some(llm_config) => {
-                // Use the new provider system with all wrappers applied consistently
-                LLMFactory::create_llm_with_config(llm_config)
+                // Use the new provider system
+                crate::llm::providers::create_llm_client(llm_config)
             }
             None => {
                 // Auto-detect LLM provider from environment variables
@@ -99,7 +157,7 @@ impl AgentFactory {
                 
                 let env_provider = EnvironmentLlmProvider::new();
                 let llm_config = env_provider.provide_defaults(&context)?;
-                LLMFactory::create_llm_with_config(&llm_config)
+                crate::llm::providers::create_llm_client(&llm_config)
             }
         }
     }
@@ -108,6 +166,7 @@ impl AgentFactory {
         config: &GolaConfig,
         local_runtimes: bool,
         non_interactive: bool,
+        cassette: Option<&Arc<Cassette>>,
     ) -> Result<HashMap<String, Arc<dyn Tool>>, AgentError> {
         let mut tools_map: HashMap<String, Arc<dyn Tool>> = HashMap::new();
         let runtime_manager = RuntimeManager::new(local_runtimes, non_interactive);
@@ -150,6 +209,17 @@ impl AgentFactory {
                 continue;
             }
 
+            if let Some(cassette) = cassette.filter(|c| c.is_replaying()) {
+                log::info!(
+                    "Replaying MCP server {} from cassette instead of starting it",
+                    mcp_server_config.name
+                );
+                let client = CassetteMCPClient::replaying(mcp_server_config.name.clone(), cassette.clone());
+                let factory = MCPToolFactory::new(Arc::new(client), mcp_server_config.description_token_limit);
+                Self::register_mcp_tools(&factory, &mcp_server_config.name, &mut tools_map).await;
+                continue;
+            }
+
             // Determine which configuration format to use and convert to execution_type for runtime manager
             let execution_type = match (&mcp_server_config.command, &mcp_server_config.execution_type, &mcp_server_config.execution_environment) {
                 // execution_environment format - clearest semantics about where code runs
@@ -198,46 +268,23 @@ impl AgentFactory {
                         "Successfully connected to MCP server: {}",
                         mcp_server_config.name
                     );
-                    let factory = MCPToolFactory::new(
-                        Arc::new(rmcp_client),
-                        mcp_server_config.description_token_limit,
-                    );
-                    log::info!(
-                        "About to call create_all_tools for: {}",
-                        mcp_server_config.name
-                    );
-
-                    // Use longer timeout for Gmail MCP server which may need OAuth setup
-                    let timeout_duration = std::time::Duration::from_secs(60); // 60 seconds for Gmail
-
-                    match tokio::time::timeout(timeout_duration, factory.create_all_tools())
-                        .await
-                    {
-                        Ok(Ok(mcp_tools_vec)) => {
-                            log::info!(
-                                "Successfully created {} tools from MCP server: {}",
-                                mcp_tools_vec.len(),
-                                mcp_server_config.name
-                            );
-                            for tool in mcp_tools_vec {
-                                let tool_name = tool.metadata().name.clone();
-                                tools_map.insert(tool_name.clone(), tool);
-                                log::debug!("Registered MCP tool: {}", tool_name);
-                            }
-                        }
-                        Ok(Err(e)) => {
-                            log::error!(
-                                "Failed to create tools from MCP server {}: {}",
-                                mcp_server_config.name,
-                                e
-                            );
-                        }
-                        Err(_) => {
-                            log::error!(
-                                "Timeout creating tools from MCP server: {}. Skipping.",
-                                mcp_server_config.name
-                            );
-                        }
+                    if let Some(cassette) = cassette {
+                        let client = CassetteMCPClient::recording(
+                            Arc::new(rmcp_client) as Arc<dyn MCPClientTrait>,
+                            mcp_server_config.name.clone(),
+                            cassette.clone(),
+                        );
+                        let factory = MCPToolFactory::new(
+                            Arc::new(client),
+                            mcp_server_config.description_token_limit,
+                        );
+                        Self::register_mcp_tools(&factory, &mcp_server_config.name, &mut tools_map).await;
+                    } else {
+                        let factory = MCPToolFactory::new(
+                            Arc::new(rmcp_client),
+                            mcp_server_config.description_token_limit,
+                        );
+                        Self::register_mcp_tools(&factory, &mcp_server_config.name, &mut tools_map).await;
                     }
                 }
                 Err(e) => {
@@ -266,6 +313,39 @@ impl AgentFactory {
         Ok(tools_map)
     }
 
+    /// Discover the tools exposed by an MCP server and add them to the tools map
+    async fn register_mcp_tools<C: MCPClientTrait + 'static>(
+        factory: &MCPToolFactory<C>,
+        server_name: &str,
+        tools_map: &mut HashMap<String, Arc<dyn Tool>>,
+    ) {
+        log::info!("About to call create_all_tools for: {}", server_name);
+
+        // Use longer timeout for Gmail MCP server which may need OAuth setup
+        let timeout_duration = std::time::Duration::from_secs(60); // 60 seconds for Gmail
+
+        match tokio::time::timeout(timeout_duration, factory.create_all_tools()).await {
+            Ok(Ok(mcp_tools_vec)) => {
+                log::info!(
+                    "Successfully created {} tools from MCP server: {}",
+                    mcp_tools_vec.len(),
+                    server_name
+                );
+                for tool in mcp_tools_vec {
+                    let tool_name = tool.metadata().name.clone();
+                    tools_map.insert(tool_name.clone(), tool);
+                    log::debug!("Registered MCP tool: {}", tool_name);
+                }
+            }
+            Ok(Err(e)) => {
+                log::error!("Failed to create tools from MCP server {}: {}", server_name, e);
+            }
+            Err(_) => {
+                log::error!("Timeout creating tools from MCP server: {}. Skipping.", server_name);
+            }
+        }
+    }
+
     async fn configure_code_executor(
         config: &GolaConfig,
     ) -> Result<Option<Arc<dyn CodeExecutor>>, AgentError> {
@@ -311,6 +391,9 @@ impl AgentFactory {
                     .as_ref()
                     .map(|p| p.vector_store_path.to_string_lossy().into_owned()),
                 embedding_cache: gola_rag_conf.embedding_cache.clone(),
+                retrieval_mode: gola_rag_conf.retrieval.mode,
+                hybrid: gola_rag_conf.retrieval.hybrid.clone(),
+                rerank_candidates: gola_rag_conf.retrieval.rerank_candidates,
             })
         } else {
             None
@@ -334,10 +417,71 @@ impl AgentFactory {
             system_prompt,
             memory_config,
             authorization_mode: AuthorizationMode::default(),
+            context_budget: agent_gola_config.behavior.context_budget.clone(),
+            loop_detection: agent_gola_config.behavior.loop_detection.clone(),
+        }
+    }
+
+    /// Whether a loaded store's embeddings can be used with the configured model.
+    /// Stores written without a model name are assumed to match.
+    fn stored_model_matches(path: &std::path::Path, stored_model: &str, configured_model: &str) -> bool {
+        if stored_model.is_empty() || stored_model == configured_model {
+            return true;
+        }
+        log::warn!(
+            "Vector store {} holds embeddings from '{}' but '{}' is configured; clearing it to re-embed",
+            path.display(),
+            stored_model,
+            configured_model
+        );
+        false
+    }
+
+    fn configure_text_splitter(
+        text_processing: &crate::config::TextProcessingConfig,
+    ) -> Result<TextSplitter, AgentError> {
+        let (chunk_size, chunk_overlap) = (text_processing.chunk_size, text_processing.chunk_overlap);
+        let splitter = match &text_processing.splitter_type {
+            crate::config::TextSplitterType::Basic => TextSplitter::new(chunk_size, chunk_overlap),
+            crate::config::TextSplitterType::Language { language } => {
+                LanguageTextSplitter::for_language(language, chunk_size, chunk_overlap)
+            }
+            crate::config::TextSplitterType::Code => {
+                TextSplitter::new(chunk_size, chunk_overlap).with_strategy(SplitStrategy::Code(None))
+            }
+            crate::config::TextSplitterType::Markdown => {
+                TextSplitter::new(chunk_size, chunk_overlap).with_strategy(SplitStrategy::Markdown)
+            }
+            crate::config::TextSplitterType::Semantic => TextSplitter::new(chunk_size, chunk_overlap)
+                .with_strategy(SplitStrategy::Semantic(text_processing.semantic.clone())),
         }
+        .with_header_options(text_processing.chunk_headers.clone());
+        Ok(match text_processing.size_unit {
+            ChunkSizeUnit::Characters => splitter,
+            ChunkSizeUnit::Tokens => splitter.with_tokenizer(Arc::new(TiktokenTokenizer::cl100k()?)),
+        })
     }
 
-    async fn configure_rag_system(config: &GolaConfig) -> Result<Box<dyn Rag>, AgentError> {
+    /// Brings the configured RAG index up to date without creating an agent
+    ///
+    /// This is the indexing pass agent creation runs, on its own: new and changed
+    /// sources are embedded and sources dropped from the configuration removed.
+    pub async fn reindex_from_config(config: &GolaConfig) -> Result<ReindexReport, AgentError> {
+        crate::http::configure(&config.http)?;
+        if !config.rag.as_ref().is_some_and(|r| r.enabled) {
+            return Err(AgentError::ConfigError(
+                "RAG is not enabled in this configuration".to_string(),
+            ));
+        }
+        let llm = Self::configure_base_llm(config)?;
+        let (_, report) = Self::configure_rag_system(config, llm).await?;
+        Ok(report)
+    }
+
+    async fn configure_rag_system(
+        config: &GolaConfig,
+        llm: Arc<dyn LLM>,
+    ) -> Result<(Box<dyn Rag>, ReindexReport), AgentError> {
         let rag_gola_config = config.rag.as_ref().ok_or_else(|| {
             AgentError::ConfigError("RAG config expected but not found".to_string())
         })?;
@@ -360,6 +504,9 @@ impl AgentFactory {
                 .as_ref()
                 .map(|p| p.vector_store_path.to_string_lossy().into_owned()),
             embedding_cache: rag_gola_config.embedding_cache.clone(),
+            retrieval_mode: rag_gola_config.retrieval.mode,
+            hybrid: rag_gola_config.retrieval.hybrid.clone(),
+            rerank_candidates: rag_gola_config.retrieval.rerank_candidates,
         };
 
         let embedding_generator: Box<dyn EmbeddingGenerator> = match &rag_gola_config
@@ -385,6 +532,9 @@ impl AgentFactory {
                             "https://api-inference.huggingface.co/pipeline/feature-extraction"
                                 .to_string()
                         }
+                        crate::config::EmbeddingProvider::AzureOpenAI { endpoint, .. } => {
+                            endpoint.clone()
+                        }
                         _ => {
                             return Err(AgentError::ConfigError(
                                 "Unsupported REST embedding provider configuration for base URL"
@@ -392,7 +542,14 @@ impl AgentFactory {
                             ))
                         }
                     },
-                    api_key: rag_gola_config.embeddings.auth.api_key.clone(),
+                    api_key: rag_gola_config.embeddings.auth.api_key.clone().or_else(|| {
+                        rag_gola_config
+                            .embeddings
+                            .auth
+                            .api_key_env
+                            .as_ref()
+                            .and_then(|var| std::env::var(var).ok())
+                    }),
                     model_name: rag_gola_config.embeddings.model.clone(),
                     embedding_dimension: rag_gola_config.embeddings.dimension,
                     timeout_seconds: 30,
@@ -403,6 +560,14 @@ impl AgentFactory {
                         crate::config::EmbeddingProvider::HuggingFace => {
                             RagEmbeddingProvider::HuggingFace
                         }
+                        crate::config::EmbeddingProvider::AzureOpenAI {
+                            deployment,
+                            api_version,
+                            ..
+                        } => RagEmbeddingProvider::AzureOpenAI {
+                            deployment: deployment.clone(),
+                            api_version: api_version.clone(),
+                        },
                         crate::config::EmbeddingProvider::Custom { .. } => {
                             RagEmbeddingProvider::Custom
                         }
@@ -420,6 +585,7 @@ impl AgentFactory {
                         crate::config::EmbeddingProvider::Cohere => "cohere",
                         crate::config::EmbeddingProvider::HuggingFace => "huggingface",
                         crate::config::EmbeddingProvider::Custom { .. } => "custom",
+                        crate::config::EmbeddingProvider::AzureOpenAI { .. } => "azure_openai",
                         _ => "openai",
                     },
                     Some(rag_gola_config.embeddings.model.clone()),
@@ -461,32 +627,92 @@ impl AgentFactory {
                     .map(|p| &p.mode)
                     .unwrap_or(&crate::config::PersistenceMode::CreateOrLoad);
 
-                match mode {
+                let loaded = match mode {
                     crate::config::PersistenceMode::Load => {
-                        Box::new(PersistentVectorStore::load(&path).await.map_err(|e| {
+                        Some(PersistentVectorStore::load(&path).await.map_err(|e| {
                             AgentError::ConfigError(format!(
                                 "Failed to load persistent vector store: {}",
                                 e
                             ))
                         })?)
                     }
-                    crate::config::PersistenceMode::Create => {
-                        Box::new(PersistentVectorStore::new().with_file_path(path))
-                    }
+                    crate::config::PersistenceMode::Create => None,
                     crate::config::PersistenceMode::CreateOrLoad => {
-                        match PersistentVectorStore::load(&path).await {
-                            Ok(store) => Box::new(store),
-                            Err(_) => Box::new(PersistentVectorStore::new().with_file_path(path)),
+                        PersistentVectorStore::load(&path).await.ok()
+                    }
+                };
+                let mut store = match loaded {
+                    Some(mut store) => {
+                        if !Self::stored_model_matches(
+                            &path,
+                            store.embedding_model(),
+                            &rag_gola_config.embeddings.model,
+                        ) {
+                            store.clear().await?;
                         }
+                        store
+                    }
+                    None => PersistentVectorStore::new().with_file_path(path),
+                };
+                // A non-empty store keeps the name of the model its embeddings came from
+                if store.document_count() == 0 {
+                    store = store.with_embedding_model(rag_gola_config.embeddings.model.clone());
+                }
+                Box::new(store.with_quantization(rag_gola_config.vector_store.quantization))
+            }
+            crate::config::VectorStoreType::Hnsw => {
+                let hnsw_config = rag_gola_config.vector_store.hnsw.clone();
+                match &rag_gola_config.vector_store.persistence {
+                    None => Box::new(HnswVectorStore::new(hnsw_config)),
+                    Some(persistence) => {
+                        let path = persistence.vector_store_path.clone();
+                        let loaded = match persistence.mode {
+                            crate::config::PersistenceMode::Create => None,
+                            crate::config::PersistenceMode::Load => {
+                                Some(HnswVectorStore::load(&path).await.map_err(|e| {
+                                    AgentError::ConfigError(format!(
+                                        "Failed to load HNSW index: {}",
+                                        e
+                                    ))
+                                })?)
+                            }
+                            crate::config::PersistenceMode::CreateOrLoad => {
+                                HnswVectorStore::load(&path).await.ok()
+                            }
+                        };
+                        let mut store = match loaded {
+                            Some(mut store) => {
+                                if !Self::stored_model_matches(
+                                    &path,
+                                    store.embedding_model(),
+                                    &rag_gola_config.embeddings.model,
+                                ) {
+                                    store.clear().await?;
+                                }
+                                if store.config().m != hnsw_config.m
+                                    || store.config().ef_construction != hnsw_config.ef_construction
+                                {
+                                    log::warn!(
+                                        "HNSW index at {} was built with {:?}; only ef_search is taken from the configuration",
+                                        path.display(),
+                                        store.config()
+                                    );
+                                }
+                                store.set_ef_search(hnsw_config.ef_search);
+                                store
+                            }
+                            None => HnswVectorStore::new(hnsw_config).with_file_path(path),
+                        };
+                        if store.document_count() == 0 {
+                            store = store.with_embedding_model(rag_gola_config.embeddings.model.clone());
+                        }
+                        Box::new(store.with_quantization(rag_gola_config.vector_store.quantization))
                     }
                 }
             }
         };
 
-        let text_splitter = TextSplitter::new(
-            rag_gola_config.text_processing.chunk_size,
-            rag_gola_config.text_processing.chunk_overlap,
-        );
+        let text_splitter = Self::configure_text_splitter(&rag_gola_config.text_processing)?;
 
         // Use with_components_cached instead of with_components to enable caching
         let mut rag_system = RagSystem::with_components_cached(
@@ -497,69 +723,107 @@ impl AgentFactory {
         )
         .await?;
 
+        if rag_gola_config.retrieval.enable_reranking {
+            rag_system.set_reranker(Self::configure_reranker(config, &rag_gola_config.retrieval, llm)?);
+        }
+
+        // Restore what earlier runs indexed, so that only changed sources are embedded
+        let state_path = rag_gola_config
+            .vector_store
+            .persistence
+            .as_ref()
+            .map(|p| p.system_path.clone());
+        if let Some(state_path) = &state_path {
+            rag_system.load_state(state_path).await?;
+        }
+
+        let mut report = ReindexReport::default();
         for source_config in &rag_gola_config.document_sources {
+            let mut options = LoadOptions {
+                recursive: false,
+                include_extensions: source_config.config.include_extensions.clone(),
+                exclude_patterns: source_config.config.exclude_patterns.clone(),
+                follow_symlinks: source_config.config.follow_symlinks,
+                metadata: source_config.config.metadata.clone(),
+            };
             match &source_config.source_type {
                 crate::config::DocumentSourceType::Files { paths } => {
-                    let path_strs: Vec<String> = paths
-                        .iter()
-                        .map(|p| p.to_string_lossy().into_owned())
-                        .collect();
-                    rag_system
-                        .add_documents_from_paths(&path_strs)
-                        .await
-                        .map_err(|e| {
-                            AgentError::ConfigError(format!(
-                                "Failed to add documents from paths: {}",
-                                e
-                            ))
-                        })?;
+                    report.merge(rag_system.reindex(paths, &options).await.map_err(|e| {
+                        AgentError::ConfigError(format!(
+                            "Failed to add documents from paths: {}",
+                            e
+                        ))
+                    })?);
                 }
-                crate::config::DocumentSourceType::Directory { path, recursive: _ } => {
-                    rag_system
-                        .add_documents_from_paths(&[path.to_string_lossy().into_owned()])
-                        .await
-                        .map_err(|e| {
-                            AgentError::ConfigError(format!(
-                                "Failed to add documents from directory: {}",
-                                e
-                            ))
-                        })?;
+                crate::config::DocumentSourceType::Directory { path, recursive } => {
+                    options.recursive = *recursive;
+                    report.merge(
+                        rag_system
+                            .reindex(std::slice::from_ref(path), &options)
+                            .await
+                            .map_err(|e| {
+                                AgentError::ConfigError(format!(
+                                    "Failed to add documents from directory: {}",
+                                    e
+                                ))
+                            })?,
+                    );
                 }
                 crate::config::DocumentSourceType::Url { url } => {
-                    let content = reqwest::get(url)
+                    let response = crate::http::client()
+                        .get(url)
+                        .send()
                         .await
                         .map_err(|e| {
                             AgentError::ConfigError(format!("Failed to fetch URL {}: {}", url, e))
-                        })?
-                        .text()
-                        .await
-                        .map_err(|e| {
-                            AgentError::ConfigError(format!(
-                                "Failed to read content from URL {}: {}",
-                                url, e
-                            ))
-                        })?;
-                    rag_system
-                        .add_documents(vec![RagDocument::new(content, url.clone())])
-                        .await
-                        .map_err(|e| {
-                            AgentError::ConfigError(format!(
-                                "Failed to add document from URL: {}",
-                                e
-                            ))
                         })?;
+                    let mime_type = response
+                        .headers()
+                        .get(reqwest::header::CONTENT_TYPE)
+                        .and_then(|value| value.to_str().ok())
+                        .map(str::to_string);
+                    let bytes = response.bytes().await.map_err(|e| {
+                        AgentError::ConfigError(format!(
+                            "Failed to read content from URL {}: {}",
+                            url, e
+                        ))
+                    })?;
+                    report.merge(
+                        rag_system
+                            .index_content(url, bytes.to_vec(), mime_type.as_deref(), options.metadata)
+                            .await
+                            .map_err(|e| {
+                                AgentError::ConfigError(format!(
+                                    "Failed to add document from URL: {}",
+                                    e
+                                ))
+                            })?,
+                    );
                 }
                 crate::config::DocumentSourceType::Inline { content, name } => {
-                    rag_system
-                        .add_documents(vec![RagDocument::new(content.clone(), name.clone())])
-                        .await
-                        .map_err(|e| {
-                            AgentError::ConfigError(format!("Failed to add inline document: {}", e))
-                        })?;
+                    let document =
+                        RagDocument::new(content.clone(), name.clone()).with_metadata(options.metadata);
+                    report.merge(rag_system.index_document(document).await.map_err(|e| {
+                        AgentError::ConfigError(format!("Failed to add inline document: {}", e))
+                    })?);
                 }
             }
         }
 
+        // Sources dropped from the configuration since the last run
+        let configured: HashSet<String> = report.seen_sources().map(str::to_string).collect();
+        report.merge(rag_system.prune_sources(&configured).await?);
+        log::info!("Indexed RAG sources: {}", report);
+
+        if let Some(state_path) = &state_path {
+            if let Some(parent) = state_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                let _ = tokio::fs::create_dir_all(parent).await;
+            }
+            if let Err(e) = rag_system.save(state_path).await {
+                log::warn!("Failed to save RAG state to {}: {}", state_path.display(), e);
+            }
+        }
+
         // Explicitly save the cache after initial document loading
         let current_rag_config = rag_system.config();
         if current_rag_config.embedding_cache.persistent {
@@ -593,7 +857,39 @@ impl AgentFactory {
             );
         }
 
-        Ok(Box::new(rag_system))
+        Ok((Box::new(rag_system), report))
+    }
+
+    fn configure_reranker(
+        config: &GolaConfig,
+        retrieval: &crate::config::RetrievalConfig,
+        llm: Arc<dyn LLM>,
+    ) -> Result<Arc<dyn Reranker>, AgentError> {
+        let model = retrieval.reranker_model.clone();
+        let reranker: Arc<dyn Reranker> = match &retrieval.reranker {
+            crate::config::RerankerConfig::Llm => {
+                let llm = match (&model, &config.llm) {
+                    (Some(model), Some(llm_config)) => {
+                        let mut llm_config = llm_config.clone();
+                        llm_config.model = model.clone();
+                        crate::llm::providers::create_llm_client(&llm_config)?
+                    }
+                    _ => llm,
+                };
+                Arc::new(LlmReranker::new(llm))
+            }
+            crate::config::RerankerConfig::Http { endpoint, auth } => {
+                let api_key = auth.api_key.clone().or_else(|| {
+                    auth.api_key_env
+                        .as_ref()
+                        .and_then(|var| std::env::var(var).ok())
+                });
+                Arc::new(HttpReranker::new(endpoint.clone(), api_key, model))
+            }
+            crate::config::RerankerConfig::Lexical => Arc::new(LexicalReranker::new()),
+        };
+        log::info!("Reranking retrieval results with {:?}", retrieval.reranker);
+        Ok(reranker)
     }
 
     /// Convert execution_environment to execution_type for backward compatibility with runtime manager
@@ -43,7 +43,7 @@ pub struct AuthorizationClient {
 impl AuthorizationClient {
     pub fn new(server_url: String) -> Self {
         Self {
-            client: Client::new(),
+            client: crate::http::client(),
             server_url,
             mode: Arc::new(Mutex::new(ToolAuthorizationMode::Ask)),
             is_polling: Arc::new(Mutex::new(false)),
@@ -0,0 +1,113 @@
+//! Cassette-backed LLM wrapper
+//!
+//! In record mode every call is forwarded to the wrapped client and the
+//! exchange is appended to the cassette. In replay mode no client is needed;
+//! responses are served from the cassette by normalized request.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::Cassette;
+use crate::core_types::{LLMResponse, Message};
+use crate::errors::AgentError;
+use crate::llm::{ToolMetadata, LLM};
+
+pub struct CassetteLLM {
+    inner: Option<Arc<dyn LLM>>,
+    cassette: Arc<Cassette>,
+}
+
+impl CassetteLLM {
+    /// Wraps a live client and records its traffic
+    pub fn recording(inner: Arc<dyn LLM>, cassette: Arc<Cassette>) -> Self {
+        Self {
+            inner: Some(inner),
+            cassette,
+        }
+    }
+
+    /// Serves responses from the cassette without a live client
+    pub fn replaying(cassette: Arc<Cassette>) -> Self {
+        Self {
+            inner: None,
+            cassette,
+        }
+    }
+}
+
+#[async_trait]
+impl LLM for CassetteLLM {
+    async fn generate(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolMetadata>>,
+    ) -> Result<LLMResponse, AgentError> {
+        let inner = match &self.inner {
+            Some(inner) if !self.cassette.is_replaying() => inner,
+            _ => return self.cassette.replay_llm(&messages, tools.as_deref()),
+        };
+
+        let result = inner.generate(messages.clone(), tools.clone()).await;
+        if let Err(e) = self.cassette.record_llm(&messages, tools.as_deref(), &result) {
+            log::error!("Failed to record LLM interaction: {}", e);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cassette::CassetteConfig;
+    use crate::core_types::Role;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingLLM {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl LLM for CountingLLM {
+        async fn generate(
+            &self,
+            messages: Vec<Message>,
+            _tools: Option<Vec<ToolMetadata>>,
+        ) -> Result<LLMResponse, AgentError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(LLMResponse {
+                content: Some(format!("echo: {}", messages[0].content)),
+                tool_calls: None,
+                finish_reason: Some("stop".to_string()),
+                usage: None,
+                reasoning: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recorded_session_replays_without_live_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("llm.json");
+        let messages = vec![Message {
+            role: Role::User,
+            content: "ping".to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            reasoning: None,
+        }];
+
+        let live = Arc::new(CountingLLM { calls: AtomicUsize::new(0) });
+        let recorder = Arc::new(Cassette::open(&CassetteConfig::record(&path)).unwrap());
+        let recording = CassetteLLM::recording(live.clone(), recorder);
+        let recorded = recording.generate(messages.clone(), None).await.unwrap();
+        assert_eq!(live.calls.load(Ordering::SeqCst), 1);
+
+        let player = Arc::new(Cassette::open(&CassetteConfig::replay(&path)).unwrap());
+        let replaying = CassetteLLM::replaying(player);
+        let replayed = replaying.generate(messages, None).await.unwrap();
+
+        assert_eq!(replayed.content, recorded.content);
+        assert_eq!(replayed.finish_reason, Some("stop".to_string()));
+    }
+}
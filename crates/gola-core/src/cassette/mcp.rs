@@ -0,0 +1,129 @@
+//! Cassette-backed MCP client wrapper
+//!
+//! Mirrors [`CassetteLLM`](super::CassetteLLM) for MCP servers: tool discovery
+//! and tool calls are recorded per server name, and replayed without spawning
+//! the server process.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::Cassette;
+use crate::errors::AgentError;
+use crate::tools::{MCPClientTrait, MCPToolInfo};
+
+pub struct CassetteMCPClient {
+    inner: Option<Arc<dyn MCPClientTrait>>,
+    server_name: String,
+    cassette: Arc<Cassette>,
+}
+
+impl CassetteMCPClient {
+    /// Wraps a connected client and records its traffic
+    pub fn recording(inner: Arc<dyn MCPClientTrait>, server_name: String, cassette: Arc<Cassette>) -> Self {
+        Self {
+            inner: Some(inner),
+            server_name,
+            cassette,
+        }
+    }
+
+    /// Serves tool lists and results from the cassette without a live server
+    pub fn replaying(server_name: String, cassette: Arc<Cassette>) -> Self {
+        Self {
+            inner: None,
+            server_name,
+            cassette,
+        }
+    }
+
+    fn live_client(&self) -> Option<&Arc<dyn MCPClientTrait>> {
+        self.inner.as_ref().filter(|_| !self.cassette.is_replaying())
+    }
+}
+
+#[async_trait]
+impl MCPClientTrait for CassetteMCPClient {
+    async fn list_tools(&self) -> Result<Vec<MCPToolInfo>, AgentError> {
+        let Some(inner) = self.live_client() else {
+            return self.cassette.replay_list_tools(&self.server_name);
+        };
+
+        let result = inner.list_tools().await;
+        if let Err(e) = self.cassette.record_list_tools(&self.server_name, &result) {
+            log::error!("Failed to record MCP tool list for '{}': {}", self.server_name, e);
+        }
+        result
+    }
+
+    async fn call_tool(&self, tool_name: &str, arguments: Value) -> Result<String, AgentError> {
+        let Some(inner) = self.live_client() else {
+            return self.cassette.replay_call_tool(&self.server_name, tool_name, &arguments);
+        };
+
+        let result = inner.call_tool(tool_name, arguments.clone()).await;
+        if let Err(e) = self
+            .cassette
+            .record_call_tool(&self.server_name, tool_name, &arguments, &result)
+        {
+            log::error!("Failed to record MCP call '{}' on '{}': {}", tool_name, self.server_name, e);
+        }
+        result
+    }
+
+    async fn is_connected(&self) -> bool {
+        match self.live_client() {
+            Some(inner) => inner.is_connected().await,
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cassette::CassetteConfig;
+    use crate::tools::MockMCPClient;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_mcp_session_replays_without_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mcp.json");
+
+        let recorder = Arc::new(Cassette::open(&CassetteConfig::record(&path)).unwrap());
+        let recording = CassetteMCPClient::recording(
+            Arc::new(MockMCPClient::new()),
+            "mock".to_string(),
+            recorder,
+        );
+        let tools = recording.list_tools().await.unwrap();
+        let output = recording
+            .call_tool("mock_tool_1", json!({"input": "abc"}))
+            .await
+            .unwrap();
+
+        let player = Arc::new(Cassette::open(&CassetteConfig::replay(&path)).unwrap());
+        let replaying = CassetteMCPClient::replaying("mock".to_string(), player);
+        assert!(replaying.is_connected().await);
+
+        let replayed_tools = replaying.list_tools().await.unwrap();
+        assert_eq!(replayed_tools.len(), tools.len());
+        assert_eq!(replayed_tools[0].name, "mock_tool_1");
+
+        // Tool lists may be requested repeatedly during discovery
+        assert_eq!(replaying.list_tools().await.unwrap().len(), tools.len());
+
+        let replayed_output = replaying
+            .call_tool("mock_tool_1", json!({"input": "abc"}))
+            .await
+            .unwrap();
+        assert_eq!(replayed_output, output);
+
+        assert!(replaying
+            .call_tool("mock_tool_1", json!({"input": "different"}))
+            .await
+            .is_err());
+    }
+}
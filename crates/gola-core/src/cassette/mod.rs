@@ -0,0 +1,587 @@
+//! Record/replay cassettes for deterministic agent runs
+//!
+//! A cassette captures every request/response pair exchanged with the language
+//! model and with MCP servers during a run, and can later serve those responses
+//! back without touching the network. Requests are matched on a normalized form
+//! (whitespace-collapsed message content, positional tool call ids, tools sorted
+//! by name, canonical JSON arguments) so that incidental differences such as
+//! random call ids or tool registration order do not break replay.
+//!
+//! On disk a cassette is JSON Lines: a header with the format version, then one
+//! interaction per line, appended as soon as it is captured.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::core_types::{LLMResponse, Message};
+use crate::errors::AgentError;
+use crate::llm::ToolMetadata;
+use crate::tools::MCPToolInfo;
+
+pub mod llm;
+pub mod mcp;
+
+pub use llm::CassetteLLM;
+pub use mcp::CassetteMCPClient;
+
+const CASSETTE_FORMAT_VERSION: u32 = 2;
+
+/// Whether a cassette captures live traffic or serves previously captured traffic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// Location and mode of a cassette file
+#[derive(Debug, Clone)]
+pub struct CassetteConfig {
+    pub path: PathBuf,
+    pub mode: CassetteMode,
+}
+
+impl CassetteConfig {
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), mode: CassetteMode::Record }
+    }
+
+    pub fn replay(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), mode: CassetteMode::Replay }
+    }
+}
+
+/// Outcome of a recorded call, so that failures replay as failures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedOutcome<T> {
+    Ok(T),
+    Err(AgentError),
+}
+
+impl<T: Clone> RecordedOutcome<T> {
+    fn from_result(result: &Result<T, AgentError>) -> Self {
+        match result {
+            Ok(value) => RecordedOutcome::Ok(value.clone()),
+            Err(e) => RecordedOutcome::Err(e.clone()),
+        }
+    }
+
+    fn into_result(self) -> Result<T, AgentError> {
+        match self {
+            RecordedOutcome::Ok(value) => Ok(value),
+            RecordedOutcome::Err(error) => Err(error),
+        }
+    }
+}
+
+/// A single captured exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Interaction {
+    Llm {
+        key: String,
+        request: Value,
+        response: RecordedOutcome<LLMResponse>,
+    },
+    McpListTools {
+        server: String,
+        response: RecordedOutcome<Vec<MCPToolInfo>>,
+    },
+    McpCallTool {
+        server: String,
+        key: String,
+        tool_name: String,
+        arguments: Value,
+        response: RecordedOutcome<String>,
+    },
+}
+
+impl Interaction {
+    fn matches_llm(&self, wanted: &str) -> bool {
+        matches!(self, Interaction::Llm { key, .. } if key == wanted)
+    }
+
+    fn matches_list_tools(&self, wanted: &str) -> bool {
+        matches!(self, Interaction::McpListTools { server, .. } if server == wanted)
+    }
+
+    fn matches_call_tool(&self, wanted_server: &str, wanted_key: &str) -> bool {
+        matches!(self, Interaction::McpCallTool { server, key, .. } if server == wanted_server && key == wanted_key)
+    }
+}
+
+/// First line of a cassette file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteHeader {
+    pub version: u32,
+}
+
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    consumed: Vec<bool>,
+    /// Open for appending while recording
+    writer: Option<File>,
+}
+
+/// Shared cassette used by the LLM and MCP wrappers of a single run
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    state: Mutex<CassetteState>,
+}
+
+impl Cassette {
+    /// Opens a cassette. Replay requires the file to exist; record truncates the
+    /// file and appends interactions to it as they are captured.
+    pub fn open(config: &CassetteConfig) -> Result<Self, AgentError> {
+        let (interactions, writer) = match config.mode {
+            CassetteMode::Replay => (Self::load_file(&config.path)?, None),
+            CassetteMode::Record => (Vec::new(), Some(Self::create_file(&config.path)?)),
+        };
+        let consumed = vec![false; interactions.len()];
+
+        log::info!(
+            "Opened cassette {} in {:?} mode with {} interactions",
+            config.path.display(),
+            config.mode,
+            interactions.len()
+        );
+
+        Ok(Self {
+            path: config.path.clone(),
+            mode: config.mode,
+            state: Mutex::new(CassetteState { interactions, consumed, writer }),
+        })
+    }
+
+    fn create_file(path: &Path) -> Result<File, AgentError> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        let mut file = File::create(path).map_err(|e| {
+            AgentError::IoError(format!("Failed to create cassette {}: {}", path.display(), e))
+        })?;
+        let header = CassetteHeader { version: CASSETTE_FORMAT_VERSION };
+        writeln!(file, "{}", json!(header)).map_err(|e| {
+            AgentError::IoError(format!("Failed to write cassette {}: {}", path.display(), e))
+        })?;
+        Ok(file)
+    }
+
+    fn load_file(path: &Path) -> Result<Vec<Interaction>, AgentError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AgentError::IoError(format!("Failed to read cassette {}: {}", path.display(), e))
+        })?;
+        let invalid = |e: serde_json::Error| {
+            AgentError::ParsingError(format!("Invalid cassette {}: {}", path.display(), e))
+        };
+
+        let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+        // Older cassettes are a single JSON document, so read the version from the whole file
+        let header: CassetteHeader = match lines.next().map(serde_json::from_str) {
+            Some(Ok(header)) => header,
+            _ => serde_json::from_str(&content).map_err(invalid)?,
+        };
+        if header.version != CASSETTE_FORMAT_VERSION {
+            return Err(AgentError::ConfigError(format!(
+                "Unsupported cassette version {} in {} (expected {})",
+                header.version,
+                path.display(),
+                CASSETTE_FORMAT_VERSION
+            )));
+        }
+
+        lines
+            .map(|line| serde_json::from_str(line).map_err(invalid))
+            .collect()
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.mode == CassetteMode::Replay
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of interactions currently held by the cassette
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().interactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of recorded interactions that were never served during replay
+    pub fn unplayed_count(&self) -> usize {
+        self.state.lock().unwrap().consumed.iter().filter(|c| !**c).count()
+    }
+
+    fn record(&self, interaction: Interaction) -> Result<(), AgentError> {
+        let mut state = self.state.lock().unwrap();
+
+        // Append every interaction as it happens so the cassette survives abrupt exits
+        let line = serde_json::to_string(&interaction).map_err(|e| {
+            AgentError::InternalError(format!("Failed to serialize cassette: {}", e))
+        })?;
+        let writer = state.writer.as_mut().ok_or_else(|| {
+            AgentError::InternalError(format!("Cassette {} is not recording", self.path.display()))
+        })?;
+        writeln!(writer, "{}", line).map_err(|e| {
+            AgentError::IoError(format!("Failed to write cassette {}: {}", self.path.display(), e))
+        })?;
+
+        state.interactions.push(interaction);
+        state.consumed.push(true);
+        Ok(())
+    }
+
+    /// Takes the first unplayed interaction accepted by `matches`. When
+    /// `reuse_played` is set and every match was already served, the most
+    /// recent match is served again.
+    fn take(&self, matches: impl Fn(&Interaction) -> bool, reuse_played: bool) -> Option<Interaction> {
+        let mut state = self.state.lock().unwrap();
+        let CassetteState { interactions, consumed, .. } = &mut *state;
+
+        if let Some(index) = (0..interactions.len())
+            .find(|&i| !consumed[i] && matches(&interactions[i]))
+        {
+            consumed[index] = true;
+            return Some(interactions[index].clone());
+        }
+
+        if reuse_played {
+            return interactions.iter().rev().find(|i| matches(i)).cloned();
+        }
+
+        None
+    }
+
+    pub(crate) fn record_llm(
+        &self,
+        messages: &[Message],
+        tools: Option<&[ToolMetadata]>,
+        result: &Result<LLMResponse, AgentError>,
+    ) -> Result<(), AgentError> {
+        let request = normalize_llm_request(messages, tools);
+        self.record(Interaction::Llm {
+            key: hash_value(&request),
+            request,
+            response: RecordedOutcome::from_result(result),
+        })
+    }
+
+    pub(crate) fn replay_llm(
+        &self,
+        messages: &[Message],
+        tools: Option<&[ToolMetadata]>,
+    ) -> Result<LLMResponse, AgentError> {
+        let request = normalize_llm_request(messages, tools);
+        let key = hash_value(&request);
+
+        match self.take(|i| i.matches_llm(&key), false) {
+            Some(Interaction::Llm { response, .. }) => response.into_result(),
+            _ => Err(AgentError::LLMError(format!(
+                "Cassette {} has no recorded LLM response for request {}",
+                self.path.display(),
+                key
+            ))),
+        }
+    }
+
+    pub(crate) fn record_list_tools(
+        &self,
+        server: &str,
+        result: &Result<Vec<MCPToolInfo>, AgentError>,
+    ) -> Result<(), AgentError> {
+        self.record(Interaction::McpListTools {
+            server: server.to_string(),
+            response: RecordedOutcome::from_result(result),
+        })
+    }
+
+    pub(crate) fn replay_list_tools(&self, server: &str) -> Result<Vec<MCPToolInfo>, AgentError> {
+        match self.take(|i| i.matches_list_tools(server), true) {
+            Some(Interaction::McpListTools { response, .. }) => response.into_result(),
+            _ => Err(AgentError::MCPError(format!(
+                "Cassette {} has no recorded tool list for MCP server '{}'",
+                self.path.display(),
+                server
+            ))),
+        }
+    }
+
+    pub(crate) fn record_call_tool(
+        &self,
+        server: &str,
+        tool_name: &str,
+        arguments: &Value,
+        result: &Result<String, AgentError>,
+    ) -> Result<(), AgentError> {
+        self.record(Interaction::McpCallTool {
+            server: server.to_string(),
+            key: tool_call_key(tool_name, arguments),
+            tool_name: tool_name.to_string(),
+            arguments: arguments.clone(),
+            response: RecordedOutcome::from_result(result),
+        })
+    }
+
+    pub(crate) fn replay_call_tool(
+        &self,
+        server: &str,
+        tool_name: &str,
+        arguments: &Value,
+    ) -> Result<String, AgentError> {
+        let key = tool_call_key(tool_name, arguments);
+
+        match self.take(|i| i.matches_call_tool(server, &key), false) {
+            Some(Interaction::McpCallTool { response, .. }) => response.into_result(),
+            _ => Err(AgentError::MCPError(format!(
+                "Cassette {} has no recorded result for '{}' on MCP server '{}' with arguments {}",
+                self.path.display(),
+                tool_name,
+                server,
+                arguments
+            ))),
+        }
+    }
+}
+
+/// Builds the normalized form of an LLM request used for matching.
+pub fn normalize_llm_request(messages: &[Message], tools: Option<&[ToolMetadata]>) -> Value {
+    let mut call_ids: Vec<String> = Vec::new();
+    let mut placeholder = |id: &Option<String>| -> Value {
+        match id {
+            Some(id) => {
+                let index = match call_ids.iter().position(|known| known == id) {
+                    Some(index) => index,
+                    None => {
+                        call_ids.push(id.clone());
+                        call_ids.len() - 1
+                    }
+                };
+                json!(format!("call_{}", index))
+            }
+            None => Value::Null,
+        }
+    };
+
+    let normalized_messages: Vec<Value> = messages
+        .iter()
+        .map(|message| {
+            let tool_calls: Vec<Value> = message
+                .tool_calls
+                .iter()
+                .flatten()
+                .map(|call| {
+                    json!({
+                        "id": placeholder(&call.id),
+                        "name": call.name,
+                        "arguments": call.arguments,
+                    })
+                })
+                .collect();
+
+            json!({
+                "role": message.role,
+                "content": normalize_text(&message.content),
+                "tool_call_id": placeholder(&message.tool_call_id),
+                "tool_calls": tool_calls,
+            })
+        })
+        .collect();
+
+    let mut normalized_tools: Vec<Value> = tools
+        .unwrap_or_default()
+        .iter()
+        .map(|tool| json!({ "name": tool.name, "input_schema": tool.input_schema }))
+        .collect();
+    normalized_tools.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+    json!({
+        "messages": normalized_messages,
+        "tools": normalized_tools,
+    })
+}
+
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn tool_call_key(tool_name: &str, arguments: &Value) -> String {
+    hash_value(&json!({ "name": tool_name, "arguments": arguments }))
+}
+
+fn hash_value(value: &Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(value.to_string());
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_types::{Role, ToolCall};
+
+    fn message(role: Role, content: &str) -> Message {
+        Message {
+            role,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            reasoning: None,
+        }
+    }
+
+    fn tool(name: &str) -> ToolMetadata {
+        ToolMetadata {
+            name: name.to_string(),
+            description: format!("{} tool", name),
+            input_schema: json!({"type": "object"}),
+        }
+    }
+
+    #[test]
+    fn test_normalization_ignores_whitespace_and_tool_order() {
+        let a = normalize_llm_request(
+            &[message(Role::User, "What  is\n2 + 2? ")],
+            Some(&[tool("b"), tool("a")]),
+        );
+        let b = normalize_llm_request(
+            &[message(Role::User, "What is 2 + 2?")],
+            Some(&[tool("a"), tool("b")]),
+        );
+        assert_eq!(hash_value(&a), hash_value(&b));
+    }
+
+    #[test]
+    fn test_normalization_replaces_call_ids_positionally() {
+        let conversation = |id: &str| {
+            vec![
+                Message {
+                    role: Role::Assistant,
+                    content: String::new(),
+                    tool_call_id: None,
+                    tool_calls: Some(vec![ToolCall {
+                        id: Some(id.to_string()),
+                        name: "calculator".to_string(),
+                        arguments: json!({"expression": "2+2"}),
+                    }]),
+                    reasoning: None,
+                },
+                Message {
+                    role: Role::Tool,
+                    content: "4".to_string(),
+                    tool_call_id: Some(id.to_string()),
+                    tool_calls: None,
+                    reasoning: None,
+                },
+            ]
+        };
+
+        let a = normalize_llm_request(&conversation("call_abc"), None);
+        let b = normalize_llm_request(&conversation("call_xyz"), None);
+        assert_eq!(a, b);
+        assert_eq!(a["messages"][1]["tool_call_id"], "call_0");
+    }
+
+    #[test]
+    fn test_record_then_replay_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassettes").join("run.json");
+        let messages = vec![message(Role::User, "hello")];
+        let response = LLMResponse {
+            content: Some("hi".to_string()),
+            tool_calls: None,
+            finish_reason: None,
+            usage: None,
+            reasoning: None,
+        };
+
+        let recorder = Cassette::open(&CassetteConfig::record(&path)).unwrap();
+        recorder.record_llm(&messages, None, &Ok(response)).unwrap();
+        recorder
+            .record_call_tool("time", "get_time", &json!({"tz": "UTC"}), &Ok("noon".to_string()))
+            .unwrap();
+        assert_eq!(recorder.len(), 2);
+
+        let player = Cassette::open(&CassetteConfig::replay(&path)).unwrap();
+        assert!(player.is_replaying());
+        assert_eq!(player.unplayed_count(), 2);
+
+        let replayed = player.replay_llm(&messages, None).unwrap();
+        assert_eq!(replayed.content, Some("hi".to_string()));
+
+        let result = player.replay_call_tool("time", "get_time", &json!({"tz": "UTC"})).unwrap();
+        assert_eq!(result, "noon");
+        assert_eq!(player.unplayed_count(), 0);
+
+        // Each recorded LLM exchange is served once
+        assert!(player.replay_llm(&messages, None).is_err());
+    }
+
+    #[test]
+    fn test_replay_reproduces_recorded_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.json");
+
+        let recorder = Cassette::open(&CassetteConfig::record(&path)).unwrap();
+        recorder
+            .record_call_tool("fs", "read", &json!({}), &Err(AgentError::MCPError("denied".to_string())))
+            .unwrap();
+
+        let player = Cassette::open(&CassetteConfig::replay(&path)).unwrap();
+        let err = player.replay_call_tool("fs", "read", &json!({})).unwrap_err();
+        assert!(matches!(&err, AgentError::MCPError(message) if message == "denied"));
+        assert_eq!(err.to_string(), "MCP client error: denied");
+    }
+
+    #[test]
+    fn test_recording_appends_one_line_per_interaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.jsonl");
+
+        let recorder = Cassette::open(&CassetteConfig::record(&path)).unwrap();
+        recorder.record_list_tools("time", &Ok(Vec::new())).unwrap();
+        recorder
+            .record_call_tool("time", "get_time", &json!({}), &Ok("noon".to_string()))
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], r#"{"version":2}"#);
+        assert!(lines[2].contains("get_time"));
+    }
+
+    #[test]
+    fn test_replay_rejects_old_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old.json");
+        std::fs::write(&path, "{\n  \"version\": 1,\n  \"interactions\": []\n}").unwrap();
+
+        let result = Cassette::open(&CassetteConfig::replay(&path));
+        assert!(matches!(result, Err(AgentError::ConfigError(message)) if message.contains("version 1")));
+    }
+
+    #[test]
+    fn test_replay_requires_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = Cassette::open(&CassetteConfig::replay(dir.path().join("missing.json")));
+        assert!(matches!(result, Err(AgentError::IoError(_))));
+    }
+}
@@ -31,6 +31,7 @@ impl ConfigBuilder {
                 llm: Some(LlmConfig {
                     provider: LlmProvider::OpenAI,
                     model: "gpt-4.1-mini".to_string(),
+                    base_url: None,
                     parameters: ModelParameters::default(),
                     auth: LlmAuth::default(),
                 }),
@@ -40,6 +41,9 @@ impl ConfigBuilder {
                 environment: EnvironmentConfig::default(),
                 logging: LoggingConfig::default(),
                 tracing: TracingConfig::default(),
+                http: Default::default(),
+                hooks: Vec::new(),
+                dry_run: Default::default(),
             },
         }
     }
@@ -211,6 +215,7 @@ impl ConfigBuilder {
             self.config.llm = Some(LlmConfig {
                 provider,
                 model: model.into(),
+                base_url: None,
                 parameters: ModelParameters::default(),
                 auth: LlmAuth::default(),
             });
@@ -269,6 +274,7 @@ impl ConfigBuilder {
             self.config.llm = Some(LlmConfig {
                 provider: LlmProvider::OpenAI,
                 model: "gpt-4.1-mini".to_string(),
+                base_url: None,
                 parameters: ModelParameters::default(),
                 auth: LlmAuth::default(),
             });
@@ -159,6 +159,9 @@ impl ProfileManager {
             environment: override_config.environment,
             logging: override_config.logging,
             tracing: override_config.tracing,
+            http: Default::default(),
+            hooks: Vec::new(),
+            dry_run: Default::default(),
         })
     }
     
@@ -260,11 +263,15 @@ impl ProfileManager {
                         tool_timeout: 60,
                         continue_on_error: false,
                         memory: MemoryConfig::default(),
+                        context_budget: None,
+                        review: None,
+                        loop_detection: LoopDetectionConfig::default(),
                     },
                 },
                 llm: Some(LlmConfig {
                     provider: LlmProvider::OpenAI,
                     model: "gpt-4.1-mini".to_string(),
+                    base_url: None,
                     auth: LlmAuth {
                         api_key: None,
                         api_key_env: Some("OPENAI_API_KEY".to_string()),
@@ -296,6 +303,9 @@ impl ProfileManager {
                     trace_file: "gola_trace.jsonl".to_string(),
                     model_provider: "openai".to_string(),
                 },
+                http: Default::default(),
+                hooks: Vec::new(),
+                dry_run: Default::default(),
             },
             metadata: Some(ProfileMetadata {
                 created_at: Some(chrono::Utc::now().to_rfc3339()),
@@ -335,11 +345,15 @@ impl ProfileManager {
                         tool_timeout: 30,
                         continue_on_error: false,
                         memory: MemoryConfig::default(),
+                        context_budget: None,
+                        review: None,
+                        loop_detection: LoopDetectionConfig::default(),
                     },
                 },
                 llm: Some(LlmConfig {
                     provider: LlmProvider::Anthropic,
                     model: "claude-3-5-sonnet-latest".to_string(),
+                    base_url: None,
                     auth: LlmAuth {
                         api_key: None,
                         api_key_env: Some("ANTHROPIC_API_KEY".to_string()),
@@ -376,6 +390,9 @@ impl ProfileManager {
                     trace_file: "gola_trace.jsonl".to_string(),
                     model_provider: "openai".to_string(),
                 },
+                http: Default::default(),
+                hooks: Vec::new(),
+                dry_run: Default::default(),
             },
             metadata: Some(ProfileMetadata {
                 created_at: Some(chrono::Utc::now().to_rfc3339()),
@@ -415,11 +432,15 @@ impl ProfileManager {
                         tool_timeout: 120,
                         continue_on_error: false,
                         memory: MemoryConfig::default(),
+                        context_budget: None,
+                        review: None,
+                        loop_detection: LoopDetectionConfig::default(),
                     },
                 },
                 llm: Some(LlmConfig {
                     provider: LlmProvider::Anthropic,
                     model: "claude-3-5-sonnet-latest".to_string(),
+                    base_url: None,
                     auth: LlmAuth {
                         api_key: None,
                         api_key_env: Some("ANTHROPIC_API_KEY".to_string()),
@@ -460,6 +481,9 @@ impl ProfileManager {
                         chunk_size: 1500,
                         chunk_overlap: 300,
                         splitter_type: TextSplitterType::Basic,
+                        size_unit: Default::default(),
+                        semantic: Default::default(),
+                        chunk_headers: Default::default(),
                     },
                     vector_store: VectorStoreConfig {
                         store_type: VectorStoreType::InMemory,
@@ -468,6 +492,8 @@ impl ProfileManager {
                             vector_store_path: PathBuf::from("./data/research_vector_store"),
                             mode: PersistenceMode::CreateOrLoad,
                         }),
+                        hnsw: Default::default(),
+                        quantization: Default::default(),
                     },
                     document_sources: vec![],
                     retrieval: RetrievalConfig {
@@ -475,6 +501,10 @@ impl ProfileManager {
                         similarity_threshold: 0.6,
                         enable_reranking: true,
                         reranker_model: None,
+                        mode: Default::default(),
+                        hybrid: Default::default(),
+                        reranker: Default::default(),
+                        rerank_candidates: 20,
                     },
                     embedding_cache: crate::rag::cache::EmbeddingCacheConfig::default(),
                 }),
@@ -496,6 +526,9 @@ impl ProfileManager {
                     trace_file: "gola_trace.jsonl".to_string(),
                     model_provider: "openai".to_string(),
                 },
+                http: Default::default(),
+                hooks: Vec::new(),
+                dry_run: Default::default(),
             },
             metadata: Some(ProfileMetadata {
                 created_at: Some(chrono::Utc::now().to_rfc3339()),
@@ -53,6 +53,9 @@ impl DefaultProvider<GolaConfig> for ConventionGolaProvider {
                     tool_timeout: if context.project_info.is_rust_project { 60 } else { 30 },
                     continue_on_error: false,
                     memory: MemoryConfig::default(),
+                    context_budget: None,
+                    review: None,
+                    loop_detection: LoopDetectionConfig::default(),
                 },
             },
             llm: Some(ConventionLlmProvider::new().provide_defaults(context)?),
@@ -67,6 +70,9 @@ impl DefaultProvider<GolaConfig> for ConventionGolaProvider {
                 trace_file: "gola_trace.jsonl".to_string(),
                 model_provider: "openai".to_string(),
             },
+            http: Default::default(),
+            hooks: Vec::new(),
+            dry_run: Default::default(),
         };
         
         Ok(config)
@@ -152,6 +158,7 @@ impl DefaultProvider<LlmConfig> for ConventionLlmProvider {
         Ok(LlmConfig {
             provider,
             model,
+            base_url: None,
             auth: LlmAuth {
                 api_key: None,
                 api_key_env: Some(api_key_env),
@@ -166,7 +173,16 @@ impl DefaultProvider<LlmConfig> for ConventionLlmProvider {
                 stop_sequences: Vec::new(),
                 anthropic_version,
                 safety_settings: None,
+                tool_choice: None,
+                response_format: None,
                 system_message: None,
+                reasoning_budget: None,
+                num_ctx: None,
+                keep_alive: None,
+                tool_protocol: None,
+                api: None,
+                store: false,
+                prompt_cache: None,
             },
         })
     }
@@ -182,6 +198,8 @@ impl ConventionLlmProvider {
             LlmProvider::OpenAI => "OPENAI_API_KEY".to_string(),
             LlmProvider::Anthropic => "ANTHROPIC_API_KEY".to_string(),
             LlmProvider::Gemini => "GEMINI_API_KEY".to_string(),
+            LlmProvider::Ollama => "OLLAMA_API_KEY".to_string(),
+            LlmProvider::AzureOpenAI { .. } => "AZURE_OPENAI_API_KEY".to_string(),
             LlmProvider::Custom { .. } => "CUSTOM_API_KEY".to_string(),
         }
     }
@@ -2,7 +2,14 @@
 
 use super::types::{DetectedProvider, DetectionConfidence};
 use crate::config::types::LlmProvider;
+use crate::llm::providers::ollama;
 use std::env;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// How long to wait for a local Ollama server before giving up
+const OLLAMA_PROBE_TIMEOUT: Duration = Duration::from_millis(300);
 
 /// Detector for LLM providers in environment variables
 pub struct LlmProviderDetector {
@@ -29,6 +36,11 @@ impl LlmProviderDetector {
         // Detect Gemini providers
         providers.extend(self.detect_gemini_providers(&env_vars));
         
+        // Fall back to a local Ollama server when no hosted provider is configured
+        if providers.is_empty() {
+            providers.extend(self.detect_ollama_provider(&env_vars));
+        }
+        
         // Sort by confidence (highest first)
         providers.sort_by(|a, b| b.confidence.cmp(&a.confidence));
         
@@ -186,6 +198,32 @@ impl LlmProviderDetector {
         providers
     }
     
+    /// Detect a running Ollama server and the models installed on it
+    fn detect_ollama_provider(&self, env_vars: &std::collections::HashMap<String, String>) -> Option<DetectedProvider> {
+        let base_url = ollama::base_url_from_env();
+        let models = match probe_ollama(&base_url) {
+            Some(models) => models,
+            None => {
+                log::debug!("No Ollama server found at {}", base_url);
+                return None;
+            }
+        };
+        
+        let mut provider = DetectedProvider::new(
+            LlmProvider::Ollama,
+            "OLLAMA_HOST".to_string(),
+            DetectionConfidence::Low,
+        )
+        .with_base_url(base_url);
+        
+        // Prefer an explicit model, then whatever is installed
+        if let Some(model) = env_vars.get("OLLAMA_MODEL").cloned().or_else(|| models.into_iter().next()) {
+            provider = provider.with_config("model".to_string(), model);
+        }
+        
+        Some(provider)
+    }
+    
     /// Remove duplicate providers, keeping the highest confidence detection
     fn deduplicate_providers(&self, mut providers: Vec<DetectedProvider>) -> Vec<DetectedProvider> {
         // Sort by provider type and confidence
@@ -210,6 +248,8 @@ impl LlmProviderDetector {
                 LlmProvider::OpenAI => "openai",
                 LlmProvider::Anthropic => "anthropic", 
                 LlmProvider::Gemini => "gemini",
+                LlmProvider::Ollama => "ollama",
+                LlmProvider::AzureOpenAI { .. } => "azure_openai",
                 LlmProvider::Custom { .. } => "custom",
             };
             
@@ -223,6 +263,30 @@ impl LlmProviderDetector {
     }
 }
 
+/// Fetches `/api/tags` with a short blocking request
+///
+/// Detection runs while defaults are resolved, outside any async runtime, so this
+/// speaks just enough HTTP/1.0 over a plain socket. Only `http://` hosts are probed.
+fn probe_ollama(base_url: &str) -> Option<Vec<String>> {
+    let authority = base_url.strip_prefix("http://")?.trim_end_matches('/');
+    let addr = authority.to_socket_addrs().ok()?.next()?;
+    
+    let mut stream = TcpStream::connect_timeout(&addr, OLLAMA_PROBE_TIMEOUT).ok()?;
+    stream.set_read_timeout(Some(OLLAMA_PROBE_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(OLLAMA_PROBE_TIMEOUT)).ok()?;
+    write!(stream, "GET /api/tags HTTP/1.0\r\nHost: {}\r\n\r\n", authority).ok()?;
+    
+    let mut raw = String::new();
+    stream.read_to_string(&mut raw).ok()?;
+    let (head, body) = raw.split_once("\r\n\r\n")?;
+    if head.split_whitespace().nth(1) != Some("200") {
+        return None;
+    }
+    
+    let tags: ollama::OllamaTags = serde_json::from_str(body).ok()?;
+    Some(tags.models.into_iter().map(|m| m.name).collect())
+}
+
 impl Default for LlmProviderDetector {
     fn default() -> Self {
         Self::new()
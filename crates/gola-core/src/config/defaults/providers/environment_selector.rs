@@ -57,8 +57,9 @@ impl LlmProviderSelector {
                 "openai" => LlmProvider::OpenAI,
                 "anthropic" => LlmProvider::Anthropic,
                 "gemini" => LlmProvider::Gemini,
+                "ollama" => LlmProvider::Ollama,
                 _ => {
-                    log::warn!("Unknown PREFERRED_LLM_PROVIDER value: '{}'. Valid options: openai, anthropic, gemini", preferred);
+                    log::warn!("Unknown PREFERRED_LLM_PROVIDER value: '{}'. Valid options: openai, anthropic, gemini, ollama", preferred);
                     return Ok(None);
                 }
             };
@@ -128,6 +129,7 @@ impl LlmProviderSelector {
             LlmProvider::Anthropic,
             LlmProvider::OpenAI,
             LlmProvider::Gemini,
+            LlmProvider::Ollama,
         ];
         
         for preferred_type in &priority_order {
@@ -41,6 +41,10 @@ mod tests {
         for var in &vars_to_clear {
             env::remove_var(var);
         }
+        
+        // Point Ollama detection at a closed port so a local server doesn't leak into tests
+        env::remove_var("OLLAMA_MODEL");
+        env::set_var("OLLAMA_HOST", "127.0.0.1:1");
     }
 
     fn create_test_provider(provider_type: LlmProvider, env_var: &str) -> DetectedProvider {
@@ -281,6 +285,45 @@ mod tests {
         env::remove_var("GEMINI_API_KEY");
     }
 
+    #[test]
+    #[serial]
+    fn test_detects_local_ollama_without_api_keys() {
+        clear_llm_env_vars();
+        
+        // Stand-in Ollama answering a single /api/tags request
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            use std::io::{Read, Write};
+            let (mut stream, _) = listener.accept().unwrap();
+            // Read the whole request, closing with unread input resets the connection
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let body = r#"{"models":[{"name":"qwen3:8b"},{"name":"llama3.2:latest"}]}"#;
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        });
+        env::set_var("OLLAMA_HOST", addr.to_string());
+        
+        let detector = LlmProviderDetector::new();
+        let providers = detector.detect_providers();
+        server.join().unwrap();
+        
+        assert_eq!(providers.len(), 1);
+        assert!(matches!(providers[0].provider_type, LlmProvider::Ollama));
+        let config = providers[0].to_llm_config().unwrap();
+        assert_eq!(config.model, "qwen3:8b");
+        assert!(config.auth.api_key_env.is_none());
+        assert_eq!(config.base_url, Some(format!("http://{}", addr)));
+        
+        clear_llm_env_vars();
+    }
+
     #[test]
     #[serial]
     fn test_selector_explicit_selection_openai() {
@@ -65,6 +65,8 @@ impl DetectedProvider {
             LlmProvider::OpenAI => "gpt-4.1-mini",
             LlmProvider::Anthropic => "claude-3-5-sonnet-latest", 
             LlmProvider::Gemini => "gemini-2.0-flash",
+            LlmProvider::Ollama => "llama3.2",
+            LlmProvider::AzureOpenAI { .. } => "gpt-4.1-mini",
             LlmProvider::Custom { .. } => "unknown",
         }
     }
@@ -76,14 +78,19 @@ impl DetectedProvider {
             .map(|s| s.clone())
             .unwrap_or_else(|| self.get_default_model().to_string());
             
+        // Ollama is detected from OLLAMA_HOST, which is an address rather than a key
+        let api_key_env = match self.provider_type {
+            LlmProvider::Ollama => None,
+            _ => Some(self.api_key_env.clone()),
+        };
         let auth = LlmAuth {
             api_key: None,
-            api_key_env: Some(self.api_key_env.clone()),
+            api_key_env,
             headers: HashMap::new(),
         };
         
         let parameters = match self.provider_type {
-            LlmProvider::OpenAI => ModelParameters {
+            LlmProvider::OpenAI | LlmProvider::AzureOpenAI { .. } => ModelParameters {
                 temperature: 0.0,
                 max_tokens: 8000,
                 top_p: 0.9,
@@ -93,6 +100,15 @@ impl DetectedProvider {
                 anthropic_version: None,
                 system_message: None,
                 safety_settings: None,
+                tool_choice: None,
+                response_format: None,
+                reasoning_budget: None,
+                num_ctx: None,
+                keep_alive: None,
+                tool_protocol: None,
+                api: None,
+                store: false,
+                prompt_cache: None,
             },
             LlmProvider::Anthropic => ModelParameters {
                 temperature: 0.7,
@@ -104,6 +120,15 @@ impl DetectedProvider {
                 anthropic_version: Some("2023-06-01".to_string()),
                 system_message: None,
                 safety_settings: None,
+                tool_choice: None,
+                response_format: None,
+                reasoning_budget: None,
+                num_ctx: None,
+                keep_alive: None,
+                tool_protocol: None,
+                api: None,
+                store: false,
+                prompt_cache: None,
             },
             LlmProvider::Gemini => ModelParameters {
                 temperature: 0.7,
@@ -115,13 +140,26 @@ impl DetectedProvider {
                 anthropic_version: None,
                 system_message: None,
                 safety_settings: Some(serde_json::json!({"category": "moderate"})),
+                tool_choice: None,
+                response_format: None,
+                reasoning_budget: None,
+                num_ctx: None,
+                keep_alive: None,
+                tool_protocol: None,
+                api: None,
+                store: false,
+                prompt_cache: None,
             },
-            LlmProvider::Custom { .. } => ModelParameters::default(),
+            LlmProvider::Ollama | LlmProvider::Custom { .. } => ModelParameters::default(),
         };
         
         Ok(LlmConfig {
             provider: self.provider_type.clone(),
             model,
+            base_url: match self.provider_type {
+                LlmProvider::Ollama => self.base_url.clone(),
+                _ => None,
+            },
             auth,
             parameters,
         })
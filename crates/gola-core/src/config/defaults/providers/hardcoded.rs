@@ -50,6 +50,9 @@ impl DefaultProvider<GolaConfig> for HardcodedGolaProvider {
                     tool_timeout: 30,
                     continue_on_error: false,
                     memory: MemoryConfig::default(),
+                    context_budget: None,
+                    review: None,
+                    loop_detection: LoopDetectionConfig::default(),
                 },
             },
             llm: Some(HardcodedLlmProvider::new().provide_defaults(context)?),
@@ -64,6 +67,9 @@ impl DefaultProvider<GolaConfig> for HardcodedGolaProvider {
                 trace_file: "gola_trace.jsonl".to_string(),
                 model_provider: "openai".to_string(),
             },
+            http: Default::default(),
+            hooks: Vec::new(),
+            dry_run: Default::default(),
         })
     }
     
@@ -94,6 +100,7 @@ impl DefaultProvider<LlmConfig> for HardcodedLlmProvider {
         Ok(LlmConfig {
             provider: LlmProvider::OpenAI,
             model: "gpt-4.1-mini".to_string(),
+            base_url: None,
             auth: LlmAuth {
                 api_key: None,
                 api_key_env: Some("OPENAI_API_KEY".to_string()),
@@ -109,7 +116,16 @@ impl DefaultProvider<LlmConfig> for HardcodedLlmProvider {
                 // Provider-specific defaults
                 anthropic_version: None,
                 safety_settings: None,
+                tool_choice: None,
+                response_format: None,
                 system_message: None,
+                reasoning_budget: None,
+                num_ctx: None,
+                keep_alive: None,
+                tool_protocol: None,
+                api: None,
+                store: false,
+                prompt_cache: None,
             },
         })
     }
@@ -182,6 +198,9 @@ impl DefaultProvider<RagSystemConfig> for HardcodedRagProvider {
                 chunk_size: 1000,
                 chunk_overlap: 200,
                 splitter_type: TextSplitterType::Basic,
+                size_unit: Default::default(),
+                semantic: Default::default(),
+                chunk_headers: Default::default(),
             },
             vector_store: VectorStoreConfig {
                 store_type: VectorStoreType::InMemory,
@@ -190,6 +209,8 @@ impl DefaultProvider<RagSystemConfig> for HardcodedRagProvider {
                     vector_store_path: PathBuf::from("./data/vector_store"),
                     mode: PersistenceMode::CreateOrLoad,
                 }),
+                hnsw: Default::default(),
+                quantization: Default::default(),
             },
             document_sources: vec![],
             retrieval: RetrievalConfig {
@@ -197,6 +218,10 @@ impl DefaultProvider<RagSystemConfig> for HardcodedRagProvider {
                 similarity_threshold: 0.7,
                 enable_reranking: false,
                 reranker_model: None,
+                mode: Default::default(),
+                hybrid: Default::default(),
+                reranker: Default::default(),
+                rerank_candidates: 20,
             },
             embedding_cache: crate::rag::cache::EmbeddingCacheConfig::default(),
         })
@@ -28,8 +28,7 @@ impl GitHubConfigLoader {
         fs::create_dir_all(&cache_dir)
             .map_err(|e| AgentError::ConfigError(format!("Failed to create cache directory {}: {}", cache_dir.display(), e)))?;
 
-        let mut client_builder = reqwest::Client::builder()
-            .user_agent("gola-cli/0.1.0");
+        let mut client_builder = crate::http::builder();
         
         // For testing with self-signed certificates (e.g., github-mock)
         if std::env::var("GOLA_ACCEPT_INVALID_CERTS").is_ok() {
@@ -36,7 +36,7 @@ impl ConfigLoader {
 
     /// Load configuration from a URL
     pub async fn from_url(url: &str) -> Result<GolaConfig, AgentError> {
-        let client = reqwest::Client::new();
+        let client = crate::http::client();
         let response = client.get(url).send().await.map_err(|e| {
             AgentError::ConfigError(format!(
                 "Failed to fetch configuration from URL {}: {}",
@@ -568,6 +568,10 @@ rag:
   text_processing:
     chunk_size: 500
     chunk_overlap: 100
+    splitter_type: semantic
+    size_unit: tokens
+    semantic:
+      breakpoint_percentile: 90
   retrieval:
     top_k: 3
     similarity_threshold: 0.8
@@ -580,6 +584,10 @@ rag:
         assert!(rag.enabled);
         assert_eq!(rag.embeddings.model, "text-embedding-3-small");
         assert_eq!(rag.text_processing.chunk_size, 500);
+        assert!(matches!(rag.text_processing.splitter_type, TextSplitterType::Semantic));
+        assert_eq!(rag.text_processing.size_unit, crate::rag::ChunkSizeUnit::Tokens);
+        assert_eq!(rag.text_processing.semantic.breakpoint_percentile, 90.0);
+        assert_eq!(rag.text_processing.semantic.buffer_size, 1);
         assert_eq!(rag.retrieval.top_k, 3);
     }
 
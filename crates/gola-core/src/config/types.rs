@@ -26,6 +26,10 @@ pub struct GolaConfig {
     #[serde(default)]
     pub mcp_servers: Vec<McpServerConfig>,
     #[serde(default)]
+    pub hooks: Vec<HookConfig>,
+    #[serde(default)]
+    pub dry_run: DryRunConfig,
+    #[serde(default)]
     pub tools: ToolsConfig,
     #[serde(default)]
     pub environment: EnvironmentConfig,
@@ -33,6 +37,8 @@ pub struct GolaConfig {
     pub logging: LoggingConfig,
     #[serde(default)]
     pub tracing: TracingConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,6 +102,14 @@ pub struct AgentBehavior {
     pub continue_on_error: bool,
     #[serde(default)]
     pub memory: MemoryConfig,
+    /// Assemble requests within a token budget instead of sending the full history
+    #[serde(default)]
+    pub context_budget: Option<ContextBudgetConfig>,
+    /// Have a reviewer check final answers before they are returned
+    #[serde(default)]
+    pub review: Option<ReviewConfig>,
+    #[serde(default)]
+    pub loop_detection: LoopDetectionConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,6 +120,9 @@ pub struct LlmConfig {
     pub parameters: ModelParameters,
     #[serde(default)]
     pub auth: LlmAuth,
+    /// Server address for self-hosted providers; Ollama falls back to `OLLAMA_HOST`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
 }
 
 /// LLM provider types
@@ -115,6 +132,16 @@ pub enum LlmProvider {
     OpenAI,
     Anthropic,
     Gemini,
+    /// Ollama server at `base_url`, `OLLAMA_HOST` or http://localhost:11434
+    Ollama,
+    /// Azure-hosted OpenAI deployment, addressed by resource endpoint and deployment name
+    #[serde(rename = "azure_openai")]
+    AzureOpenAI {
+        endpoint: String,
+        deployment: String,
+        #[serde(default = "default_azure_api_version")]
+        api_version: String,
+    },
     Custom {
         base_url: String,
     },
@@ -141,6 +168,38 @@ pub struct ModelParameters {
     pub stop_sequences: Vec<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub safety_settings: Option<serde_json::Value>,
+    /// Whether the model may, must, or must not call tools
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    /// Constrains the final answer to JSON, optionally matching a schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    /// Token budget for extended thinking on providers that support it
+    ///
+    /// Must be below `max_tokens`. Anthropic only allows automatic tool choice
+    /// while thinking, so it can't be combined with `response_format` there.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_budget: Option<u32>,
+    /// Context window size for Ollama models
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+    /// How long Ollama keeps the model loaded after a request, e.g. "5m"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+    /// Describe tools in the prompt and parse calls from the reply text, for models
+    /// without native function calling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_protocol: Option<ToolProtocol>,
+    /// Which OpenAI API to call; Chat Completions unless set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api: Option<OpenAIApi>,
+    /// Keep Responses API turns on OpenAI's servers and continue them with
+    /// `previous_response_id`, sending only the new input each turn
+    #[serde(default)]
+    pub store: bool,
+    /// Anthropic prompt caching breakpoints; caching is off unless set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_cache: Option<PromptCacheConfig>,
 }
 
 impl Default for ModelParameters {
@@ -155,10 +214,97 @@ impl Default for ModelParameters {
             system_message: None,
             stop_sequences: Vec::new(),
             safety_settings: None,
+            tool_choice: None,
+            response_format: None,
+            reasoning_budget: None,
+            num_ctx: None,
+            keep_alive: None,
+            tool_protocol: None,
+            api: None,
+            store: false,
+            prompt_cache: None,
+        }
+    }
+}
+
+/// Provider-neutral tool selection policy
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// The model decides whether to call a tool
+    Auto,
+    /// The model must answer without calling tools
+    None,
+    /// The model must call at least one tool
+    Required,
+    /// The model must call the named tool
+    Tool { name: String },
+}
+
+/// Where `cache_control` breakpoints are placed in Anthropic requests
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptCacheConfig {
+    /// Cache the system prompt
+    #[serde(default = "default_true")]
+    pub system: bool,
+    /// Cache the tool definitions, up to and including the last one
+    #[serde(default = "default_true")]
+    pub tools: bool,
+    /// Cache the conversation up to the latest message, moving forward each turn
+    #[serde(default = "default_true")]
+    pub history: bool,
+    /// Cache lifetime, "5m" (default) or "1h"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<String>,
+}
+
+impl Default for PromptCacheConfig {
+    fn default() -> Self {
+        Self {
+            system: true,
+            tools: true,
+            history: true,
+            ttl: None,
         }
     }
 }
 
+/// OpenAI HTTP API used by the OpenAI and OpenAI-compatible providers
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenAIApi {
+    /// `/chat/completions`
+    #[default]
+    ChatCompletions,
+    /// `/responses`, with reasoning items carried across tool turns
+    Responses,
+}
+
+/// Text convention used when tools are described in the prompt instead of the API
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolProtocol {
+    /// `<tool_call>{"name": ..., "arguments": {...}}</tool_call>` tags
+    Xml,
+    /// Fenced ```` ```tool_call ```` blocks holding the same JSON object
+    JsonBlock,
+}
+
+/// Provider-neutral output format for the model's answer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    /// Any valid JSON object
+    JsonObject,
+    /// A JSON object matching the given schema
+    JsonSchema {
+        name: String,
+        schema: serde_json::Value,
+        #[serde(default)]
+        strict: bool,
+    },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlmAuth {
     #[serde(default)]
@@ -208,6 +354,14 @@ pub enum EmbeddingProvider {
     Cohere,
     HuggingFace,
     Simple,
+    /// Embedding deployment on an Azure OpenAI resource
+    #[serde(rename = "azure_openai")]
+    AzureOpenAI {
+        endpoint: String,
+        deployment: String,
+        #[serde(default = "default_azure_api_version")]
+        api_version: String,
+    },
     Custom {
         base_url: String,
     },
@@ -231,6 +385,15 @@ pub struct TextProcessingConfig {
     pub chunk_overlap: usize,
     #[serde(default)]
     pub splitter_type: TextSplitterType,
+    /// Whether `chunk_size` and `chunk_overlap` count characters or tokens
+    #[serde(default)]
+    pub size_unit: crate::rag::ChunkSizeUnit,
+    /// Settings of the `semantic` splitter type
+    #[serde(default)]
+    pub semantic: crate::rag::SemanticSplitConfig,
+    /// Text prefixed to chunks, such as a note of the document they come from
+    #[serde(default)]
+    pub chunk_headers: crate::rag::ChunkHeaderOptions,
 }
 
 /// Text splitter types
@@ -238,7 +401,15 @@ pub struct TextProcessingConfig {
 #[serde(rename_all = "lowercase")]
 pub enum TextSplitterType {
     Basic,
+    /// Splits at syntax boundaries when the language has a grammar, and at the
+    /// language's separators otherwise
     Language { language: String },
+    /// Splits source files at function, class and impl boundaries, choosing the
+    /// grammar by file extension; other files are split as with `basic`
+    Code,
+    /// Splits at Markdown headings and records the heading path of each chunk
+    Markdown,
+    /// Splits where the embedding distance between sentences jumps
     Semantic,
 }
 
@@ -249,6 +420,13 @@ pub struct VectorStoreConfig {
     pub store_type: VectorStoreType,
     #[serde(default)]
     pub persistence: Option<PersistenceConfig>,
+    /// Graph parameters for the `hnsw` store type
+    #[serde(default)]
+    pub hnsw: crate::rag::HnswConfig,
+    /// How saved embeddings are stored: `f32`, or `int8` for files about four
+    /// times smaller at a small loss of precision
+    #[serde(default)]
+    pub quantization: crate::rag::Quantization,
 }
 
 /// Vector store types
@@ -257,6 +435,9 @@ pub struct VectorStoreConfig {
 pub enum VectorStoreType {
     InMemory,
     Persistent,
+    /// Approximate nearest neighbour index for large corpora, saved to the
+    /// persistence path when one is configured
+    Hnsw,
 }
 
 /// Persistence configuration
@@ -300,8 +481,13 @@ pub enum DocumentSourceType {
 pub struct DocumentSourceConfig {
     #[serde(default)]
     pub include_extensions: Vec<String>,
+    /// Gitignore-style patterns, relative to the source directory
     #[serde(default)]
     pub exclude_patterns: Vec<String>,
+    /// Walk into symbolic links instead of skipping them
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Attached to every chunk loaded from this source
     #[serde(default)]
     pub metadata: HashMap<String, String>,
 }
@@ -317,6 +503,36 @@ pub struct RetrievalConfig {
     pub enable_reranking: bool,
     #[serde(default)]
     pub reranker_model: Option<String>,
+    /// `vector`, `keyword` (BM25) or `hybrid`. The similarity threshold only
+    /// applies to vector matches.
+    #[serde(default)]
+    pub mode: crate::rag::RetrievalMode,
+    /// Weights for fusing the rankings in hybrid mode
+    #[serde(default)]
+    pub hybrid: crate::rag::HybridSearchConfig,
+    /// Reranker used when `enable_reranking` is set
+    #[serde(default)]
+    pub reranker: RerankerConfig,
+    /// Candidates retrieved for the reranker to choose `top_k` from
+    #[serde(default = "default_rerank_candidates")]
+    pub rerank_candidates: usize,
+}
+
+/// How retrieved candidates are reranked
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RerankerConfig {
+    /// The agent's LLM orders the candidates, or `reranker_model` on the same provider
+    #[default]
+    Llm,
+    /// A Cohere or Jina compatible rerank endpoint, given as the full URL
+    Http {
+        endpoint: String,
+        #[serde(default)]
+        auth: EmbeddingAuth,
+    },
+    /// Query term overlap, without external calls
+    Lexical,
 }
 
 /// MCP server configuration
@@ -542,6 +758,82 @@ pub struct McpCommand {
     pub token_limit: u32,
 }
 
+/// External program called at points of the agent loop
+///
+/// The event is written to stdin as JSON and the program may answer on stdout to
+/// modify it or veto the action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookConfig {
+    pub name: String,
+    pub run: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Events passed to the hook, all of them when empty
+    #[serde(default)]
+    pub events: Vec<HookEventKind>,
+    /// Seconds to wait for the program
+    #[serde(default = "default_hook_timeout")]
+    pub timeout: u64,
+    #[serde(default)]
+    pub on_failure: HookFailurePolicy,
+}
+
+/// Simulated tool execution, for watching what an agent would do
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// YAML or JSON file with canned results keyed by tool name and argument pattern
+    #[serde(default)]
+    pub fixtures: Option<PathBuf>,
+    /// Ask the LLM for a plausible result when no fixture matches
+    #[serde(default = "default_true")]
+    pub synthesize: bool,
+    /// Where the report of intended tool calls is written
+    #[serde(default = "default_dry_run_report")]
+    pub report: PathBuf,
+}
+
+fn default_dry_run_report() -> PathBuf { PathBuf::from("dry_run_report.json") }
+
+impl Default for DryRunConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            fixtures: None,
+            synthesize: true,
+            report: default_dry_run_report(),
+        }
+    }
+}
+
+/// Points of the agent loop where hooks run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEventKind {
+    RunStart,
+    BeforeLlm,
+    AfterLlm,
+    BeforeTool,
+    AfterTool,
+    RunEnd,
+}
+
+/// What happens when a hook program fails, times out or answers garbage
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HookFailurePolicy {
+    /// Log the failure and carry on as if the hook had not run
+    #[default]
+    Continue,
+    /// Treat the failure as a veto
+    Veto,
+}
+
+fn default_hook_timeout() -> u64 { 10 }
+
 /// Tool filtering configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -668,6 +960,30 @@ impl Default for TracingConfig {
     }
 }
 
+/// Outbound HTTP settings shared by every client gola-core creates
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct HttpConfig {
+    /// Proxy for all outbound requests, e.g. `http://proxy.corp:3128`. Falls back to
+    /// `GOLA_HTTP_PROXY`, then the standard `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` variables.
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Hosts or domains that bypass `proxy`; ignored without one. Falls back to `GOLA_NO_PROXY`.
+    /// Loopback hosts always bypass it
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    /// PEM files with extra root certificates, e.g. a TLS-inspecting corporate CA.
+    /// Falls back to `GOLA_CA_CERTS`
+    #[serde(default)]
+    pub ca_certs: Vec<PathBuf>,
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    /// Maximum time to wait for data on an open connection
+    #[serde(default)]
+    pub read_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+}
+
 
 /// Schema configuration for input and output validation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -786,6 +1102,176 @@ pub struct MemoryPreserveStrategy {
     pub preserve_recent_count: usize,
 }
 
+/// Token budget used to assemble each LLM request up front
+///
+/// Sections are filled in descending `priority` order, each up to its own
+/// `max_tokens` and the budget left over. The latest user turn is always sent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContextBudgetConfig {
+    /// Context window of the model
+    pub max_tokens: usize,
+    /// Tokens kept free for the completion
+    #[serde(default = "default_reserved_output_tokens")]
+    pub reserved_output_tokens: usize,
+    #[serde(default = "default_system_section")]
+    pub system: ContextSectionBudget,
+    #[serde(default = "default_tools_section")]
+    pub tools: ContextSectionBudget,
+    #[serde(default = "default_rag_section")]
+    pub rag: ContextSectionBudget,
+    #[serde(default = "default_summary_section")]
+    pub summary: ContextSectionBudget,
+    #[serde(default = "default_history_section")]
+    pub history: ContextSectionBudget,
+}
+
+/// Limit and priority of one section of the context
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContextSectionBudget {
+    /// Upper bound for this section, unbounded when unset
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+    /// Higher priorities are allocated first
+    #[serde(default)]
+    pub priority: u32,
+}
+
+impl ContextSectionBudget {
+    fn with_priority(priority: u32) -> Self {
+        Self {
+            max_tokens: None,
+            priority,
+        }
+    }
+}
+
+fn default_reserved_output_tokens() -> usize { 4096 }
+fn default_system_section() -> ContextSectionBudget { ContextSectionBudget::with_priority(100) }
+fn default_tools_section() -> ContextSectionBudget { ContextSectionBudget::with_priority(90) }
+fn default_rag_section() -> ContextSectionBudget { ContextSectionBudget::with_priority(70) }
+fn default_summary_section() -> ContextSectionBudget { ContextSectionBudget::with_priority(60) }
+fn default_history_section() -> ContextSectionBudget { ContextSectionBudget::with_priority(50) }
+
+impl ContextBudgetConfig {
+    pub fn new(max_tokens: usize) -> Self {
+        Self {
+            max_tokens,
+            reserved_output_tokens: default_reserved_output_tokens(),
+            system: default_system_section(),
+            tools: default_tools_section(),
+            rag: default_rag_section(),
+            summary: default_summary_section(),
+            history: default_history_section(),
+        }
+    }
+}
+
+/// Thresholds for detecting an agent stuck in a loop, and what to do then
+///
+/// Calls and results are only compared within the last `detection_window_size`
+/// calls, so a cycle is seen when `max_cycle_length * cycle_repetitions` fits in
+/// the window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoopDetectionConfig {
+    /// Number of recent tool calls to analyze
+    #[serde(default = "default_detection_window_size")]
+    pub detection_window_size: usize,
+    /// Threshold for exact match detection
+    #[serde(default = "default_exact_match_threshold")]
+    pub exact_match_threshold: u32,
+    /// Threshold for similar match detection
+    #[serde(default = "default_similar_match_threshold")]
+    pub similar_match_threshold: u32,
+    /// Time window in seconds for temporal grouping
+    #[serde(default = "default_time_window_seconds")]
+    pub time_window_seconds: u64,
+    /// Exact match thresholds for individual tools
+    #[serde(default)]
+    pub tool_specific_thresholds: HashMap<String, u32>,
+    /// Times a sequence of different calls must repeat to count as a cycle
+    #[serde(default = "default_cycle_repetitions")]
+    pub cycle_repetitions: u32,
+    /// Longest sequence of calls checked for cycles
+    #[serde(default = "default_max_cycle_length")]
+    pub max_cycle_length: usize,
+    /// Times the same tool call may return the same result
+    #[serde(default = "default_observation_repeat_threshold")]
+    pub observation_repeat_threshold: u32,
+    /// Consecutive tool results that only repeat earlier ones
+    #[serde(default = "default_no_progress_threshold")]
+    pub no_progress_threshold: u32,
+    #[serde(default)]
+    pub recovery: LoopRecoveryStrategy,
+}
+
+/// What the agent does once a loop is detected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopRecoveryStrategy {
+    /// Tell the model it is looping and let it try something else
+    #[default]
+    InjectGuidance,
+    /// Stop and ask the user how to proceed
+    AskUser,
+    /// End the run with an error
+    Abort,
+}
+
+fn default_detection_window_size() -> usize { 10 }
+fn default_exact_match_threshold() -> u32 { 5 }
+fn default_similar_match_threshold() -> u32 { 8 }
+fn default_time_window_seconds() -> u64 { 30 }
+fn default_cycle_repetitions() -> u32 { 3 }
+fn default_max_cycle_length() -> usize { 3 }
+fn default_observation_repeat_threshold() -> u32 { 4 }
+fn default_no_progress_threshold() -> u32 { 6 }
+
+impl Default for LoopDetectionConfig {
+    fn default() -> Self {
+        Self {
+            detection_window_size: default_detection_window_size(),
+            exact_match_threshold: default_exact_match_threshold(),
+            similar_match_threshold: default_similar_match_threshold(),
+            time_window_seconds: default_time_window_seconds(),
+            tool_specific_thresholds: HashMap::new(),
+            cycle_repetitions: default_cycle_repetitions(),
+            max_cycle_length: default_max_cycle_length(),
+            observation_repeat_threshold: default_observation_repeat_threshold(),
+            no_progress_threshold: default_no_progress_threshold(),
+            recovery: LoopRecoveryStrategy::default(),
+        }
+    }
+}
+
+/// Reviewer pass over final answers
+///
+/// The reviewer sees the original task, the output schema if one is enabled and
+/// the proposed answer. A rejected answer goes back to the agent with the
+/// reviewer's critique, at most `max_rounds` times per task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewConfig {
+    #[serde(default = "default_review_rounds")]
+    pub max_rounds: usize,
+    /// Extra review criteria added to the built-in reviewer prompt
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Model for the reviewer, the agent's own model when unset
+    #[serde(default)]
+    pub llm: Option<LlmConfig>,
+}
+
+fn default_review_rounds() -> usize { 2 }
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        Self {
+            max_rounds: default_review_rounds(),
+            prompt: None,
+            llm: None,
+        }
+    }
+}
+
 impl Default for AgentBehavior {
     fn default() -> Self {
         Self {
@@ -794,6 +1280,9 @@ impl Default for AgentBehavior {
             tool_timeout: default_tool_timeout(),
             continue_on_error: default_continue_on_error(),
             memory: MemoryConfig::default(),
+            context_budget: None,
+            review: None,
+            loop_detection: LoopDetectionConfig::default(),
         }
     }
 }
@@ -833,6 +1322,7 @@ fn default_max_steps() -> usize { 40 }
 fn default_tool_timeout() -> u64 { 30 }
 fn default_continue_on_error() -> bool { true }
 fn default_temperature() -> f32 { 0.7 }
+pub fn default_azure_api_version() -> String { "2024-10-21".to_string() }
 fn default_max_tokens() -> u32 { 16000 }
 fn default_top_p() -> f32 { 1.0 }
 fn default_embedding_dimension() -> usize { 1536 }
@@ -841,6 +1331,7 @@ fn default_chunk_size() -> usize { 1000 }
 fn default_chunk_overlap() -> usize { 200 }
 fn default_top_k() -> usize { 5 }
 fn default_similarity_threshold() -> f32 { 0.7 }
+fn default_rerank_candidates() -> usize { 20 }
 fn default_mcp_timeout() -> u64 { 30 }
 fn default_mcp_description_token_limit() -> u32 { 50 }
 pub fn default_mcp_token_limit() -> u32 { 2000 }
@@ -879,6 +1370,9 @@ impl Default for TextProcessingConfig {
             chunk_size: default_chunk_size(),
             chunk_overlap: default_chunk_overlap(),
             splitter_type: TextSplitterType::Basic,
+            size_unit: Default::default(),
+            semantic: Default::default(),
+            chunk_headers: Default::default(),
         }
     }
 }
@@ -894,6 +1388,8 @@ impl Default for VectorStoreConfig {
         Self {
             store_type: VectorStoreType::InMemory,
             persistence: None,
+            hnsw: Default::default(),
+            quantization: Default::default(),
         }
     }
 }
@@ -915,6 +1411,7 @@ impl Default for DocumentSourceConfig {
         Self {
             include_extensions: Vec::new(),
             exclude_patterns: Vec::new(),
+            follow_symlinks: false,
             metadata: HashMap::new(),
         }
     }
@@ -927,6 +1424,10 @@ impl Default for RetrievalConfig {
             similarity_threshold: default_similarity_threshold(),
             enable_reranking: false,
             reranker_model: None,
+            mode: Default::default(),
+            hybrid: Default::default(),
+            reranker: RerankerConfig::default(),
+            rerank_candidates: default_rerank_candidates(),
         }
     }
 }
@@ -1085,6 +1586,12 @@ impl GolaConfig {
                     return Err(AgentError::ConfigError("RAG chunk size must be greater than 0".to_string()));
                 }
 
+                if !(0.0..=100.0).contains(&rag.text_processing.semantic.breakpoint_percentile) {
+                    return Err(AgentError::ConfigError(
+                        "RAG semantic breakpoint_percentile must be between 0 and 100".to_string(),
+                    ));
+                }
+
                 if rag.retrieval.top_k == 0 {
                     return Err(AgentError::ConfigError("RAG top_k must be greater than 0".to_string()));
                 }
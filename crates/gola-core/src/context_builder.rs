@@ -0,0 +1,621 @@
+//! Budget-aware assembly of LLM requests
+//!
+//! The builder splits a request into sections (system prompt, tool schemas,
+//! retrieved RAG chunks, conversation summaries and earlier history) and fills them
+//! in priority order from a fixed token budget, so requests fit the model before
+//! they are sent. Whatever does not fit is listed in a [`ContextReport`] that ends
+//! up in the step trace. The latest user turn and the tool exchanges that follow it
+//! are always included, as are the control-plane tools the agent needs to finish a
+//! run.
+//!
+//! Token counts are estimates (four bytes per token), the same approximation the
+//! memory implementations use.
+
+use crate::config::{ContextBudgetConfig, ContextSectionBudget};
+use crate::core_types::{Message, Role};
+use crate::llm::ToolMetadata;
+use crate::rag::RetrievedContext;
+use crate::tools::ControlPlaneServer;
+use serde::{Deserialize, Serialize};
+
+/// Fixed cost of a message or tool definition on top of its text
+const ITEM_OVERHEAD_TOKENS: usize = 4;
+const TRUNCATION_MARKER: &str = "\n[truncated]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextSection {
+    System,
+    Tools,
+    Rag,
+    Summary,
+    History,
+}
+
+/// Something left out of, or shortened in, an assembled request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DroppedContext {
+    pub section: ContextSection,
+    pub description: String,
+    pub tokens: usize,
+}
+
+/// How the budget was spent on one request
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContextReport {
+    pub budget_tokens: usize,
+    pub used_tokens: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropped: Vec<DroppedContext>,
+}
+
+/// Everything that could go into a request
+pub struct ContextInputs<'a> {
+    pub system_prompt: Option<&'a str>,
+    pub tools: Vec<ToolMetadata>,
+    pub rag: Option<&'a RetrievedContext>,
+    /// Messages from conversation memory, leading system messages being summaries
+    pub conversation: Vec<Message>,
+}
+
+pub struct AssembledContext {
+    pub messages: Vec<Message>,
+    pub tools: Vec<ToolMetadata>,
+    /// Only present when a budget is configured
+    pub report: Option<ContextReport>,
+}
+
+/// Rough token estimate for `text`
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+fn message_tokens(message: &Message) -> usize {
+    let calls: usize = message
+        .tool_calls
+        .iter()
+        .flatten()
+        .map(|call| estimate_tokens(&call.name) + estimate_tokens(&call.arguments.to_string()))
+        .sum();
+    ITEM_OVERHEAD_TOKENS + estimate_tokens(&message.content) + calls
+}
+
+fn tool_tokens(tool: &ToolMetadata) -> usize {
+    ITEM_OVERHEAD_TOKENS
+        + estimate_tokens(&tool.name)
+        + estimate_tokens(&tool.description)
+        + estimate_tokens(&tool.input_schema.to_string())
+}
+
+fn system_message(content: String) -> Message {
+    Message {
+        role: Role::System,
+        content,
+        tool_call_id: None,
+        tool_calls: None,
+        reasoning: None,
+    }
+}
+
+/// Builds the system message carrying retrieved chunks
+fn rag_message(header: &str, chunks: &[String]) -> Message {
+    system_message(format!(
+        "{}{}Use the above context to help answer the task if relevant.",
+        header,
+        chunks.concat()
+    ))
+}
+
+/// Shortens a message so that it costs at most `tokens`, or returns None if even
+/// a stub would not fit
+fn truncate_message(message: &Message, tokens: usize) -> Option<Message> {
+    let marker_tokens = estimate_tokens(TRUNCATION_MARKER);
+    let text_tokens = tokens.checked_sub(ITEM_OVERHEAD_TOKENS + marker_tokens)?;
+    let mut end = (text_tokens * 4).min(message.content.len());
+    while !message.content.is_char_boundary(end) {
+        end -= 1;
+    }
+    if end == 0 {
+        return None;
+    }
+
+    let mut truncated = message.clone();
+    truncated.content = format!("{}{}", &message.content[..end], TRUNCATION_MARKER);
+    Some(truncated)
+}
+
+/// Assembles requests, within a token budget when one is configured
+pub struct ContextBuilder {
+    budget: Option<ContextBudgetConfig>,
+}
+
+/// Parts of a request as they are filled in
+#[derive(Default)]
+struct Sections {
+    system: Option<Message>,
+    tools: Vec<ToolMetadata>,
+    rag: Option<Message>,
+    summaries: Vec<Message>,
+    history: Vec<Message>,
+}
+
+impl ContextBuilder {
+    pub fn new(budget: Option<ContextBudgetConfig>) -> Self {
+        Self { budget }
+    }
+
+    pub fn assemble(&self, inputs: ContextInputs<'_>) -> AssembledContext {
+        let system = inputs
+            .system_prompt
+            .filter(|prompt| !prompt.is_empty())
+            .map(|prompt| system_message(prompt.to_string()));
+
+        let mut conversation = inputs.conversation;
+        let summary_count = conversation
+            .iter()
+            .take_while(|m| m.role == Role::System)
+            .count();
+        let mut history = conversation.split_off(summary_count);
+        let summaries = conversation;
+        let current_turn_start = history.iter().rposition(|m| m.role == Role::User).unwrap_or(0);
+        let current_turn = history.split_off(current_turn_start);
+
+        let (rag_header, rag_chunks) = match inputs.rag.filter(|context| !context.is_empty()) {
+            Some(context) => (
+                "Retrieved Context:\n\n".to_string(),
+                context
+                    .documents
+                    .iter()
+                    .zip(&context.sources)
+                    .enumerate()
+                    .map(|(i, (doc, source))| {
+                        (
+                            source.clone(),
+                            format!("Source {}: {}\nContent: {}\n\n", i + 1, source, doc.content),
+                        )
+                    })
+                    .collect(),
+            ),
+            None => (String::new(), Vec::new()),
+        };
+
+        let Some(budget) = &self.budget else {
+            let chunks: Vec<String> = rag_chunks.into_iter().map(|(_, chunk)| chunk).collect();
+            let sections = Sections {
+                system,
+                tools: inputs.tools,
+                rag: (!chunks.is_empty()).then(|| rag_message(&rag_header, &chunks)),
+                summaries,
+                history,
+            };
+            return sections.into_context(current_turn, None);
+        };
+
+        let budget_tokens = budget.max_tokens.saturating_sub(budget.reserved_output_tokens);
+        let current_turn_tokens: usize = current_turn.iter().map(message_tokens).sum();
+        if current_turn_tokens > budget_tokens {
+            log::warn!(
+                "Latest turn needs ~{} tokens, more than the context budget of {}",
+                current_turn_tokens,
+                budget_tokens
+            );
+        }
+        let mut remaining = budget_tokens.saturating_sub(current_turn_tokens);
+        let mut used_tokens = current_turn_tokens;
+
+        let mut order = [
+            (ContextSection::System, &budget.system),
+            (ContextSection::Tools, &budget.tools),
+            (ContextSection::Rag, &budget.rag),
+            (ContextSection::Summary, &budget.summary),
+            (ContextSection::History, &budget.history),
+        ];
+        // Stable, so equal priorities keep the order above
+        order.sort_by_key(|(_, limits)| std::cmp::Reverse(limits.priority));
+
+        let mut sections = Sections::default();
+        let mut dropped = Vec::new();
+        let mut system = system;
+        let mut tools = inputs.tools;
+        let mut rag_chunks = rag_chunks;
+        let mut summaries = summaries;
+
+        for (section, limits) in order {
+            let allowance = section_allowance(limits, remaining);
+            let used = match section {
+                ContextSection::System => {
+                    fill_system(system.take(), allowance, &mut sections.system, &mut dropped)
+                }
+                ContextSection::Tools => {
+                    fill_tools(std::mem::take(&mut tools), allowance, &mut sections.tools, &mut dropped)
+                }
+                ContextSection::Rag => fill_rag(
+                    &rag_header,
+                    std::mem::take(&mut rag_chunks),
+                    allowance,
+                    &mut sections.rag,
+                    &mut dropped,
+                ),
+                ContextSection::Summary => fill_summaries(
+                    std::mem::take(&mut summaries),
+                    allowance,
+                    &mut sections.summaries,
+                    &mut dropped,
+                ),
+                ContextSection::History => fill_history(
+                    std::mem::take(&mut history),
+                    allowance,
+                    &mut sections.history,
+                    &mut dropped,
+                ),
+            };
+            remaining = remaining.saturating_sub(used);
+            used_tokens += used;
+        }
+
+        for item in &dropped {
+            log::warn!(
+                "Context budget: dropped {:?} content ({}, ~{} tokens)",
+                item.section,
+                item.description,
+                item.tokens
+            );
+        }
+        let report = ContextReport {
+            budget_tokens,
+            used_tokens,
+            dropped,
+        };
+        sections.into_context(current_turn, Some(report))
+    }
+}
+
+impl Sections {
+    fn into_context(self, current_turn: Vec<Message>, report: Option<ContextReport>) -> AssembledContext {
+        let mut messages = Vec::new();
+        messages.extend(self.system);
+        messages.extend(self.rag);
+        messages.extend(self.summaries);
+        messages.extend(self.history);
+        messages.extend(current_turn);
+        AssembledContext {
+            messages,
+            tools: self.tools,
+            report,
+        }
+    }
+}
+
+fn section_allowance(limits: &ContextSectionBudget, remaining: usize) -> usize {
+    limits.max_tokens.map_or(remaining, |max| max.min(remaining))
+}
+
+fn fill_system(
+    system: Option<Message>,
+    allowance: usize,
+    out: &mut Option<Message>,
+    dropped: &mut Vec<DroppedContext>,
+) -> usize {
+    let Some(system) = system else { return 0 };
+    let tokens = message_tokens(&system);
+    if tokens <= allowance {
+        *out = Some(system);
+        return tokens;
+    }
+
+    *out = truncate_message(&system, allowance);
+    let used = out.as_ref().map_or(0, message_tokens);
+    dropped.push(DroppedContext {
+        section: ContextSection::System,
+        description: if used > 0 { "system prompt truncated" } else { "system prompt" }.to_string(),
+        tokens: tokens - used,
+    });
+    used
+}
+
+/// Fills tools in the given order after the control-plane tools, which are kept
+/// even when they exceed the allowance
+fn fill_tools(
+    tools: Vec<ToolMetadata>,
+    allowance: usize,
+    out: &mut Vec<ToolMetadata>,
+    dropped: &mut Vec<DroppedContext>,
+) -> usize {
+    let (control, tools): (Vec<_>, Vec<_>) = tools
+        .into_iter()
+        .partition(|tool| ControlPlaneServer::is_control_tool(&tool.name));
+    let mut used = control.iter().map(tool_tokens).sum();
+    out.extend(control);
+
+    for tool in tools {
+        let tokens = tool_tokens(&tool);
+        if used + tokens <= allowance {
+            used += tokens;
+            out.push(tool);
+        } else {
+            dropped.push(DroppedContext {
+                section: ContextSection::Tools,
+                description: format!("tool '{}'", tool.name),
+                tokens,
+            });
+        }
+    }
+    used
+}
+
+/// Keeps chunks in retrieval order, which is best match first
+fn fill_rag(
+    header: &str,
+    chunks: Vec<(String, String)>,
+    allowance: usize,
+    out: &mut Option<Message>,
+    dropped: &mut Vec<DroppedContext>,
+) -> usize {
+    if chunks.is_empty() {
+        return 0;
+    }
+
+    let mut used = message_tokens(&rag_message(header, &[]));
+    let mut kept = Vec::new();
+    for (source, chunk) in chunks {
+        let tokens = estimate_tokens(&chunk);
+        if used + tokens <= allowance {
+            used += tokens;
+            kept.push(chunk);
+        } else {
+            dropped.push(DroppedContext {
+                section: ContextSection::Rag,
+                description: format!("chunk from {}", source),
+                tokens,
+            });
+        }
+    }
+
+    if kept.is_empty() {
+        return 0;
+    }
+    *out = Some(rag_message(header, &kept));
+    used
+}
+
+fn fill_summaries(
+    summaries: Vec<Message>,
+    allowance: usize,
+    out: &mut Vec<Message>,
+    dropped: &mut Vec<DroppedContext>,
+) -> usize {
+    let mut used = 0;
+    for summary in summaries {
+        let tokens = message_tokens(&summary);
+        if used + tokens <= allowance {
+            used += tokens;
+            out.push(summary);
+            continue;
+        }
+
+        let truncated = truncate_message(&summary, allowance - used);
+        let kept = truncated.as_ref().map_or(0, message_tokens);
+        dropped.push(DroppedContext {
+            section: ContextSection::Summary,
+            description: if kept > 0 { "conversation summary truncated" } else { "conversation summary" }
+                .to_string(),
+            tokens: tokens - kept,
+        });
+        used += kept;
+        out.extend(truncated);
+    }
+    used
+}
+
+/// Keeps the most recent messages that fit, never starting on a tool result
+/// whose assistant turn was cut
+fn fill_history(
+    mut history: Vec<Message>,
+    allowance: usize,
+    out: &mut Vec<Message>,
+    dropped: &mut Vec<DroppedContext>,
+) -> usize {
+    let mut used = 0;
+    let mut start = history.len();
+    while start > 0 {
+        let tokens = message_tokens(&history[start - 1]);
+        if used + tokens > allowance {
+            break;
+        }
+        used += tokens;
+        start -= 1;
+    }
+    while start < history.len() && history[start].role == Role::Tool {
+        used -= message_tokens(&history[start]);
+        start += 1;
+    }
+
+    let kept = history.split_off(start);
+    if !history.is_empty() {
+        dropped.push(DroppedContext {
+            section: ContextSection::History,
+            description: format!("{} earlier message(s)", history.len()),
+            tokens: history.iter().map(message_tokens).sum(),
+        });
+    }
+    out.extend(kept);
+    used
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_types::ToolCall;
+    use crate::rag::RagDocument;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn message(role: Role, content: &str) -> Message {
+        Message {
+            role,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            reasoning: None,
+        }
+    }
+
+    fn tool(name: &str) -> ToolMetadata {
+        ToolMetadata {
+            name: name.to_string(),
+            description: "x".repeat(200),
+            input_schema: json!({ "type": "object" }),
+        }
+    }
+
+    fn conversation() -> Vec<Message> {
+        let mut call = message(Role::Assistant, "");
+        call.tool_calls = Some(vec![ToolCall {
+            id: Some("call_1".to_string()),
+            name: "search".to_string(),
+            arguments: json!({ "q": "a" }),
+        }]);
+        let mut result = message(Role::Tool, &"r".repeat(600));
+        result.tool_call_id = Some("call_1".to_string());
+
+        vec![
+            message(Role::System, "Summary of the earlier conversation."),
+            message(Role::User, "first question"),
+            call,
+            result,
+            message(Role::Assistant, "first answer"),
+            message(Role::User, "second question"),
+        ]
+    }
+
+    fn retrieved() -> RetrievedContext {
+        let mut context = RetrievedContext::new();
+        for (i, size) in [200, 2000].iter().enumerate() {
+            let doc = RagDocument {
+                content: "d".repeat(*size),
+                metadata: HashMap::new(),
+                source: format!("doc{}.md", i),
+                chunk_index: None,
+            };
+            context.add_document(doc, format!("doc{}.md", i), 0.9);
+        }
+        context
+    }
+
+    #[test]
+    fn test_without_budget_everything_is_sent() {
+        let context = retrieved();
+        let assembled = ContextBuilder::new(None).assemble(ContextInputs {
+            system_prompt: Some("You are helpful."),
+            tools: vec![tool("a"), tool("b")],
+            rag: Some(&context),
+            conversation: conversation(),
+        });
+
+        assert!(assembled.report.is_none());
+        assert_eq!(assembled.tools.len(), 2);
+        assert_eq!(assembled.messages.len(), 8);
+        assert_eq!(assembled.messages[0].content, "You are helpful.");
+        assert!(assembled.messages[1].content.starts_with("Retrieved Context:"));
+        assert!(assembled.messages[1].content.contains("Source 2: doc1.md"));
+        assert_eq!(assembled.messages[7].content, "second question");
+    }
+
+    #[test]
+    fn test_sections_are_filled_by_priority_within_budget() {
+        let mut budget = ContextBudgetConfig::new(400);
+        budget.reserved_output_tokens = 100;
+        budget.tools.max_tokens = Some(80);
+        let context = retrieved();
+
+        let assembled = ContextBuilder::new(Some(budget)).assemble(ContextInputs {
+            system_prompt: Some("You are helpful."),
+            tools: vec![tool("a"), tool("b")],
+            rag: Some(&context),
+            conversation: conversation(),
+        });
+
+        let report = assembled.report.unwrap();
+        assert_eq!(report.budget_tokens, 300);
+        assert!(report.used_tokens <= 300, "{:?}", report);
+
+        // One tool fits the tools cap, the large chunk does not fit at all
+        assert_eq!(assembled.tools.len(), 1);
+        assert!(report.dropped.contains(&DroppedContext {
+            section: ContextSection::Tools,
+            description: "tool 'b'".to_string(),
+            tokens: tool_tokens(&tool("b")),
+        }));
+        assert!(report
+            .dropped
+            .iter()
+            .any(|d| d.section == ContextSection::Rag && d.description == "chunk from doc1.md"));
+
+        // History is cut before the tool result, never between call and result
+        let roles: Vec<Role> = assembled.messages.iter().map(|m| m.role.clone()).collect();
+        assert_eq!(
+            roles,
+            vec![Role::System, Role::System, Role::System, Role::Assistant, Role::User]
+        );
+        assert_eq!(assembled.messages[3].content, "first answer");
+        assert!(report
+            .dropped
+            .iter()
+            .any(|d| d.section == ContextSection::History && d.description == "3 earlier message(s)"));
+    }
+
+    #[test]
+    fn test_control_plane_tools_survive_a_tight_tools_budget() {
+        let mut budget = ContextBudgetConfig::new(1000);
+        budget.reserved_output_tokens = 0;
+        budget.tools.max_tokens = Some(tool_tokens(&tool("search")));
+
+        let assembled = ContextBuilder::new(Some(budget)).assemble(ContextInputs {
+            system_prompt: None,
+            tools: vec![tool("search"), tool("fetch"), tool("assistant_done")],
+            rag: None,
+            conversation: vec![message(Role::User, "hi")],
+        });
+
+        let names: Vec<&str> = assembled.tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["assistant_done"]);
+        let report = assembled.report.unwrap();
+        assert_eq!(report.dropped.len(), 2);
+        assert!(report.dropped.iter().all(|d| d.section == ContextSection::Tools));
+    }
+
+    #[test]
+    fn test_section_caps_truncate_system_and_keep_latest_turn() {
+        let mut budget = ContextBudgetConfig::new(100);
+        budget.reserved_output_tokens = 0;
+        budget.system.max_tokens = Some(20);
+        budget.history.priority = 200;
+
+        let long_prompt = "p".repeat(400);
+        let assembled = ContextBuilder::new(Some(budget)).assemble(ContextInputs {
+            system_prompt: Some(&long_prompt),
+            tools: Vec::new(),
+            rag: None,
+            conversation: vec![message(Role::User, &"q".repeat(1000))],
+        });
+
+        let report = assembled.report.unwrap();
+        assert_eq!(assembled.messages.len(), 1);
+        assert_eq!(assembled.messages[0].role, Role::User);
+        assert_eq!(report.dropped.len(), 1);
+        assert_eq!(report.dropped[0].description, "system prompt");
+
+        let mut budget = ContextBudgetConfig::new(1000);
+        budget.system.max_tokens = Some(20);
+        budget.reserved_output_tokens = 0;
+        let assembled = ContextBuilder::new(Some(budget)).assemble(ContextInputs {
+            system_prompt: Some(&long_prompt),
+            tools: Vec::new(),
+            rag: None,
+            conversation: vec![message(Role::User, "hi")],
+        });
+        assert!(assembled.messages[0].content.ends_with(TRUNCATION_MARKER));
+        assert!(message_tokens(&assembled.messages[0]) <= 20);
+        assert_eq!(assembled.report.unwrap().dropped[0].description, "system prompt truncated");
+    }
+}
@@ -25,6 +25,35 @@ pub struct Message {
     pub tool_call_id: Option<String>, 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>, 
+    /// Reasoning the model produced for this turn, kept so providers that sign
+    /// it can have it sent back on later turns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<Vec<ReasoningBlock>>,
+}
+
+/// A block of model reasoning (thinking) returned alongside the answer
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReasoningBlock {
+    /// Human-readable reasoning; empty for redacted blocks
+    pub text: String,
+    /// Provider signature that must be sent back unchanged (Anthropic), or the
+    /// reasoning item id (OpenAI Responses)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Encrypted reasoning returned instead of, or alongside, text (Anthropic
+    /// redacted thinking, OpenAI Responses encrypted content)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redacted_data: Option<String>,
+}
+
+impl ReasoningBlock {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            signature: None,
+            redacted_data: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -54,6 +83,12 @@ pub struct Usage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
+    /// Prompt tokens served from the provider's prompt cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens: Option<u32>,
+    /// Prompt tokens written to the provider's prompt cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens: Option<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -64,6 +99,28 @@ pub struct LLMResponse {
     pub finish_reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub usage: Option<Usage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<Vec<ReasoningBlock>>,
+}
+
+impl LLMResponse {
+    /// Readable reasoning text, with redacted blocks left out
+    pub fn reasoning_text(&self) -> Option<String> {
+        let text = self
+            .reasoning
+            .iter()
+            .flatten()
+            .map(|block| block.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        if text.is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
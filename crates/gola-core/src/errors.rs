@@ -6,9 +6,10 @@
 //! errors by their source (LLM, tools, configuration), the system enables targeted
 //! retry logic and graceful degradation when specific subsystems fail.
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Error, Debug, Clone)]
+#[derive(Error, Debug, Clone, Serialize, Deserialize)]
 pub enum AgentError {
     #[error("LLM interaction failed: {0}")]
     LLMError(String),
@@ -48,6 +49,8 @@ pub enum AgentError {
     InstallerError(String),
     #[error("Runtime error: {0}")]
     RuntimeError(String),
+    #[error("Vetoed by hook: {0}")]
+    HookVeto(String),
 }
 
 impl From<std::io::Error> for AgentError {
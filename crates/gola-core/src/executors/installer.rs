@@ -42,7 +42,7 @@ impl Installer {
             }
             crate::config::types::BinarySource::GitHub { repo, asset_name } => {
                 let url = format!("https://api.github.com/repos/{}/releases/latest", repo);
-                let client = reqwest::Client::new();
+                let client = crate::http::client();
                 let response = client.get(&url).header("User-Agent", "gola-agent").send().await?;
                 let release: serde_json::Value = response.json().await?;
                 let assets = release["assets"].as_array().ok_or_else(|| AgentError::InstallerError("No assets found in release".to_string()))?;
@@ -0,0 +1,191 @@
+//! Lifecycle hooks around the agent loop
+//!
+//! Hooks see an event at each point of a run (run start, before and after every
+//! LLM call and tool call, run end). They can rewrite the event in place, for
+//! instance to redact a task or enrich tool arguments, or veto the action with a
+//! reason. Hooks run in registration order and each one sees the changes made by
+//! the ones before it.
+//!
+//! Implement [`AgentHook`] to hook in from Rust, or configure a [`ScriptHook`] to
+//! hand events to an external program.
+
+pub mod script;
+
+pub use script::ScriptHook;
+
+use crate::config::HookEventKind;
+use crate::core_types::{LLMResponse, Message, Observation, ToolCall};
+use crate::errors::AgentError;
+use crate::llm::ToolMetadata;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// An event of the agent loop, as seen and modified by hooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum HookEvent {
+    /// A user task is about to be added to memory
+    RunStart { task: String },
+    /// A request is about to be sent to the LLM
+    BeforeLlm {
+        messages: Vec<Message>,
+        tools: Vec<ToolMetadata>,
+    },
+    /// The LLM answered, before the answer is stored or acted on
+    AfterLlm { response: LLMResponse },
+    /// A tool is about to be called
+    BeforeTool { tool_call: ToolCall },
+    /// A tool returned, before the result is stored
+    AfterTool {
+        tool_call: ToolCall,
+        observation: Observation,
+    },
+    /// The run finished with a result or an error
+    RunEnd {
+        result: Option<String>,
+        error: Option<String>,
+    },
+}
+
+impl HookEvent {
+    pub fn kind(&self) -> HookEventKind {
+        match self {
+            HookEvent::RunStart { .. } => HookEventKind::RunStart,
+            HookEvent::BeforeLlm { .. } => HookEventKind::BeforeLlm,
+            HookEvent::AfterLlm { .. } => HookEventKind::AfterLlm,
+            HookEvent::BeforeTool { .. } => HookEventKind::BeforeTool,
+            HookEvent::AfterTool { .. } => HookEventKind::AfterTool,
+            HookEvent::RunEnd { .. } => HookEventKind::RunEnd,
+        }
+    }
+}
+
+/// What a hook decided about an event
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookAction {
+    /// Go ahead, with the event as the hook left it
+    Continue,
+    /// Stop the action. Vetoing `RunEnd` has no effect.
+    Veto(String),
+}
+
+#[async_trait]
+pub trait AgentHook: Send + Sync {
+    /// Inspects and optionally modifies `event`
+    async fn on_event(&self, event: &mut HookEvent) -> Result<HookAction, AgentError>;
+}
+
+/// Hooks registered on an agent, run in order
+#[derive(Clone, Default)]
+pub struct HookChain {
+    hooks: Vec<Arc<dyn AgentHook>>,
+}
+
+impl HookChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, hook: Arc<dyn AgentHook>) {
+        self.hooks.push(hook);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    /// Passes `event` through every hook
+    ///
+    /// Returns the event as modified by the hooks, or [`AgentError::HookVeto`] from
+    /// the first hook that vetoed it.
+    pub async fn dispatch(&self, mut event: HookEvent) -> Result<HookEvent, AgentError> {
+        let kind = event.kind();
+        for hook in &self.hooks {
+            if let HookAction::Veto(reason) = hook.on_event(&mut event).await? {
+                log::info!("Hook vetoed {:?}: {}", kind, reason);
+                return Err(AgentError::HookVeto(reason));
+            }
+            if event.kind() != kind {
+                return Err(AgentError::InternalError(format!(
+                    "Hook replaced a {:?} event with a {:?} event",
+                    kind,
+                    event.kind()
+                )));
+            }
+        }
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Redact;
+
+    #[async_trait]
+    impl AgentHook for Redact {
+        async fn on_event(&self, event: &mut HookEvent) -> Result<HookAction, AgentError> {
+            if let HookEvent::RunStart { task } = event {
+                *task = task.replace("hunter2", "[redacted]");
+            }
+            Ok(HookAction::Continue)
+        }
+    }
+
+    struct DenyTool(&'static str);
+
+    #[async_trait]
+    impl AgentHook for DenyTool {
+        async fn on_event(&self, event: &mut HookEvent) -> Result<HookAction, AgentError> {
+            match event {
+                HookEvent::BeforeTool { tool_call } if tool_call.name == self.0 => {
+                    Ok(HookAction::Veto(format!("{} is disabled", self.0)))
+                }
+                _ => Ok(HookAction::Continue),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hooks_modify_and_veto_in_order() {
+        let mut chain = HookChain::new();
+        chain.push(Arc::new(Redact));
+        chain.push(Arc::new(DenyTool("shell")));
+
+        let event = chain
+            .dispatch(HookEvent::RunStart { task: "my password is hunter2".to_string() })
+            .await
+            .unwrap();
+        assert!(matches!(event, HookEvent::RunStart { task } if task == "my password is [redacted]"));
+
+        let call = |name: &str| HookEvent::BeforeTool {
+            tool_call: ToolCall {
+                id: None,
+                name: name.to_string(),
+                arguments: json!({}),
+            },
+        };
+        assert!(chain.dispatch(call("calculator")).await.is_ok());
+        let err = chain.dispatch(call("shell")).await.unwrap_err();
+        assert!(matches!(err, AgentError::HookVeto(reason) if reason == "shell is disabled"));
+    }
+
+    #[test]
+    fn test_event_json_shape() {
+        let event = HookEvent::RunEnd {
+            result: Some("done".to_string()),
+            error: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({ "event": "run_end", "result": "done", "error": null })
+        );
+    }
+}
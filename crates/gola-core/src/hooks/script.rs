@@ -0,0 +1,208 @@
+//! Hooks implemented by external programs
+//!
+//! The program is started once per event with the event JSON on stdin, for example
+//! `{"event": "before_tool", "tool_call": {...}}`. It may print one of these on
+//! stdout, and printing nothing means continue:
+//!
+//! - `{"action": "continue"}`
+//! - `{"action": "modify", "event": {...}}` with an event of the same kind
+//! - `{"action": "veto", "reason": "..."}`
+//!
+//! A non-zero exit, a timeout or unparseable output counts as a failure, which is
+//! handled according to the hook's [`HookFailurePolicy`].
+
+use super::{AgentHook, HookAction, HookEvent};
+use crate::config::{HookConfig, HookFailurePolicy};
+use crate::errors::AgentError;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum ScriptReply {
+    Continue,
+    Modify { event: HookEvent },
+    Veto { reason: String },
+}
+
+pub struct ScriptHook {
+    config: HookConfig,
+}
+
+impl ScriptHook {
+    pub fn new(config: HookConfig) -> Self {
+        Self { config }
+    }
+
+    async fn run(&self, event: &HookEvent) -> Result<Option<ScriptReply>, String> {
+        let input = serde_json::to_vec(event).map_err(|e| format!("failed to encode event: {}", e))?;
+
+        let mut child = Command::new(&self.config.run)
+            .args(&self.config.args)
+            .envs(&self.config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("failed to start '{}': {}", self.config.run, e))?;
+
+        // Written alongside reading the output, so a hook that prints a lot before
+        // draining its input cannot fill both pipes and stall
+        let mut stdin = child.stdin.take().ok_or("stdin not available")?;
+        let writer = tokio::spawn(async move {
+            // A hook that ignores its input may exit before reading it
+            if let Err(e) = stdin.write_all(&input).await {
+                log::debug!("Hook did not read its input: {}", e);
+            }
+        });
+
+        let output = tokio::time::timeout(Duration::from_secs(self.config.timeout), child.wait_with_output()).await;
+        writer.abort();
+        let output = output
+            .map_err(|_| format!("timed out after {}s", self.config.timeout))?
+            .map_err(|e| e.to_string())?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if stdout.trim().is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_str(stdout.trim())
+            .map(Some)
+            .map_err(|e| format!("invalid reply: {}", e))
+    }
+}
+
+#[async_trait]
+impl AgentHook for ScriptHook {
+    async fn on_event(&self, event: &mut HookEvent) -> Result<HookAction, AgentError> {
+        let kind = event.kind();
+        if !self.config.events.is_empty() && !self.config.events.contains(&kind) {
+            return Ok(HookAction::Continue);
+        }
+
+        let failure = match self.run(event).await {
+            Ok(None) | Ok(Some(ScriptReply::Continue)) => return Ok(HookAction::Continue),
+            Ok(Some(ScriptReply::Veto { reason })) => return Ok(HookAction::Veto(reason)),
+            Ok(Some(ScriptReply::Modify { event: modified })) if modified.kind() == kind => {
+                *event = modified;
+                return Ok(HookAction::Continue);
+            }
+            Ok(Some(ScriptReply::Modify { event: modified })) => {
+                format!("answered a {:?} event with a {:?} event", kind, modified.kind())
+            }
+            Err(failure) => failure,
+        };
+
+        match self.config.on_failure {
+            HookFailurePolicy::Continue => {
+                log::warn!("Hook '{}' failed on {:?}, ignoring it: {}", self.config.name, kind, failure);
+                Ok(HookAction::Continue)
+            }
+            HookFailurePolicy::Veto => Ok(HookAction::Veto(format!(
+                "hook '{}' failed: {}",
+                self.config.name, failure
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HookEventKind;
+    use crate::core_types::ToolCall;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn shell_hook(script: &str) -> HookConfig {
+        HookConfig {
+            name: "test".to_string(),
+            run: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            env: HashMap::new(),
+            events: Vec::new(),
+            timeout: 5,
+            on_failure: HookFailurePolicy::Continue,
+        }
+    }
+
+    fn before_tool() -> HookEvent {
+        HookEvent::BeforeTool {
+            tool_call: ToolCall {
+                id: Some("call_1".to_string()),
+                name: "search".to_string(),
+                arguments: json!({ "q": "rust" }),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_script_can_modify_and_veto() {
+        // Reads the event and sends it back with an extra argument
+        let hook = ScriptHook::new(shell_hook(
+            r#"read -r e; printf '{"action":"modify","event":%s}' "$(printf '%s' "$e" | sed 's/"q":"rust"/"q":"rust","lang":"en"/')""#,
+        ));
+        let mut event = before_tool();
+        assert_eq!(hook.on_event(&mut event).await.unwrap(), HookAction::Continue);
+        let HookEvent::BeforeTool { tool_call } = event else { panic!("kind changed") };
+        assert_eq!(tool_call.arguments, json!({ "q": "rust", "lang": "en" }));
+
+        let hook = ScriptHook::new(shell_hook(r#"echo '{"action":"veto","reason":"no searching today"}'"#));
+        assert_eq!(
+            hook.on_event(&mut before_tool()).await.unwrap(),
+            HookAction::Veto("no searching today".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_large_output_before_reading_input_does_not_stall() {
+        // Both the event and the output are larger than a pipe buffer
+        let hook = ScriptHook::new(shell_hook(
+            r#"head -c 300000 /dev/zero | tr '\0' ' '; cat > /dev/null; echo '{"action":"veto","reason":"read it all"}'"#,
+        ));
+        let mut event = HookEvent::BeforeTool {
+            tool_call: ToolCall {
+                id: Some("call_1".to_string()),
+                name: "write".to_string(),
+                arguments: json!({ "text": "x".repeat(300_000) }),
+            },
+        };
+        assert_eq!(
+            hook.on_event(&mut event).await.unwrap(),
+            HookAction::Veto("read it all".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_events_filter_and_failure_policy() {
+        let mut config = shell_hook("exit 3");
+        config.events = vec![HookEventKind::RunEnd];
+        assert_eq!(
+            ScriptHook::new(config.clone()).on_event(&mut before_tool()).await.unwrap(),
+            HookAction::Continue
+        );
+
+        config.events.clear();
+        assert_eq!(
+            ScriptHook::new(config.clone()).on_event(&mut before_tool()).await.unwrap(),
+            HookAction::Continue
+        );
+
+        config.on_failure = HookFailurePolicy::Veto;
+        let action = ScriptHook::new(config).on_event(&mut before_tool()).await.unwrap();
+        assert!(matches!(action, HookAction::Veto(reason) if reason.starts_with("hook 'test' failed: exited with")));
+    }
+}
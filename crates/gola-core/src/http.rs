@@ -0,0 +1,287 @@
+//! Shared factory for outbound HTTP clients
+//!
+//! Every HTTP call gola-core makes (LLM providers, embeddings, web search, GitHub
+//! config loading, installers) gets its client from here, so the `http` section of
+//! the configuration applies uniformly. Settings the configuration leaves unset are
+//! taken from `GOLA_HTTP_PROXY`, `GOLA_NO_PROXY` (comma separated) and
+//! `GOLA_CA_CERTS` (a path list), which also apply before [`configure`] is called,
+//! e.g. while a configuration is fetched from GitHub. Without a proxy, reqwest
+//! honours the standard `HTTPS_PROXY` family of variables.
+
+use crate::config::HttpConfig;
+use crate::errors::AgentError;
+use reqwest::{Certificate, Client, ClientBuilder, NoProxy, Proxy};
+use std::sync::RwLock;
+use std::time::Duration;
+
+const DEFAULT_USER_AGENT: &str = concat!("gola/", env!("CARGO_PKG_VERSION"));
+
+/// Hosts that never go through the proxy, such as a local Ollama or gola server
+const LOOPBACK_HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+
+struct Shared {
+    config: HttpConfig,
+    client: Client,
+}
+
+static SHARED: RwLock<Option<Shared>> = RwLock::new(None);
+
+/// Installs `config` as the settings for all clients created afterwards
+pub fn configure(config: &HttpConfig) -> Result<(), AgentError> {
+    let config = &with_env_defaults(config.clone(), |name| std::env::var(name).ok());
+    if config.proxy.is_none() && !config.no_proxy.is_empty() {
+        log::warn!("http.no_proxy is ignored because no http.proxy is set; use NO_PROXY with the HTTPS_PROXY variables instead");
+    }
+
+    let client = builder_for(config)?
+        .build()
+        .map_err(|e| AgentError::ConfigError(format!("Failed to create HTTP client: {}", e)))?;
+
+    let mut shared = SHARED.write().unwrap_or_else(|e| e.into_inner());
+    *shared = Some(Shared {
+        config: config.clone(),
+        client,
+    });
+    Ok(())
+}
+
+/// Returns the shared client
+///
+/// Clones share one connection pool, so callers can keep their own copy.
+pub fn client() -> Client {
+    if let Some(shared) = SHARED.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return shared.client.clone();
+    }
+
+    let mut shared = SHARED.write().unwrap_or_else(|e| e.into_inner());
+    shared
+        .get_or_insert_with(|| {
+            let config = env_config();
+            let client = builder_for(&config)
+                .and_then(|builder| {
+                    builder.build().map_err(|e| AgentError::ConfigError(e.to_string()))
+                })
+                .unwrap_or_else(|e| {
+                    log::warn!("Falling back to a default HTTP client: {}", e);
+                    Client::new()
+                });
+            Shared { config, client }
+        })
+        .client
+        .clone()
+}
+
+/// Returns a builder preloaded with the current settings
+///
+/// For callers that need something on top, such as an overall request timeout.
+pub fn builder() -> ClientBuilder {
+    let config = SHARED
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .map(|shared| shared.config.clone())
+        .unwrap_or_else(env_config);
+
+    builder_for(&config).unwrap_or_else(|e| {
+        log::warn!("Ignoring HTTP settings: {}", e);
+        Client::builder().user_agent(DEFAULT_USER_AGENT)
+    })
+}
+
+fn env_config() -> HttpConfig {
+    with_env_defaults(HttpConfig::default(), |name| std::env::var(name).ok())
+}
+
+/// Fills the proxy and CA settings `config` leaves unset from `GOLA_*` variables
+fn with_env_defaults(mut config: HttpConfig, var: impl Fn(&str) -> Option<String>) -> HttpConfig {
+    let var = |name| var(name).filter(|value: &String| !value.trim().is_empty());
+
+    if config.proxy.is_none() {
+        config.proxy = var("GOLA_HTTP_PROXY");
+    }
+    if config.no_proxy.is_empty() {
+        if let Some(hosts) = var("GOLA_NO_PROXY") {
+            config.no_proxy = hosts.split(',').map(|h| h.trim().to_string()).filter(|h| !h.is_empty()).collect();
+        }
+    }
+    if config.ca_certs.is_empty() {
+        if let Some(paths) = var("GOLA_CA_CERTS") {
+            config.ca_certs = std::env::split_paths(&paths).collect();
+        }
+    }
+    config
+}
+
+/// Joins the configured no-proxy hosts with the loopback ones
+fn no_proxy_list(config: &HttpConfig) -> String {
+    LOOPBACK_HOSTS
+        .iter()
+        .copied()
+        .chain(config.no_proxy.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Builds a client builder from `config` without touching the shared settings
+pub fn builder_for(config: &HttpConfig) -> Result<ClientBuilder, AgentError> {
+    let mut builder = Client::builder().user_agent(
+        config
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string()),
+    );
+
+    if let Some(proxy_url) = &config.proxy {
+        let proxy = Proxy::all(proxy_url)
+            .map_err(|e| AgentError::ConfigError(format!("Invalid HTTP proxy '{}': {}", proxy_url, e)))?
+            .no_proxy(NoProxy::from_string(&no_proxy_list(config)));
+        builder = builder.proxy(proxy);
+    }
+
+    for path in &config.ca_certs {
+        let pem = std::fs::read(path).map_err(|e| {
+            AgentError::ConfigError(format!("Failed to read CA certificate {}: {}", path.display(), e))
+        })?;
+        let certificates = Certificate::from_pem_bundle(&pem).map_err(|e| {
+            AgentError::ConfigError(format!("Invalid CA certificate {}: {}", path.display(), e))
+        })?;
+        if certificates.is_empty() {
+            return Err(AgentError::ConfigError(format!(
+                "No PEM certificates found in {}",
+                path.display()
+            )));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    if let Some(secs) = config.connect_timeout_secs {
+        builder = builder.connect_timeout(Duration::from_secs(secs));
+    }
+    if let Some(secs) = config.read_timeout_secs {
+        builder = builder.read_timeout(Duration::from_secs(secs));
+    }
+
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_requests_go_through_configured_proxy() {
+        // The stand-in proxy answers any request itself and reports what it received
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+        let proxy = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let n = stream.read(&mut buf).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok")
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+
+        let config = HttpConfig {
+            proxy: Some(proxy_url),
+            user_agent: Some("corp-agent/1.0".to_string()),
+            connect_timeout_secs: Some(5),
+            ..Default::default()
+        };
+        let client = builder_for(&config).unwrap().build().unwrap();
+        let body = client
+            .get("http://api.example.invalid/v1/models")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "ok");
+
+        let request = proxy.join().unwrap().to_lowercase();
+        assert!(request.starts_with("get http://api.example.invalid/v1/models http/1.1"), "{}", request);
+        assert!(request.contains("user-agent: corp-agent/1.0"), "{}", request);
+    }
+
+    #[tokio::test]
+    async fn test_loopback_bypasses_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/tags", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok")
+                .unwrap();
+        });
+
+        // Nothing listens on the proxy, so the request only succeeds if it skips it
+        let config = HttpConfig {
+            proxy: Some("http://proxy.example.invalid:3128".to_string()),
+            no_proxy: vec![".corp".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(no_proxy_list(&config), "localhost,127.0.0.1,::1,.corp");
+
+        let client = builder_for(&config).unwrap().build().unwrap();
+        let body = client.get(&url).send().await.unwrap().text().await.unwrap();
+        assert_eq!(body, "ok");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_environment_fills_unset_settings() {
+        let env = |name: &str| match name {
+            "GOLA_HTTP_PROXY" => Some("http://env-proxy:3128".to_string()),
+            "GOLA_NO_PROXY" => Some("localhost, .corp".to_string()),
+            "GOLA_CA_CERTS" => std::env::join_paths(["/etc/a.pem", "/etc/b.pem"])
+                .ok()
+                .map(|p| p.to_string_lossy().to_string()),
+            _ => None,
+        };
+
+        let config = with_env_defaults(HttpConfig::default(), env);
+        assert_eq!(config.proxy.as_deref(), Some("http://env-proxy:3128"));
+        assert_eq!(config.no_proxy, vec!["localhost", ".corp"]);
+        assert_eq!(config.ca_certs, vec![std::path::PathBuf::from("/etc/a.pem"), "/etc/b.pem".into()]);
+
+        // The configuration file wins over the environment
+        let configured = HttpConfig {
+            proxy: Some("http://file-proxy:8080".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(with_env_defaults(configured, env).proxy.as_deref(), Some("http://file-proxy:8080"));
+    }
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        let bad_proxy = HttpConfig {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(builder_for(&bad_proxy), Err(AgentError::ConfigError(_))));
+
+        let missing_ca = HttpConfig {
+            ca_certs: vec!["/nonexistent/corp-ca.pem".into()],
+            ..Default::default()
+        };
+        let err = builder_for(&missing_ca).err().unwrap().to_string();
+        assert!(err.contains("/nonexistent/corp-ca.pem"), "{}", err);
+
+        let dir = tempfile::tempdir().unwrap();
+        let not_pem = dir.path().join("ca.pem");
+        std::fs::write(&not_pem, "hello").unwrap();
+        let empty_bundle = HttpConfig {
+            ca_certs: vec![not_pem],
+            ..Default::default()
+        };
+        assert!(builder_for(&empty_bundle).is_err());
+    }
+}
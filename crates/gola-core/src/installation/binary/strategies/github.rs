@@ -27,7 +27,7 @@ impl GitHubReleaseStrategy {
             repo,
             asset_pattern: None,
             version: None,
-            client: Client::new(),
+            client: crate::http::client(),
         }
     }
 
@@ -20,6 +20,7 @@
 
 pub mod agent;
 pub mod agent_factory;
+pub mod cassette;
 pub mod core_types;
 pub mod errors;
 pub mod executors;
@@ -29,6 +30,8 @@ pub mod loop_detection;
 pub mod memory;
 pub mod tools;
 pub mod guardrails;
+pub mod hooks;
+pub mod http;
 pub mod sse_authorization_handler;
 pub mod polling_authorization_handler;
 pub mod authorization_client;
@@ -37,6 +40,8 @@ pub mod tracing;
 pub mod rag;
 pub mod ag_ui_handler;
 pub mod config;
+pub mod context_builder;
+pub mod review;
 
 pub use authorization_client::AuthorizationClient;
 pub use agent::{Agent, AgentConfig};
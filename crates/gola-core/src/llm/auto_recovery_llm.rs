@@ -126,6 +126,7 @@ impl AutoRecoveryLLM {
                                     content: "[Tool execution was interrupted - continuing conversation]".to_string(),
                                     tool_call_id: Some(id.clone()),
                                     tool_calls: None,
+                                    reasoning: None,
                                 };
                                 
                                 insertions.push((insert_index, synthetic_response, id.clone()));
@@ -223,6 +224,7 @@ impl AutoRecoveryLLM {
                         content: "Please continue our conversation.".to_string(),
                         tool_call_id: None,
                         tool_calls: None,
+                        reasoning: None,
                     }];
                     self.inner.generate(generic_message, None).await
                 }
@@ -330,6 +332,7 @@ mod tests {
             tool_calls: None,
             finish_reason: None,
             usage: None,
+            reasoning: None,
         });
         
         let mock_llm = Arc::new(MockLLM::new(vec![mock_response]));
@@ -340,6 +343,7 @@ mod tests {
             content: "Hello".to_string(),
             tool_call_id: None,
             tool_calls: None,
+            reasoning: None,
         }];
 
         let result = auto_recovery_llm.generate(messages, None).await;
@@ -358,6 +362,7 @@ mod tests {
             tool_calls: None,
             finish_reason: None,
             usage: None,
+            reasoning: None,
         });
 
         let mock_llm = Arc::new(MockLLM::new(vec![success_response, error_response]));
@@ -373,12 +378,14 @@ mod tests {
                     name: "test_tool".to_string(),
                     arguments: json!({}),
                 }]),
+                reasoning: None,
             },
             Message {
                 role: Role::User,
                 content: "What happened?".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -398,6 +405,7 @@ mod tests {
             tool_calls: None,
             finish_reason: None,
             usage: None,
+            reasoning: None,
         });
 
         // MockLLM pops from the end, so we need to reverse the order
@@ -416,6 +424,7 @@ mod tests {
             content: "Hello".to_string(),
             tool_call_id: None,
             tool_calls: None,
+            reasoning: None,
         }];
 
         let result = auto_recovery_llm.generate(messages, None).await;
@@ -152,6 +152,7 @@ impl ContextTruncatingLLM {
                 ),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             };
             
             // Insert after system message or at beginning
@@ -310,6 +311,7 @@ mod tests {
                     tool_calls: None,
                     finish_reason: None,
                     usage: None,
+                    reasoning: None,
                 });
             }
 
@@ -324,6 +326,7 @@ mod tests {
                 tool_calls: None,
                 finish_reason: None,
                 usage: None,
+                reasoning: None,
             })
         }
     }
@@ -339,12 +342,14 @@ mod tests {
                 content: "You are a helpful assistant.".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
             Message {
                 role: Role::User,
                 content: "Hello!".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -365,12 +370,14 @@ mod tests {
                 content: "System prompt".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
             Message {
                 role: Role::Tool,
                 content: long_content.clone(),
                 tool_call_id: Some("tool_123".to_string()),
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -391,6 +398,7 @@ mod tests {
                 content: "Hello!".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -412,24 +420,28 @@ mod tests {
                 content: "System prompt".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
             Message {
                 role: Role::User,
                 content: "Old message 1".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
             Message {
                 role: Role::Tool,
                 content: long_content.clone(),
                 tool_call_id: Some("tool_123".to_string()),
                 tool_calls: None,
+                reasoning: None,
             },
             Message {
                 role: Role::User,
                 content: "Recent message".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
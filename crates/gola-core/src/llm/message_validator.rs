@@ -227,6 +227,7 @@ impl MessageValidator {
                 content: "[Tool execution was interrupted or failed - continuing conversation]".to_string(),
                 tool_call_id: Some(tool_call_id.clone()),
                 tool_calls: None,
+                reasoning: None,
             };
             
             messages.insert(insert_index + i, synthetic_response);
@@ -259,6 +260,7 @@ impl MessageValidator {
                 content: format!("Previous tool result: {}", truncated_content),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             };
 
             if self.log_issues {
@@ -325,18 +327,21 @@ mod tests {
                 content: "Hello".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
             Message {
                 role: Role::Assistant,
                 content: "I'll help you".to_string(),
                 tool_call_id: None,
                 tool_calls: Some(vec![create_test_tool_call("call_1", "test_tool")]),
+                reasoning: None,
             },
             Message {
                 role: Role::Tool,
                 content: "Tool result".to_string(),
                 tool_call_id: Some("call_1".to_string()),
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -355,12 +360,14 @@ mod tests {
                 content: "Hello".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
             Message {
                 role: Role::Assistant,
                 content: "I'll use a tool".to_string(),
                 tool_call_id: None,
                 tool_calls: Some(vec![create_test_tool_call("call_1", "test_tool")]),
+                reasoning: None,
             },
             // Missing tool response - this should be detected and fixed
             Message {
@@ -368,6 +375,7 @@ mod tests {
                 content: "What happened?".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -387,18 +395,21 @@ mod tests {
                 content: "Hello".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
             Message {
                 role: Role::Assistant,
                 content: "I'll use a tool".to_string(),
                 tool_call_id: None,
                 tool_calls: Some(vec![create_test_tool_call("call_1", "test_tool")]),
+                reasoning: None,
             },
             Message {
                 role: Role::User,
                 content: "What happened?".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -424,12 +435,14 @@ mod tests {
                 content: "Hello".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
             Message {
                 role: Role::Tool,
                 content: "Tool result".to_string(),
                 tool_call_id: Some("nonexistent_call".to_string()),
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -449,12 +462,14 @@ mod tests {
                 content: "Hello".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
             Message {
                 role: Role::Tool,
                 content: "Tool result".to_string(),
                 tool_call_id: Some("nonexistent_call".to_string()),
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -481,6 +496,7 @@ mod tests {
                 content: long_content.clone(),
                 tool_call_id: Some("nonexistent_call".to_string()),
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -507,12 +523,14 @@ mod tests {
                     create_test_tool_call("call_1", "tool_1"),
                     create_test_tool_call("call_2", "tool_2"),
                 ]),
+                reasoning: None,
             },
             Message {
                 role: Role::User,
                 content: "What happened?".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -542,12 +560,14 @@ mod tests {
                     create_test_tool_call("call_1", "tool_1"),
                     create_test_tool_call("call_2", "tool_2"),
                 ]),
+                reasoning: None,
             },
             Message {
                 role: Role::Tool,
                 content: "First tool result".to_string(),
                 tool_call_id: Some("call_1".to_string()),
                 tool_calls: None,
+                reasoning: None,
             },
             // Missing response for call_2
             Message {
@@ -555,6 +575,7 @@ mod tests {
                 content: "What about the second tool?".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -583,12 +604,14 @@ mod tests {
                 content: "I'll use a tool".to_string(),
                 tool_call_id: None,
                 tool_calls: Some(vec![create_test_tool_call("call_1", "test_tool")]),
+                reasoning: None,
             },
             Message {
                 role: Role::User,
                 content: "What happened?".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -17,12 +17,15 @@ pub mod utils;
 pub mod message_validator;
 pub mod auto_recovery_llm;
 pub mod summarizer;
+pub mod structured_output;
+pub mod text_tool_protocol;
 
 pub use response_parser::ResponseParser;
 pub use context_truncation::ContextTruncatingLLM;
 pub use utils::LLMFactory;
 pub use message_validator::MessageValidator;
 pub use auto_recovery_llm::AutoRecoveryLLM;
+pub use text_tool_protocol::TextToolProtocolLLM;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolMetadata {
@@ -51,7 +54,7 @@ impl HttpLLMClient {
     pub fn new(endpoint_url: String) -> Self {
         Self {
             endpoint_url,
-            client: Client::new(),
+            client: crate::http::client(),
         }
     }
 }
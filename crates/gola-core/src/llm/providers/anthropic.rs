@@ -7,15 +7,18 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 
-use crate::config::{LlmConfig, ModelParameters};
-use crate::core_types::{LLMResponse, Message, Role, ToolCall, Usage};
+use crate::config::{LlmConfig, ModelParameters, ToolChoice};
+use crate::core_types::{LLMResponse, Message, ReasoningBlock, Role, ToolCall, Usage};
 use crate::errors::AgentError;
+use crate::llm::structured_output::{self, STRUCTURED_OUTPUT_TOOL};
 use crate::llm::{LLM, ToolMetadata};
 
 const ANTHROPIC_API_BASE: &str = "https://api.anthropic.com";
 const DEFAULT_ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Beta flag required for the one hour cache lifetime
+const EXTENDED_CACHE_TTL_BETA: &str = "extended-cache-ttl-2025-04-11";
 
 #[derive(Debug, Clone)]
 pub struct AnthropicClient {
@@ -35,6 +38,10 @@ struct AnthropicMessage {
 #[derive(Debug, Serialize)]
 #[serde(tag = "type")]
 enum AnthropicContent {
+    #[serde(rename = "thinking")]
+    Thinking { thinking: String, signature: String },
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
     #[serde(rename = "text")]
     Text { text: String },
     #[serde(rename = "tool_use")]
@@ -65,6 +72,10 @@ struct AnthropicRequest {
     stop_sequences: Vec<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tools: Vec<AnthropicTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -94,6 +105,10 @@ struct AnthropicResponse {
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type")]
 enum AnthropicResponseContent {
+    #[serde(rename = "thinking")]
+    Thinking { thinking: String, signature: String },
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking { data: String },
     #[serde(rename = "text")]
     Text { text: String },
     #[serde(rename = "tool_use")]
@@ -108,6 +123,10 @@ enum AnthropicResponseContent {
 struct AnthropicUsage {
     input_tokens: u32,
     output_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: Option<u32>,
+    #[serde(default)]
+    cache_creation_input_tokens: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -125,7 +144,7 @@ impl AnthropicClient {
         api_base: Option<String>,
     ) -> Self {
         Self {
-            client: Client::new(),
+            client: crate::http::client(),
             api_key,
             api_base: api_base.unwrap_or_else(|| ANTHROPIC_API_BASE.to_string()),
             model,
@@ -170,10 +189,32 @@ impl AnthropicClient {
                         });
                     }
 
-                    // Add to current assistant content
-                    current_assistant_content.push(AnthropicContent::Text {
-                        text: message.content,
-                    });
+                    // Thinking blocks must come first and be returned unchanged,
+                    // otherwise Anthropic rejects the follow-up tool results
+                    let has_reasoning = message.reasoning.as_ref().is_some_and(|r| !r.is_empty());
+                    for block in message.reasoning.unwrap_or_default() {
+                        match (block.redacted_data, block.signature) {
+                            (Some(data), _) => {
+                                current_assistant_content.push(AnthropicContent::RedactedThinking { data });
+                            }
+                            (None, Some(signature)) => {
+                                current_assistant_content.push(AnthropicContent::Thinking {
+                                    thinking: block.text,
+                                    signature,
+                                });
+                            }
+                            // Unsigned reasoning came from another provider and can't be replayed
+                            (None, None) => {}
+                        }
+                    }
+
+                    // Add to current assistant content, skipping empty text next to other blocks
+                    let has_tool_calls = message.tool_calls.as_ref().is_some_and(|c| !c.is_empty());
+                    if !message.content.is_empty() || !(has_reasoning || has_tool_calls) {
+                        current_assistant_content.push(AnthropicContent::Text {
+                            text: message.content,
+                        });
+                    }
 
                     // Handle tool calls
                     if let Some(tool_calls) = message.tool_calls {
@@ -188,6 +229,14 @@ impl AnthropicClient {
                     _last_role = Some(Role::Assistant);
                 }
                 Role::Tool => {
+                    // The tool_use blocks being answered must be sent first
+                    if !current_assistant_content.is_empty() {
+                        anthropic_messages.push(AnthropicMessage {
+                            role: "assistant".to_string(),
+                            content: std::mem::take(&mut current_assistant_content),
+                        });
+                    }
+
                     // Tool results should be added to user messages in Anthropic
                     current_user_content.push(AnthropicContent::ToolResult {
                         tool_use_id: message.tool_call_id.unwrap_or_default(),
@@ -231,12 +280,132 @@ impl AnthropicClient {
             .collect()
     }
 
+    /// Maps the configured tool choice onto Anthropic's `tool_choice`
+    ///
+    /// Anthropic has no JSON mode, so a configured response format is emulated
+    /// with the structured output tool. The model is then made to answer through
+    /// a tool: the output tool alone when it has nothing else to call, or any
+    /// tool otherwise. Forcing some other tool would leave no way to answer, so
+    /// that combination is rejected.
+    fn convert_tool_choice(&self, tools: &[AnthropicTool], emulating_output: bool) -> Result<Option<Value>, AgentError> {
+        if tools.is_empty() {
+            return Ok(None);
+        }
+
+        // Extended thinking only supports automatic tool choice. A configured
+        // tool choice is downgraded, but the structured answer can't be forced.
+        if self.parameters.reasoning_budget.is_some() {
+            if emulating_output {
+                return Err(AgentError::ConfigError(
+                    "response_format can't be used with reasoning_budget on Anthropic, which only allows automatic tool choice while thinking".to_string(),
+                ));
+            }
+            if self.parameters.tool_choice.is_some() {
+                log::warn!("Anthropic extended thinking is enabled; using automatic tool choice instead of the configured one");
+            }
+            return Ok(Some(json!({ "type": "auto" })));
+        }
+
+        let force_output = json!({ "type": "tool", "name": STRUCTURED_OUTPUT_TOOL });
+        let tool_choice = match (&self.parameters.tool_choice, emulating_output) {
+            (Some(ToolChoice::Tool { name }), true) if name != STRUCTURED_OUTPUT_TOOL => {
+                return Err(AgentError::ConfigError(format!(
+                    "tool_choice '{}' conflicts with response_format, which must be answered through '{}'",
+                    name, STRUCTURED_OUTPUT_TOOL
+                )));
+            }
+            (Some(ToolChoice::Tool { name }), _) => Some(json!({ "type": "tool", "name": name })),
+            (Some(ToolChoice::None), true) => Some(force_output),
+            (_, true) if tools.len() == 1 => Some(force_output),
+            (_, true) => Some(json!({ "type": "any" })),
+            (Some(ToolChoice::Auto), false) => Some(json!({ "type": "auto" })),
+            (Some(ToolChoice::None), false) => Some(json!({ "type": "none" })),
+            (Some(ToolChoice::Required), false) => Some(json!({ "type": "any" })),
+            (None, false) => None,
+        };
+        Ok(tool_choice)
+    }
+
+    /// Builds the extended thinking config, whose budget must fit in `max_tokens`
+    fn thinking_config(&self) -> Result<Option<Value>, AgentError> {
+        let Some(budget) = self.parameters.reasoning_budget else {
+            return Ok(None);
+        };
+        if budget >= self.parameters.max_tokens {
+            return Err(AgentError::ConfigError(format!(
+                "reasoning_budget ({}) must be less than max_tokens ({})",
+                budget, self.parameters.max_tokens
+            )));
+        }
+        Ok(Some(json!({ "type": "enabled", "budget_tokens": budget })))
+    }
+
+    /// Marks the configured `cache_control` breakpoints on a serialized request
+    ///
+    /// The system prompt and the last tool definition are stable across turns. The
+    /// history breakpoint sits on the newest message, so each call writes the
+    /// conversation so far and the next one reads it back.
+    fn apply_cache_breakpoints(&self, body: &mut Value) {
+        let Some(cache) = &self.parameters.prompt_cache else {
+            return;
+        };
+        let marker = match &cache.ttl {
+            Some(ttl) => json!({ "type": "ephemeral", "ttl": ttl }),
+            None => json!({ "type": "ephemeral" }),
+        };
+
+        if cache.system {
+            if let Some(system) = body["system"].as_str().filter(|s| !s.is_empty()).map(str::to_string) {
+                body["system"] = json!([{ "type": "text", "text": system, "cache_control": marker }]);
+            }
+        }
+
+        if cache.tools {
+            if let Some(last_tool) = body["tools"].as_array_mut().and_then(|tools| tools.last_mut()) {
+                last_tool["cache_control"] = marker.clone();
+            }
+        }
+
+        if cache.history {
+            // Thinking blocks and empty text can't carry a breakpoint
+            let last_block = body["messages"]
+                .as_array_mut()
+                .and_then(|messages| messages.last_mut())
+                .and_then(|message| message["content"].as_array_mut())
+                .and_then(|blocks| {
+                    blocks.iter_mut().rev().find(|block| match block["type"].as_str() {
+                        Some("thinking") | Some("redacted_thinking") => false,
+                        Some("text") => block["text"].as_str().is_some_and(|t| !t.is_empty()),
+                        _ => true,
+                    })
+                });
+            if let Some(block) = last_block {
+                block["cache_control"] = marker;
+            }
+        }
+    }
+
     fn convert_response(&self, response: AnthropicResponse) -> Result<LLMResponse, AgentError> {
         let mut content = String::new();
         let mut tool_calls = Vec::new();
+        let mut reasoning = Vec::new();
 
         for content_block in response.content {
             match content_block {
+                AnthropicResponseContent::Thinking { thinking, signature } => {
+                    reasoning.push(ReasoningBlock {
+                        text: thinking,
+                        signature: Some(signature),
+                        redacted_data: None,
+                    });
+                }
+                AnthropicResponseContent::RedactedThinking { data } => {
+                    reasoning.push(ReasoningBlock {
+                        text: String::new(),
+                        signature: None,
+                        redacted_data: Some(data),
+                    });
+                }
                 AnthropicResponseContent::Text { text } => {
                     content.push_str(&text);
                 }
@@ -250,15 +419,24 @@ impl AnthropicClient {
             }
         }
 
+        // `input_tokens` only counts the uncached part of the prompt
+        let usage = &response.usage;
+        let cache_read = usage.cache_read_input_tokens.unwrap_or(0);
+        let cache_write = usage.cache_creation_input_tokens.unwrap_or(0);
+        let prompt_tokens = usage.input_tokens + cache_read + cache_write;
+
         Ok(LLMResponse {
             content: if content.is_empty() { None } else { Some(content) },
             tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
             finish_reason: response.stop_reason,
             usage: Some(Usage {
-                prompt_tokens: response.usage.input_tokens,
-                completion_tokens: response.usage.output_tokens,
-                total_tokens: response.usage.input_tokens + response.usage.output_tokens,
+                prompt_tokens,
+                completion_tokens: usage.output_tokens,
+                total_tokens: prompt_tokens + usage.output_tokens,
+                cache_read_tokens: usage.cache_read_input_tokens,
+                cache_write_tokens: usage.cache_creation_input_tokens,
             }),
+            reasoning: if reasoning.is_empty() { None } else { Some(reasoning) },
         })
     }
 }
@@ -271,38 +449,65 @@ impl LLM for AnthropicClient {
         tools: Option<Vec<ToolMetadata>>,
     ) -> Result<LLMResponse, AgentError> {
         let (system_message, anthropic_messages) = self.convert_messages(messages)?;
-        let anthropic_tools = self.convert_tools(tools);
+        let mut anthropic_tools = self.convert_tools(tools);
+
+        let emulating_output = match &self.parameters.response_format {
+            Some(format) => {
+                let output_tool = structured_output::output_tool(format);
+                anthropic_tools.push(AnthropicTool {
+                    name: output_tool.name,
+                    description: output_tool.description,
+                    input_schema: output_tool.input_schema,
+                });
+                true
+            }
+            None => false,
+        };
+        let tool_choice = self.convert_tool_choice(&anthropic_tools, emulating_output)?;
+        let thinking = self.thinking_config()?;
 
         let request = AnthropicRequest {
             model: self.model.clone(),
             max_tokens: self.parameters.max_tokens,
             messages: anthropic_messages,
             system: system_message,
-            temperature: if self.parameters.temperature > 0.0 {
+            // Sampling parameters can't be changed while thinking is enabled
+            temperature: if self.parameters.temperature > 0.0 && thinking.is_none() {
                 Some(self.parameters.temperature)
             } else {
                 None
             },
-            top_p: if self.parameters.top_p < 1.0 {
+            top_p: if self.parameters.top_p < 1.0 && thinking.is_none() {
                 Some(self.parameters.top_p)
             } else {
                 None
             },
             stop_sequences: self.parameters.stop_sequences.clone(),
             tools: anthropic_tools,
+            tool_choice,
+            thinking,
         };
 
+        let mut body = serde_json::to_value(&request)
+            .map_err(|e| AgentError::LLMError(format!("Failed to serialize request: {}", e)))?;
+        self.apply_cache_breakpoints(&mut body);
+
         let anthropic_version = self.parameters.anthropic_version
             .as_deref()
             .unwrap_or(DEFAULT_ANTHROPIC_VERSION);
 
-        let response = self
+        let mut http_request = self
             .client
             .post(&format!("{}/v1/messages", self.api_base))
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", anthropic_version)
-            .header("content-type", "application/json")
-            .json(&request)
+            .header("content-type", "application/json");
+        if self.parameters.prompt_cache.as_ref().and_then(|c| c.ttl.as_deref()) == Some("1h") {
+            http_request = http_request.header("anthropic-beta", EXTENDED_CACHE_TTL_BETA);
+        }
+
+        let response = http_request
+            .json(&body)
             .send()
             .await
             .map_err(|e| AgentError::LLMError(format!("Request failed: {}", e)))?;
@@ -333,7 +538,12 @@ impl LLM for AnthropicClient {
             .await
             .map_err(|e| AgentError::LLMError(format!("Failed to parse response: {}", e)))?;
 
-        self.convert_response(anthropic_response)
+        let response = self.convert_response(anthropic_response)?;
+        if emulating_output {
+            Ok(structured_output::unwrap_output_tool(response))
+        } else {
+            Ok(response)
+        }
     }
 }
 
@@ -361,8 +571,10 @@ pub fn create_client(config: &LlmConfig) -> Result<Arc<dyn LLM>, AgentError> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core_types::{Message, Role, ToolCall};
+    use crate::core_types::{Message, ReasoningBlock, Role, ToolCall};
+    use crate::config::PromptCacheConfig;
     use crate::llm::ToolMetadata;
+    use crate::test_utils::mock_llm_server::MockLLMServer;
     use serde_json::json;
 
     fn create_test_client() -> AnthropicClient {
@@ -412,12 +624,14 @@ mod tests {
                 content: "You are a helpful assistant".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
             Message {
                 role: Role::User,
                 content: "Hello".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
         ];
 
@@ -445,12 +659,14 @@ mod tests {
                 content: "What's 2+2?".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
             Message {
                 role: Role::Assistant,
                 content: "2+2 equals 4".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
         ];
 
@@ -478,6 +694,7 @@ mod tests {
                 content: "Calculate 5 * 3".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
             Message {
                 role: Role::Assistant,
@@ -488,6 +705,7 @@ mod tests {
                     arguments: json!({"operation": "multiply", "a": 5, "b": 3}),
                 }]),
                 tool_call_id: None,
+                reasoning: None,
             },
         ];
 
@@ -515,6 +733,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_signed_thinking_is_sent_back_first() {
+        let client = create_test_client();
+
+        let messages = vec![
+            Message {
+                role: Role::User,
+                content: "Calculate 5 * 3".to_string(),
+                tool_calls: None,
+                tool_call_id: None,
+                reasoning: None,
+            },
+            Message {
+                role: Role::Assistant,
+                content: String::new(),
+                tool_calls: Some(vec![ToolCall {
+                    id: Some("toolu_1".to_string()),
+                    name: "calculator".to_string(),
+                    arguments: json!({"operation": "multiply", "a": 5, "b": 3}),
+                }]),
+                tool_call_id: None,
+                reasoning: Some(vec![
+                    ReasoningBlock {
+                        text: "Multiplication, use the calculator.".to_string(),
+                        signature: Some("sig-abc".to_string()),
+                        redacted_data: None,
+                    },
+                    ReasoningBlock::text("unsigned text from another provider"),
+                ]),
+            },
+        ];
+
+        let (_, anthropic_messages) = client.convert_messages(messages).unwrap();
+        let assistant = &anthropic_messages[1].content;
+
+        // Thinking first, no empty text block, unsigned reasoning dropped
+        assert_eq!(assistant.len(), 2);
+        assert!(matches!(
+            &assistant[0],
+            AnthropicContent::Thinking { signature, .. } if signature == "sig-abc"
+        ));
+        assert!(matches!(&assistant[1], AnthropicContent::ToolUse { .. }));
+    }
+
+    #[test]
+    fn test_response_conversion_with_thinking() {
+        let client = create_test_client();
+        let response: AnthropicResponse = serde_json::from_value(json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": [
+                { "type": "thinking", "thinking": "Let me add them.", "signature": "sig-1" },
+                { "type": "redacted_thinking", "data": "opaque" },
+                { "type": "text", "text": "4" }
+            ],
+            "model": "claude-sonnet-4-0",
+            "stop_reason": "end_turn",
+            "stop_sequence": null,
+            "usage": { "input_tokens": 10, "output_tokens": 5 }
+        }))
+        .unwrap();
+
+        let converted = client.convert_response(response).unwrap();
+        assert_eq!(converted.content.as_deref(), Some("4"));
+
+        let reasoning = converted.reasoning.as_ref().unwrap();
+        assert_eq!(reasoning.len(), 2);
+        assert_eq!(reasoning[0].signature.as_deref(), Some("sig-1"));
+        assert_eq!(reasoning[1].redacted_data.as_deref(), Some("opaque"));
+        assert_eq!(converted.reasoning_text().as_deref(), Some("Let me add them."));
+    }
+
     #[test]
     fn test_message_conversion_with_tool_result() {
         let client = create_test_client();
@@ -525,12 +816,14 @@ mod tests {
                 content: "Calculate 5 * 3".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
             Message {
                 role: Role::Tool,
                 content: "15".to_string(),
                 tool_calls: None,
                 tool_call_id: Some("call_123".to_string()),
+                reasoning: None,
             },
         ];
 
@@ -570,6 +863,7 @@ mod tests {
                 content: "Hello".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
         ];
 
@@ -593,12 +887,14 @@ mod tests {
                 content: "You are a helpful assistant".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
             Message {
                 role: Role::User,
                 content: "Hello".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
         ];
 
@@ -669,6 +965,8 @@ mod tests {
             usage: AnthropicUsage {
                 input_tokens: 10,
                 output_tokens: 25,
+                cache_read_input_tokens: None,
+                cache_creation_input_tokens: None,
             },
         };
 
@@ -708,6 +1006,8 @@ mod tests {
             usage: AnthropicUsage {
                 input_tokens: 15,
                 output_tokens: 30,
+                cache_read_input_tokens: None,
+                cache_creation_input_tokens: None,
             },
         };
 
@@ -745,6 +1045,8 @@ mod tests {
             usage: AnthropicUsage {
                 input_tokens: 5,
                 output_tokens: 10,
+                cache_read_input_tokens: None,
+                cache_creation_input_tokens: None,
             },
         };
 
@@ -762,6 +1064,7 @@ mod tests {
         let config = LlmConfig {
             provider: LlmProvider::Anthropic,
             model: "claude-3-5-sonnet-latest".to_string(),
+            base_url: None,
             auth: LlmAuth {
                 api_key: Some("test-api-key".to_string()),
                 api_key_env: None,
@@ -786,6 +1089,7 @@ mod tests {
         let config = LlmConfig {
             provider: LlmProvider::Anthropic,
             model: "claude-3-5-sonnet-latest".to_string(),
+            base_url: None,
             auth: LlmAuth {
                 api_key: None,
                 api_key_env: Some("TEST_ANTHROPIC_KEY".to_string()),
@@ -809,6 +1113,7 @@ mod tests {
         let config = LlmConfig {
             provider: LlmProvider::Anthropic,
             model: "claude-3-5-sonnet-latest".to_string(),
+            base_url: None,
             auth: LlmAuth {
                 api_key: None,
                 api_key_env: None,
@@ -843,6 +1148,8 @@ mod tests {
             top_p: None,
             stop_sequences: vec![],
             tools: vec![],
+            tool_choice: None,
+            thinking: None,
         };
 
         let serialized = serde_json::to_value(&request).unwrap();
@@ -857,6 +1164,128 @@ mod tests {
         assert_eq!(serialized["messages"].as_array().unwrap().len(), 1);
     }
 
+    #[test]
+    fn test_tool_choice_conversion() {
+        let tools = vec![AnthropicTool {
+            name: "calculator".to_string(),
+            description: "Perform calculations".to_string(),
+            input_schema: json!({ "type": "object" }),
+        }];
+
+        let client = create_test_client();
+        assert!(client.convert_tool_choice(&tools, false).unwrap().is_none());
+        assert!(client.convert_tool_choice(&[], false).unwrap().is_none());
+
+        let client = create_test_client_with_params(ModelParameters {
+            tool_choice: Some(ToolChoice::Required),
+            ..Default::default()
+        });
+        assert_eq!(client.convert_tool_choice(&tools, false).unwrap(), Some(json!({ "type": "any" })));
+
+        let client = create_test_client_with_params(ModelParameters {
+            tool_choice: Some(ToolChoice::Tool { name: "calculator".to_string() }),
+            ..Default::default()
+        });
+        assert_eq!(
+            client.convert_tool_choice(&tools, false).unwrap(),
+            Some(json!({ "type": "tool", "name": "calculator" }))
+        );
+    }
+
+    #[test]
+    fn test_thinking_config_validation() {
+        let client = create_test_client_with_params(ModelParameters {
+            max_tokens: 4096,
+            reasoning_budget: Some(2048),
+            ..Default::default()
+        });
+        assert_eq!(
+            client.thinking_config().unwrap(),
+            Some(json!({ "type": "enabled", "budget_tokens": 2048 }))
+        );
+
+        let client = create_test_client_with_params(ModelParameters {
+            max_tokens: 1024,
+            reasoning_budget: Some(2048),
+            ..Default::default()
+        });
+        assert!(client.thinking_config().is_err());
+
+        // The structured answer can't be forced while thinking
+        let client = create_test_client_with_params(ModelParameters {
+            reasoning_budget: Some(1024),
+            response_format: Some(crate::config::ResponseFormat::JsonObject),
+            ..Default::default()
+        });
+        let output_tool = AnthropicTool {
+            name: STRUCTURED_OUTPUT_TOOL.to_string(),
+            description: String::new(),
+            input_schema: json!({ "type": "object" }),
+        };
+        assert!(client.convert_tool_choice(&[output_tool], true).is_err());
+    }
+
+    #[test]
+    fn test_response_format_is_emulated_with_output_tool() {
+        let client = create_test_client_with_params(ModelParameters {
+            response_format: Some(crate::config::ResponseFormat::JsonObject),
+            ..Default::default()
+        });
+        let output_tool = AnthropicTool {
+            name: STRUCTURED_OUTPUT_TOOL.to_string(),
+            description: String::new(),
+            input_schema: json!({ "type": "object" }),
+        };
+
+        // With nothing else to call, the output tool is forced
+        assert_eq!(
+            client.convert_tool_choice(std::slice::from_ref(&output_tool), true).unwrap(),
+            Some(json!({ "type": "tool", "name": STRUCTURED_OUTPUT_TOOL }))
+        );
+
+        // Alongside real tools, the model must answer through some tool
+        let tools = vec![
+            AnthropicTool {
+                name: "calculator".to_string(),
+                description: String::new(),
+                input_schema: json!({ "type": "object" }),
+            },
+            output_tool,
+        ];
+        assert_eq!(client.convert_tool_choice(&tools, true).unwrap(), Some(json!({ "type": "any" })));
+
+        // Forcing another tool would leave no way to give the structured answer
+        let client = create_test_client_with_params(ModelParameters {
+            response_format: Some(crate::config::ResponseFormat::JsonObject),
+            tool_choice: Some(ToolChoice::Tool { name: "calculator".to_string() }),
+            ..Default::default()
+        });
+        assert!(client.convert_tool_choice(&tools, true).is_err());
+
+        let response = AnthropicResponse {
+            _id: "msg_1".to_string(),
+            _type: "message".to_string(),
+            _role: "assistant".to_string(),
+            content: vec![AnthropicResponseContent::ToolUse {
+                id: "toolu_1".to_string(),
+                name: STRUCTURED_OUTPUT_TOOL.to_string(),
+                input: json!({ "answer": 4 }),
+            }],
+            _model: "claude-3-5-sonnet-latest".to_string(),
+            stop_reason: Some("tool_use".to_string()),
+            _stop_sequence: None,
+            usage: AnthropicUsage {
+                input_tokens: 10,
+                output_tokens: 5,
+                cache_read_input_tokens: None,
+                cache_creation_input_tokens: None,
+            },
+        };
+        let converted = structured_output::unwrap_output_tool(client.convert_response(response).unwrap());
+        assert_eq!(converted.content.as_deref(), Some(r#"{"answer":4}"#));
+        assert!(converted.tool_calls.is_none());
+    }
+
     #[test]
     fn test_anthropic_response_deserialization() {
         let response_json = json!({
@@ -893,4 +1322,147 @@ mod tests {
             panic!("Expected text content");
         }
     }
-}
\ No newline at end of file
+
+    fn conversation() -> Vec<Message> {
+        let message = |role, content: &str| Message {
+            role,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            reasoning: None,
+        };
+        vec![
+            message(Role::System, "You are a meticulous research assistant."),
+            message(Role::User, "Look up the weather."),
+            Message {
+                tool_calls: Some(vec![ToolCall {
+                    id: Some("toolu_1".to_string()),
+                    name: "weather".to_string(),
+                    arguments: json!({ "city": "Oslo" }),
+                }]),
+                ..message(Role::Assistant, "")
+            },
+            Message {
+                tool_call_id: Some("toolu_1".to_string()),
+                ..message(Role::Tool, "Sunny, 21C")
+            },
+        ]
+    }
+
+    fn tools() -> Vec<ToolMetadata> {
+        ["search", "weather"]
+            .iter()
+            .map(|name| ToolMetadata {
+                name: name.to_string(),
+                description: format!("The {} tool", name),
+                input_schema: json!({ "type": "object" }),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_prompt_cache_breakpoints_and_usage() {
+        let server = MockLLMServer::start(vec![Ok(LLMResponse {
+            content: Some("It is sunny in Oslo.".to_string()),
+            tool_calls: None,
+            finish_reason: None,
+            usage: Some(Usage {
+                prompt_tokens: 12,
+                completion_tokens: 8,
+                total_tokens: 20,
+                cache_read_tokens: Some(1800),
+                cache_write_tokens: Some(200),
+            }),
+            reasoning: None,
+        })])
+        .await;
+
+        let params = ModelParameters {
+            prompt_cache: Some(PromptCacheConfig::default()),
+            ..ModelParameters::default()
+        };
+        let client = AnthropicClient::new(
+            "test-key".to_string(),
+            "claude-sonnet-4-5".to_string(),
+            params,
+            Some(server.address()),
+        );
+
+        let response = client.generate(conversation(), Some(tools())).await.unwrap();
+        let usage = response.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, 12 + 1800 + 200);
+        assert_eq!(usage.total_tokens, 2020);
+        assert_eq!(usage.cache_read_tokens, Some(1800));
+        assert_eq!(usage.cache_write_tokens, Some(200));
+
+        let body = server.get_raw_requests().remove(0);
+        let ephemeral = json!({ "type": "ephemeral" });
+        assert_eq!(
+            body["system"],
+            json!([{ "type": "text", "text": "You are a meticulous research assistant.", "cache_control": ephemeral }])
+        );
+        assert!(body["tools"][0].get("cache_control").is_none());
+        assert_eq!(body["tools"][1]["cache_control"], ephemeral);
+
+        let messages = body["messages"].as_array().unwrap();
+        let last_block = messages.last().unwrap()["content"].as_array().unwrap().last().unwrap().clone();
+        assert_eq!(last_block["type"], "tool_result");
+        assert_eq!(last_block["cache_control"], ephemeral);
+        let breakpoints = messages
+            .iter()
+            .flat_map(|m| m["content"].as_array().unwrap())
+            .filter(|block| block.get("cache_control").is_some())
+            .count();
+        assert_eq!(breakpoints, 1);
+
+        server.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_prompt_cache_is_off_by_default_and_selective() {
+        let reply = || {
+            Ok(LLMResponse {
+                content: Some("ok".to_string()),
+                tool_calls: None,
+                finish_reason: None,
+                usage: None,
+                reasoning: None,
+            })
+        };
+        let server = MockLLMServer::start(vec![reply(), reply()]).await;
+
+        let client = AnthropicClient::new(
+            "test-key".to_string(),
+            "claude-sonnet-4-5".to_string(),
+            ModelParameters::default(),
+            Some(server.address()),
+        );
+        client.generate(conversation(), Some(tools())).await.unwrap();
+
+        let only_system = ModelParameters {
+            prompt_cache: Some(PromptCacheConfig {
+                tools: false,
+                history: false,
+                ttl: Some("1h".to_string()),
+                ..PromptCacheConfig::default()
+            }),
+            ..ModelParameters::default()
+        };
+        let client = AnthropicClient::new(
+            "test-key".to_string(),
+            "claude-sonnet-4-5".to_string(),
+            only_system,
+            Some(server.address()),
+        );
+        client.generate(conversation(), Some(tools())).await.unwrap();
+
+        let bodies = server.get_raw_requests();
+        assert_eq!(bodies[0]["system"], "You are a meticulous research assistant.");
+        assert!(!bodies[0].to_string().contains("cache_control"));
+
+        assert_eq!(bodies[1]["system"][0]["cache_control"], json!({ "type": "ephemeral", "ttl": "1h" }));
+        assert_eq!(bodies[1].to_string().matches("cache_control").count(), 1);
+
+        server.shutdown().await;
+    }
+}
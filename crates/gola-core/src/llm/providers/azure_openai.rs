@@ -0,0 +1,484 @@
+//! Azure OpenAI provider
+//!
+//! Azure hosts OpenAI models behind per-resource deployments. Requests go to
+//! `{endpoint}/openai/deployments/{deployment}/...?api-version=...` and authenticate
+//! with an `api-key` header or a Microsoft Entra ID bearer token. Request and response
+//! bodies follow the OpenAI chat completions format, so those are shared with
+//! [`OpenAIClient`].
+
+use super::openai::OpenAIClient;
+use crate::config::{LlmConfig, ResponseFormat, ToolChoice};
+use crate::core_types::{LLMResponse, Message};
+use crate::errors::AgentError;
+use crate::llm::{ToolMetadata, LLM};
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+/// Default key variable for Azure OpenAI resources
+pub const AZURE_OPENAI_API_KEY_ENV: &str = "AZURE_OPENAI_API_KEY";
+/// Microsoft Entra ID access token, used when no API key is configured
+pub const AZURE_OPENAI_AD_TOKEN_ENV: &str = "AZURE_OPENAI_AD_TOKEN";
+
+/// Supplies Microsoft Entra ID access tokens
+///
+/// The provider is asked before every request, so it can refresh a token
+/// before it expires.
+#[async_trait]
+pub trait EntraTokenProvider: Send + Sync {
+    async fn token(&self) -> Result<String, AgentError>;
+}
+
+/// A fixed token
+#[async_trait]
+impl EntraTokenProvider for String {
+    async fn token(&self) -> Result<String, AgentError> {
+        Ok(self.clone())
+    }
+}
+
+/// Reads `AZURE_OPENAI_AD_TOKEN` for every request, so a host process can
+/// replace the token in its environment
+struct EnvTokenProvider;
+
+#[async_trait]
+impl EntraTokenProvider for EnvTokenProvider {
+    async fn token(&self) -> Result<String, AgentError> {
+        std::env::var(AZURE_OPENAI_AD_TOKEN_ENV).map_err(|_| {
+            AgentError::ConfigError(format!("{} is no longer set", AZURE_OPENAI_AD_TOKEN_ENV))
+        })
+    }
+}
+
+/// How requests to an Azure OpenAI resource are authenticated
+#[derive(Clone)]
+pub enum AzureCredential {
+    /// Resource key sent in the `api-key` header
+    ApiKey(String),
+    /// Microsoft Entra ID access token sent as a bearer token, taken from the
+    /// provider for each request
+    EntraToken(Arc<dyn EntraTokenProvider>),
+}
+
+impl fmt::Debug for AzureCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the secret
+        match self {
+            Self::ApiKey(_) => f.write_str("ApiKey"),
+            Self::EntraToken(_) => f.write_str("EntraToken"),
+        }
+    }
+}
+
+impl AzureCredential {
+    /// Authenticates with Entra ID tokens from `provider`
+    pub fn entra_token(provider: impl EntraTokenProvider + 'static) -> Self {
+        Self::EntraToken(Arc::new(provider))
+    }
+
+    /// Resolves a key from `api_key`, `api_key_env` or `AZURE_OPENAI_API_KEY`, falling
+    /// back to an Entra token in `AZURE_OPENAI_AD_TOKEN`, which is read again for
+    /// every request
+    pub fn resolve(api_key: Option<&str>, api_key_env: Option<&str>) -> Result<Self, AgentError> {
+        let key = api_key
+            .map(str::to_string)
+            .or_else(|| api_key_env.and_then(|var| std::env::var(var).ok()))
+            .or_else(|| std::env::var(AZURE_OPENAI_API_KEY_ENV).ok());
+        if let Some(key) = key {
+            return Ok(Self::ApiKey(key));
+        }
+
+        if std::env::var_os(AZURE_OPENAI_AD_TOKEN_ENV).is_none() {
+            return Err(AgentError::ConfigError(format!(
+                "No credentials found for Azure OpenAI. Set api_key, api_key_env, {} or {}",
+                AZURE_OPENAI_API_KEY_ENV, AZURE_OPENAI_AD_TOKEN_ENV
+            )));
+        }
+        Ok(Self::entra_token(EnvTokenProvider))
+    }
+
+    pub(crate) async fn apply(&self, request: RequestBuilder) -> Result<RequestBuilder, AgentError> {
+        match self {
+            Self::ApiKey(key) => Ok(request.header("api-key", key)),
+            Self::EntraToken(provider) => Ok(request.bearer_auth(provider.token().await?)),
+        }
+    }
+}
+
+/// Builds the URL for an operation on a deployment, e.g. `chat/completions` or `embeddings`
+pub(crate) fn deployment_url(endpoint: &str, deployment: &str, operation: &str, api_version: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    let endpoint = if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+        endpoint.to_string()
+    } else {
+        format!("https://{}", endpoint)
+    };
+    format!(
+        "{}/openai/deployments/{}/{}?api-version={}",
+        endpoint, deployment, operation, api_version
+    )
+}
+
+/// Turns an Azure error body into a readable message
+///
+/// Azure OpenAI answers with `{"error": {"code", "message", "innererror": {"code"}}}`,
+/// while errors raised by the API gateway in front of it use a top-level `message`.
+pub(crate) fn describe_error(status: StatusCode, body: &str) -> String {
+    let parsed: Option<Value> = serde_json::from_str(body).ok();
+    let detail = parsed.as_ref().and_then(|json| {
+        if let Some(error) = json.get("error") {
+            let message = error["message"].as_str().unwrap_or_default();
+            let code = error["innererror"]["code"]
+                .as_str()
+                .or_else(|| error["code"].as_str());
+            match code {
+                Some(code) => Some(format!("{}: {}", code, message)),
+                None => Some(message.to_string()),
+            }
+        } else {
+            json["message"].as_str().map(str::to_string)
+        }
+    });
+
+    format!(
+        "Azure OpenAI request failed with status {}: {}",
+        status,
+        detail.filter(|d| !d.is_empty()).unwrap_or_else(|| body.to_string())
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct AzureOpenAIClient {
+    client: Client,
+    credential: AzureCredential,
+    endpoint: String,
+    deployment: String,
+    api_version: String,
+    // Builds request bodies and parses responses, which match the OpenAI format
+    openai: OpenAIClient,
+}
+
+impl AzureOpenAIClient {
+    pub fn new(
+        credential: AzureCredential,
+        endpoint: String,
+        deployment: String,
+        api_version: String,
+    ) -> Self {
+        Self {
+            client: crate::http::client(),
+            credential,
+            openai: OpenAIClient::new(String::new(), deployment.clone()),
+            endpoint,
+            deployment,
+            api_version,
+        }
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.openai = self.openai.with_temperature(temperature);
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.openai = self.openai.with_max_tokens(max_tokens);
+        self
+    }
+
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.openai = self.openai.with_tool_choice(tool_choice);
+        self
+    }
+
+    pub fn with_response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.openai = self.openai.with_response_format(response_format);
+        self
+    }
+
+    fn chat_url(&self) -> String {
+        deployment_url(&self.endpoint, &self.deployment, "chat/completions", &self.api_version)
+    }
+}
+
+#[async_trait]
+impl LLM for AzureOpenAIClient {
+    async fn generate(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolMetadata>>,
+    ) -> Result<LLMResponse, AgentError> {
+        let url = self.chat_url();
+        let body = self.openai.build_request_body(&messages, tools.as_deref());
+
+        log::debug!("Azure OpenAI API request to {}", url);
+        log::debug!("Request body: {}", serde_json::to_string_pretty(&body).unwrap_or_default());
+
+        let response = self
+            .credential
+            .apply(self.client.post(&url))
+            .await?
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AgentError::LLMError(format!("HTTP request failed: {}", e)))?;
+
+        let status = response.status();
+        let response_text = response
+            .text()
+            .await
+            .map_err(|e| AgentError::LLMError(format!("Failed to read response: {}", e)))?;
+
+        log::debug!("Azure OpenAI API response ({}): {}", status, response_text);
+
+        if !status.is_success() {
+            return Err(AgentError::LLMError(describe_error(status, &response_text)));
+        }
+
+        let response_json: Value = serde_json::from_str(&response_text)
+            .map_err(|e| AgentError::ParsingError(format!("Invalid JSON response: {}", e)))?;
+
+        self.openai.parse_response(response_json)
+    }
+}
+
+/// Create an Azure OpenAI client from configuration
+pub fn create_client(config: &LlmConfig) -> Result<Arc<dyn LLM>, AgentError> {
+    let crate::config::LlmProvider::AzureOpenAI { endpoint, deployment, api_version } = &config.provider else {
+        return Err(AgentError::ConfigError(
+            "Azure OpenAI client requires the azure_openai provider".to_string(),
+        ));
+    };
+
+    let credential = AzureCredential::resolve(
+        config.auth.api_key.as_deref(),
+        config.auth.api_key_env.as_deref(),
+    )?;
+
+    let mut client = AzureOpenAIClient::new(
+        credential,
+        endpoint.clone(),
+        deployment.clone(),
+        api_version.clone(),
+    );
+
+    if config.parameters.temperature > 0.0 {
+        client = client.with_temperature(config.parameters.temperature);
+    }
+    if config.parameters.max_tokens > 0 {
+        client = client.with_max_tokens(config.parameters.max_tokens);
+    }
+    if let Some(tool_choice) = &config.parameters.tool_choice {
+        client = client.with_tool_choice(tool_choice.clone());
+    }
+    if let Some(response_format) = &config.parameters.response_format {
+        client = client.with_response_format(response_format.clone());
+    }
+
+    Ok(Arc::new(client))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_types::Role;
+    use axum::{
+        extract::{Path, Query, State},
+        http::{HeaderMap, StatusCode as AxumStatus},
+        routing::post,
+        Json, Router,
+    };
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    /// Deployment, query and headers of each request the stand-in received
+    #[derive(Debug, Clone)]
+    struct Seen {
+        deployment: String,
+        query: HashMap<String, String>,
+        headers: HeaderMap,
+    }
+
+    type Recorded = Arc<Mutex<Vec<Seen>>>;
+
+    /// Serves a canned chat completions response for any deployment
+    async fn start_stand_in(status: AxumStatus, body: Value) -> (String, Recorded) {
+        let recorded: Recorded = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route(
+                "/openai/deployments/{deployment}/chat/completions",
+                post(
+                    move |State(recorded): State<Recorded>,
+                          Path(deployment): Path<String>,
+                          Query(query): Query<HashMap<String, String>>,
+                          headers: HeaderMap| {
+                        let body = body.clone();
+                        async move {
+                            recorded.lock().unwrap().push(Seen { deployment, query, headers });
+                            (status, Json(body))
+                        }
+                    },
+                ),
+            )
+            .with_state(recorded.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        (format!("http://{}", addr), recorded)
+    }
+
+    fn user(content: &str) -> Message {
+        Message {
+            role: Role::User,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            reasoning: None,
+        }
+    }
+
+    #[test]
+    fn test_provider_config_defaults_api_version() {
+        let provider: crate::config::LlmProvider = serde_yaml::from_str(
+            "!azure_openai\nendpoint: https://acme.openai.azure.com\ndeployment: gpt4o",
+        )
+        .unwrap();
+        assert_eq!(
+            provider,
+            crate::config::LlmProvider::AzureOpenAI {
+                endpoint: "https://acme.openai.azure.com".to_string(),
+                deployment: "gpt4o".to_string(),
+                api_version: crate::config::types::default_azure_api_version(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_deployment_url() {
+        assert_eq!(
+            deployment_url("https://acme.openai.azure.com/", "gpt4o", "chat/completions", "2024-10-21"),
+            "https://acme.openai.azure.com/openai/deployments/gpt4o/chat/completions?api-version=2024-10-21"
+        );
+        assert_eq!(
+            deployment_url("acme.openai.azure.com", "ada", "embeddings", "2024-02-01"),
+            "https://acme.openai.azure.com/openai/deployments/ada/embeddings?api-version=2024-02-01"
+        );
+    }
+
+    #[test]
+    fn test_describe_error_shapes() {
+        let service = json!({
+            "error": { "code": "DeploymentNotFound", "message": "The API deployment for this resource does not exist." }
+        });
+        assert_eq!(
+            describe_error(StatusCode::NOT_FOUND, &service.to_string()),
+            "Azure OpenAI request failed with status 404 Not Found: DeploymentNotFound: The API deployment for this resource does not exist."
+        );
+
+        let filtered = json!({
+            "error": {
+                "code": "content_filter",
+                "message": "The response was filtered.",
+                "innererror": { "code": "ResponsibleAIPolicyViolation" }
+            }
+        });
+        assert!(describe_error(StatusCode::BAD_REQUEST, &filtered.to_string())
+            .ends_with("ResponsibleAIPolicyViolation: The response was filtered."));
+
+        let gateway = json!({ "statusCode": 401, "message": "Access denied due to invalid subscription key." });
+        assert!(describe_error(StatusCode::UNAUTHORIZED, &gateway.to_string())
+            .ends_with(": Access denied due to invalid subscription key."));
+
+        assert!(describe_error(StatusCode::BAD_GATEWAY, "upstream unavailable").ends_with(": upstream unavailable"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_uses_deployment_url_and_api_key() {
+        let (endpoint, recorded) = start_stand_in(
+            AxumStatus::OK,
+            json!({
+                "choices": [{ "message": { "role": "assistant", "content": "Hello from Azure" }, "finish_reason": "stop" }],
+                "usage": { "prompt_tokens": 3, "completion_tokens": 4, "total_tokens": 7 }
+            }),
+        )
+        .await;
+
+        let client = AzureOpenAIClient::new(
+            AzureCredential::ApiKey("secret".to_string()),
+            endpoint,
+            "gpt4o-prod".to_string(),
+            "2024-10-21".to_string(),
+        );
+        let response = client.generate(vec![user("hi")], None).await.unwrap();
+        assert_eq!(response.content.as_deref(), Some("Hello from Azure"));
+
+        let seen = recorded.lock().unwrap()[0].clone();
+        assert_eq!(seen.deployment, "gpt4o-prod");
+        assert_eq!(seen.query.get("api-version").map(String::as_str), Some("2024-10-21"));
+        assert_eq!(seen.headers.get("api-key").unwrap(), "secret");
+        assert!(seen.headers.get("authorization").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_generate_with_entra_token_and_error() {
+        let (endpoint, recorded) = start_stand_in(
+            AxumStatus::TOO_MANY_REQUESTS,
+            json!({ "error": { "code": "429", "message": "Rate limit exceeded. Retry after 2 seconds." } }),
+        )
+        .await;
+
+        let client = AzureOpenAIClient::new(
+            AzureCredential::entra_token("entra-token".to_string()),
+            endpoint,
+            "gpt4o".to_string(),
+            "2024-10-21".to_string(),
+        );
+        let err = client.generate(vec![user("hi")], None).await.unwrap_err();
+        assert!(err.to_string().contains("429: Rate limit exceeded. Retry after 2 seconds."));
+
+        let seen = recorded.lock().unwrap()[0].clone();
+        assert_eq!(seen.headers.get("authorization").unwrap(), "Bearer entra-token");
+        assert!(seen.headers.get("api-key").is_none());
+    }
+
+    // Hands out a new token for every request
+    struct RotatingTokens(std::sync::atomic::AtomicUsize);
+
+    #[async_trait]
+    impl EntraTokenProvider for RotatingTokens {
+        async fn token(&self) -> Result<String, AgentError> {
+            let n = self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            Ok(format!("token-{}", n))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entra_token_is_fetched_per_request() {
+        let (endpoint, recorded) = start_stand_in(
+            AxumStatus::OK,
+            json!({ "choices": [{ "message": { "role": "assistant", "content": "ok" }, "finish_reason": "stop" }] }),
+        )
+        .await;
+
+        let client = AzureOpenAIClient::new(
+            AzureCredential::entra_token(RotatingTokens(Default::default())),
+            endpoint,
+            "gpt4o".to_string(),
+            "2024-10-21".to_string(),
+        );
+        client.generate(vec![user("hi")], None).await.unwrap();
+        client.generate(vec![user("again")], None).await.unwrap();
+
+        let recorded = recorded.lock().unwrap();
+        assert_eq!(recorded[0].headers.get("authorization").unwrap(), "Bearer token-1");
+        assert_eq!(recorded[1].headers.get("authorization").unwrap(), "Bearer token-2");
+    }
+}
@@ -3,23 +3,30 @@
 //! This module provides a native Google Gemini API client that directly integrates
 //! with Google's Generative AI API endpoints.
 
-use crate::config::{LlmConfig, LlmProvider};
-use crate::core_types::{LLMResponse, Message, Role, ToolCall, Usage};
+use crate::config::{LlmConfig, LlmProvider, ResponseFormat, ToolChoice};
+use crate::core_types::{LLMResponse, Message, ReasoningBlock, Role, ToolCall, Usage};
 use crate::errors::AgentError;
+use crate::llm::structured_output::{self, STRUCTURED_OUTPUT_TOOL};
 use crate::llm::{ToolMetadata, LLM};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::env;
 use std::sync::Arc;
 
+/// Output token limit, which also bounds the thinking budget
+const MAX_OUTPUT_TOKENS: u32 = 4096;
+
 /// Google Gemini API client
 pub struct GeminiClient {
     api_key: String,
     model: String,
     client: Client,
     base_url: String,
+    tool_choice: Option<ToolChoice>,
+    response_format: Option<ResponseFormat>,
+    reasoning_budget: Option<u32>,
 }
 
 impl GeminiClient {
@@ -28,8 +35,11 @@ impl GeminiClient {
         Self {
             api_key,
             model,
-            client: Client::new(),
+            client: crate::http::client(),
             base_url: "https://generativelanguage.googleapis.com/v1beta".to_string(),
+            tool_choice: None,
+            response_format: None,
+            reasoning_budget: None,
         }
     }
 
@@ -38,10 +48,31 @@ impl GeminiClient {
         Self {
             api_key,
             model,
-            client: Client::new(),
+            client: crate::http::client(),
             base_url,
+            tool_choice: None,
+            response_format: None,
+            reasoning_budget: None,
         }
     }
+
+    /// Set the function calling mode
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
+    /// Request JSON output, optionally constrained by a schema
+    pub fn with_response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.response_format = Some(response_format);
+        self
+    }
+
+    /// Set the thinking budget and ask for thought summaries in responses
+    pub fn with_reasoning_budget(mut self, reasoning_budget: u32) -> Self {
+        self.reasoning_budget = Some(reasoning_budget);
+        self
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -55,6 +86,8 @@ struct GeminiRequest {
     system_instruction: Option<GeminiContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<GeminiTool>>,
+    #[serde(rename = "toolConfig", skip_serializing_if = "Option::is_none")]
+    tool_config: Option<Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -67,10 +100,23 @@ struct GeminiContent {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 enum GeminiPart {
-    Text { text: String },
+    // Must precede `Text`, which would otherwise match thought parts too
+    Thought {
+        text: String,
+        thought: bool,
+        #[serde(rename = "thoughtSignature", default, skip_serializing_if = "Option::is_none")]
+        thought_signature: Option<String>,
+    },
+    Text {
+        text: String,
+        #[serde(rename = "thoughtSignature", default, skip_serializing_if = "Option::is_none")]
+        thought_signature: Option<String>,
+    },
     FunctionCall {
         #[serde(rename = "functionCall")]
         function_call: GeminiFunctionCall,
+        #[serde(rename = "thoughtSignature", default, skip_serializing_if = "Option::is_none")]
+        thought_signature: Option<String>,
     },
     FunctionResponse {
         #[serde(rename = "functionResponse")]
@@ -99,6 +145,12 @@ struct GeminiGenerationConfig {
     top_p: f32,
     #[serde(rename = "stopSequences", skip_serializing_if = "Vec::is_empty")]
     stop_sequences: Vec<String>,
+    #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<String>,
+    #[serde(rename = "responseJsonSchema", skip_serializing_if = "Option::is_none")]
+    response_json_schema: Option<Value>,
+    #[serde(rename = "thinkingConfig", skip_serializing_if = "Option::is_none")]
+    thinking_config: Option<Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -165,6 +217,7 @@ impl GeminiClient {
                         role: None,
                         parts: vec![GeminiPart::Text {
                             text: message.content,
+                            thought_signature: None,
                         }],
                     });
                 }
@@ -173,18 +226,38 @@ impl GeminiClient {
                         role: Some("user".to_string()),
                         parts: vec![GeminiPart::Text {
                             text: message.content,
+                            thought_signature: None,
                         }],
                     });
                 }
                 Role::Assistant => {
                     let mut parts = Vec::new();
-                    
+
+                    // Thought signatures must be returned on the parts they came
+                    // with, or Gemini rejects the follow-up function responses
+                    let mut answer_signature = None;
+                    for block in message.reasoning.unwrap_or_default() {
+                        match block.signature {
+                            Some(signature) if block.text.is_empty() => answer_signature = Some(signature),
+                            thought_signature if !block.text.is_empty() => {
+                                parts.push(GeminiPart::Thought {
+                                    text: block.text,
+                                    thought: true,
+                                    thought_signature,
+                                });
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    let has_tool_calls = message.tool_calls.as_ref().is_some_and(|c| !c.is_empty());
                     if !message.content.is_empty() {
                         parts.push(GeminiPart::Text {
                             text: message.content,
+                            thought_signature: if has_tool_calls { None } else { answer_signature.take() },
                         });
                     }
-                    
+
                     if let Some(tool_calls) = &message.tool_calls {
                         for tool_call in tool_calls {
                             parts.push(GeminiPart::FunctionCall {
@@ -192,10 +265,11 @@ impl GeminiClient {
                                     name: tool_call.name.clone(),
                                     args: tool_call.arguments.clone(),
                                 },
+                                thought_signature: answer_signature.take(),
                             });
                         }
                     }
-                    
+
                     contents.push(GeminiContent {
                         role: Some("model".to_string()),
                         parts,
@@ -241,6 +315,38 @@ impl GeminiClient {
         }]
     }
 
+    /// Maps the configured tool choice onto Gemini's function calling config
+    ///
+    /// Gemini rejects a response schema alongside function declarations, so with
+    /// tools offered a configured response format is emulated with the structured
+    /// output tool, and the model is made to answer through a tool.
+    fn convert_tool_config(&self, has_tools: bool, emulating_output: bool) -> Result<Option<Value>, AgentError> {
+        if !has_tools {
+            return Ok(None);
+        }
+
+        let config = match (&self.tool_choice, emulating_output) {
+            (Some(ToolChoice::Tool { name }), true) if name != STRUCTURED_OUTPUT_TOOL => {
+                return Err(AgentError::ConfigError(format!(
+                    "tool_choice '{}' conflicts with response_format, which must be answered through '{}'",
+                    name, STRUCTURED_OUTPUT_TOOL
+                )));
+            }
+            (Some(ToolChoice::None), true) => {
+                json!({ "mode": "ANY", "allowedFunctionNames": [STRUCTURED_OUTPUT_TOOL] })
+            }
+            (_, true) => json!({ "mode": "ANY" }),
+            (None, false) => return Ok(None),
+            (Some(ToolChoice::Auto), false) => json!({ "mode": "AUTO" }),
+            (Some(ToolChoice::None), false) => json!({ "mode": "NONE" }),
+            (Some(ToolChoice::Required), false) => json!({ "mode": "ANY" }),
+            (Some(ToolChoice::Tool { name }), false) => {
+                json!({ "mode": "ANY", "allowedFunctionNames": [name] })
+            }
+        };
+        Ok(Some(json!({ "functionCallingConfig": config })))
+    }
+
     fn convert_gemini_response_to_llm(&self, response: GeminiResponse) -> Result<LLMResponse, AgentError> {
         let candidate = response
             .candidates
@@ -250,18 +356,30 @@ impl GeminiClient {
 
         let mut content_parts = Vec::new();
         let mut tool_calls = Vec::new();
+        let mut reasoning = Vec::new();
+        let mut answer_signature = None;
 
         for part in candidate.content.parts {
             match part {
-                GeminiPart::Text { text } => {
+                GeminiPart::Thought { text, thought: true, thought_signature } => {
+                    reasoning.push(ReasoningBlock {
+                        text,
+                        signature: thought_signature,
+                        redacted_data: None,
+                    });
+                }
+                GeminiPart::Thought { text, thought_signature, .. }
+                | GeminiPart::Text { text, thought_signature } => {
                     content_parts.push(text);
+                    answer_signature = answer_signature.or(thought_signature);
                 }
-                GeminiPart::FunctionCall { function_call } => {
+                GeminiPart::FunctionCall { function_call, thought_signature } => {
                     tool_calls.push(ToolCall {
                         id: Some(format!("call_{}", uuid::Uuid::new_v4().simple())),
                         name: function_call.name,
                         arguments: function_call.args,
                     });
+                    answer_signature = answer_signature.or(thought_signature);
                 }
                 GeminiPart::FunctionResponse { .. } => {
                     // Function responses shouldn't appear in the final response
@@ -270,6 +388,16 @@ impl GeminiClient {
             }
         }
 
+        // A signature on the answer itself is kept as a block without text, to
+        // be put back on the first function call, or on the text, next turn
+        if let Some(signature) = answer_signature {
+            reasoning.push(ReasoningBlock {
+                text: String::new(),
+                signature: Some(signature),
+                redacted_data: None,
+            });
+        }
+
         let content = if content_parts.is_empty() {
             None
         } else {
@@ -286,6 +414,8 @@ impl GeminiClient {
             prompt_tokens: u.prompt_token_count.unwrap_or(0) as u32,
             completion_tokens: u.candidates_token_count.unwrap_or(0) as u32,
             total_tokens: u.total_token_count.unwrap_or(0) as u32,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
         });
 
         Ok(LLMResponse {
@@ -293,6 +423,7 @@ impl GeminiClient {
             tool_calls,
             finish_reason: candidate.finish_reason,
             usage,
+            reasoning: if reasoning.is_empty() { None } else { Some(reasoning) },
         })
     }
 }
@@ -305,22 +436,53 @@ impl LLM for GeminiClient {
         tools: Option<Vec<ToolMetadata>>,
     ) -> Result<LLMResponse, AgentError> {
         let (system_instruction, contents) = self.convert_messages_to_gemini_contents(messages);
-        
+        let mut tools = tools.unwrap_or_default();
+
+        let emulating_output = match &self.response_format {
+            Some(format) if !tools.is_empty() => {
+                tools.push(structured_output::output_tool(format));
+                true
+            }
+            _ => false,
+        };
+        let (response_mime_type, response_json_schema) = match &self.response_format {
+            _ if emulating_output => (None, None),
+            Some(ResponseFormat::JsonObject) => (Some("application/json".to_string()), None),
+            Some(ResponseFormat::JsonSchema { schema, .. }) => {
+                (Some("application/json".to_string()), Some(schema.clone()))
+            }
+            None => (None, None),
+        };
+
+        if let Some(budget) = self.reasoning_budget.filter(|budget| *budget >= MAX_OUTPUT_TOKENS) {
+            return Err(AgentError::ConfigError(format!(
+                "reasoning_budget ({}) must be less than the Gemini output token limit ({})",
+                budget, MAX_OUTPUT_TOKENS
+            )));
+        }
+
         let generation_config = GeminiGenerationConfig {
             temperature: 0.7,
-            max_output_tokens: 4096,
+            max_output_tokens: MAX_OUTPUT_TOKENS,
             top_p: 0.9,
             stop_sequences: Vec::new(),
+            response_mime_type,
+            response_json_schema,
+            thinking_config: self
+                .reasoning_budget
+                .map(|budget| json!({ "thinkingBudget": budget, "includeThoughts": true })),
         };
 
-        let tools_gemini = tools.map(|t| self.convert_tools_to_gemini(t));
+        let tools_gemini = self.convert_tools_to_gemini(tools);
+        let tool_config = self.convert_tool_config(!tools_gemini.is_empty(), emulating_output)?;
 
         let request = GeminiRequest {
             contents,
             generation_config,
             safety_settings: None,
             system_instruction,
-            tools: tools_gemini,
+            tools: if tools_gemini.is_empty() { None } else { Some(tools_gemini) },
+            tool_config,
         };
 
         let url = format!(
@@ -363,7 +525,12 @@ impl LLM for GeminiClient {
             .await
             .map_err(|e| AgentError::ParsingError(format!("Failed to parse Gemini response: {}", e)))?;
 
-        self.convert_gemini_response_to_llm(gemini_response)
+        let response = self.convert_gemini_response_to_llm(gemini_response)?;
+        if emulating_output {
+            Ok(structured_output::unwrap_output_tool(response))
+        } else {
+            Ok(response)
+        }
     }
 }
 
@@ -384,7 +551,7 @@ pub fn create_client(config: &LlmConfig) -> Result<Arc<dyn LLM>, AgentError> {
         },
     };
 
-    let client = match &config.provider {
+    let mut client = match &config.provider {
         LlmProvider::Gemini => GeminiClient::new(api_key, config.model.clone()),
         LlmProvider::Custom { base_url } => {
             GeminiClient::with_base_url(api_key, config.model.clone(), base_url.clone())
@@ -396,6 +563,16 @@ pub fn create_client(config: &LlmConfig) -> Result<Arc<dyn LLM>, AgentError> {
         }
     };
 
+    if let Some(tool_choice) = &config.parameters.tool_choice {
+        client = client.with_tool_choice(tool_choice.clone());
+    }
+    if let Some(response_format) = &config.parameters.response_format {
+        client = client.with_response_format(response_format.clone());
+    }
+    if let Some(reasoning_budget) = config.parameters.reasoning_budget {
+        client = client.with_reasoning_budget(reasoning_budget);
+    }
+
     Ok(Arc::new(client))
 }
 
@@ -422,6 +599,7 @@ mod tests {
                 content: "Hello".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
         ];
 
@@ -440,12 +618,14 @@ mod tests {
                 content: "You are helpful".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
             Message {
                 role: Role::User,
                 content: "Hello".to_string(),
                 tool_calls: None,
                 tool_call_id: None,
+                reasoning: None,
             },
         ];
 
@@ -482,11 +662,101 @@ mod tests {
         assert_eq!(gemini_tools[0].function_declarations[0].name, "calculator");
     }
 
+    #[test]
+    fn test_tool_config_conversion() {
+        let client = GeminiClient::new("test-key".to_string(), "gemini-pro".to_string());
+        assert!(client.convert_tool_config(true, false).unwrap().is_none());
+
+        let client = client.with_tool_choice(ToolChoice::Tool { name: "calculator".to_string() });
+        let config = client.convert_tool_config(true, false).unwrap().unwrap();
+        assert_eq!(config["functionCallingConfig"]["mode"], "ANY");
+        assert_eq!(config["functionCallingConfig"]["allowedFunctionNames"][0], "calculator");
+
+        // No tool config without function declarations
+        assert!(client.convert_tool_config(false, false).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_response_format_with_tools_is_emulated() {
+        let client = GeminiClient::new("test-key".to_string(), "gemini-pro".to_string())
+            .with_response_format(ResponseFormat::JsonObject);
+        let config = client.convert_tool_config(true, true).unwrap().unwrap();
+        assert_eq!(config["functionCallingConfig"]["mode"], "ANY");
+
+        let client = client.with_tool_choice(ToolChoice::None);
+        let config = client.convert_tool_config(true, true).unwrap().unwrap();
+        assert_eq!(config["functionCallingConfig"]["allowedFunctionNames"][0], STRUCTURED_OUTPUT_TOOL);
+
+        // Forcing another tool would leave no way to give the structured answer
+        let client = client.with_tool_choice(ToolChoice::Tool { name: "calculator".to_string() });
+        assert!(client.convert_tool_config(true, true).is_err());
+    }
+
+    #[test]
+    fn test_thought_parts_become_reasoning() {
+        let client = GeminiClient::new("test-key".to_string(), "gemini-2.5-flash".to_string());
+        let response: GeminiResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        { "text": "The user wants a sum.", "thought": true },
+                        { "text": "4" }
+                    ]
+                },
+                "finishReason": "STOP"
+            }]
+        }))
+        .unwrap();
+
+        let converted = client.convert_gemini_response_to_llm(response).unwrap();
+        assert_eq!(converted.content.as_deref(), Some("4"));
+        assert_eq!(converted.reasoning_text().as_deref(), Some("The user wants a sum."));
+    }
+
+    #[test]
+    fn test_thought_signatures_are_sent_back() {
+        let client = GeminiClient::new("test-key".to_string(), "gemini-2.5-flash".to_string());
+        let response: GeminiResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        { "text": "Add them.", "thought": true },
+                        {
+                            "functionCall": { "name": "calculator", "args": { "a": 2, "b": 2 } },
+                            "thoughtSignature": "sig-1"
+                        }
+                    ]
+                },
+                "finishReason": "STOP"
+            }]
+        }))
+        .unwrap();
+
+        let converted = client.convert_gemini_response_to_llm(response).unwrap();
+        assert_eq!(converted.reasoning_text().as_deref(), Some("Add them."));
+
+        let messages = vec![Message {
+            role: Role::Assistant,
+            content: String::new(),
+            tool_calls: converted.tool_calls,
+            tool_call_id: None,
+            reasoning: converted.reasoning,
+        }];
+        let (_, contents) = client.convert_messages_to_gemini_contents(messages);
+        let parts = serde_json::to_value(&contents[0].parts).unwrap();
+        assert_eq!(parts[0]["thought"], true);
+        assert_eq!(parts[1]["functionCall"]["name"], "calculator");
+        assert_eq!(parts[1]["thoughtSignature"], "sig-1");
+    }
+
     #[test]
     fn test_create_client_from_config() {
         let config = LlmConfig {
             provider: LlmProvider::Gemini,
             model: "gemini-pro".to_string(),
+            base_url: None,
             auth: LlmAuth {
                 api_key: Some("test-key".to_string()),
                 api_key_env: None,
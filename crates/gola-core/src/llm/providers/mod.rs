@@ -12,13 +12,27 @@ use crate::errors::AgentError;
 pub mod openai;
 pub mod anthropic;
 pub mod gemini;
+pub mod ollama;
+pub mod azure_openai;
 
 /// Create an LLM client based on the provider configuration
 pub fn create_llm_client(config: &LlmConfig) -> Result<Arc<dyn LLM>, AgentError> {
+    let client = create_provider_client(config)?;
+    
+    // Models without native function calling get tools through the prompt instead
+    match config.parameters.tool_protocol {
+        Some(protocol) => Ok(Arc::new(crate::llm::TextToolProtocolLLM::new(client, protocol))),
+        None => Ok(client),
+    }
+}
+
+fn create_provider_client(config: &LlmConfig) -> Result<Arc<dyn LLM>, AgentError> {
     match &config.provider {
         LlmProvider::OpenAI => openai::create_client(config),
         LlmProvider::Anthropic => anthropic::create_client(config),
         LlmProvider::Gemini => gemini::create_client(config),
+        LlmProvider::Ollama => ollama::create_client(config),
+        LlmProvider::AzureOpenAI { .. } => azure_openai::create_client(config),
         LlmProvider::Custom { base_url } => {
             // For custom providers, use OpenAI-compatible client with custom base URL
             openai::create_custom_client(config, base_url)
@@ -32,6 +46,9 @@ pub fn get_default_model(provider: &LlmProvider) -> &'static str {
         LlmProvider::OpenAI => "gpt-4.1-mini",
         LlmProvider::Anthropic => "claude-3-5-sonnet-latest",
         LlmProvider::Gemini => "gemini-2.0-flash",
+        LlmProvider::Ollama => "llama3.2",
+        // Azure routes by deployment; the model name is informational
+        LlmProvider::AzureOpenAI { .. } => "gpt-4.1-mini",
         LlmProvider::Custom { .. } => "gpt-4.1-mini",
     }
 }
@@ -70,6 +87,21 @@ pub fn validate_provider_config(config: &LlmConfig) -> Result<(), AgentError> {
                 ));
             }
         }
+        LlmProvider::Ollama => {
+            // Local Ollama servers need no credentials
+        }
+        LlmProvider::AzureOpenAI { endpoint, deployment, api_version } => {
+            if endpoint.is_empty() || deployment.is_empty() {
+                return Err(AgentError::ConfigError(
+                    "Azure OpenAI provider requires 'endpoint' and 'deployment'".to_string()
+                ));
+            }
+            if api_version.is_empty() {
+                return Err(AgentError::ConfigError(
+                    "Azure OpenAI provider requires a non-empty 'api_version'".to_string()
+                ));
+            }
+        }
         LlmProvider::Custom { base_url } => {
             if base_url.is_empty() {
                 return Err(AgentError::ConfigError(
@@ -0,0 +1,768 @@
+//! Ollama API client implementation
+//!
+//! Talks to a local (or remote) Ollama server through its native `/api/chat`
+//! endpoint rather than the OpenAI compatibility layer, so Ollama-only options
+//! such as `num_ctx` and `keep_alive` can be set and its tool calling quirks
+//! smoothed over.
+
+use crate::config::{LlmConfig, ResponseFormat, ToolChoice};
+use crate::core_types::{LLMResponse, Message, ReasoningBlock, Role, ToolCall, Usage};
+use crate::errors::AgentError;
+use crate::llm::{ToolMetadata, LLM};
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+
+/// Address Ollama listens on when `OLLAMA_HOST` is not set
+pub const DEFAULT_OLLAMA_HOST: &str = "http://localhost:11434";
+
+/// Resolves the Ollama base URL from `OLLAMA_HOST`
+///
+/// Accepts the same forms as the Ollama CLI: a full URL, `host:port`, or a bare host.
+pub fn base_url_from_env() -> String {
+    match env::var("OLLAMA_HOST") {
+        Ok(host) if !host.trim().is_empty() => normalize_host(host.trim()),
+        _ => DEFAULT_OLLAMA_HOST.to_string(),
+    }
+}
+
+/// Resolves the Ollama base URL of `config`, falling back to `OLLAMA_HOST`
+pub fn base_url_for(config: &LlmConfig) -> String {
+    match &config.base_url {
+        Some(base_url) if !base_url.trim().is_empty() => normalize_host(base_url.trim()),
+        _ => base_url_from_env(),
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    let with_scheme = if host.starts_with("http://") || host.starts_with("https://") {
+        host.to_string()
+    } else {
+        format!("http://{}", host)
+    };
+    let trimmed = with_scheme.trim_end_matches('/');
+
+    let authority = trimmed.split("://").nth(1).unwrap_or_default();
+    if authority.contains(':') {
+        trimmed.to_string()
+    } else {
+        format!("{}:11434", trimmed)
+    }
+}
+
+/// Lists the models installed on an Ollama server via `/api/tags`
+pub async fn list_models(base_url: &str) -> Result<Vec<String>, AgentError> {
+    let url = format!("{}/api/tags", base_url.trim_end_matches('/'));
+    let response = crate::http::client()
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| AgentError::LLMError(format!("Ollama is not reachable at {}: {}", base_url, e)))?;
+
+    if !response.status().is_success() {
+        return Err(AgentError::LLMError(format!(
+            "Ollama model listing failed with status {}",
+            response.status()
+        )));
+    }
+
+    let tags: OllamaTags = response
+        .json()
+        .await
+        .map_err(|e| AgentError::ParsingError(format!("Failed to parse Ollama model list: {}", e)))?;
+
+    Ok(tags.models.into_iter().map(|m| m.name).collect())
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OllamaTags {
+    #[serde(default)]
+    pub(crate) models: Vec<OllamaModel>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct OllamaModel {
+    pub(crate) name: String,
+}
+
+/// Ollama API client
+pub struct OllamaClient {
+    model: String,
+    client: Client,
+    base_url: String,
+    temperature: Option<f32>,
+    top_p: Option<f32>,
+    max_tokens: Option<u32>,
+    num_ctx: Option<u32>,
+    keep_alive: Option<String>,
+    stop_sequences: Vec<String>,
+    tool_choice: Option<ToolChoice>,
+    response_format: Option<ResponseFormat>,
+    think: bool,
+}
+
+impl OllamaClient {
+    /// Create a new Ollama client for the server named by `OLLAMA_HOST`
+    pub fn new(model: String) -> Self {
+        Self::with_base_url(model, base_url_from_env())
+    }
+
+    /// Create a new Ollama client with a custom base URL
+    pub fn with_base_url(model: String, base_url: String) -> Self {
+        Self {
+            model,
+            client: crate::http::client(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            temperature: None,
+            top_p: None,
+            max_tokens: None,
+            num_ctx: None,
+            keep_alive: None,
+            stop_sequences: Vec::new(),
+            tool_choice: None,
+            response_format: None,
+            think: false,
+        }
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Set the context window the model is loaded with
+    pub fn with_num_ctx(mut self, num_ctx: u32) -> Self {
+        self.num_ctx = Some(num_ctx);
+        self
+    }
+
+    /// Set how long the model stays loaded after the request
+    pub fn with_keep_alive(mut self, keep_alive: String) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    pub fn with_stop_sequences(mut self, stop_sequences: Vec<String>) -> Self {
+        self.stop_sequences = stop_sequences;
+        self
+    }
+
+    /// Set the tool selection policy
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
+    /// Request JSON output, optionally constrained by a schema
+    pub fn with_response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.response_format = Some(response_format);
+        self
+    }
+
+    /// Ask thinking models to return their reasoning separately
+    pub fn with_think(mut self, think: bool) -> Self {
+        self.think = think;
+        self
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaChatRequest {
+    model: String,
+    messages: Vec<OllamaMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<Value>,
+    options: Value,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    think: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct OllamaMessage {
+    role: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    thinking: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<OllamaToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct OllamaToolCall {
+    function: OllamaFunctionCall,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct OllamaFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponse {
+    message: OllamaMessage,
+    #[serde(default)]
+    done_reason: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: Option<u32>,
+    #[serde(default)]
+    eval_count: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaError {
+    error: String,
+}
+
+impl OllamaClient {
+    fn convert_messages(&self, messages: Vec<Message>) -> Vec<OllamaMessage> {
+        // Ollama pairs tool results with calls by function name rather than id
+        let mut tool_names: HashMap<String, String> = HashMap::new();
+        let mut converted = Vec::with_capacity(messages.len());
+
+        for message in messages {
+            let role = match message.role {
+                Role::System => "system",
+                Role::User => "user",
+                Role::Assistant => "assistant",
+                Role::Tool => "tool",
+            };
+
+            let tool_calls = message.tool_calls.as_ref().map(|calls| {
+                calls
+                    .iter()
+                    .map(|call| {
+                        if let Some(id) = &call.id {
+                            tool_names.insert(id.clone(), call.name.clone());
+                        }
+                        OllamaToolCall {
+                            function: OllamaFunctionCall {
+                                name: call.name.clone(),
+                                arguments: call.arguments.clone(),
+                            },
+                        }
+                    })
+                    .collect()
+            });
+
+            let tool_name = match message.role {
+                Role::Tool => message
+                    .tool_call_id
+                    .as_ref()
+                    .and_then(|id| tool_names.get(id).cloned()),
+                _ => None,
+            };
+
+            converted.push(OllamaMessage {
+                role: role.to_string(),
+                content: message.content,
+                thinking: None,
+                tool_calls,
+                tool_name,
+            });
+        }
+
+        converted
+    }
+
+    fn convert_tools(&self, tools: Vec<ToolMetadata>) -> Vec<Value> {
+        let tools = match &self.tool_choice {
+            // Ollama has no tool_choice; leaving the tools out is the only way to forbid them
+            Some(ToolChoice::None) => return Vec::new(),
+            Some(ToolChoice::Tool { name }) => tools.into_iter().filter(|t| &t.name == name).collect(),
+            Some(ToolChoice::Required) => {
+                log::debug!("Ollama cannot force a tool call; tool_choice 'required' is treated as 'auto'");
+                tools
+            }
+            _ => tools,
+        };
+
+        tools
+            .into_iter()
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.input_schema,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn build_options(&self) -> Value {
+        let mut options = serde_json::Map::new();
+        if let Some(temperature) = self.temperature {
+            options.insert("temperature".to_string(), json!(temperature));
+        }
+        if let Some(top_p) = self.top_p {
+            options.insert("top_p".to_string(), json!(top_p));
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            options.insert("num_predict".to_string(), json!(max_tokens));
+        }
+        if let Some(num_ctx) = self.num_ctx {
+            options.insert("num_ctx".to_string(), json!(num_ctx));
+        }
+        if !self.stop_sequences.is_empty() {
+            options.insert("stop".to_string(), json!(self.stop_sequences));
+        }
+        Value::Object(options)
+    }
+
+    fn build_request(&self, messages: Vec<Message>, tools: Option<Vec<ToolMetadata>>) -> OllamaChatRequest {
+        let format = match &self.response_format {
+            Some(ResponseFormat::JsonObject) => Some(json!("json")),
+            Some(ResponseFormat::JsonSchema { schema, .. }) => Some(schema.clone()),
+            None => None,
+        };
+
+        OllamaChatRequest {
+            model: self.model.clone(),
+            messages: self.convert_messages(messages),
+            tools: tools.map(|t| self.convert_tools(t)).unwrap_or_default(),
+            format,
+            options: self.build_options(),
+            stream: false,
+            keep_alive: self.keep_alive.clone(),
+            think: self.think,
+        }
+    }
+
+    fn convert_response(&self, response: OllamaChatResponse, tool_names: &[String]) -> LLMResponse {
+        let message = response.message;
+
+        let mut tool_calls: Vec<ToolCall> = message
+            .tool_calls
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| ToolCall {
+                // Ollama does not assign call ids; they must stay unique across turns
+                id: Some(new_call_id()),
+                name: call.function.name,
+                arguments: parse_arguments(call.function.arguments),
+            })
+            .collect();
+
+        let mut content = message.content;
+        if tool_calls.is_empty() {
+            if let Some(call) = tool_call_from_content(&content, tool_names) {
+                tool_calls.push(call);
+                content.clear();
+            }
+        }
+
+        let reasoning = message
+            .thinking
+            .filter(|thinking| !thinking.trim().is_empty())
+            .map(|thinking| vec![ReasoningBlock::text(thinking)]);
+
+        let usage = match (response.prompt_eval_count, response.eval_count) {
+            (None, None) => None,
+            (prompt, completion) => {
+                let prompt_tokens = prompt.unwrap_or(0);
+                let completion_tokens = completion.unwrap_or(0);
+                Some(Usage {
+                    prompt_tokens,
+                    completion_tokens,
+                    total_tokens: prompt_tokens + completion_tokens,
+                    cache_read_tokens: None,
+                    cache_write_tokens: None,
+                })
+            }
+        };
+
+        LLMResponse {
+            content: if content.trim().is_empty() { None } else { Some(content) },
+            tool_calls: if tool_calls.is_empty() { None } else { Some(tool_calls) },
+            finish_reason: response.done_reason,
+            usage,
+            reasoning,
+        }
+    }
+
+    async fn missing_model_error(&self) -> AgentError {
+        let installed = list_models(&self.base_url).await.unwrap_or_default();
+        let installed = if installed.is_empty() {
+            "none".to_string()
+        } else {
+            installed.join(", ")
+        };
+
+        AgentError::LLMError(format!(
+            "Ollama model '{}' is not installed. Run `ollama pull {}`. Installed models: {}",
+            self.model, self.model, installed
+        ))
+    }
+}
+
+fn new_call_id() -> String {
+    format!("call_{}", uuid::Uuid::new_v4().simple())
+}
+
+/// Some models return arguments as a JSON string instead of an object
+fn parse_arguments(arguments: Value) -> Value {
+    match arguments {
+        Value::String(raw) => serde_json::from_str(&raw).unwrap_or(Value::String(raw)),
+        Value::Null => json!({}),
+        other => other,
+    }
+}
+
+/// Recovers a tool call that the model wrote into its text instead of `tool_calls`
+///
+/// Smaller models without a tool-aware template answer with
+/// `{"name": ..., "parameters": {...}}` as plain content.
+fn tool_call_from_content(content: &str, tool_names: &[String]) -> Option<ToolCall> {
+    let trimmed = content
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```")
+        .trim();
+    let value: Value = serde_json::from_str(trimmed).ok()?;
+    let name = value.get("name")?.as_str()?;
+    if !tool_names.iter().any(|t| t == name) {
+        return None;
+    }
+
+    let arguments = value
+        .get("parameters")
+        .or_else(|| value.get("arguments"))
+        .cloned()
+        .map(parse_arguments)
+        .unwrap_or_else(|| json!({}));
+
+    Some(ToolCall {
+        id: Some(new_call_id()),
+        name: name.to_string(),
+        arguments,
+    })
+}
+
+#[async_trait]
+impl LLM for OllamaClient {
+    async fn generate(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolMetadata>>,
+    ) -> Result<LLMResponse, AgentError> {
+        let request = self.build_request(messages, tools);
+        let tool_names: Vec<String> = request
+            .tools
+            .iter()
+            .filter_map(|t| t["function"]["name"].as_str().map(str::to_string))
+            .collect();
+
+        let response = self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| {
+                AgentError::LLMError(format!(
+                    "Ollama request to {} failed: {}. Is `ollama serve` running?",
+                    self.base_url, e
+                ))
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            let message = serde_json::from_str::<OllamaError>(&error_text)
+                .map(|e| e.error)
+                .unwrap_or(error_text);
+
+            if status == StatusCode::NOT_FOUND && message.contains("not found") {
+                return Err(self.missing_model_error().await);
+            }
+
+            return Err(AgentError::LLMError(format!(
+                "Ollama request failed with status {}: {}",
+                status, message
+            )));
+        }
+
+        let chat_response: OllamaChatResponse = response
+            .json()
+            .await
+            .map_err(|e| AgentError::ParsingError(format!("Failed to parse Ollama response: {}", e)))?;
+
+        Ok(self.convert_response(chat_response, &tool_names))
+    }
+}
+
+/// Create an Ollama LLM client from configuration
+pub fn create_client(config: &LlmConfig) -> Result<Arc<dyn LLM>, AgentError> {
+    let params = &config.parameters;
+    let mut client = OllamaClient::with_base_url(config.model.clone(), base_url_for(config))
+        .with_temperature(params.temperature)
+        .with_top_p(params.top_p)
+        .with_stop_sequences(params.stop_sequences.clone())
+        .with_think(params.reasoning_budget.is_some());
+
+    if params.max_tokens > 0 {
+        client = client.with_max_tokens(params.max_tokens);
+    }
+    if let Some(num_ctx) = params.num_ctx {
+        client = client.with_num_ctx(num_ctx);
+    }
+    if let Some(keep_alive) = &params.keep_alive {
+        client = client.with_keep_alive(keep_alive.clone());
+    }
+    if let Some(tool_choice) = &params.tool_choice {
+        client = client.with_tool_choice(tool_choice.clone());
+    }
+    if let Some(response_format) = &params.response_format {
+        client = client.with_response_format(response_format.clone());
+    }
+
+    Ok(Arc::new(client))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::State,
+        http::StatusCode as AxumStatus,
+        routing::{get, post},
+        Json, Router,
+    };
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    type Recorded = Arc<Mutex<Vec<Value>>>;
+
+    /// Serves canned `/api/chat` and `/api/tags` responses and records chat requests
+    async fn start_stand_in(chat_status: AxumStatus, chat_body: Value) -> (String, Recorded) {
+        let recorded: Recorded = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route(
+                "/api/chat",
+                post(move |State(recorded): State<Recorded>, Json(body): Json<Value>| {
+                    let chat_body = chat_body.clone();
+                    async move {
+                        recorded.lock().unwrap().push(body);
+                        (chat_status, Json(chat_body))
+                    }
+                }),
+            )
+            .route(
+                "/api/tags",
+                get(|| async {
+                    Json(json!({ "models": [{ "name": "llama3.2:latest" }, { "name": "qwen3:8b" }] }))
+                }),
+            )
+            .with_state(recorded.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        (format!("http://{}", addr), recorded)
+    }
+
+    fn calculator_tool() -> ToolMetadata {
+        ToolMetadata {
+            name: "calculator".to_string(),
+            description: "Adds numbers".to_string(),
+            input_schema: json!({ "type": "object", "properties": { "a": { "type": "number" } } }),
+        }
+    }
+
+    fn user(content: &str) -> Message {
+        Message {
+            role: Role::User,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            reasoning: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(normalize_host("127.0.0.1"), "http://127.0.0.1:11434");
+        assert_eq!(normalize_host("0.0.0.0:8080"), "http://0.0.0.0:8080");
+        assert_eq!(normalize_host("https://ollama.internal/"), "https://ollama.internal:11434");
+        assert_eq!(normalize_host("http://gpu-box:11500"), "http://gpu-box:11500");
+    }
+
+    #[tokio::test]
+    async fn test_chat_request_carries_ollama_options_and_tools() {
+        let (base_url, recorded) = start_stand_in(
+            AxumStatus::OK,
+            json!({
+                "model": "llama3.2",
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{ "function": { "name": "calculator", "arguments": { "a": 2 } } }]
+                },
+                "done": true,
+                "done_reason": "stop",
+                "prompt_eval_count": 12,
+                "eval_count": 5
+            }),
+        )
+        .await;
+
+        let client = OllamaClient::with_base_url("llama3.2".to_string(), base_url)
+            .with_num_ctx(8192)
+            .with_keep_alive("10m".to_string())
+            .with_max_tokens(256);
+
+        let response = client
+            .generate(vec![user("add 2")], Some(vec![calculator_tool()]))
+            .await
+            .unwrap();
+
+        let calls = response.tool_calls.unwrap();
+        assert!(calls[0].id.as_deref().unwrap().starts_with("call_"));
+        assert_eq!(calls[0].arguments, json!({ "a": 2 }));
+        assert_eq!(response.usage.unwrap().total_tokens, 17);
+
+        let request = recorded.lock().unwrap()[0].clone();
+        assert_eq!(request["stream"], json!(false));
+        assert_eq!(request["keep_alive"], json!("10m"));
+        assert_eq!(request["options"]["num_ctx"], json!(8192));
+        assert_eq!(request["options"]["num_predict"], json!(256));
+        assert_eq!(request["tools"][0]["function"]["name"], json!("calculator"));
+    }
+
+    #[tokio::test]
+    async fn test_client_from_config_uses_base_url_and_fresh_call_ids() {
+        let (base_url, recorded) = start_stand_in(
+            AxumStatus::OK,
+            json!({
+                "model": "llama3.2",
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{ "function": { "name": "calculator", "arguments": { "a": 2 } } }]
+                },
+                "done": true
+            }),
+        )
+        .await;
+
+        let config = LlmConfig {
+            provider: crate::config::LlmProvider::Ollama,
+            model: "llama3.2".to_string(),
+            base_url: Some(base_url),
+            parameters: crate::config::ModelParameters {
+                max_tokens: 0,
+                ..Default::default()
+            },
+            auth: Default::default(),
+        };
+        assert_eq!(base_url_for(&config), config.base_url.clone().unwrap());
+        let client = create_client(&config).unwrap();
+
+        let first = client.generate(vec![user("add 2")], Some(vec![calculator_tool()])).await.unwrap();
+        let second = client.generate(vec![user("add 2")], Some(vec![calculator_tool()])).await.unwrap();
+        assert_ne!(first.tool_calls.unwrap()[0].id, second.tool_calls.unwrap()[0].id);
+
+        let request = recorded.lock().unwrap()[0].clone();
+        assert!(request["options"].get("num_predict").is_none());
+    }
+
+    #[test]
+    fn test_tool_results_are_paired_by_name() {
+        let client = OllamaClient::with_base_url("llama3.2".to_string(), DEFAULT_OLLAMA_HOST.to_string());
+        let messages = vec![
+            user("add 2"),
+            Message {
+                role: Role::Assistant,
+                content: String::new(),
+                tool_call_id: None,
+                tool_calls: Some(vec![ToolCall {
+                    id: Some("call_0".to_string()),
+                    name: "calculator".to_string(),
+                    arguments: json!({ "a": 2 }),
+                }]),
+                reasoning: None,
+            },
+            Message {
+                role: Role::Tool,
+                content: "2".to_string(),
+                tool_call_id: Some("call_0".to_string()),
+                tool_calls: None,
+                reasoning: None,
+            },
+        ];
+
+        let converted = client.convert_messages(messages);
+        assert_eq!(converted[2].role, "tool");
+        assert_eq!(converted[2].tool_name.as_deref(), Some("calculator"));
+    }
+
+    #[test]
+    fn test_tool_call_written_as_content_is_recovered() {
+        let tools = vec!["calculator".to_string()];
+        let call = tool_call_from_content(
+            r#"{"name": "calculator", "parameters": "{\"a\": 3}"}"#,
+            &tools,
+        )
+        .unwrap();
+        assert_eq!(call.name, "calculator");
+        assert_eq!(call.arguments, json!({ "a": 3 }));
+
+        assert!(tool_call_from_content(r#"{"name": "unknown"}"#, &tools).is_none());
+        assert!(tool_call_from_content("The answer is 3", &tools).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_missing_model_lists_installed_models() {
+        let (base_url, _) = start_stand_in(
+            AxumStatus::NOT_FOUND,
+            json!({ "error": "model \"mistral\" not found, try pulling it first" }),
+        )
+        .await;
+
+        let client = OllamaClient::with_base_url("mistral".to_string(), base_url);
+        let err = client.generate(vec![user("hi")], None).await.unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("ollama pull mistral"));
+        assert!(message.contains("llama3.2:latest, qwen3:8b"));
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let (base_url, _) = start_stand_in(AxumStatus::OK, json!({})).await;
+        let models = list_models(&base_url).await.unwrap();
+        assert_eq!(models, vec!["llama3.2:latest", "qwen3:8b"]);
+    }
+}
@@ -1,10 +1,14 @@
-use crate::core_types::{LLMResponse, Message, Role, ToolCall};
+use crate::config::{OpenAIApi, ResponseFormat, ToolChoice};
+use crate::core_types::{LLMResponse, Message, ReasoningBlock, Role, ToolCall, Usage};
 use crate::errors::AgentError;
 use crate::llm::{ToolMetadata, LLM};
 use async_trait::async_trait;
 use reqwest::Client;
 
 use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
 
 
 #[derive(Debug, Clone)]
@@ -15,17 +19,38 @@ pub struct OpenAIClient {
     model: String,
     temperature: Option<f32>,
     max_tokens: Option<u32>,
+    tool_choice: Option<ToolChoice>,
+    response_format: Option<ResponseFormat>,
+    api: OpenAIApi,
+    reasoning_budget: Option<u32>,
+    store: bool,
+    chain: Arc<Mutex<Option<ResponseChain>>>,
+}
+
+/// The last stored Responses API turn, which the next request can continue
+#[derive(Debug)]
+struct ResponseChain {
+    id: String,
+    /// Number and fingerprint of the messages that turn was sent
+    len: usize,
+    fingerprint: u64,
 }
 
 impl OpenAIClient {
     pub fn new(api_key: String, model: String) -> Self {
         Self {
-            client: Client::new(),
+            client: crate::http::client(),
             api_key,
             api_base: "https://api.openai.com/v1".to_string(),
             model,
             temperature: None,
             max_tokens: None,
+            tool_choice: None,
+            response_format: None,
+            api: OpenAIApi::default(),
+            reasoning_budget: None,
+            store: false,
+            chain: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -44,7 +69,36 @@ impl OpenAIClient {
         self
     }
 
-    fn build_request_body(
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
+    pub fn with_response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.response_format = Some(response_format);
+        self
+    }
+
+    pub fn with_api(mut self, api: OpenAIApi) -> Self {
+        self.api = api;
+        self
+    }
+
+    /// Stores turns server-side and continues them with `previous_response_id`
+    /// (Responses API only)
+    pub fn with_store(mut self, store: bool) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Requests reasoning summaries, with an effort picked from the token budget
+    /// (Responses API only)
+    pub fn with_reasoning_budget(mut self, budget: u32) -> Self {
+        self.reasoning_budget = Some(budget);
+        self
+    }
+
+    pub(crate) fn build_request_body(
         &self,
         messages: &[Message],
         tools: Option<&[ToolMetadata]>,
@@ -96,7 +150,11 @@ impl OpenAIClient {
                     })
                     .collect();
                 body["tools"] = formatted_tools.clone().into();
-                body["tool_choice"] = "auto".into();
+                body["tool_choice"] = self
+                    .tool_choice
+                    .as_ref()
+                    .map(format_tool_choice)
+                    .unwrap_or_else(|| "auto".into());
                 
                 // Log the exact formatted tools array being sent
                 log::info!("=== FORMATTED TOOLS JSON ===");
@@ -105,6 +163,10 @@ impl OpenAIClient {
             }
         }
 
+        if let Some(response_format) = &self.response_format {
+            body["response_format"] = format_response_format(response_format);
+        }
+
         body
     }
 
@@ -169,8 +231,20 @@ impl LLM for OpenAIClient {
         messages: Vec<Message>,
         tools: Option<Vec<ToolMetadata>>,
     ) -> Result<LLMResponse, AgentError> {
-        let url = format!("{}/chat/completions", self.api_base);
-        let body = self.build_request_body(&messages, tools.as_deref());
+        let (url, body) = match self.api {
+            OpenAIApi::ChatCompletions => (
+                format!("{}/chat/completions", self.api_base),
+                self.build_request_body(&messages, tools.as_deref()),
+            ),
+            OpenAIApi::Responses => {
+                let (previous_response_id, input) = self.continue_chain(&messages);
+                let mut body = self.build_responses_body(input, tools.as_deref());
+                if let Some(id) = previous_response_id {
+                    body["previous_response_id"] = id.into();
+                }
+                (format!("{}/responses", self.api_base), body)
+            }
+        };
 
         // More detailed logging of the messages
         log::debug!("OpenAI API request to {}", url);
@@ -209,12 +283,22 @@ impl LLM for OpenAIClient {
         let response_json: Value = serde_json::from_str(&response_text)
             .map_err(|e| AgentError::ParsingError(format!("Invalid JSON response: {}", e)))?;
 
-        self.parse_response(response_json)
+        match self.api {
+            OpenAIApi::ChatCompletions => self.parse_response(response_json),
+            OpenAIApi::Responses => {
+                let response_id = response_json["id"].as_str().map(str::to_string);
+                let parsed = self.parse_responses_output(response_json)?;
+                if let Some(id) = response_id {
+                    self.remember_turn(&messages, id);
+                }
+                Ok(parsed)
+            }
+        }
     }
 }
 
 impl OpenAIClient {
-    fn parse_response(&self, response: Value) -> Result<LLMResponse, AgentError> {
+    pub(crate) fn parse_response(&self, response: Value) -> Result<LLMResponse, AgentError> {
         log::info!("=== OPENAI RESPONSE PARSING ===");
         log::info!("Full response: {}", serde_json::to_string_pretty(&response).unwrap_or_default());
         
@@ -231,6 +315,14 @@ impl OpenAIClient {
 
         let content = message["content"].as_str().map(|s| s.to_string());
 
+        // OpenAI-compatible servers that expose reasoning (DeepSeek, vLLM, Ollama,
+        // OpenRouter) use one of these fields
+        let reasoning = message["reasoning_content"]
+            .as_str()
+            .or_else(|| message["reasoning"].as_str())
+            .filter(|text| !text.trim().is_empty())
+            .map(|text| vec![ReasoningBlock::text(text)]);
+
         let tool_calls = if let Some(calls) = message["tool_calls"].as_array() {
             let mut parsed_calls = Vec::new();
             for call in calls {
@@ -276,10 +368,289 @@ impl OpenAIClient {
             tool_calls,
             finish_reason: None,
             usage: None,
+            reasoning,
         })
     }
 }
 
+/// Responses API support
+///
+/// By default requests are sent with `store: false`, so each turn carries the full
+/// conversation. With [`OpenAIClient::with_store`] turns are kept server-side, and a
+/// request whose history starts with the previous turn's messages and reply sends
+/// only what came after, chained with `previous_response_id`. Reasoning items come
+/// back with their encrypted content and are kept on the assistant message as
+/// [`ReasoningBlock`]s (item id in `signature`, encrypted content in
+/// `redacted_data`), then replayed ahead of the tool calls they led to.
+impl OpenAIClient {
+    pub(crate) fn build_responses_body(
+        &self,
+        messages: &[Message],
+        tools: Option<&[ToolMetadata]>,
+    ) -> Value {
+        let mut body = json!({
+            "model": self.model,
+            "input": self.format_response_input(messages),
+            "store": self.store,
+            "include": ["reasoning.encrypted_content"],
+        });
+
+        if let Some(temp) = self.temperature {
+            body["temperature"] = temp.into();
+        }
+
+        if let Some(max_tokens) = self.max_tokens {
+            body["max_output_tokens"] = max_tokens.into();
+        }
+
+        if let Some(budget) = self.reasoning_budget {
+            let effort = match budget {
+                0..=4095 => "low",
+                4096..=16383 => "medium",
+                _ => "high",
+            };
+            body["reasoning"] = json!({ "effort": effort, "summary": "auto" });
+        }
+
+        if let Some(tools) = tools.filter(|tools| !tools.is_empty()) {
+            let formatted_tools: Vec<Value> = tools
+                .iter()
+                .map(|tool| {
+                    json!({
+                        "type": "function",
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.input_schema
+                    })
+                })
+                .collect();
+            body["tools"] = formatted_tools.into();
+            body["tool_choice"] = match &self.tool_choice {
+                Some(ToolChoice::Tool { name }) => json!({ "type": "function", "name": name }),
+                Some(choice) => format_tool_choice(choice),
+                None => "auto".into(),
+            };
+        }
+
+        if let Some(response_format) = &self.response_format {
+            let format = match response_format {
+                ResponseFormat::JsonObject => json!({ "type": "json_object" }),
+                ResponseFormat::JsonSchema { name, schema, strict } => json!({
+                    "type": "json_schema",
+                    "name": name,
+                    "schema": schema,
+                    "strict": strict
+                }),
+            };
+            body["text"] = json!({ "format": format });
+        }
+
+        body
+    }
+
+    /// Splits off the messages the stored previous turn already covers
+    ///
+    /// Returns the id to continue from and the messages still to send. Any change to
+    /// the earlier history, such as truncation, starts a fresh chain.
+    fn continue_chain<'a>(&self, messages: &'a [Message]) -> (Option<String>, &'a [Message]) {
+        if !self.store {
+            return (None, messages);
+        }
+        let chain = self.chain.lock().unwrap();
+        match chain.as_ref() {
+            // The server already holds its own reply to that turn, so skip it too
+            Some(chain)
+                if messages.len() > chain.len + 1
+                    && messages[chain.len].role == Role::Assistant
+                    && fingerprint(&messages[..chain.len]) == chain.fingerprint =>
+            {
+                (Some(chain.id.clone()), &messages[chain.len + 1..])
+            }
+            _ => (None, messages),
+        }
+    }
+
+    fn remember_turn(&self, messages: &[Message], id: String) {
+        if self.store {
+            *self.chain.lock().unwrap() = Some(ResponseChain {
+                id,
+                len: messages.len(),
+                fingerprint: fingerprint(messages),
+            });
+        }
+    }
+
+    fn format_response_input(&self, messages: &[Message]) -> Vec<Value> {
+        let mut items = Vec::new();
+        for (index, msg) in messages.iter().enumerate() {
+            match msg.role {
+                Role::Tool => items.push(json!({
+                    "type": "function_call_output",
+                    "call_id": msg.tool_call_id.clone().unwrap_or_default(),
+                    "output": msg.content
+                })),
+                Role::Assistant => {
+                    // Only reasoning that came from this API can be replayed
+                    for block in msg.reasoning.iter().flatten() {
+                        if let (Some(id), Some(encrypted)) = (&block.signature, &block.redacted_data) {
+                            let summary: Vec<Value> = if block.text.is_empty() {
+                                Vec::new()
+                            } else {
+                                vec![json!({ "type": "summary_text", "text": block.text })]
+                            };
+                            items.push(json!({
+                                "type": "reasoning",
+                                "id": id,
+                                "summary": summary,
+                                "encrypted_content": encrypted
+                            }));
+                        }
+                    }
+                    if !msg.content.is_empty() {
+                        items.push(json!({ "role": "assistant", "content": msg.content }));
+                    }
+                    for (position, call) in msg.tool_calls.iter().flatten().enumerate() {
+                        // Positional, so the id is the same every time the history is sent
+                        let call_id = call.id.clone().unwrap_or_else(|| format!("call_{}_{}", index, position));
+                        items.push(json!({
+                            "type": "function_call",
+                            "call_id": call_id,
+                            "name": call.name,
+                            "arguments": call.arguments.to_string()
+                        }));
+                    }
+                }
+                _ => items.push(json!({
+                    "role": self.format_role(&msg.role),
+                    "content": msg.content
+                })),
+            }
+        }
+        items
+    }
+
+    pub(crate) fn parse_responses_output(&self, response: Value) -> Result<LLMResponse, AgentError> {
+        let output = response["output"]
+            .as_array()
+            .ok_or_else(|| AgentError::ParsingError("No output in response".to_string()))?;
+
+        let mut text_parts = Vec::new();
+        let mut tool_calls = Vec::new();
+        let mut reasoning = Vec::new();
+
+        for item in output {
+            match item["type"].as_str() {
+                Some("message") => {
+                    for part in item["content"].as_array().into_iter().flatten() {
+                        match part["type"].as_str() {
+                            Some("output_text") => {
+                                text_parts.push(part["text"].as_str().unwrap_or_default().to_string())
+                            }
+                            Some("refusal") => {
+                                text_parts.push(part["refusal"].as_str().unwrap_or_default().to_string())
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Some("function_call") => {
+                    let name = item["name"].as_str().ok_or_else(|| {
+                        AgentError::ParsingError("Function call without a name".to_string())
+                    })?;
+                    let arguments_str = item["arguments"].as_str().unwrap_or("{}");
+                    let arguments: Value = serde_json::from_str(arguments_str).map_err(|e| {
+                        AgentError::ParsingError(format!("Invalid tool call arguments JSON: {}", e))
+                    })?;
+                    tool_calls.push(ToolCall {
+                        id: item["call_id"].as_str().map(str::to_string),
+                        name: name.to_string(),
+                        arguments,
+                    });
+                }
+                Some("reasoning") => {
+                    let text = item["summary"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|part| part["text"].as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n\n");
+                    reasoning.push(ReasoningBlock {
+                        text,
+                        signature: item["id"].as_str().map(str::to_string),
+                        redacted_data: item["encrypted_content"].as_str().map(str::to_string),
+                    });
+                }
+                other => log::debug!("Ignoring Responses output item of type {:?}", other),
+            }
+        }
+
+        let content = if text_parts.is_empty() { None } else { Some(text_parts.join("")) };
+        let tool_calls = if tool_calls.is_empty() { None } else { Some(tool_calls) };
+
+        if content.is_none() && tool_calls.is_none() {
+            let reason = response["incomplete_details"]["reason"].as_str().unwrap_or("no output");
+            return Err(AgentError::ParsingError(format!(
+                "Response has neither content nor tool calls ({})",
+                reason
+            )));
+        }
+
+        let finish_reason = if tool_calls.is_some() {
+            Some("tool_calls".to_string())
+        } else {
+            response["status"].as_str().map(str::to_string)
+        };
+        let usage = response.get("usage").filter(|u| u.is_object()).map(|usage| Usage {
+            prompt_tokens: usage["input_tokens"].as_u64().unwrap_or(0) as u32,
+            completion_tokens: usage["output_tokens"].as_u64().unwrap_or(0) as u32,
+            total_tokens: usage["total_tokens"].as_u64().unwrap_or(0) as u32,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
+        });
+
+        Ok(LLMResponse {
+            content,
+            tool_calls,
+            finish_reason,
+            usage,
+            reasoning: if reasoning.is_empty() { None } else { Some(reasoning) },
+        })
+    }
+}
+
+fn fingerprint(messages: &[Message]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(messages).unwrap_or_default().hash(&mut hasher);
+    hasher.finish()
+}
+
+fn format_tool_choice(tool_choice: &ToolChoice) -> Value {
+    match tool_choice {
+        ToolChoice::Auto => "auto".into(),
+        ToolChoice::None => "none".into(),
+        ToolChoice::Required => "required".into(),
+        ToolChoice::Tool { name } => json!({
+            "type": "function",
+            "function": { "name": name }
+        }),
+    }
+}
+
+fn format_response_format(response_format: &ResponseFormat) -> Value {
+    match response_format {
+        ResponseFormat::JsonObject => json!({ "type": "json_object" }),
+        ResponseFormat::JsonSchema { name, schema, strict } => json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": name,
+                "schema": schema,
+                "strict": strict
+            }
+        }),
+    }
+}
+
 // Gemini-compatible client (uses OpenAI protocol but different endpoint)
 #[derive(Debug, Clone)]
 pub struct GeminiClient {
@@ -343,12 +714,14 @@ mod tests {
                 content: "You are a helpful assistant.".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
             Message {
                 role: Role::User,
                 content: "Hello!".to_string(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             },
         ];
 
@@ -367,6 +740,221 @@ mod tests {
 
         assert!(client.openai_client.api_base.contains("generativelanguage.googleapis.com"));
     }
+
+    #[test]
+    fn test_reasoning_content_is_parsed_separately() {
+        let client = OpenAIClient::new("test-key".to_string(), "deepseek-reasoner".to_string());
+        let response = client
+            .parse_response(json!({
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "content": "4",
+                        "reasoning_content": "2 + 2 is 4."
+                    }
+                }]
+            }))
+            .unwrap();
+
+        assert_eq!(response.content.as_deref(), Some("4"));
+        assert_eq!(response.reasoning_text().as_deref(), Some("2 + 2 is 4."));
+    }
+
+    #[test]
+    fn test_tool_choice_and_response_format_in_request() {
+        let client = OpenAIClient::new("test-key".to_string(), "gpt-4.1-mini".to_string())
+            .with_tool_choice(ToolChoice::Tool { name: "calculator".to_string() })
+            .with_response_format(ResponseFormat::JsonSchema {
+                name: "answer".to_string(),
+                schema: json!({ "type": "object" }),
+                strict: true,
+            });
+        let tools = vec![ToolMetadata {
+            name: "calculator".to_string(),
+            description: "Perform calculations".to_string(),
+            input_schema: json!({ "type": "object" }),
+        }];
+
+        let body = client.build_request_body(&[], Some(&tools));
+        assert_eq!(body["tool_choice"]["function"]["name"], "calculator");
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert_eq!(body["response_format"]["json_schema"]["name"], "answer");
+        assert_eq!(body["response_format"]["json_schema"]["strict"], true);
+
+        // tool_choice is only valid alongside tools
+        let body = client.build_request_body(&[], None);
+        assert!(body.get("tool_choice").is_none());
+        assert_eq!(body["response_format"]["type"], "json_schema");
+    }
+
+    #[test]
+    fn test_responses_output_is_parsed() {
+        let client = OpenAIClient::new("test-key".to_string(), "o4-mini".to_string())
+            .with_api(OpenAIApi::Responses);
+        let response = json!({
+            "status": "completed",
+            "output": [
+                {
+                    "type": "reasoning",
+                    "id": "rs_1",
+                    "summary": [{ "type": "summary_text", "text": "Need the calculator." }],
+                    "encrypted_content": "gAAAA"
+                },
+                { "type": "function_call", "call_id": "call_9", "name": "calculator", "arguments": "{\"a\":2}" }
+            ],
+            "usage": { "input_tokens": 10, "output_tokens": 5, "total_tokens": 15 }
+        });
+
+        let parsed = client.parse_responses_output(response).unwrap();
+        assert!(parsed.content.is_none());
+        let calls = parsed.tool_calls.unwrap();
+        assert_eq!(calls[0].id.as_deref(), Some("call_9"));
+        assert_eq!(calls[0].arguments, json!({ "a": 2 }));
+        assert_eq!(parsed.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(parsed.usage.unwrap().total_tokens, 15);
+        let reasoning = parsed.reasoning.unwrap();
+        assert_eq!(reasoning[0].text, "Need the calculator.");
+        assert_eq!(reasoning[0].signature.as_deref(), Some("rs_1"));
+        assert_eq!(reasoning[0].redacted_data.as_deref(), Some("gAAAA"));
+
+        let answer = client
+            .parse_responses_output(json!({
+                "status": "completed",
+                "output": [{ "type": "message", "role": "assistant", "content": [{ "type": "output_text", "text": "4" }] }]
+            }))
+            .unwrap();
+        assert_eq!(answer.content.as_deref(), Some("4"));
+        assert_eq!(answer.finish_reason.as_deref(), Some("completed"));
+    }
+
+    #[test]
+    fn test_responses_input_replays_reasoning_and_tool_turns() {
+        let client = OpenAIClient::new("test-key".to_string(), "o4-mini".to_string())
+            .with_api(OpenAIApi::Responses)
+            .with_max_tokens(500)
+            .with_reasoning_budget(8000)
+            .with_tool_choice(ToolChoice::Tool { name: "calculator".to_string() });
+        let message = |role, content: &str| Message {
+            role,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            reasoning: None,
+        };
+        let messages = vec![
+            message(Role::System, "Be precise."),
+            message(Role::User, "2+2?"),
+            Message {
+                tool_calls: Some(vec![ToolCall {
+                    id: Some("call_9".to_string()),
+                    name: "calculator".to_string(),
+                    arguments: json!({ "a": 2 }),
+                }]),
+                reasoning: Some(vec![
+                    ReasoningBlock {
+                        text: "Need the calculator.".to_string(),
+                        signature: Some("rs_1".to_string()),
+                        redacted_data: Some("gAAAA".to_string()),
+                    },
+                    // Reasoning from another API has nothing to replay
+                    ReasoningBlock::text("plain"),
+                ]),
+                ..message(Role::Assistant, "")
+            },
+            Message {
+                tool_call_id: Some("call_9".to_string()),
+                ..message(Role::Tool, "4")
+            },
+        ];
+        let tools = vec![ToolMetadata {
+            name: "calculator".to_string(),
+            description: "Perform calculations".to_string(),
+            input_schema: json!({ "type": "object" }),
+        }];
+
+        let body = client.build_responses_body(&messages, Some(&tools));
+        assert_eq!(
+            body["input"],
+            json!([
+                { "role": "system", "content": "Be precise." },
+                { "role": "user", "content": "2+2?" },
+                {
+                    "type": "reasoning",
+                    "id": "rs_1",
+                    "summary": [{ "type": "summary_text", "text": "Need the calculator." }],
+                    "encrypted_content": "gAAAA"
+                },
+                { "type": "function_call", "call_id": "call_9", "name": "calculator", "arguments": "{\"a\":2}" },
+                { "type": "function_call_output", "call_id": "call_9", "output": "4" }
+            ])
+        );
+        assert_eq!(body["store"], false);
+        assert_eq!(body["max_output_tokens"], 500);
+        assert_eq!(body["reasoning"]["effort"], "medium");
+        assert_eq!(body["tools"][0]["name"], "calculator");
+        assert_eq!(body["tool_choice"], json!({ "type": "function", "name": "calculator" }));
+    }
+
+    #[test]
+    fn test_responses_call_ids_are_stable_without_ids() {
+        let client = OpenAIClient::new("test-key".to_string(), "o4-mini".to_string())
+            .with_api(OpenAIApi::Responses);
+        let messages = vec![Message {
+            role: Role::Assistant,
+            content: String::new(),
+            tool_call_id: None,
+            tool_calls: Some(vec![ToolCall {
+                id: None,
+                name: "calculator".to_string(),
+                arguments: json!({ "a": 2 }),
+            }]),
+            reasoning: None,
+        }];
+
+        let first = client.build_responses_body(&messages, None);
+        let second = client.build_responses_body(&messages, None);
+        assert_eq!(first["input"][0]["call_id"], "call_0_0");
+        assert_eq!(first["input"], second["input"]);
+    }
+
+    #[test]
+    fn test_stored_responses_continue_from_previous_turn() {
+        let client = OpenAIClient::new("test-key".to_string(), "o4-mini".to_string())
+            .with_api(OpenAIApi::Responses)
+            .with_store(true);
+        let message = |role, content: &str| Message {
+            role,
+            content: content.to_string(),
+            tool_call_id: None,
+            tool_calls: None,
+            reasoning: None,
+        };
+        let first_turn = vec![message(Role::System, "Be precise."), message(Role::User, "2+2?")];
+        client.remember_turn(&first_turn, "resp_1".to_string());
+
+        let mut history = first_turn.clone();
+        history.push(message(Role::Assistant, "Let me check."));
+        history.push(message(Role::User, "Well?"));
+        let (previous, input) = client.continue_chain(&history);
+        assert_eq!(previous.as_deref(), Some("resp_1"));
+        assert_eq!(input.len(), 1);
+        assert_eq!(input[0].content, "Well?");
+
+        let body = client.build_responses_body(input, None);
+        assert_eq!(body["store"], true);
+
+        // Edited history no longer matches the stored turn
+        history[1].content = "3+3?".to_string();
+        let (previous, input) = client.continue_chain(&history);
+        assert!(previous.is_none());
+        assert_eq!(input.len(), 4);
+
+        // Without storage every request carries the whole conversation
+        let stateless = OpenAIClient::new("test-key".to_string(), "o4-mini".to_string())
+            .with_api(OpenAIApi::Responses);
+        stateless.remember_turn(&first_turn, "resp_1".to_string());
+        assert!(stateless.continue_chain(&history).0.is_none());
+    }
 }
 
 /// Create an OpenAI LLM client from configuration
@@ -390,6 +978,21 @@ pub fn create_client(config: &crate::config::LlmConfig) -> Result<std::sync::Arc
     if config.parameters.max_tokens > 0 {
         client = client.with_max_tokens(config.parameters.max_tokens);
     }
+    if let Some(tool_choice) = &config.parameters.tool_choice {
+        client = client.with_tool_choice(tool_choice.clone());
+    }
+    if let Some(response_format) = &config.parameters.response_format {
+        client = client.with_response_format(response_format.clone());
+    }
+    if let Some(api) = config.parameters.api {
+        client = client.with_api(api);
+    }
+    if config.parameters.store {
+        client = client.with_store(true);
+    }
+    if let Some(budget) = config.parameters.reasoning_budget {
+        client = client.with_reasoning_budget(budget);
+    }
 
     Ok(std::sync::Arc::new(client))
 }
@@ -414,6 +1017,21 @@ pub fn create_custom_client(config: &crate::config::LlmConfig, base_url: &str) -
     if config.parameters.max_tokens > 0 {
         client = client.with_max_tokens(config.parameters.max_tokens);
     }
+    if let Some(tool_choice) = &config.parameters.tool_choice {
+        client = client.with_tool_choice(tool_choice.clone());
+    }
+    if let Some(response_format) = &config.parameters.response_format {
+        client = client.with_response_format(response_format.clone());
+    }
+    if let Some(api) = config.parameters.api {
+        client = client.with_api(api);
+    }
+    if config.parameters.store {
+        client = client.with_store(true);
+    }
+    if let Some(budget) = config.parameters.reasoning_budget {
+        client = client.with_reasoning_budget(budget);
+    }
 
     Ok(std::sync::Arc::new(client))
 }
@@ -1,6 +1,30 @@
 use crate::core_types::{LLMResponse, ToolCall};
 use crate::errors::AgentError;
+use regex::Regex;
 use serde_json::Value;
+use std::sync::LazyLock;
+
+/// Tool call blocks recognised by [`ResponseParser::parse_text_tool_protocol`]
+static TOOL_CALL_BLOCKS: LazyLock<[Regex; 2]> = LazyLock::new(|| {
+    [
+        // An unterminated tag at the end of the reply still counts
+        Regex::new(r"(?s)<tool_call>\s*(.*?)\s*(?:</tool_call>|\z)").expect("valid tool call pattern"),
+        Regex::new(r"(?s)```(?:tool_call|json)?[ \t]*\n(.*?)```").expect("valid tool call pattern"),
+    ]
+});
+
+/// Answer markers recognised by [`ResponseParser::extract_final_answer`], in priority order
+static FINAL_ANSWER_PATTERNS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        r"(?i)final\s+answer:\s*(.+)",
+        r"(?i)answer:\s*(.+)",
+        r"(?i)conclusion:\s*(.+)",
+        r"(?i)result:\s*(.+)",
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).expect("valid answer pattern"))
+    .collect()
+});
 
 pub struct ResponseParser;
 
@@ -33,6 +57,7 @@ impl ResponseParser {
             tool_calls,
             finish_reason: None,
             usage: None,
+            reasoning: None,
         })
     }
 
@@ -104,6 +129,7 @@ impl ResponseParser {
             tool_calls,
             finish_reason: None,
             usage: None,
+            reasoning: None,
         })
     }
 
@@ -144,8 +170,6 @@ impl ResponseParser {
     }
 
     pub fn parse_text_for_tool_calls(text: &str) -> Option<Vec<ToolCall>> {
-        use regex::Regex;
-
         // Pattern to match function calls in text like: function_name({"arg": "value"})
         let re = Regex::new(r"(\w+)\((\{[^}]*\})\)").ok()?;
 
@@ -169,28 +193,195 @@ impl ResponseParser {
         }
     }
 
-    pub fn extract_final_answer(text: &str) -> Option<String> {
-        use regex::Regex;
+    /// Extracts tool invocations written into completion text
+    ///
+    /// Recognises `<tool_call>` tags and fenced `tool_call`/`json` blocks holding
+    /// `{"name": ..., "arguments": {...}}`, regardless of which convention the prompt
+    /// asked for, since small models often mix them up. Payloads go through
+    /// [`Self::repair_json`], and only calls to one of `tool_names` are taken, so
+    /// example JSON in an answer is left alone. Returns the calls and the text with
+    /// them removed.
+    pub fn parse_text_tool_protocol(text: &str, tool_names: &[String]) -> (Vec<ToolCall>, String) {
+        let mut calls = Vec::new();
+        let mut remaining = text.to_string();
+        for re in TOOL_CALL_BLOCKS.iter() {
+            let mut kept = String::new();
+            let mut last = 0;
+            for cap in re.captures_iter(&remaining) {
+                let (Some(block), Some(payload)) = (cap.get(0), cap.get(1)) else {
+                    continue;
+                };
+                let parsed = Self::repair_json(payload.as_str())
+                    .map(|value| Self::tool_calls_from_value(&value, tool_names))
+                    .unwrap_or_default();
+                if parsed.is_empty() {
+                    continue;
+                }
+                calls.extend(parsed);
+                kept.push_str(&remaining[last..block.start()]);
+                last = block.end();
+            }
+            kept.push_str(&remaining[last..]);
+            remaining = kept;
+        }
 
-        // Look for patterns like "Final Answer:", "Answer:", etc.
-        let patterns = [
-            r"(?i)final\s+answer:\s*(.+)",
-            r"(?i)answer:\s*(.+)",
-            r"(?i)conclusion:\s*(.+)",
-            r"(?i)result:\s*(.+)",
-        ];
+        (calls, remaining.trim().to_string())
+    }
+
+    /// Reads one call, or an array of calls, from a parsed tool call payload
+    fn tool_calls_from_value(value: &Value, tool_names: &[String]) -> Vec<ToolCall> {
+        if let Some(items) = value.as_array() {
+            return items
+                .iter()
+                .flat_map(|item| Self::tool_calls_from_value(item, tool_names))
+                .collect();
+        }
+
+        let Some(name) = value["name"].as_str().filter(|name| tool_names.iter().any(|t| t == name)) else {
+            return Vec::new();
+        };
+        let arguments = match value.get("arguments").or_else(|| value.get("parameters")) {
+            Some(Value::String(raw)) => {
+                Self::repair_json(raw).unwrap_or_else(|| Value::Object(Default::default()))
+            }
+            Some(Value::Null) | None => Value::Object(Default::default()),
+            Some(arguments) => arguments.clone(),
+        };
 
-        for pattern in &patterns {
-            if let Ok(re) = Regex::new(pattern) {
-                if let Some(cap) = re.captures(text) {
-                    if let Some(answer) = cap.get(1) {
-                        return Some(answer.as_str().trim().to_string());
+        vec![ToolCall {
+            id: None,
+            name: name.to_string(),
+            arguments,
+        }]
+    }
+
+    /// Parses JSON produced by a model, fixing common malformations
+    ///
+    /// Handles surrounding prose or code fences, single-quoted strings, unquoted keys,
+    /// Python `True`/`False`/`None`, trailing commas, raw newlines inside strings and
+    /// missing closing brackets or quotes.
+    pub fn repair_json(text: &str) -> Option<Value> {
+        let trimmed = text.trim();
+        if let Ok(value) = serde_json::from_str(trimmed) {
+            return Some(value);
+        }
+
+        let start = trimmed.find(['{', '['])?;
+        let source = &trimmed[start..];
+
+        let mut out = String::with_capacity(source.len() + 8);
+        let mut closers: Vec<char> = Vec::new();
+        let mut quote: Option<char> = None;
+        let mut chars = source.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if let Some(q) = quote {
+                match c {
+                    '\\' => {
+                        out.push(c);
+                        if let Some(next) = chars.next() {
+                            // `\'` is not a JSON escape
+                            if next == '\'' {
+                                out.pop();
+                            }
+                            out.push(next);
+                        }
+                    }
+                    _ if c == q => {
+                        out.push('"');
+                        quote = None;
                     }
+                    '"' => out.push_str("\\\""),
+                    '\n' => out.push_str("\\n"),
+                    '\r' => out.push_str("\\r"),
+                    '\t' => out.push_str("\\t"),
+                    _ => out.push(c),
                 }
+                continue;
             }
+
+            match c {
+                '"' | '\'' => {
+                    out.push('"');
+                    quote = Some(c);
+                }
+                '{' => {
+                    out.push(c);
+                    closers.push('}');
+                }
+                '[' => {
+                    out.push(c);
+                    closers.push(']');
+                }
+                '}' | ']' => {
+                    Self::drop_trailing_comma(&mut out);
+                    out.push(c);
+                    closers.pop();
+                    if closers.is_empty() {
+                        break;
+                    }
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let mut word = c.to_string();
+                    while let Some(&next) = chars.peek() {
+                        if next.is_alphanumeric() || next == '_' {
+                            word.push(next);
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    let is_key = chars.clone().find(|c| !c.is_whitespace()) == Some(':');
+                    // The exponent of a number such as `1e-5`
+                    let is_exponent = out.ends_with(|c: char| c.is_ascii_digit() || c == '.')
+                        && word.starts_with(['e', 'E'])
+                        && word[1..].bytes().all(|b| b.is_ascii_digit());
+                    match word.as_str() {
+                        _ if is_exponent => out.push_str(&word),
+                        _ if is_key => {
+                            out.push('"');
+                            out.push_str(&word);
+                            out.push('"');
+                        }
+                        "true" | "True" => out.push_str("true"),
+                        "false" | "False" => out.push_str("false"),
+                        "null" | "None" => out.push_str("null"),
+                        // A bare word in value position was meant as a string
+                        _ => {
+                            out.push('"');
+                            out.push_str(&word);
+                            out.push('"');
+                        }
+                    }
+                }
+                _ => out.push(c),
+            }
+        }
+
+        if quote.is_some() {
+            out.push('"');
         }
+        while let Some(closer) = closers.pop() {
+            Self::drop_trailing_comma(&mut out);
+            out.push(closer);
+        }
+
+        serde_json::from_str(&out).ok()
+    }
 
-        None
+    fn drop_trailing_comma(out: &mut String) {
+        let trimmed_len = out.trim_end().len();
+        if out[..trimmed_len].ends_with(',') {
+            out.truncate(trimmed_len - 1);
+        }
+    }
+
+    pub fn extract_final_answer(text: &str) -> Option<String> {
+        // Look for patterns like "Final Answer:", "Answer:", etc.
+        FINAL_ANSWER_PATTERNS
+            .iter()
+            .find_map(|re| re.captures(text)?.get(1))
+            .map(|answer| answer.as_str().trim().to_string())
     }
 
     pub fn contains_tool_intent(text: &str) -> bool {
@@ -281,6 +472,52 @@ mod tests {
         assert_eq!(calls[0].name, "calculator");
     }
 
+    #[test]
+    fn test_parse_text_tool_protocol() {
+        let text = "Checking both.\n<tool_call>{\"name\": \"search\", \"arguments\": {\"q\": \"rust\"}}</tool_call>\n```tool_call\n{\"name\": \"calculator\", \"parameters\": \"{\\\"a\\\": 1}\"}\n```\n```json\n{\"unrelated\": true}\n```";
+        let tools = vec!["search".to_string(), "calculator".to_string()];
+        let (calls, remaining) = ResponseParser::parse_text_tool_protocol(text, &tools);
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].name, "search");
+        assert_eq!(calls[0].arguments, json!({ "q": "rust" }));
+        assert_eq!(calls[1].name, "calculator");
+        assert_eq!(calls[1].arguments, json!({ "a": 1 }));
+        // Blocks that aren't tool calls stay in the text
+        assert_eq!(remaining, "Checking both.\n\n\n```json\n{\"unrelated\": true}\n```");
+
+        let (calls, remaining) =
+            ResponseParser::parse_text_tool_protocol("<tool_call>\n{'name': 'search', 'arguments': {'q': 'x'", &tools);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].arguments, json!({ "q": "x" }));
+        assert!(remaining.is_empty());
+
+        let (calls, _) = ResponseParser::parse_text_tool_protocol("No tools needed.", &tools);
+        assert!(calls.is_empty());
+
+        // Example JSON naming a tool that wasn't offered is not a call
+        let example = "Call it like this:\n```json\n{\"name\": \"delete_repo\", \"arguments\": {}}\n```";
+        let (calls, remaining) = ResponseParser::parse_text_tool_protocol(example, &tools);
+        assert!(calls.is_empty());
+        assert_eq!(remaining, example);
+    }
+
+    #[test]
+    fn test_repair_json() {
+        let cases = [
+            ("```json\n{\"a\": 1,}\n```", json!({ "a": 1 })),
+            ("Sure: {query: 'it\\'s', limit: 3, exact: True, filter: None}", json!({ "query": "it's", "limit": 3, "exact": true, "filter": null })),
+            ("{\"text\": \"line one\nline two\"}", json!({ "text": "line one\nline two" })),
+            ("{\"items\": [1, 2, {\"b\": \"c", json!({ "items": [1, 2, { "b": "c" }] })),
+            ("{\"mode\": fast} trailing words", json!({ "mode": "fast" })),
+            ("{rate: 1e-5, scale: 2.5E3, step: 3e2,}", json!({ "rate": 1e-5, "scale": 2.5e3, "step": 3e2 })),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(ResponseParser::repair_json(input), Some(expected), "input: {}", input);
+        }
+        assert!(ResponseParser::repair_json("no json here").is_none());
+    }
+
     #[test]
     fn test_contains_tool_intent() {
         assert!(ResponseParser::contains_tool_intent(
@@ -0,0 +1,106 @@
+//! Forced-tool emulation of structured output
+//!
+//! Providers without a native JSON mode are given a synthetic tool whose input
+//! schema is the requested output format. The model is forced to call it, and
+//! the call's arguments are turned back into the response content.
+
+use serde_json::json;
+
+use crate::config::ResponseFormat;
+use crate::core_types::LLMResponse;
+use crate::llm::ToolMetadata;
+
+/// Name of the synthetic tool that carries the structured answer
+pub const STRUCTURED_OUTPUT_TOOL: &str = "structured_output";
+
+/// Builds the synthetic tool for the given response format
+pub fn output_tool(format: &ResponseFormat) -> ToolMetadata {
+    let (description, input_schema) = match format {
+        ResponseFormat::JsonObject => (
+            "Return the final answer as a JSON object.".to_string(),
+            json!({ "type": "object", "additionalProperties": true }),
+        ),
+        ResponseFormat::JsonSchema { name, schema, .. } => (
+            format!("Return the final answer as a '{}' JSON object.", name),
+            schema.clone(),
+        ),
+    };
+
+    ToolMetadata {
+        name: STRUCTURED_OUTPUT_TOOL.to_string(),
+        description,
+        input_schema,
+    }
+}
+
+/// Moves the synthetic tool call's arguments into the response content
+///
+/// Calls to real tools are left in place so the agent loop can still run them.
+pub fn unwrap_output_tool(mut response: LLMResponse) -> LLMResponse {
+    let Some(tool_calls) = response.tool_calls.take() else {
+        return response;
+    };
+
+    let (output, remaining): (Vec<_>, Vec<_>) = tool_calls
+        .into_iter()
+        .partition(|call| call.name == STRUCTURED_OUTPUT_TOOL);
+
+    if let Some(call) = output.into_iter().next() {
+        response.content = Some(call.arguments.to_string());
+    }
+    response.tool_calls = if remaining.is_empty() { None } else { Some(remaining) };
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_types::ToolCall;
+
+    #[test]
+    fn test_output_tool_uses_requested_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": { "answer": { "type": "integer" } },
+            "required": ["answer"]
+        });
+        let tool = output_tool(&ResponseFormat::JsonSchema {
+            name: "sum".to_string(),
+            schema: schema.clone(),
+            strict: true,
+        });
+
+        assert_eq!(tool.name, STRUCTURED_OUTPUT_TOOL);
+        assert_eq!(tool.input_schema, schema);
+        assert!(tool.description.contains("sum"));
+    }
+
+    #[test]
+    fn test_unwrap_output_tool_keeps_real_tool_calls() {
+        let response = LLMResponse {
+            content: None,
+            tool_calls: Some(vec![
+                ToolCall {
+                    id: Some("toolu_1".to_string()),
+                    name: "calculator".to_string(),
+                    arguments: json!({ "operation": "add", "a": 2, "b": 2 }),
+                },
+                ToolCall {
+                    id: Some("toolu_2".to_string()),
+                    name: STRUCTURED_OUTPUT_TOOL.to_string(),
+                    arguments: json!({ "answer": 4 }),
+                },
+            ]),
+            finish_reason: Some("tool_use".to_string()),
+            usage: None,
+            reasoning: None,
+        };
+
+        let unwrapped = unwrap_output_tool(response);
+        assert_eq!(unwrapped.content.as_deref(), Some(r#"{"answer":4}"#));
+
+        let remaining = unwrapped.tool_calls.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].name, "calculator");
+    }
+}
@@ -50,6 +50,7 @@ pub async fn summarize_message_content(
         content: prompt,
         tool_call_id: None,
         tool_calls: None,
+        reasoning: None,
     }];
 
     match llm.generate(messages, None).await {
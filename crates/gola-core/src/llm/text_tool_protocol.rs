@@ -0,0 +1,320 @@
+//! Prompt-based tool calling for models without native function calling
+//!
+//! Tool schemas are rendered into the system prompt and the model is asked to write
+//! its calls as text, which [`ResponseParser::parse_text_tool_protocol`] turns back
+//! into [`ToolCall`]s. Earlier tool calls and results in the history are rewritten
+//! as plain assistant and user turns, since such models reject the `tool` role.
+
+use crate::config::ToolProtocol;
+use crate::core_types::{LLMResponse, Message, Role, ToolCall};
+use crate::errors::AgentError;
+use crate::llm::{ResponseParser, ToolMetadata, LLM};
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// LLM wrapper that speaks a text tool protocol to the inner client
+pub struct TextToolProtocolLLM {
+    inner: Arc<dyn LLM>,
+    protocol: ToolProtocol,
+    // Ids for parsed calls, unique for the lifetime of the client
+    next_call_id: AtomicUsize,
+}
+
+impl TextToolProtocolLLM {
+    pub fn new(inner: Arc<dyn LLM>, protocol: ToolProtocol) -> Self {
+        Self {
+            inner,
+            protocol,
+            next_call_id: AtomicUsize::new(0),
+        }
+    }
+
+    /// Instructions and tool list appended to the system prompt
+    pub fn render_tool_prompt(&self, tools: &[ToolMetadata]) -> String {
+        let example = self.render_call(&ToolCall {
+            id: None,
+            name: "<tool name>".to_string(),
+            arguments: json!({ "<parameter>": "<value>" }),
+        });
+        let (result_hint, stop_hint) = match self.protocol {
+            ToolProtocol::Xml => (
+                "Results are sent back inside <tool_response> tags.",
+                "<tool_call>",
+            ),
+            ToolProtocol::JsonBlock => (
+                "Results are sent back in ```tool_result blocks.",
+                "```tool_call",
+            ),
+        };
+
+        let mut prompt = format!(
+            "# Tools\n\nYou can call the tools listed below. To call a tool, reply with:\n\n{}\n\n\
+             Use one block per call and write nothing after the last block. {} \
+             When you have everything you need, answer normally without any {} block.\n\n\
+             ## Available tools\n",
+            example, result_hint, stop_hint
+        );
+        for tool in tools {
+            prompt.push_str(&format!(
+                "\n### {}\n{}\nParameters (JSON Schema): {}\n",
+                tool.name, tool.description, tool.input_schema
+            ));
+        }
+        prompt
+    }
+
+    fn render_call(&self, call: &ToolCall) -> String {
+        let payload = json!({ "name": call.name, "arguments": call.arguments });
+        match self.protocol {
+            ToolProtocol::Xml => format!("<tool_call>\n{}\n</tool_call>", payload),
+            ToolProtocol::JsonBlock => format!("```tool_call\n{}\n```", payload),
+        }
+    }
+
+    fn render_result(&self, name: &str, content: &str) -> String {
+        match self.protocol {
+            ToolProtocol::Xml => format!(
+                "<tool_response name=\"{}\">\n{}\n</tool_response>",
+                name, content
+            ),
+            ToolProtocol::JsonBlock => format!("```tool_result {}\n{}\n```", name, content),
+        }
+    }
+
+    /// Rewrites the conversation so it only uses system, user and assistant turns
+    fn rewrite_messages(&self, messages: Vec<Message>, tools: &[ToolMetadata]) -> Vec<Message> {
+        let mut call_names: HashMap<String, String> = HashMap::new();
+        let mut rewritten: Vec<Message> = Vec::with_capacity(messages.len() + 1);
+        // Consecutive results are merged into one user turn
+        let mut pending_results: Vec<String> = Vec::new();
+
+        for message in messages {
+            if message.role != Role::Tool && !pending_results.is_empty() {
+                rewritten.push(plain_message(Role::User, pending_results.join("\n\n")));
+                pending_results.clear();
+            }
+
+            match message.role {
+                Role::Assistant if message.tool_calls.is_some() => {
+                    let mut parts = Vec::new();
+                    if !message.content.trim().is_empty() {
+                        parts.push(message.content.clone());
+                    }
+                    for call in message.tool_calls.iter().flatten() {
+                        if let Some(id) = &call.id {
+                            call_names.insert(id.clone(), call.name.clone());
+                        }
+                        parts.push(self.render_call(call));
+                    }
+                    rewritten.push(Message {
+                        role: Role::Assistant,
+                        content: parts.join("\n"),
+                        tool_call_id: None,
+                        tool_calls: None,
+                        reasoning: message.reasoning,
+                    });
+                }
+                Role::Tool => {
+                    let name = message
+                        .tool_call_id
+                        .as_ref()
+                        .and_then(|id| call_names.get(id))
+                        .map(String::as_str)
+                        .unwrap_or("tool");
+                    pending_results.push(self.render_result(name, &message.content));
+                }
+                _ => rewritten.push(message),
+            }
+        }
+        if !pending_results.is_empty() {
+            rewritten.push(plain_message(Role::User, pending_results.join("\n\n")));
+        }
+
+        if !tools.is_empty() {
+            let tool_prompt = self.render_tool_prompt(tools);
+            match rewritten.iter_mut().find(|m| m.role == Role::System) {
+                Some(system) => {
+                    system.content = format!("{}\n\n{}", system.content.trim_end(), tool_prompt);
+                }
+                None => rewritten.insert(0, plain_message(Role::System, tool_prompt)),
+            }
+        }
+
+        rewritten
+    }
+}
+
+fn plain_message(role: Role, content: String) -> Message {
+    Message {
+        role,
+        content,
+        tool_call_id: None,
+        tool_calls: None,
+        reasoning: None,
+    }
+}
+
+#[async_trait]
+impl LLM for TextToolProtocolLLM {
+    async fn generate(
+        &self,
+        messages: Vec<Message>,
+        tools: Option<Vec<ToolMetadata>>,
+    ) -> Result<LLMResponse, AgentError> {
+        let tools = tools.unwrap_or_default();
+        let messages = self.rewrite_messages(messages, &tools);
+
+        let mut response = self.inner.generate(messages, None).await?;
+        if tools.is_empty() {
+            return Ok(response);
+        }
+
+        let Some(content) = response.content.take() else {
+            return Ok(response);
+        };
+        let tool_names: Vec<String> = tools.iter().map(|t| t.name.clone()).collect();
+        let (calls, remaining) = ResponseParser::parse_text_tool_protocol(&content, &tool_names);
+        if calls.is_empty() {
+            response.content = Some(content);
+            return Ok(response);
+        }
+
+        log::debug!("Parsed {} tool call(s) from completion text", calls.len());
+        let calls = calls
+            .into_iter()
+            .map(|call| ToolCall {
+                id: Some(format!(
+                    "call_{}",
+                    self.next_call_id.fetch_add(1, Ordering::Relaxed)
+                )),
+                ..call
+            })
+            .collect();
+        response.content = if remaining.is_empty() { None } else { Some(remaining) };
+        response.tool_calls = Some(calls);
+        response.finish_reason = Some("tool_calls".to_string());
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Returns a canned completion and records what it was sent
+    struct ScriptedLLM {
+        reply: String,
+        seen: Mutex<Vec<(Vec<Message>, bool)>>,
+    }
+
+    #[async_trait]
+    impl LLM for ScriptedLLM {
+        async fn generate(
+            &self,
+            messages: Vec<Message>,
+            tools: Option<Vec<ToolMetadata>>,
+        ) -> Result<LLMResponse, AgentError> {
+            self.seen.lock().unwrap().push((messages, tools.is_some()));
+            Ok(LLMResponse {
+                content: Some(self.reply.clone()),
+                tool_calls: None,
+                finish_reason: Some("stop".to_string()),
+                usage: None,
+                reasoning: None,
+            })
+        }
+    }
+
+    fn scripted(reply: &str) -> Arc<ScriptedLLM> {
+        Arc::new(ScriptedLLM {
+            reply: reply.to_string(),
+            seen: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn calculator() -> ToolMetadata {
+        ToolMetadata {
+            name: "calculator".to_string(),
+            description: "Evaluates arithmetic".to_string(),
+            input_schema: json!({ "type": "object", "properties": { "expression": { "type": "string" } } }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calls_are_parsed_from_text() {
+        let inner = scripted(
+            "Let me work that out.\n<tool_call>\n{\"name\": \"calculator\", \"arguments\": {\"expression\": \"2+2\"}}\n</tool_call>",
+        );
+        let llm = TextToolProtocolLLM::new(inner.clone(), ToolProtocol::Xml);
+
+        let response = llm
+            .generate(
+                vec![plain_message(Role::System, "Be brief.".to_string()), plain_message(Role::User, "2+2?".to_string())],
+                Some(vec![calculator()]),
+            )
+            .await
+            .unwrap();
+
+        let calls = response.tool_calls.unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "calculator");
+        assert_eq!(calls[0].id.as_deref(), Some("call_0"));
+        assert_eq!(calls[0].arguments, json!({ "expression": "2+2" }));
+        assert_eq!(response.content.as_deref(), Some("Let me work that out."));
+
+        let (sent, native_tools) = inner.seen.lock().unwrap()[0].clone();
+        assert!(!native_tools);
+        assert_eq!(sent.len(), 2);
+        assert!(sent[0].content.starts_with("Be brief.\n\n# Tools"));
+        assert!(sent[0].content.contains("### calculator"));
+    }
+
+    #[tokio::test]
+    async fn test_history_is_rewritten_without_tool_roles() {
+        let inner = scripted("It is 4.");
+        let llm = TextToolProtocolLLM::new(inner.clone(), ToolProtocol::JsonBlock);
+
+        let assistant = Message {
+            role: Role::Assistant,
+            content: String::new(),
+            tool_call_id: None,
+            tool_calls: Some(vec![ToolCall {
+                id: Some("call_7".to_string()),
+                name: "calculator".to_string(),
+                arguments: json!({ "expression": "2+2" }),
+            }]),
+            reasoning: None,
+        };
+        let result = Message {
+            role: Role::Tool,
+            content: "4".to_string(),
+            tool_call_id: Some("call_7".to_string()),
+            tool_calls: None,
+            reasoning: None,
+        };
+
+        let response = llm
+            .generate(
+                vec![plain_message(Role::User, "2+2?".to_string()), assistant, result],
+                Some(vec![calculator()]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.content.as_deref(), Some("It is 4."));
+        assert!(response.tool_calls.is_none());
+
+        let (sent, _) = inner.seen.lock().unwrap()[0].clone();
+        let roles: Vec<Role> = sent.iter().map(|m| m.role.clone()).collect();
+        assert_eq!(roles, vec![Role::System, Role::User, Role::Assistant, Role::User]);
+        assert!(sent.iter().all(|m| m.tool_calls.is_none() && m.tool_call_id.is_none()));
+        assert_eq!(
+            sent[2].content,
+            "```tool_call\n{\"arguments\":{\"expression\":\"2+2\"},\"name\":\"calculator\"}\n```"
+        );
+        assert_eq!(sent[3].content, "```tool_result calculator\n4\n```");
+    }
+}
@@ -69,18 +69,21 @@ impl LLMFactory {
         // Create the base client using provider factory
         let base_client = crate::llm::providers::create_llm_client(config)?;
         
+        Ok(Self::wrap_with_defaults(base_client))
+    }
+
+    /// Apply the standard auto-recovery and context truncation wrappers to a base client
+    pub fn wrap_with_defaults(base_client: Arc<dyn LLM>) -> Arc<dyn LLM> {
         // Apply auto-recovery wrapper
         let client = Arc::new(AutoRecoveryLLM::new(base_client));
         
         // Apply context truncation wrapper
-        let client = Arc::new(
+        Arc::new(
             ContextTruncatingLLM::new(client)
                 .with_max_retries(5)
                 .with_truncation_ratio(0.3)
                 .with_min_messages(2)
-        );
-        
-        Ok(client)
+        )
     }
 }
 
@@ -93,6 +96,7 @@ mod tests {
         LlmConfig {
             provider: LlmProvider::OpenAI,
             model: "gpt-4.1-mini".to_string(),
+            base_url: None,
             auth: LlmAuth {
                 api_key: Some("test-key".to_string()),
                 api_key_env: None,
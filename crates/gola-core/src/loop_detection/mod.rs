@@ -4,7 +4,6 @@
 //! by analyzing patterns in tool calls and their arguments.
 
 use serde_json::Value;
-use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -13,33 +12,7 @@ pub mod similarity;
 
 pub use pattern_detector::*;
 pub use similarity::*;
-
-/// Configuration for loop detection
-#[derive(Debug, Clone)]
-pub struct LoopDetectionConfig {
-    /// Number of recent tool calls to analyze
-    pub detection_window_size: usize,
-    /// Threshold for exact match detection
-    pub exact_match_threshold: u32,
-    /// Threshold for similar match detection  
-    pub similar_match_threshold: u32,
-    /// Time window in seconds for temporal grouping
-    pub time_window_seconds: u64,
-    /// Tool-specific thresholds
-    pub tool_specific_thresholds: HashMap<String, u32>,
-}
-
-impl Default for LoopDetectionConfig {
-    fn default() -> Self {
-        Self {
-            detection_window_size: 10,
-            exact_match_threshold: 5,
-            similar_match_threshold: 8,
-            time_window_seconds: 30,
-            tool_specific_thresholds: HashMap::new(),
-        }
-    }
-}
+pub use crate::config::{LoopDetectionConfig, LoopRecoveryStrategy};
 
 /// Types of loop patterns that can be detected
 #[derive(Debug, Clone, PartialEq)]
@@ -57,6 +30,20 @@ pub enum LoopPattern {
         similarity_score: f64,
         first_occurrence_index: usize,
     },
+    /// Different calls repeating in the same order, e.g. A→B→A→B
+    CycleLoop {
+        tools: Vec<String>,
+        repetitions: u32,
+    },
+    /// A tool keeps returning the same result
+    RepeatedObservation {
+        tool_name: String,
+        count: u32,
+    },
+    /// Recent tool results only repeat earlier ones
+    NoProgress {
+        steps: u32,
+    },
     /// Suspicious pattern that might develop into a loop
     SuspiciousPattern {
         tool_name: String,
@@ -70,7 +57,14 @@ pub enum LoopPattern {
 impl LoopPattern {
     /// Check if this pattern indicates a problematic loop
     pub fn is_problematic(&self) -> bool {
-        matches!(self, LoopPattern::ExactLoop { .. } | LoopPattern::SimilarLoop { .. })
+        matches!(
+            self,
+            LoopPattern::ExactLoop { .. }
+                | LoopPattern::SimilarLoop { .. }
+                | LoopPattern::CycleLoop { .. }
+                | LoopPattern::RepeatedObservation { .. }
+                | LoopPattern::NoProgress { .. }
+        )
     }
     
     /// Get the tool name involved in the pattern
@@ -78,8 +72,10 @@ impl LoopPattern {
         match self {
             LoopPattern::ExactLoop { tool_name, .. } => Some(tool_name),
             LoopPattern::SimilarLoop { tool_name, .. } => Some(tool_name),
+            LoopPattern::CycleLoop { tools, .. } => tools.first().map(String::as_str),
+            LoopPattern::RepeatedObservation { tool_name, .. } => Some(tool_name),
             LoopPattern::SuspiciousPattern { tool_name, .. } => Some(tool_name),
-            LoopPattern::NoLoop => None,
+            LoopPattern::NoProgress { .. } | LoopPattern::NoLoop => None,
         }
     }
     
@@ -88,10 +84,41 @@ impl LoopPattern {
         match self {
             LoopPattern::ExactLoop { count, .. } => *count,
             LoopPattern::SimilarLoop { count, .. } => *count,
+            LoopPattern::CycleLoop { repetitions, .. } => *repetitions,
+            LoopPattern::RepeatedObservation { count, .. } => *count,
+            LoopPattern::NoProgress { steps } => *steps,
             LoopPattern::SuspiciousPattern { count, .. } => *count,
             LoopPattern::NoLoop => 0,
         }
     }
+
+    /// Short explanation of the pattern for the model or the user
+    pub fn describe(&self) -> String {
+        match self {
+            LoopPattern::ExactLoop { tool_name, count, .. } => {
+                format!("'{}' was called {} times with identical arguments", tool_name, count)
+            }
+            LoopPattern::SimilarLoop { tool_name, count, similarity_score, .. } => format!(
+                "'{}' was called {} times with {:.1}% similar arguments",
+                tool_name,
+                count,
+                similarity_score * 100.0
+            ),
+            LoopPattern::CycleLoop { tools, repetitions } => {
+                format!("the calls {} repeated {} times", tools.join(" → "), repetitions)
+            }
+            LoopPattern::RepeatedObservation { tool_name, count } => {
+                format!("'{}' returned the same result {} times", tool_name, count)
+            }
+            LoopPattern::NoProgress { steps } => {
+                format!("the last {} tool results only repeated earlier ones", steps)
+            }
+            LoopPattern::SuspiciousPattern { tool_name, count, pattern_type } => {
+                format!("'{}' was called {} times ({})", tool_name, count, pattern_type)
+            }
+            LoopPattern::NoLoop => "no loop".to_string(),
+        }
+    }
 }
 
 /// Represents a tool call with timing information
@@ -237,6 +264,14 @@ mod tests {
         assert_eq!(exact_loop.tool_name(), Some("test_tool"));
         assert_eq!(exact_loop.count(), 5);
         
+        let cycle = LoopPattern::CycleLoop {
+            tools: vec!["search".to_string(), "open".to_string()],
+            repetitions: 3,
+        };
+        assert!(cycle.is_problematic());
+        assert_eq!(cycle.tool_name(), Some("search"));
+        assert_eq!(cycle.describe(), "the calls search → open repeated 3 times");
+
         let no_loop = LoopPattern::NoLoop;
         assert!(!no_loop.is_problematic());
         assert_eq!(no_loop.tool_name(), None);
@@ -1,12 +1,15 @@
 //! Pattern detection engine for identifying tool call loops
 //! 
 //! This module implements the core pattern detection logic that analyzes
-//! tool call history to identify exact and similar loops.
+//! tool call history to identify exact, similar and cyclic loops, and tool
+//! results to notice when the agent stops making progress.
 
 use super::{LoopDetectionConfig, LoopPattern, ToolCallRecord};
 use crate::loop_detection::similarity::calculate_argument_similarity;
 use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
 
 /// Main pattern detector that analyzes tool call history for loops
 #[derive(Debug)]
@@ -15,6 +18,8 @@ pub struct PatternDetector {
     call_history: VecDeque<ToolCallRecord>,
     exact_match_counts: HashMap<u64, u32>,
     similar_pattern_tracking: HashMap<String, Vec<(usize, f64)>>,
+    // Tool name and hash of the call and its result for recent observations
+    observation_history: VecDeque<(String, u64)>,
 }
 
 impl PatternDetector {
@@ -25,6 +30,7 @@ impl PatternDetector {
             call_history: VecDeque::new(),
             exact_match_counts: HashMap::new(),
             similar_pattern_tracking: HashMap::new(),
+            observation_history: VecDeque::new(),
         }
     }
     
@@ -60,6 +66,11 @@ impl PatternDetector {
             return exact_pattern;
         }
         
+        // Multi-tool cycles
+        if let Some(cycle_pattern) = self.detect_cycles() {
+            return cycle_pattern;
+        }
+        
         // Layer 2: Semantic Similarity Detection
         if let Some(similar_pattern) = self.detect_similar_patterns(latest_record) {
             return similar_pattern;
@@ -77,8 +88,11 @@ impl PatternDetector {
     fn detect_exact_matches(&self, latest_record: &ToolCallRecord) -> Option<LoopPattern> {
         let exact_count = *self.exact_match_counts.get(&latest_record.signature_hash).unwrap_or(&0);
         
-        // Use generic threshold for all tools
-        let threshold = self.config.exact_match_threshold;
+        let threshold = self.config
+            .tool_specific_thresholds
+            .get(&latest_record.tool_name)
+            .copied()
+            .unwrap_or(self.config.exact_match_threshold);
         
         if exact_count >= threshold {
             // Find the first occurrence of this signature
@@ -98,6 +112,83 @@ impl PatternDetector {
         None
     }
     
+    /// Detect a sequence of different calls repeating at the end of the history
+    fn detect_cycles(&self) -> Option<LoopPattern> {
+        let repetitions = self.config.cycle_repetitions.max(2) as usize;
+        let hashes: Vec<u64> = self.call_history.iter().map(|record| record.signature_hash).collect();
+        
+        for length in 2..=self.config.max_cycle_length {
+            let needed = length * repetitions;
+            if hashes.len() < needed {
+                break;
+            }
+            let tail = &hashes[hashes.len() - needed..];
+            let cycle = &tail[..length];
+            
+            // A single repeated call is an exact loop, not a cycle
+            if cycle.iter().all(|hash| *hash == cycle[0]) {
+                continue;
+            }
+            if tail.iter().enumerate().all(|(i, hash)| *hash == cycle[i % length]) {
+                let tools = self.call_history
+                    .iter()
+                    .skip(self.call_history.len() - needed)
+                    .take(length)
+                    .map(|record| record.tool_name.clone())
+                    .collect();
+                return Some(LoopPattern::CycleLoop {
+                    tools,
+                    repetitions: repetitions as u32,
+                });
+            }
+        }
+        
+        None
+    }
+    
+    /// Add the result of a tool call and check whether results stopped changing
+    ///
+    /// Results are keyed by the call as well, so tools that answer every call with
+    /// the same acknowledgement only count when the same call is repeated.
+    pub fn add_observation(&mut self, tool_name: &str, arguments: &Value, content: &str) -> LoopPattern {
+        let mut hasher = DefaultHasher::new();
+        ToolCallRecord::calculate_signature_hash(tool_name, arguments).hash(&mut hasher);
+        content.hash(&mut hasher);
+        let hash = hasher.finish();
+        
+        let capacity = self.config
+            .detection_window_size
+            .max(self.config.no_progress_threshold as usize + 1);
+        self.observation_history.push_back((tool_name.to_string(), hash));
+        if self.observation_history.len() > capacity {
+            self.observation_history.pop_front();
+        }
+        
+        let repeats = self.observation_history
+            .iter()
+            .filter(|(_, seen)| *seen == hash)
+            .count() as u32;
+        if repeats >= self.config.observation_repeat_threshold {
+            return LoopPattern::RepeatedObservation {
+                tool_name: tool_name.to_string(),
+                count: repeats,
+            };
+        }
+        
+        // Every one of the latest results was already seen before it
+        let steps = self.config.no_progress_threshold as usize;
+        let hashes: Vec<u64> = self.observation_history.iter().map(|(_, hash)| *hash).collect();
+        if steps > 0 && hashes.len() > steps {
+            let start = hashes.len() - steps;
+            let stalled = (start..hashes.len()).all(|i| hashes[..i].contains(&hashes[i]));
+            if stalled {
+                return LoopPattern::NoProgress { steps: steps as u32 };
+            }
+        }
+        
+        LoopPattern::NoLoop
+    }
+    
     /// Layer 2: Detect similar patterns (same tool, similar arguments)
     fn detect_similar_patterns(&mut self, latest_record: &ToolCallRecord) -> Option<LoopPattern> {
         let tool_name = &latest_record.tool_name;
@@ -222,6 +313,7 @@ impl PatternDetector {
         self.call_history.clear();
         self.exact_match_counts.clear();
         self.similar_pattern_tracking.clear();
+        self.observation_history.clear();
     }
     
     /// Get the current call history (for debugging/inspection)
@@ -250,7 +342,7 @@ mod tests {
             exact_match_threshold: 3,
             similar_match_threshold: 3,
             time_window_seconds: 10,
-            tool_specific_thresholds: HashMap::new(),
+            ..Default::default()
         }
     }
 
@@ -396,4 +488,109 @@ mod tests {
             _ => panic!("Expected ExactLoop with generic threshold for search_flights"),
         }
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_tool_specific_threshold() {
+        let mut config = create_test_config();
+        config.tool_specific_thresholds.insert("poll_status".to_string(), 5);
+        let mut detector = PatternDetector::new(config);
+        let args = json!({"job": "42"});
+        
+        for step in 1..=4 {
+            let pattern = detector.add_tool_call("poll_status".to_string(), args.clone(), step);
+            assert!(!matches!(pattern, LoopPattern::ExactLoop { .. }), "step {}", step);
+        }
+        let pattern = detector.add_tool_call("poll_status".to_string(), args, 5);
+        assert!(matches!(pattern, LoopPattern::ExactLoop { count: 5, .. }));
+    }
+    
+    #[test]
+    fn test_cycle_detection() {
+        let config = LoopDetectionConfig {
+            detection_window_size: 10,
+            cycle_repetitions: 3,
+            ..Default::default()
+        };
+        let mut detector = PatternDetector::new(config);
+        let search = json!({"query": "rust loop detection"});
+        let open = json!({"url": "https://example.com/loops"});
+        
+        let patterns: Vec<LoopPattern> = (0..6)
+            .map(|step| {
+                let (tool, args) = if step % 2 == 0 { ("search", &search) } else { ("open", &open) };
+                detector.add_tool_call(tool.to_string(), args.clone(), step)
+            })
+            .collect();
+        assert!(patterns[..5].iter().all(|pattern| !pattern.is_problematic()), "{:?}", patterns);
+        assert_eq!(
+            patterns[5],
+            LoopPattern::CycleLoop {
+                tools: vec!["search".to_string(), "open".to_string()],
+                repetitions: 3,
+            }
+        );
+        
+        // Alternating tools with new arguments is progress
+        detector.clear();
+        for step in 0..6 {
+            let tool = if step % 2 == 0 { "search" } else { "open" };
+            let pattern = detector.add_tool_call(tool.to_string(), json!({"page": step}), step);
+            assert!(!pattern.is_problematic(), "step {}: {:?}", step, pattern);
+        }
+    }
+    
+    #[test]
+    fn test_repeated_observation_detection() {
+        let config = LoopDetectionConfig {
+            observation_repeat_threshold: 3,
+            ..Default::default()
+        };
+        let mut detector = PatternDetector::new(config);
+        
+        let url = json!({"url": "https://example.com"});
+        assert_eq!(detector.add_observation("fetch", &url, "503 Service Unavailable"), LoopPattern::NoLoop);
+        assert_eq!(detector.add_observation("search", &url, "503 Service Unavailable"), LoopPattern::NoLoop);
+        assert_eq!(detector.add_observation("fetch", &url, "503 Service Unavailable"), LoopPattern::NoLoop);
+        assert_eq!(
+            detector.add_observation("fetch", &url, "503 Service Unavailable"),
+            LoopPattern::RepeatedObservation { tool_name: "fetch".to_string(), count: 3 }
+        );
+    }
+    
+    #[test]
+    fn test_constant_acknowledgements_are_not_loops() {
+        let config = LoopDetectionConfig {
+            observation_repeat_threshold: 2,
+            no_progress_threshold: 2,
+            ..Default::default()
+        };
+        let mut detector = PatternDetector::new(config);
+        
+        // Saving different files always answers the same
+        for i in 0..5 {
+            let args = json!({"path": format!("file{}.txt", i)});
+            assert_eq!(detector.add_observation("write_file", &args, "File saved"), LoopPattern::NoLoop);
+        }
+    }
+    
+    #[test]
+    fn test_no_progress_detection() {
+        let config = LoopDetectionConfig {
+            no_progress_threshold: 4,
+            ..Default::default()
+        };
+        let mut detector = PatternDetector::new(config);
+        
+        // Two results keep alternating, each repeated fewer times than the repeat threshold
+        let dir = json!({"dir": "."});
+        assert_eq!(detector.add_observation("list", &dir, "a.txt"), LoopPattern::NoLoop);
+        assert_eq!(detector.add_observation("list", &dir, "b.txt"), LoopPattern::NoLoop);
+        assert_eq!(detector.add_observation("list", &dir, "a.txt"), LoopPattern::NoLoop);
+        assert_eq!(detector.add_observation("list", &dir, "b.txt"), LoopPattern::NoLoop);
+        assert_eq!(detector.add_observation("list", &dir, "a.txt"), LoopPattern::NoLoop);
+        assert_eq!(detector.add_observation("list", &dir, "b.txt"), LoopPattern::NoProgress { steps: 4 });
+        
+        // A new result resets it
+        assert_eq!(detector.add_observation("list", &dir, "c.txt"), LoopPattern::NoLoop);
+    }
+}
@@ -48,6 +48,7 @@ impl ConversationSummaryMemory {
             content: prompt,
             tool_call_id: None,
             tool_calls: None,
+            reasoning: None,
         }];
 
         let response = self.llm.generate(messages, None).await?;
@@ -71,6 +72,7 @@ impl ConversationMemory for ConversationSummaryMemory {
             content: self.summary.clone(),
             tool_call_id: None,
             tool_calls: None,
+            reasoning: None,
         }]
     }
 
@@ -91,6 +91,7 @@ impl ConversationSummaryBufferMemory {
             content: prompt,
             tool_call_id: None,
             tool_calls: None,
+            reasoning: None,
         }];
 
         let response = self.llm.generate(messages, None).await?;
@@ -114,6 +115,7 @@ impl ConversationMemory for ConversationSummaryBufferMemory {
                 content: self.moving_summary_buffer.clone(),
                 tool_call_id: None,
                 tool_calls: None,
+                reasoning: None,
             });
         }
 
@@ -0,0 +1,148 @@
+//! Lexical BM25 index over document chunks
+//!
+//! Embeddings are good at paraphrases but poor at exact identifiers such as error
+//! codes, function names or config keys. This index scores chunks by the query
+//! terms they contain. Tokens keep underscores, so `max_retries` and `E0432` are
+//! matched as whole terms, case-insensitively.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+
+/// Splits text into lowercase terms of letters, digits and underscores
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Bm25Index {
+    /// Term frequency per document, by term
+    postings: HashMap<String, HashMap<usize, u32>>,
+    document_lengths: HashMap<usize, usize>,
+    total_length: usize,
+}
+
+impl Bm25Index {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_document(&mut self, document_id: usize, text: &str) {
+        self.remove_document(document_id);
+
+        let tokens = tokenize(text);
+        for token in &tokens {
+            *self
+                .postings
+                .entry(token.clone())
+                .or_default()
+                .entry(document_id)
+                .or_insert(0) += 1;
+        }
+        self.total_length += tokens.len();
+        self.document_lengths.insert(document_id, tokens.len());
+    }
+
+    pub fn remove_document(&mut self, document_id: usize) {
+        let Some(length) = self.document_lengths.remove(&document_id) else {
+            return;
+        };
+        self.total_length -= length;
+        self.postings.retain(|_, documents| {
+            documents.remove(&document_id);
+            !documents.is_empty()
+        });
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn document_count(&self) -> usize {
+        self.document_lengths.len()
+    }
+
+    /// Returns up to `top_k` documents that pass `filter` and contain at least one
+    /// query term, best first
+    pub fn search(
+        &self,
+        query: &str,
+        top_k: usize,
+        filter: &(dyn Fn(usize) -> bool + Send + Sync),
+    ) -> Vec<(usize, f32)> {
+        if self.document_lengths.is_empty() {
+            return Vec::new();
+        }
+
+        let count = self.document_lengths.len() as f32;
+        let average_length = (self.total_length as f32 / count).max(1.0);
+        let terms: HashSet<String> = tokenize(query).into_iter().collect();
+
+        let mut scores: HashMap<usize, f32> = HashMap::new();
+        for term in &terms {
+            let Some(documents) = self.postings.get(term) else {
+                continue;
+            };
+            let matching = documents.len() as f32;
+            let idf = (1.0 + (count - matching + 0.5) / (matching + 0.5)).ln();
+
+            for (&document_id, &frequency) in documents {
+                if !filter(document_id) {
+                    continue;
+                }
+                let length = self.document_lengths[&document_id] as f32;
+                let frequency = frequency as f32;
+                let normalization = K1 * (1.0 - B + B * length / average_length);
+                *scores.entry(document_id).or_insert(0.0) +=
+                    idf * frequency * (K1 + 1.0) / (frequency + normalization);
+            }
+        }
+
+        let mut results: Vec<(usize, f32)> = scores.into_iter().collect();
+        results.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.0.cmp(&b.0))
+        });
+        results.truncate(top_k);
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_keeps_identifiers() {
+        assert_eq!(
+            tokenize("Set `retry.max_retries` to fix E0432!"),
+            vec!["set", "retry", "max_retries", "to", "fix", "e0432"]
+        );
+    }
+
+    #[test]
+    fn test_search_ranks_exact_terms() {
+        let mut index = Bm25Index::new();
+        index.add_document(0, "The connection was reset by the peer");
+        index.add_document(1, "Error ERR_CONN_RESET means the connection was reset");
+        index.add_document(2, "Unrelated text about cooking pasta");
+
+        let results = index.search("err_conn_reset", 5, &|_| true);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, 1);
+
+        let results = index.search("connection reset", 5, &|_| true);
+        assert_eq!(results.iter().map(|r| r.0).collect::<Vec<_>>(), vec![0, 1]);
+        assert!(index.search("connection reset", 5, &|id| id != 0).iter().all(|r| r.0 == 1));
+
+        index.remove_document(1);
+        assert!(index.search("err_conn_reset", 5, &|_| true).is_empty());
+        assert_eq!(index.document_count(), 2);
+    }
+}
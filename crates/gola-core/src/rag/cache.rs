@@ -1,4 +1,5 @@
 use crate::errors::AgentError;
+use crate::rag::embedding_file::{self, EmbeddingFile, EmbeddingFileHeader, Quantization};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -51,7 +52,8 @@ pub struct EmbeddingCacheConfig {
     pub persistent: bool,
     /// Path for persistent cache storage
     pub cache_file_path: Option<String>,
-    /// Whether to compress cached data
+    /// Whether to store cached embeddings int8-quantized on disk, about four
+    /// times smaller at a small loss of precision
     pub compress: bool,
     /// Cache eviction strategy
     pub eviction_strategy: EvictionStrategy,
@@ -113,6 +115,15 @@ impl CacheEntry {
     }
 }
 
+/// Per-row metadata of a saved cache; the embeddings are stored in the matrix
+#[derive(Serialize, Deserialize)]
+struct StoredCacheEntry<T> {
+    text: T,
+    access_count: u64,
+    last_accessed: u64,
+    created_at: u64,
+}
+
 /// In-memory embedding cache with configurable eviction strategies
 pub struct InMemoryEmbeddingCache {
     cache: Arc<RwLock<HashMap<String, CacheEntry>>>,
@@ -265,20 +276,36 @@ impl EmbeddingCache for InMemoryEmbeddingCache {
 
     async fn save(&self, path: &Path) -> Result<(), AgentError> {
         let cache = self.cache.read().await;
-        let data = cache.clone();
+        let dimension = cache.values().next().map_or(0, |entry| entry.embedding.len());
+        let mut rows = Vec::with_capacity(cache.len());
+        let mut entries = Vec::with_capacity(cache.len());
+        for (text, entry) in cache.iter() {
+            rows.push(entry.embedding.as_slice());
+            entries.push(StoredCacheEntry {
+                text,
+                access_count: entry.access_count,
+                last_accessed: entry.last_accessed,
+                created_at: entry.created_at,
+            });
+        }
+        let header = EmbeddingFileHeader {
+            embedding_model: String::new(),
+            dimension,
+            quantization: if self.config.compress {
+                Quantization::Int8
+            } else {
+                Quantization::F32
+            },
+        };
+        let bytes = embedding_file::encode(&header, &rows, &entries)?;
+        let count = cache.len();
         drop(cache);
 
-        let serialized = 
-            serde_json::to_vec_pretty(&data)
-                .map_err(|e| AgentError::RagError(format!("Failed to serialize cache: {}", e)))?;
-
-        tokio::fs::write(path, serialized)
-            .await
-            .map_err(|e| AgentError::RagError(format!("Failed to write cache to file: {}", e)))?;
+        embedding_file::write(path, bytes).await?;
 
         log::info!(
             "Saved embedding cache with {} entries to {:?}",
-            data.len(),
+            count,
             path
         );
         Ok(())
@@ -293,13 +320,27 @@ impl EmbeddingCache for InMemoryEmbeddingCache {
             return Ok(());
         }
 
-        let data = tokio::fs::read(path)
-            .await
-            .map_err(|e| AgentError::RagError(format!("Failed to read cache file: {}", e)))?;
-
-        let cache_data: HashMap<String, CacheEntry> = 
-            serde_json::from_slice(&data)
-                .map_err(|e| AgentError::RagError(format!("Failed to deserialize cache: {}", e)))?;
+        let bytes = embedding_file::read(path).await?;
+        let migrate = !embedding_file::is_embedding_file(&bytes);
+        let cache_data: HashMap<String, CacheEntry> = if migrate {
+            serde_json::from_slice(&bytes)
+                .map_err(|e| AgentError::RagError(format!("Failed to deserialize cache: {}", e)))?
+        } else {
+            let file: EmbeddingFile<Vec<StoredCacheEntry<String>>> = embedding_file::decode(&bytes)?;
+            file.metadata
+                .into_iter()
+                .zip(file.embeddings)
+                .map(|(stored, embedding)| {
+                    let entry = CacheEntry {
+                        embedding,
+                        access_count: stored.access_count,
+                        last_accessed: stored.last_accessed,
+                        created_at: stored.created_at,
+                    };
+                    (stored.text, entry)
+                })
+                .collect()
+        };
 
         let mut cache = self.cache.write().await;
         *cache = cache_data;
@@ -309,6 +350,12 @@ impl EmbeddingCache for InMemoryEmbeddingCache {
             cache.len(),
             path
         );
+        drop(cache);
+
+        if migrate {
+            log::info!("Migrating embedding cache {:?} from JSON to the binary format", path);
+            self.save(path).await?;
+        }
         Ok(())
     }
 
@@ -642,6 +689,35 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_json_cache_is_migrated() {
+        let temp_dir = tempdir().unwrap();
+        let cache_path = temp_dir.path().join("legacy_cache.json");
+        let legacy = serde_json::json!({
+            "hello": { "embedding": [0.5, -0.25], "access_count": 3, "last_accessed": 20, "created_at": 10 }
+        });
+        std::fs::write(&cache_path, serde_json::to_vec_pretty(&legacy).unwrap()).unwrap();
+
+        let config = EmbeddingCacheConfig {
+            compress: true,
+            ..Default::default()
+        };
+        let cache = InMemoryEmbeddingCache::new(config);
+        cache.load(&cache_path).await.unwrap();
+        assert_eq!(cache.get("hello").await, Some(vec![0.5, -0.25]));
+
+        let bytes = std::fs::read(&cache_path).unwrap();
+        assert!(embedding_file::is_embedding_file(&bytes));
+        let file: EmbeddingFile<serde_json::Value> = embedding_file::decode(&bytes).unwrap();
+        assert_eq!(file.header.quantization, Quantization::Int8);
+        assert_eq!(file.metadata[0]["access_count"], 3);
+
+        let reloaded = InMemoryEmbeddingCache::with_max_size(10);
+        reloaded.load(&cache_path).await.unwrap();
+        let embedding = reloaded.get("hello").await.unwrap();
+        assert!((embedding[0] - 0.5).abs() < 0.01 && (embedding[1] + 0.25).abs() < 0.01);
+    }
+
     #[tokio::test]
     async fn test_noop_cache() {
         let cache = NoOpEmbeddingCache::new();
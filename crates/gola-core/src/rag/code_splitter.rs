@@ -0,0 +1,513 @@
+//! Syntax-aware splitting of source code
+//!
+//! Source files are parsed with tree-sitter and chunked at definition
+//! boundaries: functions, classes, impl blocks and the like. A definition that
+//! fits in a chunk is kept whole; a class or impl block that does not is split
+//! into its members, and any other oversized definition falls back to the
+//! recursive splitter. Each chunk records the path of its symbol, such as
+//! `Config::load`, and the signatures of the definitions enclosing it.
+
+use std::path::Path;
+use tree_sitter::{Language, Node, Parser};
+
+use crate::rag::document_loader::DocumentSection;
+use crate::rag::splitter::{DocumentMetadata, TextSplitter};
+
+/// Metadata key of the path of the symbol a chunk defines or falls in, such
+/// as `Config::load`
+pub const SYMBOL_KEY: &str = "symbol";
+/// Metadata key of the signatures enclosing a chunk, outermost first, one per line
+pub const SIGNATURE_KEY: &str = "signature";
+/// Metadata key of the language of a code chunk
+pub const LANGUAGE_KEY: &str = "language";
+/// Metadata key of the 1-based line a code chunk starts on
+pub const START_LINE_KEY: &str = "start_line";
+/// Metadata key of the 1-based line a code chunk ends on
+pub const END_LINE_KEY: &str = "end_line";
+
+/// Languages with a grammar for syntax-aware splitting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+    Go,
+    Java,
+}
+
+impl CodeLanguage {
+    /// Looks up a language by name or common file extension, such as `rust` or `py`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "rust" | "rs" => Some(Self::Rust),
+            "python" | "py" | "pyi" => Some(Self::Python),
+            "javascript" | "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+            "typescript" | "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            "go" | "golang" => Some(Self::Go),
+            "java" => Some(Self::Java),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_name(&path.extension()?.to_string_lossy())
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Python => "python",
+            Self::JavaScript => "javascript",
+            Self::TypeScript => "typescript",
+            Self::Tsx => "tsx",
+            Self::Go => "go",
+            Self::Java => "java",
+        }
+    }
+
+    fn grammar(&self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE,
+            Self::Python => tree_sitter_python::LANGUAGE,
+            Self::JavaScript => tree_sitter_javascript::LANGUAGE,
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX,
+            Self::Go => tree_sitter_go::LANGUAGE,
+            Self::Java => tree_sitter_java::LANGUAGE,
+        }
+        .into()
+    }
+
+    fn syntax(&self) -> &'static Syntax {
+        match self {
+            Self::Rust => &RUST,
+            Self::Python => &PYTHON,
+            Self::JavaScript => &JAVASCRIPT,
+            Self::TypeScript | Self::Tsx => &TYPESCRIPT,
+            Self::Go => &GO,
+            Self::Java => &JAVA,
+        }
+    }
+}
+
+/// Node kinds that shape the chunks of a grammar
+struct Syntax {
+    /// Definitions that become chunks of their own
+    definitions: &'static [&'static str],
+    /// Definitions whose members are chunked separately when they are too large
+    containers: &'static [&'static str],
+    /// Nodes wrapping a definition, with the field that holds it
+    wrappers: &'static [(&'static str, &'static str)],
+    /// Comments and attributes kept with the definition right after them
+    leading: &'static [&'static str],
+    /// Joins the names of nested symbols
+    separator: &'static str,
+}
+
+static RUST: Syntax = Syntax {
+    definitions: &[
+        "function_item",
+        "function_signature_item",
+        "impl_item",
+        "trait_item",
+        "struct_item",
+        "enum_item",
+        "union_item",
+        "mod_item",
+        "macro_definition",
+    ],
+    containers: &["impl_item", "trait_item", "mod_item"],
+    wrappers: &[],
+    leading: &["line_comment", "block_comment", "attribute_item"],
+    separator: "::",
+};
+
+static PYTHON: Syntax = Syntax {
+    definitions: &["function_definition", "class_definition"],
+    containers: &["class_definition"],
+    wrappers: &[("decorated_definition", "definition")],
+    leading: &["comment"],
+    separator: ".",
+};
+
+static JAVASCRIPT: Syntax = Syntax {
+    definitions: &[
+        "function_declaration",
+        "generator_function_declaration",
+        "class_declaration",
+        "method_definition",
+        "lexical_declaration",
+        "variable_declaration",
+    ],
+    containers: &["class_declaration"],
+    wrappers: &[("export_statement", "declaration")],
+    leading: &["comment"],
+    separator: ".",
+};
+
+static TYPESCRIPT: Syntax = Syntax {
+    definitions: &[
+        "function_declaration",
+        "generator_function_declaration",
+        "class_declaration",
+        "abstract_class_declaration",
+        "method_definition",
+        "method_signature",
+        "abstract_method_signature",
+        "lexical_declaration",
+        "variable_declaration",
+        "interface_declaration",
+        "type_alias_declaration",
+        "enum_declaration",
+        "internal_module",
+    ],
+    containers: &[
+        "class_declaration",
+        "abstract_class_declaration",
+        "interface_declaration",
+        "internal_module",
+    ],
+    wrappers: &[("export_statement", "declaration")],
+    leading: &["comment"],
+    separator: ".",
+};
+
+static GO: Syntax = Syntax {
+    definitions: &["function_declaration", "method_declaration", "type_declaration"],
+    containers: &[],
+    wrappers: &[],
+    leading: &["comment"],
+    separator: ".",
+};
+
+static JAVA: Syntax = Syntax {
+    definitions: &[
+        "class_declaration",
+        "interface_declaration",
+        "enum_declaration",
+        "record_declaration",
+        "annotation_type_declaration",
+        "method_declaration",
+        "constructor_declaration",
+    ],
+    containers: &[
+        "class_declaration",
+        "interface_declaration",
+        "enum_declaration",
+        "record_declaration",
+    ],
+    wrappers: &[],
+    leading: &["line_comment", "block_comment"],
+    separator: ".",
+};
+
+/// Splits `source` at definition boundaries, or returns `None` if it cannot be parsed
+pub(crate) fn split_code(
+    splitter: &TextSplitter,
+    source: &str,
+    language: CodeLanguage,
+) -> Option<Vec<DocumentSection>> {
+    let mut parser = Parser::new();
+    parser.set_language(&language.grammar()).ok()?;
+    let tree = parser.parse(source, None)?;
+
+    let mut chunker = CodeChunker {
+        splitter,
+        language,
+        syntax: language.syntax(),
+        source,
+        chunks: Vec::new(),
+    };
+    chunker.chunk_children(tree.root_node(), &[], &[]);
+    Some(chunker.chunks)
+}
+
+struct CodeChunker<'a> {
+    splitter: &'a TextSplitter,
+    language: CodeLanguage,
+    syntax: &'static Syntax,
+    source: &'a str,
+    chunks: Vec<DocumentSection>,
+}
+
+impl CodeChunker<'_> {
+    fn text(&self, node: Node) -> &str {
+        &self.source[node.byte_range()]
+    }
+
+    /// Chunks the children of `parent`, which lie in the symbol `path` under
+    /// the enclosing `signatures`. Code between definitions, such as imports,
+    /// is chunked in runs.
+    fn chunk_children(&mut self, parent: Node, path: &[String], signatures: &[String]) {
+        let mut cursor = parent.walk();
+        let children: Vec<Node> = parent.named_children(&mut cursor).collect();
+
+        let mut run_start = 0;
+        for (i, child) in children.iter().enumerate() {
+            let Some(definition) = self.definition(*child) else {
+                continue;
+            };
+            // Doc comments and attributes directly above belong to the definition
+            let mut first = i;
+            while first > run_start
+                && self.syntax.leading.contains(&children[first - 1].kind())
+                && children[first - 1].end_position().row + 1 >= children[first].start_position().row
+            {
+                first -= 1;
+            }
+            if run_start < first {
+                self.push_span(children[run_start], children[first - 1], path, signatures);
+            }
+            self.chunk_definition(children[first], *child, definition, path, signatures);
+            run_start = i + 1;
+        }
+        if run_start < children.len() {
+            self.push_span(children[run_start], children[children.len() - 1], path, signatures);
+        }
+    }
+
+    /// The definition `node` is or wraps, if any
+    fn definition<'tree>(&self, node: Node<'tree>) -> Option<Node<'tree>> {
+        if let Some((_, field)) = self.syntax.wrappers.iter().find(|(kind, _)| *kind == node.kind()) {
+            return node
+                .child_by_field_name(field)
+                .and_then(|inner| self.definition(inner));
+        }
+        if !self.syntax.definitions.contains(&node.kind()) {
+            return None;
+        }
+        if matches!(node.kind(), "lexical_declaration" | "variable_declaration") {
+            // Only `const handler = () => ...` and the like define something
+            let value = node
+                .named_child(0)
+                .and_then(|declarator| declarator.child_by_field_name("value"))?;
+            let is_function = matches!(
+                value.kind(),
+                "arrow_function" | "function_expression" | "generator_function" | "class"
+            );
+            return is_function.then_some(node);
+        }
+        Some(node)
+    }
+
+    /// Chunks a definition spanning from `first` to `node`, where `node` is the
+    /// definition itself or a wrapper such as a decorator or `export`
+    fn chunk_definition(
+        &mut self,
+        first: Node,
+        node: Node,
+        definition: Node,
+        path: &[String],
+        signatures: &[String],
+    ) {
+        let mut path = path.to_vec();
+        if let Some(name) = self.symbol_name(definition) {
+            path.push(name);
+        }
+        let mut signatures = signatures.to_vec();
+        signatures.push(self.signature(node, definition));
+
+        let text = &self.source[first.start_byte()..node.end_byte()];
+        match definition.child_by_field_name("body") {
+            Some(body)
+                if self.splitter.measure(text) > self.splitter.chunk_size()
+                    && self.syntax.containers.contains(&definition.kind()) =>
+            {
+                self.chunk_children(body, &path, &signatures)
+            }
+            _ => self.push_span(first, node, &path, &signatures),
+        }
+    }
+
+    fn symbol_name(&self, definition: Node) -> Option<String> {
+        match definition.kind() {
+            // The type implemented, such as `Config` for `impl Display for Config`
+            "impl_item" => definition.child_by_field_name("type").map(|ty| self.text(ty).to_string()),
+            "lexical_declaration" | "variable_declaration" | "type_declaration" => definition
+                .named_child(0)
+                .and_then(|declarator| declarator.child_by_field_name("name"))
+                .map(|name| self.text(name).to_string()),
+            _ => {
+                let name = self.text(definition.child_by_field_name("name")?);
+                // Go methods are named after their receiver type
+                let receiver = definition
+                    .child_by_field_name("receiver")
+                    .and_then(|receiver| find_kind(receiver, "type_identifier"));
+                Some(match receiver {
+                    Some(receiver) => format!("{}.{}", self.text(receiver), name),
+                    None => name.to_string(),
+                })
+            }
+        }
+    }
+
+    /// The text of a definition up to its body, on one line
+    fn signature(&self, node: Node, definition: Node) -> String {
+        let signature = match definition.child_by_field_name("body") {
+            Some(body) => &self.source[node.start_byte()..body.start_byte()],
+            None => self.text(node).lines().next().unwrap_or_default(),
+        };
+        let signature = signature.split_whitespace().collect::<Vec<_>>().join(" ");
+        signature.trim_end_matches([':', '{']).trim_end().to_string()
+    }
+
+    /// Adds the code from `first` to `last` as one chunk, or several if it is too large
+    fn push_span(&mut self, first: Node, last: Node, path: &[String], signatures: &[String]) {
+        let text = self.source[first.start_byte()..last.end_byte()].trim();
+        if text.is_empty() {
+            return;
+        }
+        let mut metadata = DocumentMetadata::new();
+        metadata.insert(LANGUAGE_KEY.to_string(), self.language.name().to_string());
+        if !path.is_empty() {
+            metadata.insert(SYMBOL_KEY.to_string(), path.join(self.syntax.separator));
+        }
+        if !signatures.is_empty() {
+            metadata.insert(SIGNATURE_KEY.to_string(), signatures.join("\n"));
+        }
+        metadata.insert(START_LINE_KEY.to_string(), (first.start_position().row + 1).to_string());
+        metadata.insert(END_LINE_KEY.to_string(), (last.end_position().row + 1).to_string());
+
+        let pieces = self.splitter.split_plain(text);
+        self.chunks.extend(self.splitter.with_headers(pieces, &metadata));
+    }
+}
+
+/// The first node of `kind` under `node`, depth first
+fn find_kind<'tree>(node: Node<'tree>, kind: &str) -> Option<Node<'tree>> {
+    if node.kind() == kind {
+        return Some(node);
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children.into_iter().find_map(|child| find_kind(child, kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rag::splitter::SplitStrategy;
+
+    fn split(language: CodeLanguage, chunk_size: usize, source: &str) -> Vec<DocumentSection> {
+        TextSplitter::new(chunk_size, 0)
+            .with_strategy(SplitStrategy::Code(Some(language)))
+            .split_sections(source)
+    }
+
+    fn symbols(chunks: &[DocumentSection]) -> Vec<&str> {
+        chunks
+            .iter()
+            .map(|chunk| chunk.metadata.get(SYMBOL_KEY).map_or("", String::as_str))
+            .collect()
+    }
+
+    fn count(chunks: &[DocumentSection], symbol: &str) -> usize {
+        symbols(chunks).iter().filter(|s| **s == symbol).count()
+    }
+
+    const RUST_SOURCE: &str = r#"use std::fmt;
+
+/// Loaded settings
+#[derive(Debug)]
+pub struct Config {
+    pub name: String,
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let name = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Ok(Self { name })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+"#;
+
+    #[test]
+    fn test_rust_chunks_at_definitions() {
+        let chunks = split(CodeLanguage::Rust, 1000, RUST_SOURCE);
+        assert_eq!(symbols(&chunks), vec!["", "Config", "Config", "Config"]);
+        assert_eq!(chunks[0].text, "use std::fmt;");
+        // Doc comments and attributes stay with their definition
+        assert!(chunks[1].text.starts_with("/// Loaded settings\n#[derive(Debug)]\npub struct Config"));
+        assert_eq!(chunks[1].metadata[START_LINE_KEY], "3");
+        assert_eq!(chunks[2].metadata[SIGNATURE_KEY], "impl Config");
+        assert!(chunks[2].text.ends_with("&self.name\n    }\n}"));
+        assert_eq!(chunks[3].metadata[SIGNATURE_KEY], "impl fmt::Display for Config");
+        assert_eq!(chunks[3].metadata[LANGUAGE_KEY], "rust");
+    }
+
+    #[test]
+    fn test_oversized_containers_split_into_members() {
+        let chunks = split(CodeLanguage::Rust, 150, RUST_SOURCE);
+        let symbols = symbols(&chunks);
+        assert!(symbols.contains(&"Config::load"));
+        assert!(symbols.contains(&"Config::name"));
+        let load = &chunks[symbols.iter().position(|s| *s == "Config::load").unwrap()];
+        assert!(load.text.starts_with("pub fn load"));
+        assert_eq!(
+            load.metadata[SIGNATURE_KEY],
+            "impl Config\npub fn load(path: &str) -> Result<Self, String>"
+        );
+        assert_eq!(load.metadata[START_LINE_KEY], "10");
+        assert_eq!(load.metadata[END_LINE_KEY], "13");
+
+        // A function larger than a chunk falls back to the recursive splitter
+        let chunks = split(CodeLanguage::Rust, 40, RUST_SOURCE);
+        assert!(chunks.iter().all(|chunk| chunk.text.len() <= 40));
+        assert!(count(&chunks, "Config::load") > 1);
+    }
+
+    #[test]
+    fn test_symbols_in_other_languages() {
+        let python = "import os\n\n\nclass Agent:\n    \"\"\"Runs tools.\"\"\"\n\n    @property\n    def name(self):\n        return 'agent'\n\n    def run(self, task):\n        return task\n";
+        let chunks = split(CodeLanguage::Python, 60, python);
+        assert_eq!(symbols(&chunks), vec!["", "Agent", "Agent.name", "Agent.run"]);
+        assert!(chunks[2].text.starts_with("@property"));
+        assert_eq!(chunks[3].metadata[SIGNATURE_KEY], "class Agent\ndef run(self, task)");
+
+        let typescript = "export class Client {\n  constructor(private url: string) {}\n\n  async fetch(path: string): Promise<string> {\n    return `${this.url}/${path}`;\n  }\n}\n\nexport const retry = (times: number) => times * 2;\n";
+        let chunks = split(CodeLanguage::TypeScript, 100, typescript);
+        assert_eq!(symbols(&chunks), vec!["Client.constructor", "Client.fetch", "retry"]);
+        assert_eq!(
+            chunks[1].metadata[SIGNATURE_KEY],
+            "export class Client\nasync fetch(path: string): Promise<string>"
+        );
+
+        let go = "package main\n\ntype Server struct{}\n\nfunc (s *Server) Start() error {\n\treturn nil\n}\n";
+        let chunks = split(CodeLanguage::Go, 1000, go);
+        assert_eq!(symbols(&chunks), vec!["", "Server", "Server.Start"]);
+        assert_eq!(chunks[2].metadata[SIGNATURE_KEY], "func (s *Server) Start() error");
+    }
+
+    #[test]
+    fn test_language_lookup() {
+        assert_eq!(CodeLanguage::from_path(Path::new("src/main.rs")), Some(CodeLanguage::Rust));
+        assert_eq!(CodeLanguage::from_path(Path::new("app.TSX")), Some(CodeLanguage::Tsx));
+        assert_eq!(CodeLanguage::from_path(Path::new("README.md")), None);
+        assert_eq!(CodeLanguage::from_name("golang"), Some(CodeLanguage::Go));
+    }
+
+    #[tokio::test]
+    async fn test_language_detected_from_source() {
+        let splitter = TextSplitter::new(1000, 0).with_strategy(SplitStrategy::Code(None));
+        let embeddings = crate::rag::DummyEmbeddingGenerator::new();
+
+        let chunks = splitter.split_document("src/config.rs", RUST_SOURCE, &embeddings).await.unwrap();
+        assert_eq!(chunks.len(), 4);
+        let chunks = splitter.split_document("NOTES.txt", RUST_SOURCE, &embeddings).await.unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].metadata.is_empty());
+    }
+}
@@ -0,0 +1,246 @@
+//! Word (DOCX) text extraction
+//!
+//! Paragraph text is read from `word/document.xml`. Paragraphs styled as
+//! headings, or given an outline level, start a new section. Word records where
+//! pages broke when the file was last saved, and explicit page breaks are
+//! recorded too, so sections are also split at page boundaries and carry the
+//! page they start on.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::io::{Cursor, Read};
+
+use super::{DocumentLoader, DocumentSection, Outline, PAGE_KEY};
+use crate::errors::AgentError;
+
+#[derive(Debug, Clone, Default)]
+pub struct DocxLoader;
+
+impl DocxLoader {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DocumentLoader for DocxLoader {
+    fn extensions(&self) -> &[&str] {
+        &["docx"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/vnd.openxmlformats-officedocument.wordprocessingml.document"]
+    }
+
+    fn load(&self, bytes: &[u8]) -> Result<Vec<DocumentSection>, AgentError> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+            .map_err(|e| AgentError::RagError(format!("Failed to open DOCX archive: {}", e)))?;
+        let mut xml = String::new();
+        archive
+            .by_name("word/document.xml")
+            .map_err(|e| AgentError::RagError(format!("DOCX has no document body: {}", e)))?
+            .read_to_string(&mut xml)
+            .map_err(|e| AgentError::RagError(format!("Failed to read DOCX body: {}", e)))?;
+        parse_document(&xml)
+    }
+}
+
+/// Accumulates paragraphs into sections
+#[derive(Default)]
+struct SectionWriter {
+    outline: Outline,
+    sections: Vec<DocumentSection>,
+    text: String,
+    page: usize,
+    /// A page break was seen with no text after it, so a second marker for the
+    /// same break is not counted again
+    at_page_start: bool,
+}
+
+impl SectionWriter {
+    fn push_paragraph(&mut self, paragraph: &str) {
+        let paragraph = paragraph.trim();
+        if paragraph.is_empty() {
+            return;
+        }
+        if !self.text.is_empty() {
+            self.text.push_str("\n\n");
+        }
+        self.text.push_str(paragraph);
+        self.at_page_start = false;
+    }
+
+    fn flush(&mut self) {
+        let text = std::mem::take(&mut self.text);
+        if text.is_empty() {
+            return;
+        }
+        let section = DocumentSection::new(text).with_metadata(PAGE_KEY, self.page.to_string());
+        self.sections.push(self.outline.label(section));
+    }
+
+    fn page_break(&mut self) {
+        if self.at_page_start {
+            return;
+        }
+        self.flush();
+        self.page += 1;
+        self.at_page_start = true;
+    }
+}
+
+/// Reads the heading level of a paragraph from its style or outline level
+fn heading_level(element: &BytesStart, current: Option<usize>) -> Option<usize> {
+    let value = element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.local_name().as_ref() == b"val")
+        .and_then(|attribute| attribute.unescape_value().ok())?;
+    match element.local_name().as_ref() {
+        b"pStyle" => {
+            let style = value.to_lowercase().replace(' ', "");
+            style
+                .strip_prefix("heading")
+                .and_then(|level| level.parse().ok())
+                .or(current)
+        }
+        // Outline levels count from 0; 9 means body text
+        b"outlineLvl" => match value.parse::<usize>() {
+            Ok(level) if level < 9 => Some(level + 1),
+            _ => current,
+        },
+        _ => current,
+    }
+}
+
+fn parse_document(xml: &str) -> Result<Vec<DocumentSection>, AgentError> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = SectionWriter {
+        page: 1,
+        at_page_start: true,
+        ..Default::default()
+    };
+    let mut paragraph = String::new();
+    let mut heading: Option<usize> = None;
+    let mut in_text = false;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| AgentError::RagError(format!("Invalid DOCX XML: {}", e)))?;
+        match event {
+            Event::Start(element) | Event::Empty(element) => match element.local_name().as_ref() {
+                b"p" => {
+                    paragraph.clear();
+                    heading = None;
+                }
+                b"pStyle" | b"outlineLvl" => heading = heading_level(&element, heading),
+                b"t" => in_text = true,
+                b"tab" => paragraph.push('\t'),
+                b"cr" => paragraph.push('\n'),
+                b"br" => {
+                    let is_page = element.attributes().flatten().any(|attribute| {
+                        attribute.key.local_name().as_ref() == b"type"
+                            && attribute.value.as_ref() == b"page"
+                    });
+                    if is_page {
+                        // Text before the break stays on the earlier page
+                        writer.push_paragraph(&std::mem::take(&mut paragraph));
+                        writer.page_break();
+                    } else {
+                        paragraph.push('\n');
+                    }
+                }
+                b"lastRenderedPageBreak" => {
+                    writer.push_paragraph(&std::mem::take(&mut paragraph));
+                    writer.page_break();
+                }
+                _ => {}
+            },
+            Event::Text(text) if in_text => {
+                let text = text
+                    .unescape()
+                    .map_err(|e| AgentError::RagError(format!("Invalid DOCX text: {}", e)))?;
+                paragraph.push_str(&text);
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => {
+                    let text = std::mem::take(&mut paragraph);
+                    match heading {
+                        Some(level) if !text.trim().is_empty() => {
+                            writer.flush();
+                            writer.outline.enter(level, &text);
+                            writer.push_paragraph(&text);
+                        }
+                        _ => writer.push_paragraph(&text),
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    writer.flush();
+    Ok(writer.sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rag::document_loader::{SECTION_KEY, SECTION_NUMBER_KEY};
+    use std::io::Write;
+
+    fn paragraph(style: Option<&str>, body: &str) -> String {
+        let properties = style
+            .map(|style| format!(r#"<w:pPr><w:pStyle w:val="{}"/></w:pPr>"#, style))
+            .unwrap_or_default();
+        format!("<w:p>{}{}</w:p>", properties, body)
+    }
+
+    fn docx(paragraphs: &[String]) -> Vec<u8> {
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+            paragraphs.concat()
+        );
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("word/document.xml", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(xml.as_bytes()).unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_headings_and_pages_split_sections() {
+        let bytes = docx(&[
+            paragraph(None, "<w:r><w:t>Read this first.</w:t></w:r>"),
+            paragraph(Some("Heading1"), "<w:r><w:t>Installation</w:t></w:r>"),
+            paragraph(None, "<w:r><w:t xml:space=\"preserve\">Run </w:t></w:r><w:r><w:t>setup &amp; wait.</w:t></w:r>"),
+            // Word writes both markers for an explicit break; it counts once
+            paragraph(None, r#"<w:r><w:br w:type="page"/></w:r><w:r><w:lastRenderedPageBreak/><w:t>Then reboot.</w:t></w:r>"#),
+            paragraph(Some("Heading2"), "<w:r><w:t>Linux</w:t></w:r>"),
+            paragraph(None, "<w:r><w:t>Use the tarball.</w:t></w:r>"),
+        ]);
+        let sections = DocxLoader::new().load(&bytes).unwrap();
+
+        let texts: Vec<&str> = sections.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Read this first.",
+                "Installation\n\nRun setup & wait.",
+                "Then reboot.",
+                "Linux\n\nUse the tarball."
+            ]
+        );
+        let pages: Vec<&str> = sections.iter().map(|s| s.metadata[PAGE_KEY].as_str()).collect();
+        assert_eq!(pages, vec!["1", "1", "2", "2"]);
+        assert!(!sections[0].metadata.contains_key(SECTION_KEY));
+        assert_eq!(sections[2].metadata[SECTION_KEY], "Installation");
+        assert_eq!(sections[3].metadata[SECTION_KEY], "Installation > Linux");
+        assert_eq!(sections[3].metadata[SECTION_NUMBER_KEY], "1.1");
+
+        assert!(DocxLoader::new().load(b"not a zip").is_err());
+    }
+}
@@ -0,0 +1,290 @@
+//! HTML main-content extraction
+//!
+//! Pages are reduced to the text a reader would read. The main content is the
+//! `<main>` or single `<article>` element when the page has one; otherwise, as in
+//! readability tools, it is the element whose paragraphs hold the most text.
+//! Scripts, navigation, sidebars and other boilerplate are dropped, and headings
+//! split the content into sections.
+
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+use super::{normalize_whitespace, DocumentLoader, DocumentSection, Outline, TITLE_KEY};
+use crate::errors::AgentError;
+
+/// Elements that never hold readable content
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form",
+    "button", "svg", "iframe", "canvas", "select",
+];
+
+/// Elements whose content starts on a new line
+const BLOCK_TAGS: &[&str] = &[
+    "address", "article", "blockquote", "dd", "details", "div", "dl", "dt", "fieldset",
+    "figcaption", "figure", "hr", "li", "main", "ol", "p", "section", "summary", "table",
+    "tbody", "thead", "tfoot", "tr", "ul",
+];
+
+/// Class and id words that mark boilerplate
+const BOILERPLATE_WORDS: &[&str] = &[
+    "advert", "banner", "breadcrumb", "comment", "cookie", "footer", "menu", "nav", "popup",
+    "related", "share", "sidebar", "social", "sponsor",
+];
+
+/// Paragraphs shorter than this do not count towards a candidate's score
+const MIN_PARAGRAPH_LEN: usize = 25;
+
+#[derive(Debug, Clone, Default)]
+pub struct HtmlLoader;
+
+impl HtmlLoader {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DocumentLoader for HtmlLoader {
+    fn extensions(&self) -> &[&str] {
+        &["html", "htm", "xhtml"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["text/html", "application/xhtml+xml"]
+    }
+
+    fn load(&self, bytes: &[u8]) -> Result<Vec<DocumentSection>, AgentError> {
+        let html = Html::parse_document(&String::from_utf8_lossy(bytes));
+        let title = select_first(&html, "title")
+            .map(|title| normalize_whitespace(&title.text().collect::<String>()))
+            .filter(|title| !title.is_empty());
+
+        let mut extractor = Extractor::default();
+        extractor.walk(main_content(&html), false);
+        extractor.end_block(false);
+        extractor.flush();
+
+        let mut sections = extractor.sections;
+        if let Some(title) = title {
+            for section in &mut sections {
+                section.metadata.insert(TITLE_KEY.to_string(), title.clone());
+            }
+        }
+        Ok(sections)
+    }
+}
+
+fn select_first<'a>(html: &'a Html, selector: &str) -> Option<ElementRef<'a>> {
+    let selector = Selector::parse(selector).ok()?;
+    html.select(&selector).next()
+}
+
+/// Hidden elements and those whose tag, class or id marks them as boilerplate
+fn is_boilerplate(element: &ElementRef) -> bool {
+    let value = element.value();
+    if SKIPPED_TAGS.contains(&value.name()) {
+        return true;
+    }
+    if value.attr("hidden").is_some() || value.attr("aria-hidden") == Some("true") {
+        return true;
+    }
+    if value
+        .attr("style")
+        .is_some_and(|style| style.replace(' ', "").contains("display:none"))
+    {
+        return true;
+    }
+    value
+        .attr("class")
+        .into_iter()
+        .chain(value.attr("id"))
+        .flat_map(|names| names.split(|c: char| c.is_whitespace() || c == '-' || c == '_'))
+        .map(str::to_lowercase)
+        .any(|word| BOILERPLATE_WORDS.iter().any(|boilerplate| word.starts_with(boilerplate)))
+}
+
+/// Finds the element holding the main content of the page
+fn main_content(html: &Html) -> ElementRef<'_> {
+    if let Some(main) = select_first(html, "main, [role=main]") {
+        return main;
+    }
+    if let Ok(selector) = Selector::parse("article") {
+        let articles: Vec<_> = html.select(&selector).collect();
+        if articles.len() == 1 {
+            return articles[0];
+        }
+    }
+
+    // Score the parents of paragraphs by how much text they hold; the
+    // grandparent gets half, so a wrapper around several text blocks can win
+    let mut scores = HashMap::new();
+    if let Ok(selector) = Selector::parse("p, pre") {
+        for paragraph in html.select(&selector) {
+            if paragraph
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .any(|ancestor| is_boilerplate(&ancestor))
+            {
+                continue;
+            }
+            let text = normalize_whitespace(&paragraph.text().collect::<String>());
+            if text.len() < MIN_PARAGRAPH_LEN {
+                continue;
+            }
+            let score = 1.0 + text.matches(',').count() as f64 + (text.len() / 100).min(3) as f64;
+            let mut ancestors = paragraph.ancestors().filter_map(ElementRef::wrap);
+            if let Some(parent) = ancestors.next() {
+                *scores.entry(parent.id()).or_insert(0.0) += score;
+            }
+            if let Some(grandparent) = ancestors.next() {
+                *scores.entry(grandparent.id()).or_insert(0.0) += score / 2.0;
+            }
+        }
+    }
+    scores
+        .into_iter()
+        .max_by(|(_, a), (_, b)| f64::total_cmp(a, b))
+        .and_then(|(id, _)| html.tree.get(id))
+        .and_then(ElementRef::wrap)
+        .or_else(|| select_first(html, "body"))
+        .unwrap_or_else(|| html.root_element())
+}
+
+/// Collects text into blocks and blocks into sections split at headings
+#[derive(Default)]
+struct Extractor {
+    outline: Outline,
+    sections: Vec<DocumentSection>,
+    /// Blocks of the current section
+    blocks: Vec<String>,
+    /// Text of the current block
+    block: String,
+}
+
+impl Extractor {
+    fn walk(&mut self, element: ElementRef, preformatted: bool) {
+        for child in element.children() {
+            if let Some(text) = child.value().as_text() {
+                self.block.push_str(text);
+                continue;
+            }
+            let Some(child) = ElementRef::wrap(child) else {
+                continue;
+            };
+            if is_boilerplate(&child) {
+                continue;
+            }
+            let name = child.value().name();
+            match name {
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                    let heading = normalize_whitespace(&child.text().collect::<String>());
+                    if heading.is_empty() {
+                        continue;
+                    }
+                    self.end_block(preformatted);
+                    self.flush();
+                    self.outline.enter(name[1..].parse().unwrap_or(1), &heading);
+                    self.blocks.push(heading);
+                }
+                "br" => self.block.push('\n'),
+                "pre" => {
+                    self.end_block(preformatted);
+                    self.walk(child, true);
+                    self.end_block(true);
+                }
+                "td" | "th" => {
+                    self.walk(child, preformatted);
+                    self.block.push(' ');
+                }
+                _ if BLOCK_TAGS.contains(&name) => {
+                    self.end_block(preformatted);
+                    self.walk(child, preformatted);
+                    self.end_block(preformatted);
+                }
+                _ => self.walk(child, preformatted),
+            }
+        }
+    }
+
+    /// Closes the current block, collapsing whitespace unless it is preformatted
+    fn end_block(&mut self, preformatted: bool) {
+        let block = std::mem::take(&mut self.block);
+        let text = if preformatted {
+            block.trim_matches('\n').trim_end().to_string()
+        } else {
+            block
+                .lines()
+                .map(normalize_whitespace)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        if !text.trim().is_empty() {
+            self.blocks.push(text);
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.blocks.is_empty() {
+            return;
+        }
+        let section = DocumentSection::new(std::mem::take(&mut self.blocks).join("\n\n"));
+        self.sections.push(self.outline.label(section));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rag::document_loader::{SECTION_KEY, SECTION_NUMBER_KEY};
+
+    fn load(html: &str) -> Vec<DocumentSection> {
+        HtmlLoader::new().load(html.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_extracts_main_content_by_section() {
+        let sections = load(
+            r#"<html><head><title>Agent guide</title><style>p { color: red }</style></head>
+            <body>
+              <nav><a href="/">Home</a> <a href="/docs">Docs</a></nav>
+              <div class="sidebar"><p>Popular posts, trending now, and other links you may like.</p></div>
+              <div id="content">
+                <h1>Agent   guide</h1>
+                <p>Agents run tools in a loop, calling the model after each step.</p>
+                <h2>Retries</h2>
+                <p>Failed tool calls are retried, with <b>exponential</b> backoff.</p>
+                <pre>retries:
+  max: 3</pre>
+                <script>track()</script>
+                <div class="share-buttons">Share on social</div>
+              </div>
+              <footer>Copyright</footer>
+            </body></html>"#,
+        );
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(
+            sections[0].text,
+            "Agent guide\n\nAgents run tools in a loop, calling the model after each step."
+        );
+        assert_eq!(sections[0].metadata[SECTION_KEY], "Agent guide");
+        assert_eq!(
+            sections[1].text,
+            "Retries\n\nFailed tool calls are retried, with exponential backoff.\n\nretries:\n  max: 3"
+        );
+        assert_eq!(sections[1].metadata[SECTION_KEY], "Agent guide > Retries");
+        assert_eq!(sections[1].metadata[SECTION_NUMBER_KEY], "1.1");
+        assert_eq!(sections[1].metadata[TITLE_KEY], "Agent guide");
+    }
+
+    #[test]
+    fn test_prefers_main_element() {
+        let sections = load(
+            "<body><div><p>A long paragraph outside the main element, with commas, commas, commas.</p></div>\
+             <main><p>Short main text.</p></main></body>",
+        );
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].text, "Short main text.");
+        assert!(!sections[0].metadata.contains_key(TITLE_KEY));
+    }
+}
@@ -0,0 +1,242 @@
+//! Text extraction from document formats
+//!
+//! A [`DocumentLoader`] turns the raw bytes of a file or download into sections of
+//! plain text. Each section carries the metadata needed to cite it, such as the
+//! `page` it is on or the `section` heading and `section_number` it falls under,
+//! and that metadata is copied to every chunk of the section.
+//!
+//! [`DocumentLoaders`] picks a loader by file extension or MIME type. Plain text
+//! is the fallback, so formats without a loader are read as UTF-8 as before.
+
+mod docx;
+mod html;
+mod pdf;
+
+pub use docx::DocxLoader;
+pub use html::HtmlLoader;
+pub use pdf::PdfLoader;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::errors::AgentError;
+
+/// Metadata key of the 1-based page a section starts on
+pub const PAGE_KEY: &str = "page";
+/// Metadata key of the heading path of a section, such as `Setup > Linux`
+pub const SECTION_KEY: &str = "section";
+/// Metadata key of the outline number of a section, such as `2.1`
+pub const SECTION_NUMBER_KEY: &str = "section_number";
+/// Metadata key of the document title, such as an HTML `<title>`
+pub const TITLE_KEY: &str = "title";
+
+/// A run of text that is cited as one unit
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentSection {
+    pub text: String,
+    pub metadata: HashMap<String, String>,
+}
+
+impl DocumentSection {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    pub fn with_metadata(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+pub trait DocumentLoader: Send + Sync {
+    /// File extensions handled, lowercase and without the dot
+    fn extensions(&self) -> &[&str];
+
+    /// MIME types handled, such as `application/pdf`
+    fn mime_types(&self) -> &[&str];
+
+    /// Extracts the text sections of a document; empty sections are dropped by
+    /// the caller
+    fn load(&self, bytes: &[u8]) -> Result<Vec<DocumentSection>, AgentError>;
+}
+
+/// Reads the bytes as UTF-8, replacing invalid sequences
+#[derive(Debug, Clone, Default)]
+pub struct TextLoader;
+
+impl DocumentLoader for TextLoader {
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["text/plain"]
+    }
+
+    fn load(&self, bytes: &[u8]) -> Result<Vec<DocumentSection>, AgentError> {
+        Ok(vec![DocumentSection::new(String::from_utf8_lossy(bytes))])
+    }
+}
+
+/// The loaders available to a RAG system
+#[derive(Clone)]
+pub struct DocumentLoaders {
+    loaders: Vec<Arc<dyn DocumentLoader>>,
+    fallback: Arc<dyn DocumentLoader>,
+}
+
+impl DocumentLoaders {
+    /// Only the plain text fallback
+    pub fn empty() -> Self {
+        Self {
+            loaders: Vec::new(),
+            fallback: Arc::new(TextLoader),
+        }
+    }
+
+    /// Adds a loader, which takes precedence over those added before it
+    pub fn register(&mut self, loader: Arc<dyn DocumentLoader>) {
+        self.loaders.insert(0, loader);
+    }
+
+    pub fn for_path(&self, path: &Path) -> Arc<dyn DocumentLoader> {
+        path.extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .and_then(|extension| {
+                self.loaders
+                    .iter()
+                    .find(|loader| loader.extensions().contains(&extension.as_str()))
+            })
+            .unwrap_or(&self.fallback)
+            .clone()
+    }
+
+    /// Picks a loader by MIME type, ignoring parameters such as `charset`, and
+    /// falls back to the extension of `source` for generic types
+    pub fn for_content(&self, mime_type: Option<&str>, source: &str) -> Arc<dyn DocumentLoader> {
+        let essence = mime_type
+            .and_then(|mime_type| mime_type.split(';').next())
+            .map(|essence| essence.trim().to_lowercase());
+        if let Some(essence) = essence {
+            if let Some(loader) = self
+                .loaders
+                .iter()
+                .find(|loader| loader.mime_types().contains(&essence.as_str()))
+            {
+                return loader.clone();
+            }
+        }
+        // Query strings and fragments are not part of a URL's file name
+        let path = source.split(['?', '#']).next().unwrap_or(source);
+        self.for_path(Path::new(path))
+    }
+}
+
+impl Default for DocumentLoaders {
+    /// PDF, DOCX and HTML, with plain text for everything else
+    fn default() -> Self {
+        let mut loaders = Self::empty();
+        loaders.register(Arc::new(HtmlLoader::new()));
+        loaders.register(Arc::new(DocxLoader::new()));
+        loaders.register(Arc::new(PdfLoader::new()));
+        loaders
+    }
+}
+
+impl std::fmt::Debug for DocumentLoaders {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DocumentLoaders")
+            .field("loaders", &self.loaders.len())
+            .finish()
+    }
+}
+
+/// Tracks the heading hierarchy of a document to label sections
+#[derive(Debug, Default)]
+pub(crate) struct Outline {
+    /// Title and number of each open heading level
+    headings: Vec<(usize, String)>,
+    counters: Vec<usize>,
+}
+
+impl Outline {
+    /// Opens a heading at `level`, 1 being the highest
+    pub(crate) fn enter(&mut self, level: usize, title: &str) {
+        let level = level.max(1);
+        self.counters.resize(level, 0);
+        self.counters[level - 1] += 1;
+        self.headings.retain(|(open_level, _)| *open_level < level);
+        self.headings.push((level, title.trim().to_string()));
+    }
+
+    /// Adds `section` and `section_number` once a heading has been seen
+    pub(crate) fn label(&self, mut section: DocumentSection) -> DocumentSection {
+        if self.headings.is_empty() {
+            return section;
+        }
+        let titles: Vec<&str> = self.headings.iter().map(|(_, title)| title.as_str()).collect();
+        let number: Vec<String> = self
+            .counters
+            .iter()
+            .skip_while(|count| **count == 0)
+            .map(|count| count.max(&1).to_string())
+            .collect();
+        section.metadata.insert(SECTION_KEY.to_string(), titles.join(" > "));
+        section
+            .metadata
+            .insert(SECTION_NUMBER_KEY.to_string(), number.join("."));
+        section
+    }
+}
+
+/// Collapses runs of whitespace, as browsers do for most text
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loader_selection() {
+        let loaders = DocumentLoaders::default();
+        assert_eq!(loaders.for_path(Path::new("manual.PDF")).extensions(), PdfLoader::new().extensions());
+        assert_eq!(loaders.for_path(Path::new("notes.md")).extensions(), TextLoader.extensions());
+        assert_eq!(
+            loaders
+                .for_content(Some("text/html; charset=utf-8"), "https://example.com/guide")
+                .mime_types(),
+            HtmlLoader::new().mime_types()
+        );
+        // A generic type defers to the extension in the URL
+        assert_eq!(
+            loaders
+                .for_content(Some("application/octet-stream"), "https://example.com/a.docx?dl=1")
+                .extensions(),
+            DocxLoader::new().extensions()
+        );
+    }
+
+    #[test]
+    fn test_outline_numbers_sections() {
+        let mut outline = Outline::default();
+        assert!(outline.label(DocumentSection::new("preface")).metadata.is_empty());
+
+        outline.enter(1, "Setup");
+        outline.enter(2, "Linux");
+        outline.enter(2, "macOS");
+        let section = outline.label(DocumentSection::new("brew install"));
+        assert_eq!(section.metadata[SECTION_KEY], "Setup > macOS");
+        assert_eq!(section.metadata[SECTION_NUMBER_KEY], "1.2");
+
+        outline.enter(1, "Usage");
+        let section = outline.label(DocumentSection::new("run it"));
+        assert_eq!(section.metadata[SECTION_KEY], "Usage");
+        assert_eq!(section.metadata[SECTION_NUMBER_KEY], "2");
+    }
+}
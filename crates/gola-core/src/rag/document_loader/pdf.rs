@@ -0,0 +1,144 @@
+//! PDF text extraction
+//!
+//! Text is extracted page by page from the content streams, so each page becomes
+//! a section with its page number. When the PDF has an outline (bookmarks), each
+//! page is also labelled with the last outline entry that starts on or before it.
+
+use lopdf::Document;
+
+use super::{DocumentLoader, DocumentSection, Outline, PAGE_KEY};
+use crate::errors::AgentError;
+
+#[derive(Debug, Clone, Default)]
+pub struct PdfLoader;
+
+impl PdfLoader {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DocumentLoader for PdfLoader {
+    fn extensions(&self) -> &[&str] {
+        &["pdf"]
+    }
+
+    fn mime_types(&self) -> &[&str] {
+        &["application/pdf"]
+    }
+
+    fn load(&self, bytes: &[u8]) -> Result<Vec<DocumentSection>, AgentError> {
+        let mut document = Document::load_mem(bytes)
+            .map_err(|e| AgentError::RagError(format!("Failed to parse PDF: {}", e)))?;
+        if document.is_encrypted() {
+            // Many PDFs are encrypted only to restrict editing, with an empty user password
+            document.decrypt("").map_err(|e| {
+                AgentError::RagError(format!("PDF is encrypted and cannot be read: {}", e))
+            })?;
+        }
+
+        // Outline entries ordered by the page they start on
+        let mut entries = document.get_toc().map(|toc| toc.toc).unwrap_or_default();
+        entries.sort_by_key(|entry| entry.page);
+        let mut entries = entries.into_iter().peekable();
+        let mut outline = Outline::default();
+
+        let mut sections = Vec::new();
+        for page_number in document.get_pages().into_keys() {
+            while let Some(entry) = entries.next_if(|entry| entry.page <= page_number as usize) {
+                outline.enter(entry.level, &entry.title);
+            }
+
+            // A page with an unreadable font still yields the text of its other fonts
+            let mut text = String::new();
+            for chunk in document.extract_text_chunks(&[page_number]) {
+                match chunk {
+                    Ok(chunk) => text.push_str(&chunk),
+                    Err(e) => log::debug!("Skipping unreadable text on page {}: {}", page_number, e),
+                }
+            }
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let section = DocumentSection::new(text).with_metadata(PAGE_KEY, page_number.to_string());
+            sections.push(outline.label(section));
+        }
+        Ok(sections)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::{Content, Operation};
+    use lopdf::{dictionary, Object, Stream};
+
+    fn pdf_with_pages(pages: &[&str]) -> Vec<u8> {
+        let mut document = Document::with_version("1.5");
+        let pages_id = document.new_object_id();
+        let font_id = document.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Courier",
+        });
+        let resources_id = document.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let kids: Vec<Object> = pages
+            .iter()
+            .map(|text| {
+                let content = Content {
+                    operations: vec![
+                        Operation::new("BT", vec![]),
+                        Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                        Operation::new("Td", vec![100.into(), 600.into()]),
+                        Operation::new("Tj", vec![Object::string_literal(*text)]),
+                        Operation::new("ET", vec![]),
+                    ],
+                };
+                let content_id = document.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+                document
+                    .add_object(dictionary! {
+                        "Type" => "Page",
+                        "Parent" => pages_id,
+                        "Contents" => content_id,
+                    })
+                    .into()
+            })
+            .collect();
+        document.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => kids.len() as i64,
+                "Kids" => kids,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = document.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        document.trailer.set("Root", catalog_id);
+
+        let mut bytes = Vec::new();
+        document.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_pages_become_sections() {
+        let bytes = pdf_with_pages(&["Installing the agent", "", "Configuring retries"]);
+        let sections = PdfLoader::new().load(&bytes).unwrap();
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].text, "Installing the agent");
+        assert_eq!(sections[0].metadata[PAGE_KEY], "1");
+        assert_eq!(sections[1].text, "Configuring retries");
+        assert_eq!(sections[1].metadata[PAGE_KEY], "3");
+
+        assert!(PdfLoader::new().load(b"not a pdf").is_err());
+    }
+}
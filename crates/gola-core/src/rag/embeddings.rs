@@ -1,4 +1,5 @@
 use crate::errors::AgentError;
+use crate::llm::providers::azure_openai::{self, AzureCredential};
 use crate::llm::LLM;
 use crate::rag::cache::{EmbeddingCache, NoOpEmbeddingCache};
 use async_trait::async_trait;
@@ -336,6 +337,11 @@ pub enum EmbeddingProvider {
     OpenAI,
     Cohere,
     HuggingFace,
+    /// Azure OpenAI deployment; `api_base_url` is the resource endpoint
+    AzureOpenAI {
+        deployment: String,
+        api_version: String,
+    },
     Custom,
 }
 
@@ -480,6 +486,62 @@ mod tests {
         assert_eq!(embeddings1[1], embeddings2[0]); // Text 2
         assert_eq!(embeddings1[2], embeddings2[1]); // Text 3
     }
+
+    #[tokio::test]
+    async fn test_azure_openai_embeddings_use_deployment_url() {
+        use axum::{
+            extract::{Path, Query},
+            http::HeaderMap,
+            routing::post,
+            Json, Router,
+        };
+        use std::collections::HashMap;
+
+        let app = Router::new().route(
+            "/openai/deployments/{deployment}/embeddings",
+            post(
+                |Path(deployment): Path<String>,
+                 Query(query): Query<HashMap<String, String>>,
+                 headers: HeaderMap,
+                 Json(body): Json<serde_json::Value>| async move {
+                    assert_eq!(deployment, "ada-prod");
+                    assert_eq!(query["api-version"], "2024-10-21");
+                    assert_eq!(headers["api-key"], "azure-key");
+                    assert!(body.get("model").is_none());
+                    // Out of order on purpose; results are matched back by index
+                    Json(json!({ "data": [
+                        { "embedding": [0.0, 1.0], "index": 1 },
+                        { "embedding": [1.0, 0.0], "index": 0 }
+                    ]}))
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        let client = RestEmbeddingClient::new(RestEmbeddingConfig {
+            api_base_url: endpoint,
+            api_key: Some("azure-key".to_string()),
+            model_name: "text-embedding-3-small".to_string(),
+            embedding_dimension: 2,
+            timeout_seconds: 5,
+            max_batch_size: 16,
+            provider: EmbeddingProvider::AzureOpenAI {
+                deployment: "ada-prod".to_string(),
+                api_version: "2024-10-21".to_string(),
+            },
+        })
+        .unwrap();
+
+        let embeddings = client
+            .generate_embeddings(&["first".to_string(), "second".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(embeddings, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+    }
 }
 
 pub struct RestEmbeddingClient {
@@ -490,7 +552,7 @@ pub struct RestEmbeddingClient {
 impl RestEmbeddingClient {
     /// Create a new REST embedding client with the given configuration
     pub fn new(config: RestEmbeddingConfig) -> Result<Self, AgentError> {
-        let client = Client::builder()
+        let client = crate::http::builder()
             .timeout(Duration::from_secs(config.timeout_seconds))
             .build()
             .map_err(|e| AgentError::RagError(format!("Failed to create HTTP client: {}", e)))?;
@@ -699,6 +761,57 @@ impl RestEmbeddingClient {
         Ok(embeddings)
     }
 
+    async fn call_azure_openai_api(
+        &self,
+        texts: &[String],
+        deployment: &str,
+        api_version: &str,
+    ) -> Result<Vec<Vec<f32>>, AgentError> {
+        let credential = AzureCredential::resolve(self.config.api_key.as_deref(), None)
+            .map_err(|e| AgentError::RagError(e.to_string()))?;
+        let url = azure_openai::deployment_url(
+            &self.config.api_base_url,
+            deployment,
+            "embeddings",
+            api_version,
+        );
+
+        // The deployment selects the model, so no `model` field is sent
+        let payload = json!({ "input": texts });
+
+        let response = credential
+            .apply(self.client.post(&url))
+            .await
+            .map_err(|e| AgentError::RagError(e.to_string()))?
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| AgentError::RagError(format!("Azure OpenAI API request failed: {}", e)))?;
+
+        let status = response.status();
+        let response_text = response
+            .text()
+            .await
+            .map_err(|e| AgentError::RagError(format!("Failed to read response: {}", e)))?;
+
+        if !status.is_success() {
+            return Err(AgentError::RagError(azure_openai::describe_error(
+                status,
+                &response_text,
+            )));
+        }
+
+        let response_data: OpenAIEmbeddingResponse = serde_json::from_str(&response_text)
+            .map_err(|e| {
+                AgentError::RagError(format!("Failed to parse Azure OpenAI response: {}", e))
+            })?;
+
+        let mut items = response_data.data;
+        items.sort_by_key(|item| item.index);
+        Ok(items.into_iter().map(|item| item.embedding).collect())
+    }
+
     async fn call_custom_api(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, AgentError> {
         let url = format!("{}/embeddings", self.config.api_base_url);
 
@@ -791,10 +904,17 @@ impl EmbeddingGenerator for RestEmbeddingClient {
                 batch.len()
             );
 
-            let batch_embeddings = match self.config.provider {
+            let batch_embeddings = match &self.config.provider {
                 EmbeddingProvider::OpenAI => self.call_openai_api(batch).await?,
                 EmbeddingProvider::Cohere => self.call_cohere_api(batch).await?,
                 EmbeddingProvider::HuggingFace => self.call_huggingface_api(batch).await?,
+                EmbeddingProvider::AzureOpenAI {
+                    deployment,
+                    api_version,
+                } => {
+                    self.call_azure_openai_api(batch, deployment, api_version)
+                        .await?
+                }
                 EmbeddingProvider::Custom => self.call_custom_api(batch).await?,
             };
 
@@ -825,7 +945,6 @@ struct OpenAIEmbeddingResponse {
 }
 
 #[derive(Debug, serde::Deserialize)]
-#[allow(dead_code)]
 struct OpenAIEmbeddingItem {
     embedding: Vec<f32>,
     index: usize,
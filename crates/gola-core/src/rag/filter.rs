@@ -0,0 +1,171 @@
+//! Metadata filters for scoping retrieval
+//!
+//! A filter maps field names to conditions, and a document matches when every
+//! condition holds. The field `source` refers to the document's source, any other
+//! field to a metadata key. In JSON a condition is a string for equality, a list of
+//! strings for membership, or `{"prefix": "..."}`:
+//!
+//! ```json
+//! {"source": {"prefix": "docs/api/"}, "version": ["v2", "v3"], "lang": "en"}
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use crate::errors::AgentError;
+use crate::rag::RagDocument;
+
+/// Field name that refers to [`RagDocument::source`] rather than to metadata
+pub const SOURCE_FIELD: &str = "source";
+
+/// A condition on one field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FieldCondition {
+    Equals(String),
+    OneOf(Vec<String>),
+    Prefix { prefix: String },
+}
+
+impl FieldCondition {
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            FieldCondition::Equals(expected) => value == expected,
+            FieldCondition::OneOf(options) => options.iter().any(|option| option == value),
+            FieldCondition::Prefix { prefix } => value.starts_with(prefix.as_str()),
+        }
+    }
+}
+
+/// Conditions a document must all meet to be retrieved
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MetadataFilter {
+    conditions: BTreeMap<String, FieldCondition>,
+}
+
+impl MetadataFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn eq(mut self, field: impl Into<String>, value: impl Into<String>) -> Self {
+        self.conditions.insert(field.into(), FieldCondition::Equals(value.into()));
+        self
+    }
+
+    pub fn one_of<I, S>(mut self, field: impl Into<String>, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        self.conditions.insert(field.into(), FieldCondition::OneOf(values));
+        self
+    }
+
+    pub fn prefix(mut self, field: impl Into<String>, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        self.conditions.insert(field.into(), FieldCondition::Prefix { prefix });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// Whether `document` meets every condition. A missing metadata key never matches.
+    pub fn matches(&self, document: &RagDocument) -> bool {
+        self.conditions.iter().all(|(field, condition)| {
+            let value = if field == SOURCE_FIELD {
+                Some(&document.source)
+            } else {
+                document.metadata.get(field)
+            };
+            value.is_some_and(|value| condition.matches(value))
+        })
+    }
+
+    /// Reads the optional `filter` argument of a search tool call
+    pub fn from_arguments(arguments: &Value) -> Result<Self, AgentError> {
+        match arguments.get("filter") {
+            None | Some(Value::Null) => Ok(Self::default()),
+            Some(filter) => serde_json::from_value(filter.clone()).map_err(|_| {
+                AgentError::ParsingError(format!(
+                    "Invalid filter {}: each field needs a string, a list of strings or {{\"prefix\": string}}",
+                    filter
+                ))
+            }),
+        }
+    }
+
+    /// JSON schema of the `filter` argument, for tool definitions
+    pub fn json_schema() -> Value {
+        json!({
+            "type": "object",
+            "description": "Only search documents whose fields match. Keys are metadata keys, or \"source\" for the document path or URL. A value is a string for an exact match, a list of strings for any of them, or {\"prefix\": \"...\"} for a prefix match. All keys must match.",
+            "additionalProperties": {
+                "anyOf": [
+                    { "type": "string" },
+                    { "type": "array", "items": { "type": "string" } },
+                    {
+                        "type": "object",
+                        "properties": { "prefix": { "type": "string" } },
+                        "required": ["prefix"]
+                    }
+                ]
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn document(source: &str, metadata: &[(&str, &str)]) -> RagDocument {
+        let metadata: HashMap<String, String> = metadata
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        RagDocument::new("content".to_string(), source.to_string()).with_metadata(metadata)
+    }
+
+    #[test]
+    fn test_conditions_on_source_and_metadata() {
+        let api_v2 = document("docs/api/auth.md", &[("version", "v2"), ("lang", "en")]);
+        let guide_v1 = document("docs/guide/intro.md", &[("version", "v1")]);
+
+        let filter = MetadataFilter::new()
+            .prefix("source", "docs/api/")
+            .one_of("version", ["v2", "v3"]);
+        assert!(filter.matches(&api_v2));
+        assert!(!filter.matches(&guide_v1));
+
+        // Missing keys do not match
+        assert!(!MetadataFilter::new().eq("lang", "en").matches(&guide_v1));
+        assert!(MetadataFilter::new().matches(&guide_v1));
+    }
+
+    #[test]
+    fn test_filter_from_tool_arguments() {
+        let filter = MetadataFilter::from_arguments(&json!({
+            "query": "login",
+            "filter": { "source": { "prefix": "docs/api/" }, "version": ["v2"], "lang": "en" }
+        }))
+        .unwrap();
+        assert_eq!(
+            filter,
+            MetadataFilter::new()
+                .prefix("source", "docs/api/")
+                .one_of("version", ["v2"])
+                .eq("lang", "en")
+        );
+
+        assert!(MetadataFilter::from_arguments(&json!({ "query": "login" })).unwrap().is_empty());
+        assert!(MetadataFilter::from_arguments(&json!({ "filter": { "version": 2 } })).is_err());
+        assert!(MetadataFilter::from_arguments(&json!({ "filter": { "source": { "suffix": ".md" } } })).is_err());
+    }
+}
@@ -0,0 +1,796 @@
+//! Approximate nearest neighbour search with HNSW
+//!
+//! A hierarchical navigable small world graph: every chunk is a node linked to
+//! its nearest neighbours on layer 0, and a shrinking random subset of nodes also
+//! sits on higher layers with longer links. A search descends greedily from the
+//! top layer and explores `ef_search` candidates on layer 0, which visits a small
+//! fraction of the corpus instead of scanning all of it.
+//!
+//! Inserts and deletes update the graph in place. A deleted node's neighbours are
+//! relinked among each other so the graph stays connected. Distances are cosine
+//! distances, so scores match [`InMemoryVectorStore`](super::InMemoryVectorStore).
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::errors::AgentError;
+use crate::rag::embedding_file::{self, EmbeddingFile, EmbeddingFileHeader, Quantization};
+use crate::rag::embeddings::euclidean_distance;
+use crate::rag::vector_store::{SearchResult, VectorStore};
+
+/// Highest layer a node can be placed on
+const MAX_LEVEL: usize = 16;
+const RNG_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HnswConfig {
+    /// Links per node on the upper layers, twice as many on layer 0
+    #[serde(default = "default_m")]
+    pub m: usize,
+    /// Candidates considered when linking a new node; higher builds a better graph
+    #[serde(default = "default_ef_construction")]
+    pub ef_construction: usize,
+    /// Candidates considered per search; higher improves recall at some speed cost
+    #[serde(default = "default_ef_search")]
+    pub ef_search: usize,
+}
+
+fn default_m() -> usize {
+    16
+}
+
+fn default_ef_construction() -> usize {
+    200
+}
+
+fn default_ef_search() -> usize {
+    64
+}
+
+impl Default for HnswConfig {
+    fn default() -> Self {
+        Self {
+            m: default_m(),
+            ef_construction: default_ef_construction(),
+            ef_search: default_ef_search(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    id: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    embedding: Vec<f32>,
+    norm: f32,
+    /// Neighbour ids per layer, from layer 0 up to the node's level
+    layers: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HnswVectorStore {
+    config: HnswConfig,
+    nodes: HashMap<usize, Node>,
+    entry_point: Option<usize>,
+    embedding_dimension: Option<usize>,
+    rng_state: u64,
+    #[serde(skip)]
+    embedding_model: String,
+    #[serde(skip)]
+    quantization: Quantization,
+    #[serde(skip)]
+    file_path: Option<PathBuf>,
+}
+
+/// The graph of a saved index; the embeddings are stored in the matrix, in the
+/// order of `document_ids`
+#[derive(Serialize, Deserialize)]
+struct StoredGraph {
+    config: HnswConfig,
+    entry_point: Option<usize>,
+    rng_state: u64,
+    document_ids: Vec<usize>,
+    layers: Vec<Vec<Vec<usize>>>,
+}
+
+impl HnswVectorStore {
+    pub fn new(config: HnswConfig) -> Self {
+        Self {
+            config: HnswConfig {
+                m: config.m.max(2),
+                ef_construction: config.ef_construction.max(1),
+                ef_search: config.ef_search.max(1),
+            },
+            nodes: HashMap::new(),
+            entry_point: None,
+            embedding_dimension: None,
+            rng_state: RNG_SEED,
+            embedding_model: String::new(),
+            quantization: Quantization::default(),
+            file_path: None,
+        }
+    }
+
+    /// Saves the index to `path` after every change
+    pub fn with_file_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.file_path = Some(path.into());
+        self
+    }
+
+    pub fn config(&self) -> &HnswConfig {
+        &self.config
+    }
+
+    /// Changes the search breadth; the graph itself is unaffected
+    pub fn set_ef_search(&mut self, ef_search: usize) {
+        self.config.ef_search = ef_search.max(1);
+    }
+
+    /// Records the model in the file header so a mismatch can be detected on load
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
+    /// Sets how embeddings are written by later saves
+    pub fn with_quantization(mut self, quantization: Quantization) -> Self {
+        self.quantization = quantization;
+        self
+    }
+
+    /// Model recorded in the loaded file, empty when unknown
+    pub fn embedding_model(&self) -> &str {
+        &self.embedding_model
+    }
+
+    pub async fn save(&self) -> Result<(), AgentError> {
+        if let Some(path) = &self.file_path {
+            let mut document_ids: Vec<usize> = self.nodes.keys().copied().collect();
+            document_ids.sort_unstable();
+            let rows: Vec<&[f32]> = document_ids
+                .iter()
+                .map(|id| self.nodes[id].embedding.as_slice())
+                .collect();
+            let graph = StoredGraph {
+                config: self.config.clone(),
+                entry_point: self.entry_point,
+                rng_state: self.rng_state,
+                layers: document_ids.iter().map(|id| self.nodes[id].layers.clone()).collect(),
+                document_ids,
+            };
+            let header = EmbeddingFileHeader {
+                embedding_model: self.embedding_model.clone(),
+                dimension: self.embedding_dimension.unwrap_or(0),
+                quantization: self.quantization,
+            };
+            let bytes = embedding_file::encode(&header, &rows, &graph)?;
+            embedding_file::write(path, bytes).await?;
+        }
+        Ok(())
+    }
+
+    /// Loads an index saved by [`save`](Self::save), rewriting JSON indexes from
+    /// earlier versions in the binary format
+    pub async fn load<P: Into<PathBuf>>(path: P) -> Result<Self, AgentError> {
+        let path = path.into();
+        let bytes = embedding_file::read(&path).await?;
+
+        if !embedding_file::is_embedding_file(&bytes) {
+            let mut store: HnswVectorStore = serde_json::from_slice(&bytes).map_err(|e| {
+                AgentError::RagError(format!("Failed to deserialize HNSW index: {}", e))
+            })?;
+            log::info!(
+                "Migrating HNSW index {} from JSON to the binary format",
+                path.display()
+            );
+            store.file_path = Some(path);
+            store.save().await?;
+            return Ok(store);
+        }
+
+        let file: EmbeddingFile<StoredGraph> = embedding_file::decode(&bytes)?;
+        let graph = file.metadata;
+        if graph.document_ids.len() != file.embeddings.len()
+            || graph.layers.len() != file.embeddings.len()
+        {
+            return Err(AgentError::RagError(format!(
+                "HNSW index file lists {} nodes for {} embeddings",
+                graph.document_ids.len(),
+                file.embeddings.len()
+            )));
+        }
+
+        let nodes = graph
+            .document_ids
+            .into_iter()
+            .zip(file.embeddings)
+            .zip(graph.layers)
+            .map(|((id, embedding), layers)| {
+                // Recomputed, since quantized embeddings differ slightly from the originals
+                let norm = vector_norm(&embedding);
+                (id, Node { embedding, norm, layers })
+            })
+            .collect();
+
+        Ok(Self {
+            config: graph.config,
+            nodes,
+            entry_point: graph.entry_point,
+            embedding_dimension: (file.header.dimension > 0).then_some(file.header.dimension),
+            rng_state: graph.rng_state,
+            embedding_model: file.header.embedding_model,
+            quantization: file.header.quantization,
+            file_path: Some(path),
+        })
+    }
+
+    fn check_dimension(&mut self, embedding: &[f32]) -> Result<(), AgentError> {
+        match self.embedding_dimension {
+            Some(expected_dim) if embedding.len() != expected_dim => {
+                Err(AgentError::RagError(format!(
+                    "Embedding dimension mismatch: expected {}, got {}",
+                    expected_dim,
+                    embedding.len()
+                )))
+            }
+            Some(_) => Ok(()),
+            None => {
+                self.embedding_dimension = Some(embedding.len());
+                Ok(())
+            }
+        }
+    }
+
+    /// Draws a level with the usual exponentially decaying distribution
+    fn random_level(&mut self) -> usize {
+        // splitmix64, so that builds are reproducible
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        let uniform = ((z >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        let level = -uniform.ln() / (self.config.m as f64).ln();
+        (level as usize).min(MAX_LEVEL)
+    }
+
+    fn max_neighbors(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.config.m * 2
+        } else {
+            self.config.m
+        }
+    }
+
+    fn distance_to(&self, query: &[f32], query_norm: f32, id: usize) -> Option<f32> {
+        let node = self.nodes.get(&id)?;
+        Some(cosine_distance(query, query_norm, &node.embedding, node.norm))
+    }
+
+    fn node_distance(&self, a: usize, b: usize) -> f32 {
+        match (self.nodes.get(&a), self.nodes.get(&b)) {
+            (Some(a), Some(b)) => cosine_distance(&a.embedding, a.norm, &b.embedding, b.norm),
+            _ => f32::INFINITY,
+        }
+    }
+
+    /// Best-first search on one layer, returning up to `ef` nodes closest first
+    fn search_layer(
+        &self,
+        query: &[f32],
+        query_norm: f32,
+        entry_points: &[Candidate],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let mut visited: HashSet<usize> = entry_points.iter().map(|c| c.id).collect();
+        let mut candidates: BinaryHeap<Reverse<Candidate>> =
+            entry_points.iter().copied().map(Reverse).collect();
+        let mut nearest: BinaryHeap<Candidate> = entry_points.iter().copied().collect();
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            let furthest = nearest.peek().map_or(f32::INFINITY, |c| c.distance);
+            if current.distance > furthest && nearest.len() >= ef {
+                break;
+            }
+            let Some(neighbors) = self.nodes.get(&current.id).and_then(|n| n.layers.get(layer)) else {
+                continue;
+            };
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let Some(distance) = self.distance_to(query, query_norm, neighbor) else {
+                    continue;
+                };
+                let furthest = nearest.peek().map_or(f32::INFINITY, |c| c.distance);
+                if nearest.len() < ef || distance < furthest {
+                    let candidate = Candidate { distance, id: neighbor };
+                    candidates.push(Reverse(candidate));
+                    nearest.push(candidate);
+                    if nearest.len() > ef {
+                        nearest.pop();
+                    }
+                }
+            }
+        }
+
+        nearest.into_sorted_vec()
+    }
+
+    /// Greedy descent from the entry point to layer `target`
+    fn descend(&self, query: &[f32], query_norm: f32, target: usize) -> Vec<Candidate> {
+        let Some(entry) = self.entry_point else {
+            return Vec::new();
+        };
+        let Some(distance) = self.distance_to(query, query_norm, entry) else {
+            return Vec::new();
+        };
+        let top = self.nodes[&entry].layers.len() - 1;
+
+        let mut entry_points = vec![Candidate { distance, id: entry }];
+        for layer in (target + 1..=top).rev() {
+            let closest = self.search_layer(query, query_norm, &entry_points, 1, layer);
+            if !closest.is_empty() {
+                entry_points = closest;
+            }
+        }
+        entry_points
+    }
+
+    /// Picks up to `max` neighbours from candidates sorted by distance, preferring
+    /// ones that are not closer to an already picked neighbour, so that links
+    /// point in different directions
+    fn select_neighbors(&self, candidates: &[Candidate], max: usize) -> Vec<usize> {
+        let mut selected: Vec<usize> = Vec::with_capacity(max);
+        let mut skipped = Vec::new();
+        for candidate in candidates {
+            if selected.len() >= max {
+                break;
+            }
+            if selected
+                .iter()
+                .all(|&picked| self.node_distance(candidate.id, picked) > candidate.distance)
+            {
+                selected.push(candidate.id);
+            } else {
+                skipped.push(candidate.id);
+            }
+        }
+        // Fill up with the closest of the rest to keep the graph well connected
+        for id in skipped {
+            if selected.len() >= max {
+                break;
+            }
+            selected.push(id);
+        }
+        selected
+    }
+
+    /// Re-selects the links of `id` on `layer` from its current links and `extra`
+    fn relink(&mut self, id: usize, layer: usize, extra: &[usize]) {
+        let Some(node) = self.nodes.get(&id) else {
+            return;
+        };
+        let Some(current) = node.layers.get(layer) else {
+            return;
+        };
+
+        let mut seen = HashSet::new();
+        let mut candidates: Vec<Candidate> = current
+            .iter()
+            .chain(extra)
+            .filter(|&&other| other != id && seen.insert(other))
+            .filter_map(|&other| {
+                self.distance_to(&node.embedding, node.norm, other)
+                    .map(|distance| Candidate { distance, id: other })
+            })
+            .collect();
+        candidates.sort();
+
+        let links = self.select_neighbors(&candidates, self.max_neighbors(layer));
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.layers[layer] = links;
+        }
+    }
+
+    fn insert(&mut self, id: usize, embedding: Vec<f32>) {
+        self.remove(id);
+
+        let norm = vector_norm(&embedding);
+        let level = self.random_level();
+        let node = Node {
+            embedding: embedding.clone(),
+            norm,
+            layers: vec![Vec::new(); level + 1],
+        };
+
+        let Some(entry) = self.entry_point else {
+            self.nodes.insert(id, node);
+            self.entry_point = Some(id);
+            return;
+        };
+        let top = self.nodes[&entry].layers.len() - 1;
+        let mut entry_points = self.descend(&embedding, norm, level.min(top));
+        self.nodes.insert(id, node);
+
+        for layer in (0..=level.min(top)).rev() {
+            let nearest = self.search_layer(
+                &embedding,
+                norm,
+                &entry_points,
+                self.config.ef_construction,
+                layer,
+            );
+            let max = self.max_neighbors(layer);
+            let links = self.select_neighbors(&nearest, max);
+            for &neighbor in &links {
+                let Some(neighbor_links) = self
+                    .nodes
+                    .get_mut(&neighbor)
+                    .and_then(|n| n.layers.get_mut(layer))
+                else {
+                    continue;
+                };
+                neighbor_links.push(id);
+                if neighbor_links.len() > max {
+                    self.relink(neighbor, layer, &[]);
+                }
+            }
+            if let Some(node) = self.nodes.get_mut(&id) {
+                node.layers[layer] = links;
+            }
+            if !nearest.is_empty() {
+                entry_points = nearest;
+            }
+        }
+
+        if level > top {
+            self.entry_point = Some(id);
+        }
+    }
+
+    fn remove(&mut self, id: usize) -> bool {
+        let Some(node) = self.nodes.remove(&id) else {
+            return false;
+        };
+
+        // Links are not symmetric, so nodes the removed one never linked back to may
+        // still point at it; they are repaired along with its own neighbours
+        let mut affected: Vec<(usize, usize)> = node
+            .layers
+            .iter()
+            .enumerate()
+            .flat_map(|(layer, neighbors)| neighbors.iter().map(move |&neighbor| (neighbor, layer)))
+            .collect();
+        for (&other, other_node) in &self.nodes {
+            for (layer, links) in other_node.layers.iter().enumerate() {
+                if links.contains(&id) {
+                    affected.push((other, layer));
+                }
+            }
+        }
+        affected.sort_unstable();
+        affected.dedup();
+
+        for (neighbor, layer) in affected {
+            if let Some(links) = self
+                .nodes
+                .get_mut(&neighbor)
+                .and_then(|n| n.layers.get_mut(layer))
+            {
+                links.retain(|&link| link != id);
+            }
+            let extra = node.layers.get(layer).map_or(&[][..], Vec::as_slice);
+            self.relink(neighbor, layer, extra);
+        }
+
+        if self.entry_point == Some(id) {
+            self.entry_point = self
+                .nodes
+                .iter()
+                .max_by_key(|(id, node)| (node.layers.len(), Reverse(**id)))
+                .map(|(id, _)| *id);
+        }
+        true
+    }
+}
+
+impl Default for HnswVectorStore {
+    fn default() -> Self {
+        Self::new(HnswConfig::default())
+    }
+}
+
+fn vector_norm(vector: &[f32]) -> f32 {
+    vector.iter().map(|x| x * x).sum::<f32>().sqrt()
+}
+
+fn cosine_distance(a: &[f32], a_norm: f32, b: &[f32], b_norm: f32) -> f32 {
+    if a_norm == 0.0 || b_norm == 0.0 {
+        return 1.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    1.0 - dot / (a_norm * b_norm)
+}
+
+#[async_trait]
+impl VectorStore for HnswVectorStore {
+    async fn add_document(
+        &mut self,
+        document_id: usize,
+        embedding: Vec<f32>,
+    ) -> Result<(), AgentError> {
+        self.check_dimension(&embedding)?;
+        self.insert(document_id, embedding);
+        self.save().await
+    }
+
+    async fn add_documents(&mut self, documents: Vec<(usize, Vec<f32>)>) -> Result<(), AgentError> {
+        for (document_id, embedding) in documents {
+            self.check_dimension(&embedding)?;
+            self.insert(document_id, embedding);
+        }
+        self.save().await
+    }
+
+    async fn search(
+        &self,
+        query_embedding: &[f32],
+        top_k: usize,
+    ) -> Result<Vec<SearchResult>, AgentError> {
+        self.search_filtered(query_embedding, top_k, &|_| true).await
+    }
+
+    async fn search_filtered(
+        &self,
+        query_embedding: &[f32],
+        top_k: usize,
+        filter: &(dyn Fn(usize) -> bool + Send + Sync),
+    ) -> Result<Vec<SearchResult>, AgentError> {
+        if self.nodes.is_empty() || top_k == 0 {
+            return Ok(Vec::new());
+        }
+
+        if let Some(expected_dim) = self.embedding_dimension {
+            if query_embedding.len() != expected_dim {
+                return Err(AgentError::RagError(format!(
+                    "Query embedding dimension mismatch: expected {}, got {}",
+                    expected_dim,
+                    query_embedding.len()
+                )));
+            }
+        }
+
+        let query_norm = vector_norm(query_embedding);
+        let entry_points = self.descend(query_embedding, query_norm, 0);
+
+        // A selective filter can reject most of the nearest nodes, so widen the
+        // search until enough pass or the whole graph has been explored
+        let mut ef = self.config.ef_search.max(top_k);
+        let matches = loop {
+            let nearest = self.search_layer(query_embedding, query_norm, &entry_points, ef, 0);
+            let matches: Vec<Candidate> = nearest
+                .into_iter()
+                .filter(|candidate| filter(candidate.id))
+                .take(top_k)
+                .collect();
+            if matches.len() >= top_k || ef >= self.nodes.len() {
+                break matches;
+            }
+            ef = ef.saturating_mul(4);
+        };
+
+        Ok(matches
+            .into_iter()
+            .map(|candidate| {
+                let embedding = &self.nodes[&candidate.id].embedding;
+                SearchResult::new(
+                    candidate.id,
+                    1.0 - candidate.distance,
+                    euclidean_distance(query_embedding, embedding),
+                )
+            })
+            .collect())
+    }
+
+    async fn remove_document(&mut self, document_id: usize) -> Result<(), AgentError> {
+        if self.remove(document_id) {
+            self.save().await?;
+        }
+        Ok(())
+    }
+
+    async fn remove_documents(&mut self, document_ids: &[usize]) -> Result<(), AgentError> {
+        let mut removed = false;
+        for document_id in document_ids {
+            removed |= self.remove(*document_id);
+        }
+        if removed {
+            self.save().await?;
+        }
+        Ok(())
+    }
+
+    async fn clear(&mut self) -> Result<(), AgentError> {
+        self.nodes.clear();
+        self.entry_point = None;
+        self.embedding_dimension = None;
+        self.rng_state = RNG_SEED;
+        self.save().await
+    }
+
+    fn document_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn contains(&self, document_id: usize) -> bool {
+        self.nodes.contains_key(&document_id)
+    }
+
+    fn embedding_dimension(&self) -> Option<usize> {
+        self.embedding_dimension
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rag::vector_store::InMemoryVectorStore;
+
+    /// Reproducible pseudo-random vectors
+    fn random_vectors(count: usize, dimension: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut state = seed;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+        };
+        (0..count)
+            .map(|_| (0..dimension).map(|_| next()).collect())
+            .collect()
+    }
+
+    async fn recall(hnsw: &HnswVectorStore, exact: &InMemoryVectorStore, queries: &[Vec<f32>], k: usize) -> f32 {
+        let mut found = 0;
+        for query in queries {
+            let expected: HashSet<usize> = exact
+                .search(query, k)
+                .await
+                .unwrap()
+                .iter()
+                .map(|r| r.document_id)
+                .collect();
+            found += hnsw
+                .search(query, k)
+                .await
+                .unwrap()
+                .iter()
+                .filter(|r| expected.contains(&r.document_id))
+                .count();
+        }
+        found as f32 / (queries.len() * k) as f32
+    }
+
+    async fn build(vectors: &[Vec<f32>]) -> (HnswVectorStore, InMemoryVectorStore) {
+        let config = HnswConfig {
+            m: 12,
+            ef_construction: 48,
+            ef_search: 64,
+        };
+        let mut hnsw = HnswVectorStore::new(config);
+        let mut exact = InMemoryVectorStore::new();
+        let documents: Vec<(usize, Vec<f32>)> = vectors.iter().cloned().enumerate().collect();
+        hnsw.add_documents(documents.clone()).await.unwrap();
+        exact.add_documents(documents).await.unwrap();
+        (hnsw, exact)
+    }
+
+    #[tokio::test]
+    async fn test_recall_against_brute_force() {
+        let vectors = random_vectors(1500, 16, 7);
+        let queries = random_vectors(40, 16, 99);
+        let (hnsw, exact) = build(&vectors).await;
+
+        assert_eq!(hnsw.document_count(), 1500);
+        let recall = recall(&hnsw, &exact, &queries, 10).await;
+        assert!(recall >= 0.95, "recall@10 was {}", recall);
+
+        // Scores are cosine similarities, as in the brute-force store
+        let best = &hnsw.search(&vectors[3], 1).await.unwrap()[0];
+        assert_eq!(best.document_id, 3);
+        assert!((best.score - 1.0).abs() < 1e-5);
+    }
+
+    #[tokio::test]
+    async fn test_deletes_keep_the_graph_searchable() {
+        let vectors = random_vectors(800, 16, 11);
+        let queries = random_vectors(30, 16, 5);
+        let (mut hnsw, mut exact) = build(&vectors).await;
+
+        let entry = hnsw.entry_point.unwrap();
+        hnsw.remove_document(entry).await.unwrap();
+        exact.remove_document(entry).await.unwrap();
+        for id in (0..800).filter(|id| id % 3 == 0) {
+            hnsw.remove_document(id).await.unwrap();
+            exact.remove_document(id).await.unwrap();
+        }
+        assert_eq!(hnsw.document_count(), exact.document_count());
+
+        let results = hnsw.search(&vectors[0], 20).await.unwrap();
+        assert!(results.iter().all(|r| r.document_id % 3 != 0 && r.document_id != entry));
+        // No links are left pointing at removed nodes
+        assert!(hnsw
+            .nodes
+            .values()
+            .flat_map(|node| node.layers.iter().flatten())
+            .all(|link| hnsw.nodes.contains_key(link)));
+        let recall = recall(&hnsw, &exact, &queries, 10).await;
+        assert!(recall >= 0.9, "recall@10 after deletes was {}", recall);
+
+        // Re-inserting an id replaces its vector
+        hnsw.add_document(1, vectors[0].clone()).await.unwrap();
+        assert_eq!(hnsw.search(&vectors[0], 1).await.unwrap()[0].document_id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_filtered_search_widens_until_enough_match() {
+        let vectors = random_vectors(600, 8, 3);
+        let (hnsw, _) = build(&vectors).await;
+
+        let rare = |id: usize| id.is_multiple_of(100);
+        let results = hnsw.search_filtered(&vectors[1], 5, &rare).await.unwrap();
+        assert_eq!(results.len(), 5);
+        assert!(results.iter().all(|r| r.document_id.is_multiple_of(100)));
+    }
+
+    #[tokio::test]
+    async fn test_persistence_round_trip() {
+        let vectors = random_vectors(200, 8, 21);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.bin");
+
+        let mut hnsw = HnswVectorStore::default().with_file_path(&path);
+        let documents: Vec<(usize, Vec<f32>)> = vectors.iter().cloned().enumerate().collect();
+        hnsw.add_documents(documents).await.unwrap();
+        hnsw.remove_document(5).await.unwrap();
+
+        let loaded = HnswVectorStore::load(&path).await.unwrap();
+        assert_eq!(loaded.document_count(), 199);
+        assert_eq!(loaded.embedding_dimension(), Some(8));
+        let before = hnsw.search(&vectors[9], 5).await.unwrap();
+        let after = loaded.search(&vectors[9], 5).await.unwrap();
+        assert_eq!(
+            before.iter().map(|r| r.document_id).collect::<Vec<_>>(),
+            after.iter().map(|r| r.document_id).collect::<Vec<_>>()
+        );
+
+        let wrong_dimension = vec![0.0; 3];
+        assert!(loaded.search(&wrong_dimension, 1).await.is_err());
+    }
+}
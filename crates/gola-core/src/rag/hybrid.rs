@@ -0,0 +1,100 @@
+//! Retrieval modes and rank fusion for hybrid search
+//!
+//! Hybrid retrieval runs the vector search and the BM25 search separately and
+//! merges the two rankings with reciprocal rank fusion: a document at rank `r` in a
+//! list gets `weight / (rrf_k + r)` from it, and documents are ordered by the sum.
+//! Only ranks matter, so cosine similarities and BM25 scores need no common scale.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How `RagSystem` finds candidate chunks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetrievalMode {
+    /// Embedding similarity only
+    #[default]
+    Vector,
+    /// BM25 term matching only
+    Keyword,
+    /// Both, merged with reciprocal rank fusion
+    Hybrid,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HybridSearchConfig {
+    #[serde(default = "default_weight")]
+    pub vector_weight: f32,
+    #[serde(default = "default_weight")]
+    pub keyword_weight: f32,
+    /// Damps the advantage of top ranks; 60 is the usual choice
+    #[serde(default = "default_rrf_k")]
+    pub rrf_k: f32,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+fn default_rrf_k() -> f32 {
+    60.0
+}
+
+impl Default for HybridSearchConfig {
+    fn default() -> Self {
+        Self {
+            vector_weight: default_weight(),
+            keyword_weight: default_weight(),
+            rrf_k: default_rrf_k(),
+        }
+    }
+}
+
+/// Merges rankings of document ids, each given with its weight, best first
+pub fn reciprocal_rank_fusion(rankings: &[(&[usize], f32)], rrf_k: f32) -> Vec<(usize, f32)> {
+    let mut scores: HashMap<usize, f32> = HashMap::new();
+    for (ranking, weight) in rankings {
+        for (rank, document_id) in ranking.iter().enumerate() {
+            *scores.entry(*document_id).or_insert(0.0) += weight / (rrf_k + rank as f32 + 1.0);
+        }
+    }
+
+    let mut fused: Vec<(usize, f32)> = scores.into_iter().collect();
+    fused.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.0.cmp(&b.0))
+    });
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fusion_rewards_agreement_and_weights() {
+        let vector = [1, 2, 3];
+        let keyword = [3, 4];
+
+        let fused = reciprocal_rank_fusion(&[(&vector, 1.0), (&keyword, 1.0)], 60.0);
+        let order: Vec<usize> = fused.iter().map(|(id, _)| *id).collect();
+        // 3 appears in both lists and overtakes the vector-only leader
+        assert_eq!(order, vec![3, 1, 2, 4]);
+
+        let fused = reciprocal_rank_fusion(&[(&vector, 1.0), (&keyword, 3.0)], 60.0);
+        let order: Vec<usize> = fused.iter().map(|(id, _)| *id).collect();
+        assert_eq!(order, vec![3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn test_mode_config_parsing() {
+        let mode: RetrievalMode = serde_yaml::from_str("hybrid").unwrap();
+        assert_eq!(mode, RetrievalMode::Hybrid);
+
+        let config: HybridSearchConfig = serde_yaml::from_str("keyword_weight: 2.0").unwrap();
+        assert_eq!(config.keyword_weight, 2.0);
+        assert_eq!(config.vector_weight, 1.0);
+        assert_eq!(config.rrf_k, 60.0);
+    }
+}
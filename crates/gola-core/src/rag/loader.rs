@@ -0,0 +1,195 @@
+//! File discovery for RAG document sources
+//!
+//! Directories are walked in file name order, optionally into subdirectories.
+//! Exclude patterns use gitignore syntax relative to the directory being walked,
+//! so `drafts/`, `*.min.js` and `!keep.md` all work as they would in a
+//! `.gitignore`. Symbolic links are skipped unless `follow_symlinks` is set, in
+//! which case link cycles are detected and reported rather than walked forever.
+
+use crate::errors::AgentError;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Extensions loaded when a source does not list its own
+pub const DEFAULT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "markdown", "rst", "py", "rs", "js", "ts", "java", "cpp", "c", "h", "hpp", "go",
+    "rb", "php", "html", "htm", "css", "json", "yaml", "yml", "toml", "xml", "pdf", "docx",
+];
+
+/// How the files of a document source are selected and labelled
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    /// Walk subdirectories as well
+    pub recursive: bool,
+    /// Extensions to load, with or without the leading dot; [`DEFAULT_EXTENSIONS`] when empty
+    pub include_extensions: Vec<String>,
+    /// Gitignore-style patterns for files and directories to skip
+    pub exclude_patterns: Vec<String>,
+    pub follow_symlinks: bool,
+    /// Added to every document loaded from the source
+    pub metadata: HashMap<String, String>,
+}
+
+impl LoadOptions {
+    fn includes_extension(&self, path: &Path) -> bool {
+        let Some(extension) = path.extension() else {
+            return false;
+        };
+        let extension = extension.to_string_lossy().to_lowercase();
+        if self.include_extensions.is_empty() {
+            DEFAULT_EXTENSIONS.contains(&extension.as_str())
+        } else {
+            self.include_extensions
+                .iter()
+                .any(|wanted| wanted.trim_start_matches('.').eq_ignore_ascii_case(&extension))
+        }
+    }
+
+    fn exclude_matcher(&self, root: &Path) -> Result<Gitignore, AgentError> {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in &self.exclude_patterns {
+            builder.add_line(None, pattern).map_err(|e| {
+                AgentError::ConfigError(format!("Invalid exclude pattern '{}': {}", pattern, e))
+            })?;
+        }
+        builder
+            .build()
+            .map_err(|e| AgentError::ConfigError(format!("Invalid exclude patterns: {}", e)))
+    }
+}
+
+/// Lists the files under `root` that `options` selects
+pub fn collect_files(root: &Path, options: &LoadOptions) -> Result<Vec<PathBuf>, AgentError> {
+    let excludes = options.exclude_matcher(root)?;
+
+    let mut walker = WalkBuilder::new(root);
+    walker
+        .standard_filters(false)
+        .follow_links(options.follow_symlinks)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            entry.depth() == 0 || !excludes.matched(entry.path(), is_dir).is_ignore()
+        });
+    if !options.recursive {
+        walker.max_depth(Some(1));
+    }
+
+    let mut files = Vec::new();
+    for entry in walker.build() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::warn!("Skipping unreadable entry under {}: {}", root.display(), e);
+                continue;
+            }
+        };
+        let Some(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_symlink() {
+            log::debug!("Skipping symbolic link {}", entry.path().display());
+            continue;
+        }
+        if file_type.is_file() && options.includes_extension(entry.path()) {
+            files.push(entry.into_path());
+        }
+    }
+
+    log::info!("Found {} matching files under {}", files.len(), root.display());
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for file in [
+            "README.md",
+            "notes.txt",
+            "image.png",
+            "guide/intro.md",
+            "guide/drafts/wip.md",
+            "guide/api/reference.MD",
+            "vendor/lib.js",
+        ] {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "content").unwrap();
+        }
+        dir
+    }
+
+    fn relative(root: &Path, files: Vec<PathBuf>) -> Vec<String> {
+        files
+            .iter()
+            .map(|f| f.strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect()
+    }
+
+    #[test]
+    fn test_recursive_walk_with_excludes_and_extensions() {
+        let dir = tree();
+        let options = LoadOptions {
+            recursive: true,
+            include_extensions: vec![".md".to_string()],
+            exclude_patterns: vec!["drafts/".to_string(), "vendor".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            relative(dir.path(), collect_files(dir.path(), &options).unwrap()),
+            vec!["README.md", "guide/api/reference.MD", "guide/intro.md"]
+        );
+
+        // Without recursion only the top level is read, with the default extensions
+        assert_eq!(
+            relative(dir.path(), collect_files(dir.path(), &LoadOptions::default()).unwrap()),
+            vec!["README.md", "notes.txt"]
+        );
+    }
+
+    #[test]
+    fn test_negated_and_anchored_patterns() {
+        let dir = tree();
+        let options = LoadOptions {
+            recursive: true,
+            exclude_patterns: vec!["*.md".to_string(), "!intro.md".to_string(), "/notes.txt".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            relative(dir.path(), collect_files(dir.path(), &options).unwrap()),
+            // Patterns are case sensitive, as in git
+            vec!["guide/api/reference.MD", "guide/intro.md", "vendor/lib.js"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_are_skipped_unless_followed() {
+        let dir = tree();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("shared.md"), "shared").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("linked")).unwrap();
+        // A link back to the root must not be walked forever
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("guide/loop")).unwrap();
+
+        let mut options = LoadOptions {
+            recursive: true,
+            include_extensions: vec!["md".to_string()],
+            exclude_patterns: vec!["drafts/".to_string()],
+            ..Default::default()
+        };
+        let files = relative(dir.path(), collect_files(dir.path(), &options).unwrap());
+        assert!(!files.contains(&"linked/shared.md".to_string()));
+
+        options.follow_symlinks = true;
+        let files = relative(dir.path(), collect_files(dir.path(), &options).unwrap());
+        assert!(files.contains(&"linked/shared.md".to_string()));
+        assert_eq!(files.iter().filter(|f| f.ends_with("intro.md")).count(), 1);
+    }
+}
@@ -0,0 +1,171 @@
+//! Bookkeeping for incremental re-indexing
+//!
+//! The manifest records, for every indexed source, the modification time and
+//! content hash it was indexed with and the ids of its chunks. Re-indexing checks
+//! sources against it: a file whose modification time is unchanged is skipped
+//! without being read, a source whose content hash is unchanged is not embedded
+//! again, and a source that changed or disappeared has its old chunks removed.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// How a source was indexed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceRecord {
+    /// Modification time in nanoseconds since the Unix epoch; `None` for sources
+    /// that are not files
+    pub modified: Option<u64>,
+    /// Hex-encoded SHA-256 of the content
+    pub content_hash: String,
+    /// Metadata attached to every chunk of the source
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    pub chunk_ids: Vec<usize>,
+}
+
+/// Indexed sources by name, which is the file path for files
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SourceManifest {
+    sources: BTreeMap<String, SourceRecord>,
+}
+
+impl SourceManifest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, source: &str) -> Option<&SourceRecord> {
+        self.sources.get(source)
+    }
+
+    pub fn get_mut(&mut self, source: &str) -> Option<&mut SourceRecord> {
+        self.sources.get_mut(source)
+    }
+
+    pub fn insert(&mut self, source: String, record: SourceRecord) {
+        self.sources.insert(source, record);
+    }
+
+    pub fn remove(&mut self, source: &str) -> Option<SourceRecord> {
+        self.sources.remove(source)
+    }
+
+    pub fn sources(&self) -> impl Iterator<Item = &str> {
+        self.sources.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.sources.clear();
+    }
+}
+
+/// Hex-encoded SHA-256 of text or raw file bytes
+pub fn content_hash(content: impl AsRef<[u8]>) -> String {
+    format!("{:x}", Sha256::digest(content.as_ref()))
+}
+
+/// Modification time of a file in nanoseconds since the Unix epoch
+pub fn modified_time(metadata: &std::fs::Metadata) -> Option<u64> {
+    let modified = metadata.modified().ok()?;
+    let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(since_epoch.as_nanos() as u64)
+}
+
+/// What a re-index changed, by source
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReindexReport {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: Vec<String>,
+    /// Sources that could not be read, with the error; their previous chunks are kept
+    pub failed: Vec<(String, String)>,
+    pub chunks_embedded: usize,
+    pub chunks_removed: usize,
+}
+
+impl ReindexReport {
+    pub fn has_changes(&self) -> bool {
+        !(self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty())
+    }
+
+    /// Sources that were seen, whether or not they changed
+    pub fn seen_sources(&self) -> impl Iterator<Item = &str> {
+        self.added
+            .iter()
+            .chain(&self.updated)
+            .chain(&self.unchanged)
+            .map(String::as_str)
+            .chain(self.failed.iter().map(|(source, _)| source.as_str()))
+    }
+
+    pub fn merge(&mut self, other: ReindexReport) {
+        self.added.extend(other.added);
+        self.updated.extend(other.updated);
+        self.removed.extend(other.removed);
+        self.unchanged.extend(other.unchanged);
+        self.failed.extend(other.failed);
+        self.chunks_embedded += other.chunks_embedded;
+        self.chunks_removed += other.chunks_removed;
+    }
+}
+
+impl fmt::Display for ReindexReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} added, {} updated, {} removed, {} unchanged ({} chunks embedded, {} removed)",
+            self.added.len(),
+            self.updated.len(),
+            self.removed.len(),
+            self.unchanged.len(),
+            self.chunks_embedded,
+            self.chunks_removed
+        )?;
+        if !self.failed.is_empty() {
+            write!(f, ", {} failed", self.failed.len())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_merge_and_display() {
+        let mut report = ReindexReport {
+            added: vec!["a.md".to_string()],
+            unchanged: vec!["b.md".to_string()],
+            chunks_embedded: 3,
+            ..Default::default()
+        };
+        report.merge(ReindexReport {
+            removed: vec!["c.md".to_string()],
+            failed: vec![("d.md".to_string(), "permission denied".to_string())],
+            chunks_removed: 2,
+            ..Default::default()
+        });
+
+        assert!(report.has_changes());
+        assert_eq!(report.seen_sources().collect::<Vec<_>>(), vec!["a.md", "b.md", "d.md"]);
+        assert_eq!(
+            report.to_string(),
+            "1 added, 0 updated, 1 removed, 1 unchanged (3 chunks embedded, 2 removed), 1 failed"
+        );
+        assert!(!ReindexReport::default().has_changes());
+        assert_eq!(content_hash("abc").len(), 64);
+    }
+}
@@ -15,13 +15,35 @@ use std::path::Path;
 use crate::errors::AgentError;
 use crate::llm::LLM;
 
+pub mod bm25;
 pub mod cache;
+pub mod code_splitter;
+pub mod document_loader;
+pub mod embedding_file;
 pub mod embeddings;
+pub mod filter;
+pub mod hnsw;
+pub mod hybrid;
+pub mod loader;
+pub mod manifest;
+pub mod rerank;
 pub mod splitter;
 pub mod vector_store;
 
+pub use bm25::*;
 pub use cache::*;
+pub use code_splitter::CodeLanguage;
+pub use document_loader::{
+    DocumentLoader, DocumentLoaders, DocumentSection, DocxLoader, HtmlLoader, PdfLoader, TextLoader,
+};
+pub use embedding_file::Quantization;
 pub use embeddings::*;
+pub use filter::*;
+pub use hnsw::*;
+pub use hybrid::*;
+pub use loader::*;
+pub use manifest::*;
+pub use rerank::*;
 pub use splitter::*;
 pub use vector_store::*;
 
@@ -36,6 +58,17 @@ pub struct RagConfig {
     pub reranker_model_name: Option<String>,
     pub persistent_vector_store_path: Option<String>,
     pub embedding_cache: EmbeddingCacheConfig,
+    #[serde(default)]
+    pub retrieval_mode: RetrievalMode,
+    #[serde(default)]
+    pub hybrid: HybridSearchConfig,
+    /// Candidates fetched for the reranker, when one is set
+    #[serde(default = "default_rerank_candidates")]
+    pub rerank_candidates: usize,
+}
+
+fn default_rerank_candidates() -> usize {
+    20
 }
 
 impl Default for RagConfig {
@@ -49,6 +82,9 @@ impl Default for RagConfig {
             reranker_model_name: None,
             persistent_vector_store_path: None,
             embedding_cache: EmbeddingCacheConfig::default(),
+            retrieval_mode: RetrievalMode::default(),
+            hybrid: HybridSearchConfig::default(),
+            rerank_candidates: default_rerank_candidates(),
         }
     }
 }
@@ -108,6 +144,35 @@ pub trait Rag: Send + Sync {
         top_k: Option<usize>,
     ) -> Result<RetrievedContext, AgentError>;
 
+    /// Retrieves only documents that match `filter`
+    ///
+    /// The default implementation filters the results of [`Rag::retrieve`], so it
+    /// may return fewer than `top_k` documents even when more would match.
+    async fn retrieve_filtered(
+        &self,
+        query: &str,
+        top_k: Option<usize>,
+        filter: &MetadataFilter,
+    ) -> Result<RetrievedContext, AgentError> {
+        let context = self.retrieve(query, top_k).await?;
+        if filter.is_empty() {
+            return Ok(context);
+        }
+
+        let mut filtered = RetrievedContext::new();
+        for ((document, source), score) in context
+            .documents
+            .into_iter()
+            .zip(context.sources)
+            .zip(context.scores)
+        {
+            if filter.matches(&document) {
+                filtered.add_document(document, source, score);
+            }
+        }
+        Ok(filtered)
+    }
+
     async fn add_documents(&mut self, documents: Vec<RagDocument>) -> Result<(), AgentError>;
 
     async fn add_documents_from_paths(&mut self, paths: &[String]) -> Result<(), AgentError>;
@@ -1,12 +1,21 @@
 use async_trait::async_trait;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::errors::AgentError;
 use crate::llm::LLM;
 use crate::rag::{
+    bm25::Bm25Index,
     cache::EmbeddingCacheFactory,
+    document_loader::{DocumentLoader, DocumentLoaders, DocumentSection, SECTION_KEY, SECTION_NUMBER_KEY},
     embeddings::{CachedEmbeddingGenerator, DummyEmbeddingGenerator, EmbeddingGenerator, RestEmbeddingFactory},
+    filter::MetadataFilter,
+    hybrid::{reciprocal_rank_fusion, RetrievalMode},
+    loader::{collect_files, LoadOptions},
+    manifest::{content_hash, modified_time, ReindexReport, SourceManifest, SourceRecord},
+    rerank::{LexicalReranker, Reranker},
     splitter::TextSplitter,
     vector_store::{InMemoryVectorStore, PersistentVectorStore, VectorStore},
     Rag, RagConfig, RagDocument, RetrievedContext,
@@ -17,11 +26,19 @@ pub struct RagSystem {
     vector_store: Box<dyn VectorStore>,
     embedding_generator: Box<dyn EmbeddingGenerator>,
     text_splitter: TextSplitter,
-    documents: Vec<RagDocument>,
+    /// Chunks by document id
+    documents: BTreeMap<usize, RagDocument>,
+    keyword_index: Bm25Index,
+    manifest: SourceManifest,
+    loaders: DocumentLoaders,
+    reranker: Option<Arc<dyn Reranker>>,
     config: RagConfig,
     document_id_counter: usize,
 }
 
+/// Candidates taken from each ranking per requested result in hybrid mode
+const HYBRID_CANDIDATE_FACTOR: usize = 4;
+
 impl RagSystem {
     pub fn new(config: RagConfig, embedding_generator: Box<dyn EmbeddingGenerator>) -> Self {
         let vector_store = Box::new(InMemoryVectorStore::new());
@@ -31,7 +48,11 @@ impl RagSystem {
             vector_store,
             embedding_generator,
             text_splitter,
-            documents: Vec::new(),
+            documents: BTreeMap::new(),
+            keyword_index: Bm25Index::new(),
+            manifest: SourceManifest::new(),
+            loaders: DocumentLoaders::default(),
+            reranker: None,
             config,
             document_id_counter: 0,
         }
@@ -65,7 +86,11 @@ impl RagSystem {
             vector_store,
             embedding_generator: cached_embedding_generator,
             text_splitter,
-            documents: Vec::new(),
+            documents: BTreeMap::new(),
+            keyword_index: Bm25Index::new(),
+            manifest: SourceManifest::new(),
+            loaders: DocumentLoaders::default(),
+            reranker: None,
             config,
             document_id_counter: 0,
         })
@@ -111,7 +136,11 @@ impl RagSystem {
             vector_store,
             embedding_generator,
             text_splitter,
-            documents: Vec::new(),
+            documents: BTreeMap::new(),
+            keyword_index: Bm25Index::new(),
+            manifest: SourceManifest::new(),
+            loaders: DocumentLoaders::default(),
+            reranker: None,
             config,
             document_id_counter: 0,
         })
@@ -136,7 +165,11 @@ impl RagSystem {
             vector_store,
             embedding_generator: cached_embedding_generator,
             text_splitter,
-            documents: Vec::new(),
+            documents: BTreeMap::new(),
+            keyword_index: Bm25Index::new(),
+            manifest: SourceManifest::new(),
+            loaders: DocumentLoaders::default(),
+            reranker: None,
             config,
             document_id_counter: 0,
         })
@@ -150,14 +183,22 @@ impl RagSystem {
         let path_buf = storage_path.into();
         config.persistent_vector_store_path = Some(path_buf.to_string_lossy().into_owned());
 
-        let vector_store = Box::new(PersistentVectorStore::new().with_file_path(path_buf));
+        let vector_store = Box::new(
+            PersistentVectorStore::new()
+                .with_file_path(path_buf)
+                .with_embedding_model(config.embedding_model_name.clone()),
+        );
         let text_splitter = TextSplitter::new(config.chunk_size, config.chunk_overlap);
 
         Self {
             vector_store,
             embedding_generator,
             text_splitter,
-            documents: Vec::new(),
+            documents: BTreeMap::new(),
+            keyword_index: Bm25Index::new(),
+            manifest: SourceManifest::new(),
+            loaders: DocumentLoaders::default(),
+            reranker: None,
             config,
             document_id_counter: 0,
         }
@@ -172,7 +213,11 @@ impl RagSystem {
         let path_buf = storage_path.into();
         config.persistent_vector_store_path = Some(path_buf.to_string_lossy().into_owned());
 
-        let vector_store = Box::new(PersistentVectorStore::new().with_file_path(path_buf));
+        let vector_store = Box::new(
+            PersistentVectorStore::new()
+                .with_file_path(path_buf)
+                .with_embedding_model(config.embedding_model_name.clone()),
+        );
         let text_splitter = TextSplitter::new(config.chunk_size, config.chunk_overlap);
 
         // Create cache based on configuration
@@ -183,7 +228,11 @@ impl RagSystem {
             vector_store,
             embedding_generator: cached_embedding_generator,
             text_splitter,
-            documents: Vec::new(),
+            documents: BTreeMap::new(),
+            keyword_index: Bm25Index::new(),
+            manifest: SourceManifest::new(),
+            loaders: DocumentLoaders::default(),
+            reranker: None,
             config,
             document_id_counter: 0,
         })
@@ -199,7 +248,11 @@ impl RagSystem {
             vector_store,
             embedding_generator,
             text_splitter,
-            documents: Vec::new(),
+            documents: BTreeMap::new(),
+            keyword_index: Bm25Index::new(),
+            manifest: SourceManifest::new(),
+            loaders: DocumentLoaders::default(),
+            reranker: None,
             config,
             document_id_counter: 0,
         }
@@ -223,22 +276,36 @@ impl RagSystem {
             vector_store,
             embedding_generator: cached_embedding_generator,
             text_splitter,
-            documents: Vec::new(),
+            documents: BTreeMap::new(),
+            keyword_index: Bm25Index::new(),
+            manifest: SourceManifest::new(),
+            loaders: DocumentLoaders::default(),
+            reranker: None,
             config,
             document_id_counter: 0,
         })
     }
 
+    /// Hash of the splitter and embedding settings the chunks are made with
+    fn settings_fingerprint(&self) -> String {
+        content_hash(format!("{}\n{}", self.text_splitter.fingerprint(), self.config.embedding_model_name))
+    }
+
     fn next_document_id(&mut self) -> usize {
         let id = self.document_id_counter;
         self.document_id_counter += 1;
         id
     }
 
-    async fn process_document(&mut self, document: RagDocument) -> Result<(), AgentError> {
+    /// Splits, embeds and stores `document`, returning the ids of its chunks
+    async fn process_document(&mut self, document: RagDocument) -> Result<Vec<usize>, AgentError> {
         log::info!("Processing document: {}", document.source);
 
-        let chunks = self.text_splitter.split_text(&document.content);
+        let sections = self
+            .text_splitter
+            .split_document(&document.source, &document.content, self.embedding_generator.as_ref())
+            .await?;
+        let chunks: Vec<String> = sections.iter().map(|section| section.text.clone()).collect();
         log::debug!("Split document into {} chunks", chunks.len());
 
         let embeddings = self
@@ -247,13 +314,16 @@ impl RagSystem {
             .await?;
         log::debug!("Generated {} embeddings", embeddings.len());
 
-        for (i, (chunk, embedding)) in chunks.iter().zip(embeddings.iter()).enumerate() {
+        let mut indexed = Vec::with_capacity(chunks.len());
+        let mut chunk_ids = Vec::with_capacity(chunks.len());
+        for (i, ((chunk, section), embedding)) in chunks.iter().zip(sections).zip(embeddings).enumerate() {
             let doc_id = self.next_document_id();
 
             // Create a chunk document
             let mut chunk_doc = RagDocument::new(chunk.clone(), document.source.clone())
                 .with_chunk_index(i)
                 .with_metadata(document.metadata.clone());
+            merge_splitter_metadata(&mut chunk_doc.metadata, section.metadata);
 
             chunk_doc
                 .metadata
@@ -265,107 +335,454 @@ impl RagSystem {
                 .metadata
                 .insert("original_source".to_string(), document.source.clone());
 
-            self.documents.push(chunk_doc);
-            self.vector_store
-                .add_document(doc_id, embedding.clone())
-                .await?;
+            self.keyword_index.add_document(doc_id, chunk);
+            self.documents.insert(doc_id, chunk_doc);
+            indexed.push((doc_id, embedding));
+            chunk_ids.push(doc_id);
         }
+        // One batch, so that persistent stores write once per document
+        self.vector_store.add_documents(indexed).await?;
 
         log::info!(
             "Successfully processed document: {} ({} chunks)",
             document.source,
             chunks.len()
         );
-        Ok(())
+        Ok(chunk_ids)
+    }
+
+    /// Removes chunks from the documents, the keyword index and the vector store
+    async fn remove_chunks(&mut self, chunk_ids: &[usize]) -> Result<usize, AgentError> {
+        let mut removed = 0;
+        for chunk_id in chunk_ids {
+            if self.documents.remove(chunk_id).is_some() {
+                removed += 1;
+            }
+            self.keyword_index.remove_document(*chunk_id);
+        }
+        self.vector_store.remove_documents(chunk_ids).await?;
+        Ok(removed)
     }
 
     async fn load_documents_from_paths(&mut self, paths: &[String]) -> Result<(), AgentError> {
-        for path_str in paths {
-            let path = Path::new(path_str);
+        let paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        self.add_documents_with_options(&paths, &LoadOptions::default()).await
+    }
+
+    /// Loads files and directories, selecting directory contents with `options`
+    ///
+    /// Listed files are always loaded. The source metadata in `options` is
+    /// attached to every document, and so to every chunk. Files that were loaded
+    /// before are only embedded again if they changed.
+    pub async fn add_documents_with_options(
+        &mut self,
+        paths: &[PathBuf],
+        options: &LoadOptions,
+    ) -> Result<(), AgentError> {
+        let mut report = ReindexReport::default();
+        self.index_paths(paths, options, &mut report).await?;
+        log::info!("Loaded documents: {}", report);
+        Ok(())
+    }
 
+    /// Brings the index up to date with the files under `paths`
+    ///
+    /// New and changed files are embedded, replacing the chunks of the previous
+    /// version. Tracked files under `paths` that no longer exist, or that `options`
+    /// no longer selects, have their chunks removed.
+    pub async fn reindex(
+        &mut self,
+        paths: &[PathBuf],
+        options: &LoadOptions,
+    ) -> Result<ReindexReport, AgentError> {
+        let mut report = ReindexReport::default();
+        let found = self.index_paths(paths, options, &mut report).await?;
+
+        let stale: Vec<String> = self
+            .manifest
+            .sources()
+            .filter(|source| !found.contains(*source))
+            .filter(|source| paths.iter().any(|path| Path::new(source).starts_with(path)))
+            .map(str::to_string)
+            .collect();
+        for source in stale {
+            report.chunks_removed += self.remove_source(&source).await?;
+            report.removed.push(source);
+        }
+
+        log::info!("Reindexed {} paths: {}", paths.len(), report);
+        Ok(report)
+    }
+
+    /// Indexes a document that does not come from a file, such as fetched or inline
+    /// content, replacing an earlier version from the same source if it changed
+    pub async fn index_document(&mut self, document: RagDocument) -> Result<ReindexReport, AgentError> {
+        let mut report = ReindexReport::default();
+        let content = SourceContent {
+            source: document.source.clone(),
+            metadata: document.metadata.clone(),
+            hash: content_hash(&document.content),
+            parts: vec![document],
+        };
+        self.index_source(content, None, &mut report).await?;
+        Ok(report)
+    }
+
+    /// Indexes fetched bytes, such as a downloaded page or file, extracting the
+    /// text with the loader for `mime_type` or, failing that, the extension of
+    /// `source`
+    pub async fn index_content(
+        &mut self,
+        source: &str,
+        bytes: Vec<u8>,
+        mime_type: Option<&str>,
+        metadata: HashMap<String, String>,
+    ) -> Result<ReindexReport, AgentError> {
+        let loader = self.loaders.for_content(mime_type, source);
+        let content = extract_content(loader, source.to_string(), bytes, metadata).await?;
+        let mut report = ReindexReport::default();
+        self.index_source(content, None, &mut report).await?;
+        Ok(report)
+    }
+
+    /// Removes the chunks of a tracked source, returning how many were removed
+    pub async fn remove_source(&mut self, source: &str) -> Result<usize, AgentError> {
+        match self.manifest.remove(source) {
+            Some(record) => self.remove_chunks(&record.chunk_ids).await,
+            None => Ok(0),
+        }
+    }
+
+    /// Removes every tracked source that is not in `keep`, such as sources that
+    /// were dropped from the configuration
+    pub async fn prune_sources(&mut self, keep: &HashSet<String>) -> Result<ReindexReport, AgentError> {
+        let mut report = ReindexReport::default();
+        let stale: Vec<String> = self
+            .manifest
+            .sources()
+            .filter(|source| !keep.contains(*source))
+            .map(str::to_string)
+            .collect();
+        for source in stale {
+            report.chunks_removed += self.remove_source(&source).await?;
+            report.removed.push(source);
+        }
+        Ok(report)
+    }
+
+    /// Restores the chunks, keyword index and manifest saved by [`Rag::save`], so
+    /// that [`reindex`](Self::reindex) only embeds what changed since
+    ///
+    /// The vector store must already hold the embeddings saved alongside. If the
+    /// file is missing or unreadable, does not match the vector store, or was saved
+    /// with other splitter or embedding settings, the store is cleared and every
+    /// source will be embedded again.
+    pub async fn load_state(&mut self, path: &Path) -> Result<(), AgentError> {
+        let data = match read_system_data(path).await {
+            Ok(data) => Some(data),
+            Err(e) => {
+                if path.exists() {
+                    log::warn!("Ignoring saved RAG state: {}", e);
+                }
+                None
+            }
+        };
+
+        let settings = self.settings_fingerprint();
+        match data {
+            Some(data) if data.settings.as_ref().is_some_and(|saved| *saved != settings) => {
+                log::warn!(
+                    "Splitter or embedding settings changed since the RAG state at {} was saved; re-embedding all sources",
+                    path.display()
+                );
+                self.vector_store.clear().await?;
+            }
+            Some(data)
+                if data.documents.len() == self.vector_store.document_count()
+                    && data.documents.keys().all(|id| self.vector_store.contains(*id)) =>
+            {
+                self.keyword_index = data
+                    .keyword_index
+                    .unwrap_or_else(|| keyword_index_for(&data.documents));
+                self.documents = data.documents;
+                self.manifest = data.manifest;
+                self.document_id_counter = data.document_id_counter;
+                log::info!(
+                    "Restored {} chunks from {} sources",
+                    self.documents.len(),
+                    self.manifest.len()
+                );
+            }
+            _ => {
+                if self.vector_store.document_count() > 0 {
+                    log::warn!(
+                        "Vector store does not match the saved RAG state at {}; re-embedding all sources",
+                        path.display()
+                    );
+                    self.vector_store.clear().await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Indexes the files under `paths`, returning the sources that were found
+    async fn index_paths(
+        &mut self,
+        paths: &[PathBuf],
+        options: &LoadOptions,
+        report: &mut ReindexReport,
+    ) -> Result<HashSet<String>, AgentError> {
+        let mut found = HashSet::new();
+        for path in paths {
             if path.is_file() {
-                self.load_single_file(path).await?;
+                found.insert(path.to_string_lossy().to_string());
+                self.index_file(path, &options.metadata, report).await?;
             } else if path.is_dir() {
-                self.load_directory(path).await?;
+                let root = path.to_path_buf();
+                let walk_options = options.clone();
+                let files = tokio::task::spawn_blocking(move || collect_files(&root, &walk_options))
+                    .await
+                    .map_err(|e| AgentError::RagError(format!("Directory walk failed: {}", e)))??;
+
+                for file in files {
+                    let source = file.to_string_lossy().to_string();
+                    found.insert(source.clone());
+                    if let Err(e) = self.index_file(&file, &options.metadata, report).await {
+                        log::warn!("Failed to load file {}: {}", file.display(), e);
+                        report.failed.push((source, e.to_string()));
+                    }
+                }
             } else {
-                log::warn!("Path does not exist or is not accessible: {}", path_str);
+                log::warn!("Path does not exist or is not accessible: {}", path.display());
             }
         }
-        Ok(())
+        Ok(found)
     }
 
-    async fn load_single_file(&mut self, path: &Path) -> Result<(), AgentError> {
-        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+    async fn index_file(
+        &mut self,
+        path: &Path,
+        metadata: &HashMap<String, String>,
+        report: &mut ReindexReport,
+    ) -> Result<(), AgentError> {
+        let source = path.to_string_lossy().to_string();
+        let modified = tokio::fs::metadata(path)
+            .await
+            .ok()
+            .and_then(|file_metadata| modified_time(&file_metadata));
+
+        // An untouched file is not read again
+        if let Some(record) = self.manifest.get(&source) {
+            if modified.is_some() && record.modified == modified && &record.metadata == metadata {
+                report.unchanged.push(source);
+                return Ok(());
+            }
+        }
+
+        let bytes = tokio::fs::read(path).await.map_err(|e| {
             AgentError::RagError(format!("Failed to read file {}: {}", path.display(), e))
         })?;
-
-        let document = RagDocument::new(content, path.to_string_lossy().to_string());
-        self.process_document(document).await?;
-        Ok(())
+        let loader = self.loaders.for_path(path);
+        let content = extract_content(loader, source, bytes, metadata.clone()).await?;
+        self.index_source(content, modified, report).await
     }
 
-    async fn load_directory(&mut self, dir_path: &Path) -> Result<(), AgentError> {
-        let mut entries = tokio::fs::read_dir(dir_path).await.map_err(|e| {
-            AgentError::RagError(format!(
-                "Failed to read directory {}: {}",
-                dir_path.display(),
-                e
-            ))
-        })?;
-
-        while let Some(entry) = entries
-            .next_entry()
-            .await
-            .map_err(|e| AgentError::RagError(format!("Failed to read directory entry: {}", e)))?
-        {
-            let path = entry.path();
-            if path.is_file() {
-                // Filter by common text file extensions
-                if let Some(extension) = path.extension() {
-                    let ext = extension.to_string_lossy().to_lowercase();
-                    if matches!(
-                        ext.as_str(),
-                        "txt"
-                            | "md"
-                            | "markdown"
-                            | "rst"
-                            | "py"
-                            | "rs"
-                            | "js"
-                            | "ts"
-                            | "java"
-                            | "cpp"
-                            | "c"
-                            | "h"
-                            | "hpp"
-                            | "go"
-                            | "rb"
-                            | "php"
-                            | "html"
-                            | "css"
-                            | "json"
-                            | "yaml"
-                            | "yml"
-                            | "toml"
-                            | "xml"
-                    ) {
-                        if let Err(e) = self.load_single_file(&path).await {
-                            log::warn!("Failed to load file {}: {}", path.display(), e);
-                        }
+    /// Indexes `content` as the current version of its source
+    async fn index_source(
+        &mut self,
+        content: SourceContent,
+        modified: Option<u64>,
+        report: &mut ReindexReport,
+    ) -> Result<(), AgentError> {
+        let SourceContent {
+            source,
+            metadata,
+            hash,
+            parts,
+        } = content;
+
+        let unchanged = self
+            .manifest
+            .get(&source)
+            .filter(|record| record.content_hash == hash)
+            .map(|record| (record.metadata.clone(), record.chunk_ids.clone()));
+        if let Some((previous_metadata, chunk_ids)) = unchanged {
+            // Embeddings depend only on the content, so the chunks are relabelled
+            let relabelled = previous_metadata == metadata
+                || self.relabel_chunks(&chunk_ids, &previous_metadata, &metadata, &parts);
+            if relabelled {
+                if let Some(record) = self.manifest.get_mut(&source) {
+                    record.modified = modified;
+                    if record.metadata == metadata {
+                        report.unchanged.push(source);
+                    } else {
+                        record.metadata = metadata;
+                        report.updated.push(source);
                     }
                 }
+                return Ok(());
             }
         }
+
+        let previous = self.manifest.remove(&source);
+        if let Some(previous) = &previous {
+            report.chunks_removed += self.remove_chunks(&previous.chunk_ids).await?;
+        }
+
+        let mut chunk_ids = Vec::new();
+        for part in parts {
+            chunk_ids.extend(self.process_document(part).await?);
+        }
+        report.chunks_embedded += chunk_ids.len();
+        self.manifest.insert(
+            source.clone(),
+            SourceRecord {
+                modified,
+                content_hash: hash,
+                metadata,
+                chunk_ids,
+            },
+        );
+
+        if previous.is_some() {
+            report.updated.push(source);
+        } else {
+            report.added.push(source);
+        }
         Ok(())
     }
 
+    /// Moves the chunks of a source from `previous` to `metadata` without
+    /// embedding them again
+    ///
+    /// The chunks of each part follow each other, numbered from 0, so each one
+    /// takes the source keys from its part as indexing would have set them. Keys
+    /// the loader set for a section keep their value, and other keys are left
+    /// alone. Returns false, changing nothing, when the chunks can't be matched
+    /// to `parts`.
+    fn relabel_chunks(
+        &mut self,
+        chunk_ids: &[usize],
+        previous: &HashMap<String, String>,
+        metadata: &HashMap<String, String>,
+        parts: &[RagDocument],
+    ) -> bool {
+        let mut part_of_chunk = Vec::with_capacity(chunk_ids.len());
+        let mut part = None;
+        for chunk_id in chunk_ids {
+            let Some(chunk) = self.documents.get(chunk_id) else {
+                return false;
+            };
+            if chunk.chunk_index == Some(0) {
+                part = Some(part.map_or(0, |index| index + 1));
+            }
+            match part {
+                Some(index) if index < parts.len() => part_of_chunk.push(index),
+                _ => return false,
+            }
+        }
+        if part.map_or(0, |index| index + 1) != parts.len() {
+            return false;
+        }
+
+        let keys: HashSet<&String> = previous.keys().chain(metadata.keys()).collect();
+        for (chunk_id, index) in chunk_ids.iter().zip(part_of_chunk) {
+            let Some(chunk) = self.documents.get_mut(chunk_id) else {
+                continue;
+            };
+            for key in &keys {
+                match parts[index].metadata.get(*key) {
+                    Some(value) => chunk.metadata.insert((*key).clone(), value.clone()),
+                    None => chunk.metadata.remove(*key),
+                };
+            }
+        }
+        true
+    }
+
+    /// Chunks by embedding similarity, above the similarity threshold
+    async fn vector_ranking(
+        &self,
+        query: &str,
+        k: usize,
+        filter: &MetadataFilter,
+    ) -> Result<Vec<(usize, f32)>, AgentError> {
+        let query_embedding = self.embedding_generator.generate_embedding(query).await?;
+        let search_results = if filter.is_empty() {
+            self.vector_store.search(&query_embedding, k).await?
+        } else {
+            let matches = |id: usize| self.documents.get(&id).is_some_and(|d| filter.matches(d));
+            self.vector_store.search_filtered(&query_embedding, k, &matches).await?
+        };
+        Ok(search_results
+            .into_iter()
+            .filter(|result| result.score >= self.config.similarity_threshold)
+            .map(|result| (result.document_id, result.score))
+            .collect())
+    }
+
+    /// Chunks by BM25 score, containing at least one query term
+    fn keyword_ranking(&self, query: &str, k: usize, filter: &MetadataFilter) -> Vec<(usize, f32)> {
+        let matches = |id: usize| {
+            filter.is_empty() || self.documents.get(&id).is_some_and(|d| filter.matches(d))
+        };
+        self.keyword_index.search(query, k, &matches)
+    }
+
+    /// Orders the ranked chunks with `reranker` and keeps the best `top_k`. Falls
+    /// back to lexical overlap if the reranker fails.
+    async fn rerank(
+        &self,
+        reranker: &dyn Reranker,
+        query: &str,
+        ranked: Vec<(usize, f32)>,
+        top_k: usize,
+    ) -> Vec<(usize, f32)> {
+        let ranked: Vec<(usize, f32)> = ranked
+            .into_iter()
+            .filter(|(id, _)| self.documents.contains_key(id))
+            .collect();
+        if ranked.is_empty() {
+            return ranked;
+        }
+        let texts: Vec<&str> = ranked
+            .iter()
+            .map(|(id, _)| self.documents[id].content.as_str())
+            .collect();
+
+        let order = match reranker.rerank(query, &texts, top_k).await {
+            Ok(order) => order,
+            Err(e) => {
+                log::warn!("Reranking failed, using lexical overlap instead: {}", e);
+                LexicalReranker::new().rerank(query, &texts, top_k).await.unwrap_or_default()
+            }
+        };
+        log::debug!("Reranked {} candidates to {}", ranked.len(), order.len());
+        order
+            .into_iter()
+            .filter_map(|(index, score)| ranked.get(index).map(|(id, _)| (*id, score)))
+            .collect()
+    }
+
+    /// Reranks over-fetched candidates before they are cut to `top_k`
+    pub fn set_reranker(&mut self, reranker: Arc<dyn Reranker>) {
+        self.reranker = Some(reranker);
+    }
+
+    /// Adds a loader for another document format, taking precedence over the
+    /// built-in loaders for the same extensions and MIME types
+    pub fn register_loader(&mut self, loader: Arc<dyn DocumentLoader>) {
+        self.loaders.register(loader);
+    }
+
     pub fn config(&self) -> &RagConfig {
         &self.config
     }
 
     pub fn set_config(&mut self, config: RagConfig) {
         self.config = config.clone();
-        self.text_splitter = TextSplitter::new(config.chunk_size, config.chunk_overlap);
+        self.text_splitter.set_chunk_size(config.chunk_size, config.chunk_overlap);
     }
 
     pub fn stats(&self) -> RagStats {
@@ -422,19 +839,66 @@ impl Rag for RagSystem {
         query: &str,
         top_k: Option<usize>,
     ) -> Result<RetrievedContext, AgentError> {
-        let k = top_k.unwrap_or(self.config.top_k);
+        self.retrieve_filtered(query, top_k, &MetadataFilter::default()).await
+    }
 
-        log::info!("Retrieving top {} documents for query: {}", k, query);
+    async fn retrieve_filtered(
+        &self,
+        query: &str,
+        top_k: Option<usize>,
+        filter: &MetadataFilter,
+    ) -> Result<RetrievedContext, AgentError> {
+        let top_k = top_k.unwrap_or(self.config.top_k);
 
-        let query_embedding = self.embedding_generator.generate_embedding(query).await?;
-        let search_results = self.vector_store.search(&query_embedding, k).await?;
-        let mut context = RetrievedContext::new();
+        log::info!(
+            "Retrieving top {} documents ({:?}) for query: {}",
+            top_k,
+            self.config.retrieval_mode,
+            query
+        );
+        if !filter.is_empty() {
+            log::info!("Restricting search to documents matching {:?}", filter);
+        }
 
-        for result in search_results {
-            if result.score >= self.config.similarity_threshold {
-                if let Some(document) = self.documents.get(result.document_id) {
-                    context.add_document(document.clone(), document.source.clone(), result.score);
-                }
+        // Over-fetch when a reranker picks the final results
+        let k = match &self.reranker {
+            Some(_) => top_k.max(self.config.rerank_candidates),
+            None => top_k,
+        };
+        let mut ranked = match self.config.retrieval_mode {
+            RetrievalMode::Vector => self.vector_ranking(query, k, filter).await?,
+            RetrievalMode::Keyword => self.keyword_ranking(query, k, filter),
+            RetrievalMode::Hybrid => {
+                let candidates = k * HYBRID_CANDIDATE_FACTOR;
+                let vector: Vec<usize> = self
+                    .vector_ranking(query, candidates, filter)
+                    .await?
+                    .into_iter()
+                    .map(|(id, _)| id)
+                    .collect();
+                let keyword: Vec<usize> = self
+                    .keyword_ranking(query, candidates, filter)
+                    .into_iter()
+                    .map(|(id, _)| id)
+                    .collect();
+                let hybrid = &self.config.hybrid;
+                let mut fused = reciprocal_rank_fusion(
+                    &[(&vector, hybrid.vector_weight), (&keyword, hybrid.keyword_weight)],
+                    hybrid.rrf_k,
+                );
+                fused.truncate(k);
+                fused
+            }
+        };
+
+        if let Some(reranker) = &self.reranker {
+            ranked = self.rerank(reranker.as_ref(), query, ranked, top_k).await;
+        }
+
+        let mut context = RetrievedContext::new();
+        for (document_id, score) in ranked {
+            if let Some(document) = self.documents.get(&document_id) {
+                context.add_document(document.clone(), document.source.clone(), score);
             }
         }
 
@@ -462,6 +926,8 @@ impl Rag for RagSystem {
         log::info!("Clearing RAG system");
         self.documents.clear();
         self.vector_store.clear().await?;
+        self.keyword_index.clear();
+        self.manifest.clear();
         self.document_id_counter = 0;
         Ok(())
     }
@@ -473,7 +939,10 @@ impl Rag for RagSystem {
     async fn save(&self, path: &Path) -> Result<(), AgentError> {
         let data = RagSystemData {
             documents: self.documents.clone(),
+            keyword_index: Some(self.keyword_index.clone()),
+            manifest: self.manifest.clone(),
             config: self.config.clone(),
+            settings: Some(self.settings_fingerprint()),
             document_id_counter: self.document_id_counter,
         };
 
@@ -492,12 +961,7 @@ impl Rag for RagSystem {
         path: &Path,
         _embedding_llm_unused: Box<dyn LLM>,
     ) -> Result<Box<dyn Rag>, AgentError> {
-        let json_data = tokio::fs::read_to_string(path)
-            .await
-            .map_err(|e| AgentError::RagError(format!("Failed to read RAG data file: {}", e)))?;
-
-        let data: RagSystemData = serde_json::from_str(&json_data)
-            .map_err(|e| AgentError::RagError(format!("Failed to deserialize RAG data: {}", e)))?;
+        let data = read_system_data(path).await?;
 
         let provider_name =
             std::env::var("RAG_EMBEDDING_PROVIDER").unwrap_or_else(|_| "openai".to_string());
@@ -532,6 +996,14 @@ impl Rag for RagSystem {
                         "Successfully loaded PersistentVectorStore with {} embeddings.",
                         loaded_store.document_count()
                     );
+                    let stored_model = loaded_store.embedding_model();
+                    if !stored_model.is_empty() && stored_model != data.config.embedding_model_name {
+                        log::warn!(
+                            "Vector store was embedded with '{}' but '{}' is configured.",
+                            stored_model,
+                            data.config.embedding_model_name
+                        );
+                    }
                     if let Some(loaded_dim) = loaded_store.embedding_dimension() {
                         if loaded_dim != embedding_generator_for_load.embedding_dimension() {
                             log::warn!(
@@ -545,11 +1017,11 @@ impl Rag for RagSystem {
                 Err(e) => {
                     log::warn!("Failed to load PersistentVectorStore from {}: {}. Falling back to in-memory and re-embedding.", persistent_path.display(), e);
                     let mut in_memory_store = InMemoryVectorStore::new();
-                    for (doc_id, document_metadata) in data.documents.iter().enumerate() {
+                    for (doc_id, document_metadata) in &data.documents {
                         let embedding = embedding_generator_for_load
                             .generate_embedding(&document_metadata.content)
                             .await?;
-                        in_memory_store.add_document(doc_id, embedding).await?;
+                        in_memory_store.add_document(*doc_id, embedding).await?;
                     }
                     vector_store = Box::new(in_memory_store);
                 }
@@ -557,22 +1029,29 @@ impl Rag for RagSystem {
         } else {
             log::info!("No persistent_vector_store_path in config. Using InMemoryVectorStore and re-embedding.");
             let mut in_memory_store = InMemoryVectorStore::new();
-            for (doc_id, document_metadata) in data.documents.iter().enumerate() {
+            for (doc_id, document_metadata) in &data.documents {
                 let embedding = embedding_generator_for_load
                     .generate_embedding(&document_metadata.content)
                     .await?;
-                in_memory_store.add_document(doc_id, embedding).await?;
+                in_memory_store.add_document(*doc_id, embedding).await?;
             }
             vector_store = Box::new(in_memory_store);
         }
 
         let text_splitter = TextSplitter::new(data.config.chunk_size, data.config.chunk_overlap);
+        let keyword_index = data
+            .keyword_index
+            .unwrap_or_else(|| keyword_index_for(&data.documents));
 
         let rag_system = RagSystem {
             vector_store,
             embedding_generator: embedding_generator_for_load,
             text_splitter,
             documents: data.documents,
+            keyword_index,
+            manifest: data.manifest,
+            loaders: DocumentLoaders::default(),
+            reranker: None,
             config: data.config,
             document_id_counter: data.document_id_counter,
         };
@@ -596,11 +1075,120 @@ pub struct RagStats {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct RagSystemData {
-    documents: Vec<RagDocument>,
+    #[serde(deserialize_with = "deserialize_chunks")]
+    documents: BTreeMap<usize, RagDocument>,
+    /// Missing from files saved before keyword search existed
+    #[serde(default)]
+    keyword_index: Option<Bm25Index>,
+    /// Missing from files saved before incremental re-indexing existed
+    #[serde(default)]
+    manifest: SourceManifest,
     config: RagConfig,
+    /// Splitter and embedding settings the chunks were made with; missing from
+    /// files saved before they were recorded, which are taken to match
+    #[serde(default)]
+    settings: Option<String>,
     document_id_counter: usize,
 }
 
+/// Reads chunks keyed by document id, or as a list indexed by position as they
+/// were saved before chunks could be removed
+fn deserialize_chunks<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<usize, RagDocument>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Chunks {
+        // Untagged buffering keeps JSON object keys as strings
+        ById(BTreeMap<String, RagDocument>),
+        List(Vec<RagDocument>),
+    }
+
+    match Chunks::deserialize(deserializer)? {
+        Chunks::ById(chunks) => chunks
+            .into_iter()
+            .map(|(id, chunk)| {
+                id.parse()
+                    .map(|id| (id, chunk))
+                    .map_err(|_| serde::de::Error::custom(format!("invalid document id {}", id)))
+            })
+            .collect(),
+        Chunks::List(chunks) => Ok(chunks.into_iter().enumerate().collect()),
+    }
+}
+
+async fn read_system_data(path: &Path) -> Result<RagSystemData, AgentError> {
+    let json_data = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| AgentError::RagError(format!("Failed to read RAG data file: {}", e)))?;
+
+    serde_json::from_str(&json_data)
+        .map_err(|e| AgentError::RagError(format!("Failed to deserialize RAG data: {}", e)))
+}
+
+/// Adds what the splitter found about a chunk to the metadata of its source and
+/// section. Keys the loader or caller already set are kept, and so is the
+/// section number of a section they named.
+fn merge_splitter_metadata(metadata: &mut HashMap<String, String>, splitter_metadata: HashMap<String, String>) {
+    let has_section = metadata.contains_key(SECTION_KEY);
+    for (key, value) in splitter_metadata {
+        if has_section && key == SECTION_NUMBER_KEY {
+            continue;
+        }
+        metadata.entry(key).or_insert(value);
+    }
+}
+
+/// Rebuilds the keyword index of chunks stored by document id
+fn keyword_index_for(documents: &BTreeMap<usize, RagDocument>) -> Bm25Index {
+    let mut index = Bm25Index::new();
+    for (document_id, document) in documents {
+        index.add_document(*document_id, &document.content);
+    }
+    index
+}
+
+/// The text of one version of a source, ready to be indexed
+struct SourceContent {
+    source: String,
+    /// Metadata of the source, without that of its sections
+    metadata: HashMap<String, String>,
+    hash: String,
+    /// Sections of the source, each split into chunks separately
+    parts: Vec<RagDocument>,
+}
+
+/// Extracts the sections of a document off the async runtime, since parsing
+/// large PDFs is CPU-bound
+async fn extract_content(
+    loader: Arc<dyn DocumentLoader>,
+    source: String,
+    bytes: Vec<u8>,
+    metadata: HashMap<String, String>,
+) -> Result<SourceContent, AgentError> {
+    let hash = content_hash(&bytes);
+    let sections: Vec<DocumentSection> = tokio::task::spawn_blocking(move || loader.load(&bytes))
+        .await
+        .map_err(|e| AgentError::RagError(format!("Document extraction failed: {}", e)))?
+        .map_err(|e| AgentError::RagError(format!("Failed to extract text from {}: {}", source, e)))?;
+
+    let parts = sections
+        .into_iter()
+        .filter(|section| !section.text.trim().is_empty())
+        .map(|section| {
+            let mut part_metadata = metadata.clone();
+            part_metadata.extend(section.metadata);
+            RagDocument::new(section.text, source.clone()).with_metadata(part_metadata)
+        })
+        .collect();
+    Ok(SourceContent {
+        source,
+        metadata,
+        hash,
+        parts,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -634,6 +1222,7 @@ mod tests {
             reranker_model_name: None,
             persistent_vector_store_path: None,
             embedding_cache: EmbeddingCacheConfig::default(),
+            ..Default::default()
         };
 
         let rag = RagSystem::with_config(config.clone());
@@ -656,6 +1245,7 @@ mod tests {
                 persistent: false,
                 ..Default::default()
             },
+            ..Default::default()
         };
 
         let rag = RagSystem::with_config_cached(config.clone()).await.unwrap();
@@ -723,8 +1313,10 @@ mod tests {
 
     #[tokio::test]
     async fn test_retrieve_with_threshold() {
-        let mut config = RagConfig::default();
-        config.similarity_threshold = 0.9; // Very high threshold
+        let config = RagConfig {
+            similarity_threshold: 0.9, // Very high threshold
+            ..RagConfig::default()
+        };
 
         let mut rag = RagSystem::with_config(config);
 
@@ -739,4 +1331,378 @@ mod tests {
 
         assert!(results.len() <= 1); // Might be 0 or 1 depending on hash collision
     }
+
+    #[tokio::test]
+    async fn test_directory_source_options_and_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("guide/drafts")).unwrap();
+        std::fs::write(dir.path().join("guide/intro.adoc"), "Introduction").unwrap();
+        std::fs::write(dir.path().join("guide/drafts/todo.adoc"), "Unfinished").unwrap();
+        std::fs::write(dir.path().join("README.md"), "Not an adoc file").unwrap();
+
+        let options = LoadOptions {
+            recursive: true,
+            include_extensions: vec!["adoc".to_string()],
+            exclude_patterns: vec!["drafts/".to_string()],
+            metadata: HashMap::from([("team".to_string(), "docs".to_string())]),
+            ..Default::default()
+        };
+        let mut rag = RagSystem::new_with_dummy();
+        rag.add_documents_with_options(&[dir.path().to_path_buf()], &options)
+            .await
+            .unwrap();
+
+        assert_eq!(rag.document_count(), 1);
+        let chunk = &rag.documents[&0];
+        assert!(chunk.source.ends_with("intro.adoc"));
+        assert_eq!(chunk.metadata.get("team").map(String::as_str), Some("docs"));
+        assert_eq!(chunk.metadata.get("chunk_index").map(String::as_str), Some("0"));
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_filtered_by_source_and_metadata() {
+        let config = RagConfig {
+            similarity_threshold: -1.0,
+            ..RagConfig::default()
+        };
+        let mut rag = RagSystem::with_config(config);
+
+        let version = |v: &str| HashMap::from([("version".to_string(), v.to_string())]);
+        rag.add_documents(vec![
+            RagDocument::new("Authentication tokens".to_string(), "docs/api/auth.md".to_string())
+                .with_metadata(version("v2")),
+            RagDocument::new("Authentication tokens".to_string(), "docs/api/auth_v1.md".to_string())
+                .with_metadata(version("v1")),
+            RagDocument::new("Authentication tokens".to_string(), "docs/guide/auth.md".to_string())
+                .with_metadata(version("v2")),
+        ])
+        .await
+        .unwrap();
+
+        let filter = MetadataFilter::new().prefix("source", "docs/api/").eq("version", "v2");
+        let results = rag.retrieve_filtered("authentication", Some(5), &filter).await.unwrap();
+        assert_eq!(results.sources, vec!["docs/api/auth.md"]);
+
+        let filter = MetadataFilter::new().one_of("version", ["v1", "v3"]);
+        let results = rag.retrieve_filtered("authentication", Some(1), &filter).await.unwrap();
+        assert_eq!(results.sources, vec!["docs/api/auth_v1.md"]);
+    }
+
+    fn identifier_documents() -> Vec<RagDocument> {
+        vec![
+            RagDocument::new("Set max_retries in the client config".to_string(), "retry.md".to_string()),
+            RagDocument::new(
+                "ERR_CONN_RESET is returned when the peer resets the connection".to_string(),
+                "errors.md".to_string(),
+            ),
+            RagDocument::new("Boil the pasta for ten minutes".to_string(), "food.md".to_string()),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_keyword_and_hybrid_retrieval() {
+        let config = RagConfig {
+            retrieval_mode: RetrievalMode::Keyword,
+            ..RagConfig::default()
+        };
+        let mut rag = RagSystem::with_config(config);
+        rag.add_documents(identifier_documents()).await.unwrap();
+
+        let results = rag.retrieve("what does ERR_CONN_RESET mean", Some(5)).await.unwrap();
+        assert_eq!(results.sources, vec!["errors.md"]);
+
+        // Every chunk is a vector candidate, the keyword match still comes first
+        let mut config = rag.config().clone();
+        config.retrieval_mode = RetrievalMode::Hybrid;
+        config.similarity_threshold = -1.0;
+        rag.set_config(config);
+        let results = rag.retrieve("ERR_CONN_RESET", Some(5)).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results.sources[0], "errors.md");
+
+        let filter = MetadataFilter::new().eq("source", "retry.md");
+        let results = rag.retrieve_filtered("ERR_CONN_RESET", Some(5), &filter).await.unwrap();
+        assert_eq!(results.sources, vec!["retry.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_keyword_index_is_saved() {
+        let mut rag = RagSystem::new_with_dummy();
+        rag.add_documents(identifier_documents()).await.unwrap();
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        rag.save(file.path()).await.unwrap();
+        let mut saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(file.path()).unwrap()).unwrap();
+        let data: RagSystemData = serde_json::from_value(saved.clone()).unwrap();
+        let index = data.keyword_index.unwrap();
+        assert_eq!(index.document_count(), 3);
+
+        // Older files without an index get one rebuilt from the chunks
+        saved.as_object_mut().unwrap().remove("keyword_index");
+        let data: RagSystemData = serde_json::from_value(saved).unwrap();
+        assert!(data.keyword_index.is_none());
+        let rebuilt = keyword_index_for(&data.documents);
+        assert_eq!(
+            rebuilt.search("max_retries", 5, &|_| true),
+            index.search("max_retries", 5, &|_| true)
+        );
+    }
+
+    fn touch(path: &Path, seconds: u64) {
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reindex_embeds_only_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.md", "b.md", "c.md"] {
+            std::fs::write(dir.path().join(name), format!("Contents of {}", name)).unwrap();
+        }
+        let paths = [dir.path().to_path_buf()];
+        let mut options = LoadOptions::default();
+        let mut rag = RagSystem::new_with_dummy();
+
+        let report = rag.reindex(&paths, &options).await.unwrap();
+        assert_eq!((report.added.len(), report.chunks_embedded), (3, 3));
+        let report = rag.reindex(&paths, &options).await.unwrap();
+        assert_eq!((report.unchanged.len(), report.chunks_embedded), (3, 0));
+        assert!(!report.has_changes());
+
+        std::fs::write(dir.path().join("b.md"), "New contents of b.md").unwrap();
+        touch(&dir.path().join("b.md"), 1_000_000);
+        // A new modification time alone does not cause re-embedding
+        touch(&dir.path().join("a.md"), 1_000_000);
+        std::fs::remove_file(dir.path().join("c.md")).unwrap();
+        std::fs::write(dir.path().join("d.md"), "Contents of d.md").unwrap();
+
+        let report = rag.reindex(&paths, &options).await.unwrap();
+        let name = |sources: &[String]| -> Vec<String> {
+            sources.iter().map(|s| s.rsplit('/').next().unwrap().to_string()).collect()
+        };
+        assert_eq!(name(&report.added), vec!["d.md"]);
+        assert_eq!(name(&report.updated), vec!["b.md"]);
+        assert_eq!(name(&report.removed), vec!["c.md"]);
+        assert_eq!(name(&report.unchanged), vec!["a.md"]);
+        assert_eq!((report.chunks_embedded, report.chunks_removed), (2, 2));
+
+        assert_eq!(rag.document_count(), 3);
+        assert_eq!(rag.vector_store.document_count(), 3);
+        assert_eq!(rag.keyword_index.document_count(), 3);
+        assert!(rag.documents.values().all(|d| !d.source.ends_with("c.md")));
+        assert!(rag.documents.values().any(|d| d.content == "New contents of b.md"));
+
+        // Changed source metadata relabels the chunks without embedding them
+        options.metadata = HashMap::from([("team".to_string(), "docs".to_string())]);
+        let report = rag.reindex(&paths, &options).await.unwrap();
+        assert_eq!((report.updated.len(), report.chunks_embedded), (3, 0));
+        assert!(rag.documents.values().all(|d| d.metadata.contains_key("team")));
+    }
+
+    #[tokio::test]
+    async fn test_relabelling_keeps_loader_metadata() {
+        let mut rag = RagSystem::new_with_dummy();
+        let html = b"<html><head><title>Guide</title></head><body><p>Install it.</p></body></html>".to_vec();
+        let metadata = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+
+        rag.index_content("guide.html", html.clone(), Some("text/html"), metadata(&[("title", "Configured"), ("team", "a")]))
+            .await
+            .unwrap();
+        let chunk = rag.documents.values().next().unwrap();
+        assert_eq!((chunk.metadata["title"].as_str(), chunk.metadata["team"].as_str()), ("Guide", "a"));
+
+        let report = rag
+            .index_content("guide.html", html, Some("text/html"), metadata(&[("audience", "ops")]))
+            .await
+            .unwrap();
+        assert_eq!((report.updated.len(), report.chunks_embedded), (1, 0));
+        let chunk = rag.documents.values().next().unwrap();
+        assert_eq!(chunk.metadata["title"], "Guide");
+        assert_eq!(chunk.metadata["audience"], "ops");
+        assert!(!chunk.metadata.contains_key("team"));
+        assert_eq!(chunk.metadata["chunk_index"], "0");
+    }
+
+    #[tokio::test]
+    async fn test_splitter_metadata_reaches_chunks() {
+        let mut rag = RagSystem::with_components(
+            RagConfig::default(),
+            Box::new(InMemoryVectorStore::new()),
+            Box::new(DummyEmbeddingGenerator::new()),
+            TextSplitter::new(1000, 0).with_strategy(crate::rag::SplitStrategy::Markdown),
+        );
+        let document = RagDocument::new("# Setup\nInstall it.\n\n# Usage\nRun it.".to_string(), "guide.md".to_string())
+            .with_metadata(HashMap::from([("team".to_string(), "docs".to_string())]));
+        rag.add_documents(vec![document]).await.unwrap();
+
+        let usage = rag.documents.values().find(|d| d.content.contains("Run it")).unwrap();
+        assert_eq!(usage.metadata["section"], "Usage");
+        assert_eq!(usage.metadata["section_number"], "2");
+        assert_eq!(usage.metadata["team"], "docs");
+    }
+
+    #[tokio::test]
+    async fn test_loader_sections_win_over_splitter_headings() {
+        let mut rag = RagSystem::with_components(
+            RagConfig::default(),
+            Box::new(InMemoryVectorStore::new()),
+            Box::new(DummyEmbeddingGenerator::new()),
+            TextSplitter::new(1000, 0).with_strategy(crate::rag::SplitStrategy::Markdown),
+        );
+        let document = RagDocument::new("# Usage\nRun it.".to_string(), "manual.pdf".to_string())
+            .with_metadata(HashMap::from([("section".to_string(), "Getting started".to_string())]));
+        rag.add_documents(vec![document]).await.unwrap();
+
+        let usage = rag.documents.values().find(|d| d.content.contains("Run it")).unwrap();
+        assert_eq!(usage.metadata["section"], "Getting started");
+        assert!(!usage.metadata.contains_key("section_number"));
+    }
+
+    #[tokio::test]
+    async fn test_loaders_label_chunks_with_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let page = "<html><body><nav>Home</nav><main><h1>Setup</h1><p>Install it.</p>\
+                    <h2>Linux</h2><p>Use the tarball.</p></main></body></html>";
+        std::fs::write(dir.path().join("guide.html"), page).unwrap();
+        let mut rag = RagSystem::new_with_dummy();
+
+        let options = LoadOptions {
+            metadata: HashMap::from([("team".to_string(), "docs".to_string())]),
+            ..Default::default()
+        };
+        let report = rag.reindex(&[dir.path().to_path_buf()], &options).await.unwrap();
+        assert_eq!(report.chunks_embedded, 2);
+        let linux = rag.documents.values().find(|d| d.content.contains("tarball")).unwrap();
+        assert_eq!(linux.content, "Linux\n\nUse the tarball.");
+        assert_eq!(linux.metadata["section"], "Setup > Linux");
+        assert_eq!(linux.metadata["section_number"], "1.1");
+        assert_eq!(linux.metadata["team"], "docs");
+        assert!(rag.documents.values().all(|d| !d.content.contains("Home")));
+
+        // Fetched content is picked by MIME type, whatever the URL looks like
+        let report = rag
+            .index_content(
+                "https://example.com/guide",
+                page.as_bytes().to_vec(),
+                Some("text/html; charset=utf-8"),
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(report.added, vec!["https://example.com/guide"]);
+        assert!(rag.documents.values().all(|d| !d.content.contains('<')));
+    }
+
+    #[tokio::test]
+    async fn test_saved_state_avoids_reembedding() {
+        let dir = tempfile::tempdir().unwrap();
+        let docs = dir.path().join("docs");
+        std::fs::create_dir(&docs).unwrap();
+        std::fs::write(docs.join("guide.md"), "Setup guide").unwrap();
+        std::fs::write(docs.join("faq.md"), "Questions").unwrap();
+        let store_path = dir.path().join("vectors.bin");
+        let state_path = dir.path().join("rag_state.json");
+        let paths = [docs.clone()];
+
+        let mut rag = RagSystem::with_persistent_storage(
+            RagConfig::default(),
+            &store_path,
+            Box::new(DummyEmbeddingGenerator::new()),
+        );
+        rag.reindex(&paths, &LoadOptions::default()).await.unwrap();
+        rag.save(&state_path).await.unwrap();
+
+        let store = PersistentVectorStore::load(&store_path).await.unwrap();
+        let mut restarted = RagSystem::with_components(
+            RagConfig::default(),
+            Box::new(store),
+            Box::new(DummyEmbeddingGenerator::new()),
+            TextSplitter::new(1000, 200),
+        );
+        restarted.load_state(&state_path).await.unwrap();
+        std::fs::remove_file(docs.join("faq.md")).unwrap();
+        let report = restarted.reindex(&paths, &LoadOptions::default()).await.unwrap();
+        assert_eq!(report.unchanged.len(), 1);
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(report.chunks_embedded, 0);
+        assert_eq!(restarted.vector_store.document_count(), 1);
+
+        // State that does not match the vector store is dropped
+        let mut fresh = RagSystem::new_with_dummy();
+        fresh.load_state(&state_path).await.unwrap();
+        assert_eq!(fresh.document_count(), 0);
+        let report = fresh.reindex(&paths, &LoadOptions::default()).await.unwrap();
+        assert_eq!(report.added.len(), 1);
+
+        // So is state for as many chunks but with other ids
+        restarted.save(&state_path).await.unwrap();
+        let mut other_ids = InMemoryVectorStore::new();
+        other_ids.add_document(999, vec![1.0; 384]).await.unwrap();
+        let mut mismatched = RagSystem::with_components(
+            RagConfig::default(),
+            Box::new(other_ids),
+            Box::new(DummyEmbeddingGenerator::new()),
+            TextSplitter::new(1000, 200),
+        );
+        mismatched.load_state(&state_path).await.unwrap();
+        assert_eq!(mismatched.document_count(), 0);
+        assert_eq!(mismatched.vector_store.document_count(), 0);
+
+        // And state saved with another chunk size
+        let store = PersistentVectorStore::load(&store_path).await.unwrap();
+        let mut resized = RagSystem::with_components(
+            RagConfig::default(),
+            Box::new(store),
+            Box::new(DummyEmbeddingGenerator::new()),
+            TextSplitter::new(500, 100),
+        );
+        resized.load_state(&state_path).await.unwrap();
+        assert_eq!(resized.document_count(), 0);
+        assert_eq!(resized.vector_store.document_count(), 0);
+        let report = resized.reindex(&paths, &LoadOptions::default()).await.unwrap();
+        assert_eq!(report.added.len(), 1);
+
+        // Files from before chunks were keyed by id list them by position
+        let legacy = serde_json::json!({
+            "documents": [{ "content": "x", "metadata": {}, "source": "x.md", "chunk_index": 0 }],
+            "config": RagConfig::default(),
+            "document_id_counter": 1
+        });
+        let data: RagSystemData = serde_json::from_value(legacy).unwrap();
+        assert_eq!(data.documents[&0].source, "x.md");
+        assert!(data.manifest.is_empty());
+    }
+
+    struct FailingReranker;
+
+    #[async_trait]
+    impl Reranker for FailingReranker {
+        async fn rerank(&self, _: &str, _: &[&str], _: usize) -> Result<Vec<(usize, f32)>, AgentError> {
+            Err(AgentError::RagError("service unavailable".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reranker_picks_from_overfetched_candidates() {
+        let config = RagConfig {
+            similarity_threshold: -1.0,
+            ..RagConfig::default()
+        };
+        let mut rag = RagSystem::with_config(config);
+        rag.add_documents(identifier_documents()).await.unwrap();
+
+        rag.set_reranker(Arc::new(LexicalReranker::new()));
+        let results = rag.retrieve("when is ERR_CONN_RESET returned", Some(1)).await.unwrap();
+        assert_eq!(results.sources, vec!["errors.md"]);
+
+        // A failing reranker falls back to lexical overlap
+        rag.set_reranker(Arc::new(FailingReranker));
+        let results = rag.retrieve("pasta minutes", Some(1)).await.unwrap();
+        assert_eq!(results.sources, vec!["food.md"]);
+        assert_eq!(results.scores, vec![1.0]);
+    }
 }
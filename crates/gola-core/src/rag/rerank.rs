@@ -0,0 +1,324 @@
+//! Reranking of retrieved candidates
+//!
+//! Retrieval over-fetches candidates and a [`Reranker`] orders them by relevance
+//! to the query before they are cut to `top_k`. Three rerankers are provided:
+//! listwise ordering by an LLM, Cohere/Jina-compatible `/rerank` endpoints, and a
+//! lexical overlap score that needs no external service.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::core_types::{Message, Role};
+use crate::errors::AgentError;
+use crate::llm::LLM;
+use crate::rag::bm25::tokenize;
+
+const LLM_RERANK_PROMPT: &str = "You rank passages by how well they answer a search query. \
+Reply with only a JSON array of passage numbers, most relevant first, for example [3, 1, 2]. \
+Leave out passages that are irrelevant.";
+
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Orders `documents` by relevance to `query`
+    ///
+    /// Returns at most `top_k` pairs of an index into `documents` and a relevance
+    /// score, best first.
+    async fn rerank(
+        &self,
+        query: &str,
+        documents: &[&str],
+        top_k: usize,
+    ) -> Result<Vec<(usize, f32)>, AgentError>;
+}
+
+/// Scores documents by the share of query terms they contain
+#[derive(Debug, Clone, Default)]
+pub struct LexicalReranker;
+
+impl LexicalReranker {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn rank(query: &str, documents: &[&str], top_k: usize) -> Vec<(usize, f32)> {
+        let terms: HashSet<String> = tokenize(query).into_iter().collect();
+        let mut scored: Vec<(usize, f32)> = documents
+            .iter()
+            .enumerate()
+            .map(|(index, document)| {
+                if terms.is_empty() {
+                    return (index, 0.0);
+                }
+                let words: HashSet<String> = tokenize(document).into_iter().collect();
+                let found = terms.iter().filter(|term| words.contains(*term)).count();
+                (index, found as f32 / terms.len() as f32)
+            })
+            .collect();
+        // Stable, so ties keep the retrieval order
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(top_k);
+        scored
+    }
+}
+
+#[async_trait]
+impl Reranker for LexicalReranker {
+    async fn rerank(
+        &self,
+        query: &str,
+        documents: &[&str],
+        top_k: usize,
+    ) -> Result<Vec<(usize, f32)>, AgentError> {
+        Ok(Self::rank(query, documents, top_k))
+    }
+}
+
+/// Asks an LLM to order all candidates at once
+pub struct LlmReranker {
+    llm: Arc<dyn LLM>,
+}
+
+impl LlmReranker {
+    pub fn new(llm: Arc<dyn LLM>) -> Self {
+        Self { llm }
+    }
+}
+
+#[async_trait]
+impl Reranker for LlmReranker {
+    async fn rerank(
+        &self,
+        query: &str,
+        documents: &[&str],
+        top_k: usize,
+    ) -> Result<Vec<(usize, f32)>, AgentError> {
+        let mut request = format!("Query: {}\n\n", query);
+        for (index, document) in documents.iter().enumerate() {
+            request.push_str(&format!("Passage {}:\n{}\n\n", index + 1, document));
+        }
+
+        let messages = vec![
+            Message {
+                role: Role::System,
+                content: LLM_RERANK_PROMPT.to_string(),
+                tool_call_id: None,
+                tool_calls: None,
+                reasoning: None,
+            },
+            Message {
+                role: Role::User,
+                content: request,
+                tool_call_id: None,
+                tool_calls: None,
+                reasoning: None,
+            },
+        ];
+        let reply = self.llm.generate(messages, None).await?.content.unwrap_or_default();
+        let order = parse_ranking(&reply, documents.len()).ok_or_else(|| {
+            AgentError::ParsingError(format!("Unreadable ranking from reranker: {}", reply))
+        })?;
+
+        let count = order.len() as f32;
+        Ok(order
+            .into_iter()
+            .take(top_k)
+            .enumerate()
+            .map(|(rank, index)| (index, 1.0 - rank as f32 / count))
+            .collect())
+    }
+}
+
+/// Reads the 1-based passage numbers from the reply, which may wrap them in prose,
+/// and returns them as distinct indices
+fn parse_ranking(reply: &str, count: usize) -> Option<Vec<usize>> {
+    let start = reply.find('[')?;
+    let end = reply.rfind(']')?;
+    if end < start {
+        return None;
+    }
+    let numbers: Vec<usize> = serde_json::from_str(&reply[start..=end]).ok()?;
+
+    let mut seen = HashSet::new();
+    Some(
+        numbers
+            .into_iter()
+            .filter(|&number| number >= 1 && number <= count)
+            .map(|number| number - 1)
+            .filter(|&index| seen.insert(index))
+            .collect(),
+    )
+}
+
+/// Calls a Cohere or Jina compatible rerank endpoint
+pub struct HttpReranker {
+    client: Client,
+    endpoint: String,
+    api_key: Option<String>,
+    model: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RerankResponse {
+    results: Vec<RerankResult>,
+}
+
+#[derive(Deserialize)]
+struct RerankResult {
+    index: usize,
+    relevance_score: f32,
+}
+
+impl HttpReranker {
+    /// `endpoint` is the full URL, such as `https://api.cohere.com/v2/rerank`
+    pub fn new(endpoint: String, api_key: Option<String>, model: Option<String>) -> Self {
+        Self {
+            client: crate::http::client(),
+            endpoint,
+            api_key,
+            model,
+        }
+    }
+}
+
+#[async_trait]
+impl Reranker for HttpReranker {
+    async fn rerank(
+        &self,
+        query: &str,
+        documents: &[&str],
+        top_k: usize,
+    ) -> Result<Vec<(usize, f32)>, AgentError> {
+        let mut body = json!({
+            "query": query,
+            "documents": documents,
+            "top_n": top_k,
+        });
+        if let Some(model) = &self.model {
+            body["model"] = json!(model);
+        }
+
+        let mut request = self.client.post(&self.endpoint).json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AgentError::RagError(format!("Rerank request failed: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(AgentError::RagError(format!(
+                "Rerank endpoint returned {}: {}",
+                status, text
+            )));
+        }
+
+        let parsed: RerankResponse = response
+            .json()
+            .await
+            .map_err(|e| AgentError::RagError(format!("Invalid rerank response: {}", e)))?;
+
+        let mut results: Vec<(usize, f32)> = parsed
+            .results
+            .into_iter()
+            .filter(|result| result.index < documents.len())
+            .map(|result| (result.index, result.relevance_score))
+            .collect();
+        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        results.truncate(top_k);
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_types::LLMResponse;
+    use crate::llm::ToolMetadata;
+    use axum::{http::HeaderMap, routing::post, Json, Router};
+    use serde_json::Value;
+
+    const DOCUMENTS: [&str; 3] = [
+        "Boil the pasta for ten minutes",
+        "Set max_retries in the client config",
+        "The client retries failed requests",
+    ];
+
+    struct RankingLLM;
+
+    #[async_trait]
+    impl LLM for RankingLLM {
+        async fn generate(
+            &self,
+            messages: Vec<Message>,
+            _tools: Option<Vec<ToolMetadata>>,
+        ) -> Result<LLMResponse, AgentError> {
+            assert!(messages[1].content.contains("Passage 3:\nThe client retries"));
+            Ok(LLMResponse {
+                content: Some("Ranking: [2, 3, 2, 9]".to_string()),
+                tool_calls: None,
+                finish_reason: None,
+                usage: None,
+                reasoning: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lexical_reranker() {
+        let ranked = LexicalReranker::new()
+            .rerank("client max_retries", &DOCUMENTS, 2)
+            .await
+            .unwrap();
+        assert_eq!(ranked, vec![(1, 1.0), (2, 0.5)]);
+    }
+
+    #[tokio::test]
+    async fn test_llm_reranker() {
+        let ranked = LlmReranker::new(Arc::new(RankingLLM))
+            .rerank("how are retries configured", &DOCUMENTS, 5)
+            .await
+            .unwrap();
+        // Duplicates and out-of-range numbers are dropped
+        assert_eq!(ranked.iter().map(|r| r.0).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(ranked[0].1 > ranked[1].1);
+
+        assert_eq!(parse_ranking("no ranking here", 3), None);
+    }
+
+    #[tokio::test]
+    async fn test_http_reranker() {
+        let app = Router::new().route(
+            "/v2/rerank",
+            post(|headers: HeaderMap, Json(body): Json<Value>| async move {
+                assert_eq!(headers["authorization"], "Bearer rerank-key");
+                assert_eq!(body["model"], "rerank-v3.5");
+                assert_eq!(body["top_n"], 2);
+                assert_eq!(body["documents"].as_array().unwrap().len(), 3);
+                Json(json!({ "results": [
+                    { "index": 2, "relevance_score": 0.4 },
+                    { "index": 1, "relevance_score": 0.9 }
+                ]}))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}/v2/rerank", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        let reranker = HttpReranker::new(
+            endpoint,
+            Some("rerank-key".to_string()),
+            Some("rerank-v3.5".to_string()),
+        );
+        let ranked = reranker.rerank("retries", &DOCUMENTS, 2).await.unwrap();
+        assert_eq!(ranked, vec![(1, 0.9), (2, 0.4)]);
+    }
+}
@@ -1,10 +1,107 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use tiktoken_rs::CoreBPE;
+
+use crate::errors::AgentError;
+use crate::rag::code_splitter::{split_code, CodeLanguage};
+use crate::rag::document_loader::{DocumentSection, Outline};
+use crate::rag::embeddings::{cosine_similarity, EmbeddingGenerator};
+
+/// Counts tokens, for chunk sizes measured in tokens
+pub trait Tokenizer: Send + Sync {
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+/// BPE tokenizer of the OpenAI models
+pub struct TiktokenTokenizer {
+    bpe: CoreBPE,
+}
+
+impl TiktokenTokenizer {
+    /// The `cl100k_base` encoding, used by the OpenAI embedding models
+    pub fn cl100k() -> Result<Self, AgentError> {
+        let bpe = tiktoken_rs::cl100k_base()
+            .map_err(|e| AgentError::RagError(format!("Failed to load tokenizer: {}", e)))?;
+        Ok(Self { bpe })
+    }
+}
+
+impl Tokenizer for TiktokenTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        self.bpe.encode_ordinary(text).len()
+    }
+}
+
+/// What chunk sizes and overlaps are measured in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkSizeUnit {
+    /// UTF-8 bytes, which is characters for ASCII text
+    #[default]
+    Characters,
+    /// Tokens of the `cl100k_base` encoding
+    Tokens,
+}
+
+/// Settings of the semantic split strategy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SemanticSplitConfig {
+    /// Percentile of the embedding distances between neighbouring sentences
+    /// above which a new chunk is started; lower values give smaller chunks
+    #[serde(default = "default_breakpoint_percentile")]
+    pub breakpoint_percentile: f32,
+    /// Sentences on each side embedded along with a sentence, which keeps short
+    /// sentences from causing spurious breaks
+    #[serde(default = "default_buffer_size")]
+    pub buffer_size: usize,
+}
+
+fn default_breakpoint_percentile() -> f32 {
+    95.0
+}
+
+fn default_buffer_size() -> usize {
+    1
+}
+
+impl Default for SemanticSplitConfig {
+    fn default() -> Self {
+        Self {
+            breakpoint_percentile: default_breakpoint_percentile(),
+            buffer_size: default_buffer_size(),
+        }
+    }
+}
+
+/// Where chunk boundaries are placed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum SplitStrategy {
+    /// Split at the first separator that makes pieces small enough
+    #[default]
+    Recursive,
+    /// Split at Markdown headings, recording the heading path of each chunk
+    Markdown,
+    /// Split where the meaning shifts, measured by the embedding distance
+    /// between neighbouring sentences
+    Semantic(SemanticSplitConfig),
+    /// Split source code at function, class and impl boundaries. With `None`
+    /// the language is taken from the file extension of each document, and
+    /// documents in other languages are split recursively.
+    Code(Option<CodeLanguage>),
+}
 
 /// Text splitter for breaking documents into chunks
 pub struct TextSplitter {
     chunk_size: usize,
     chunk_overlap: usize,
     separators: Vec<String>,
+    strategy: SplitStrategy,
+    /// Sizes are counted in tokens rather than characters when set
+    tokenizer: Option<Arc<dyn Tokenizer>>,
+    header_options: ChunkHeaderOptions,
 }
 
 impl TextSplitter {
@@ -18,6 +115,9 @@ impl TextSplitter {
                 " ".to_string(),
                 "".to_string(),
             ],
+            strategy: SplitStrategy::Recursive,
+            tokenizer: None,
+            header_options: ChunkHeaderOptions::default(),
         }
     }
 
@@ -26,9 +126,166 @@ impl TextSplitter {
         self
     }
 
+    pub fn with_strategy(mut self, strategy: SplitStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Measures chunk size and overlap in tokens of `tokenizer`
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = Some(tokenizer);
+        self
+    }
+
+    /// Prefixes chunks with headers, which do not count towards the chunk size
+    pub fn with_header_options(mut self, header_options: ChunkHeaderOptions) -> Self {
+        self.header_options = header_options;
+        self
+    }
+
+    /// Changes the chunk size and overlap, keeping the other settings
+    pub fn set_chunk_size(&mut self, chunk_size: usize, chunk_overlap: usize) {
+        self.chunk_size = chunk_size;
+        self.chunk_overlap = chunk_overlap;
+    }
+
+    pub fn strategy(&self) -> &SplitStrategy {
+        &self.strategy
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Describes every setting that shapes the chunks, so chunks saved by an
+    /// earlier run can be checked against the ones this splitter would make
+    pub fn fingerprint(&self) -> String {
+        let unit = if self.tokenizer.is_some() {
+            ChunkSizeUnit::Tokens
+        } else {
+            ChunkSizeUnit::Characters
+        };
+        format!(
+            "{} {} {:?} {:?} {:?} {:?}",
+            self.chunk_size, self.chunk_overlap, unit, self.strategy, self.separators, self.header_options
+        )
+    }
+
     /// Split text into chunks based on the configured parameters
+    ///
+    /// The semantic strategy needs embeddings and splits recursively here; use
+    /// [`split_document`](Self::split_document) for it.
     pub fn split_text(&self, text: &str) -> Vec<String> {
-        if text.len() <= self.chunk_size {
+        self.split_sections(text)
+            .into_iter()
+            .map(|section| section.text)
+            .collect()
+    }
+
+    /// Splits text into chunks with metadata, such as the Markdown heading path
+    pub fn split_sections(&self, text: &str) -> Vec<DocumentSection> {
+        match self.strategy {
+            SplitStrategy::Markdown => self.split_markdown(text),
+            SplitStrategy::Code(Some(language)) => self.split_code_or_plain(text, language),
+            SplitStrategy::Recursive | SplitStrategy::Semantic(_) | SplitStrategy::Code(None) => {
+                self.with_headers(self.split_plain(text), &DocumentMetadata::new())
+            }
+        }
+    }
+
+    /// Splits the text of the document `source` with any strategy, embedding
+    /// sentences with `embeddings` for the semantic one
+    pub async fn split_document(
+        &self,
+        source: &str,
+        text: &str,
+        embeddings: &dyn EmbeddingGenerator,
+    ) -> Result<Vec<DocumentSection>, AgentError> {
+        match &self.strategy {
+            SplitStrategy::Semantic(config) => self.split_semantic(text, config, embeddings).await,
+            SplitStrategy::Code(None) => Ok(match CodeLanguage::from_path(Path::new(source)) {
+                Some(language) => self.split_code_or_plain(text, language),
+                None => self.split_sections(text),
+            }),
+            _ => Ok(self.split_sections(text)),
+        }
+    }
+
+    fn split_code_or_plain(&self, text: &str, language: CodeLanguage) -> Vec<DocumentSection> {
+        split_code(self, text, language).unwrap_or_else(|| {
+            log::warn!("Could not parse {} source; splitting it as text", language.name());
+            self.with_headers(self.split_plain(text), &DocumentMetadata::new())
+        })
+    }
+
+    pub(crate) fn measure(&self, text: &str) -> usize {
+        match &self.tokenizer {
+            Some(tokenizer) => tokenizer.count_tokens(text),
+            None => text.len(),
+        }
+    }
+
+    /// End of the longest prefix of `text` that fits in `budget`
+    fn prefix_end(&self, text: &str, budget: usize) -> usize {
+        match &self.tokenizer {
+            Some(tokenizer) => {
+                let boundaries = char_boundaries(text);
+                let fitting =
+                    boundaries.partition_point(|&end| tokenizer.count_tokens(&text[..end]) <= budget);
+                boundaries[fitting.saturating_sub(1)]
+            }
+            None => {
+                let mut end = budget.min(text.len());
+                while end > 0 && !text.is_char_boundary(end) {
+                    end -= 1;
+                }
+                end
+            }
+        }
+    }
+
+    /// Start of the suffix of `text` that is about `budget` long
+    fn suffix_start(&self, text: &str, budget: usize) -> usize {
+        match &self.tokenizer {
+            Some(tokenizer) => {
+                let boundaries = char_boundaries(text);
+                let too_long =
+                    boundaries.partition_point(|&start| tokenizer.count_tokens(&text[start..]) > budget);
+                boundaries[too_long.min(boundaries.len() - 1)]
+            }
+            None => {
+                let mut start = text.len().saturating_sub(budget);
+                while start > 0 && !text.is_char_boundary(start) {
+                    start -= 1;
+                }
+                start
+            }
+        }
+    }
+
+    /// Prefixes each piece of one run of text with the configured headers
+    pub(crate) fn with_headers(&self, pieces: Vec<String>, metadata: &DocumentMetadata) -> Vec<DocumentSection> {
+        pieces
+            .into_iter()
+            .enumerate()
+            .map(|(i, piece)| {
+                let mut text = self.header_options.chunk_header.clone();
+                if i > 0 {
+                    if let Some(overlap_header) = &self.header_options.chunk_overlap_header {
+                        text.push_str(overlap_header);
+                    }
+                }
+                text.push_str(&piece);
+                DocumentSection {
+                    text,
+                    metadata: metadata.clone(),
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn split_plain(&self, text: &str) -> Vec<String> {
+        if self.measure(text) <= self.chunk_size {
             return vec![text.to_string()];
         }
 
@@ -58,7 +315,7 @@ impl TextSplitter {
                 format!("{}{}{}", current_chunk, separator, split)
             };
 
-            if potential_chunk.len() <= self.chunk_size {
+            if self.measure(&potential_chunk) <= self.chunk_size {
                 current_chunk = potential_chunk;
             } else {
                 // Current chunk is ready, process it
@@ -66,14 +323,8 @@ impl TextSplitter {
                     final_chunks.push(current_chunk.clone());
 
                     // Handle overlap
-                    if self.chunk_overlap > 0 && current_chunk.len() > self.chunk_overlap {
-                        let mut overlap_start = current_chunk.len() - self.chunk_overlap;
-                        
-                        // Ensure overlap_start is on a character boundary
-                        while overlap_start > 0 && !current_chunk.is_char_boundary(overlap_start) {
-                            overlap_start -= 1;
-                        }
-                        
+                    if self.chunk_overlap > 0 && self.measure(&current_chunk) > self.chunk_overlap {
+                        let overlap_start = self.suffix_start(&current_chunk, self.chunk_overlap);
                         current_chunk = current_chunk[overlap_start..].to_string();
                     } else {
                         current_chunk.clear();
@@ -81,7 +332,7 @@ impl TextSplitter {
                 }
 
                 // If the split itself is too large, recursively split it
-                if split.len() > self.chunk_size {
+                if self.measure(split) > self.chunk_size {
                     let sub_chunks = self.recursive_split(split, remaining_separators);
                     final_chunks.extend(sub_chunks);
                 } else {
@@ -107,26 +358,20 @@ impl TextSplitter {
         let mut start = 0;
 
         while start < text.len() {
-            let mut end = (start + self.chunk_size).min(text.len());
-            
-            // Ensure end is on a character boundary
-            while end > start && !text.is_char_boundary(end) {
-                end -= 1;
+            let rest = &text[start..];
+            let mut end = start + self.prefix_end(rest, self.chunk_size);
+            // A character larger than the chunk size still has to go somewhere
+            if end == start {
+                end += rest.chars().next().map_or(rest.len(), char::len_utf8);
             }
-            
-            let chunk = text[start..end].to_string();
-            chunks.push(chunk);
+
+            let chunk = &text[start..end];
+            chunks.push(chunk.to_string());
 
             // Move start position considering overlap
             if self.chunk_overlap > 0 && end < text.len() {
-                let mut new_start = end - self.chunk_overlap.min(end - start);
-                
-                // Ensure new_start is on a character boundary
-                while new_start > start && !text.is_char_boundary(new_start) {
-                    new_start -= 1;
-                }
-                
-                start = new_start;
+                let new_start = start + self.suffix_start(chunk, self.chunk_overlap);
+                start = if new_start > start { new_start } else { end };
             } else {
                 start = end;
             }
@@ -134,6 +379,167 @@ impl TextSplitter {
 
         chunks
     }
+
+    /// Splits at ATX headings outside code blocks. Each chunk is labelled with
+    /// the heading path it falls under, and a heading directly followed by
+    /// another stays with the content of the second.
+    fn split_markdown(&self, text: &str) -> Vec<DocumentSection> {
+        let mut outline = Outline::default();
+        let mut chunks = Vec::new();
+        let mut section = String::new();
+        let mut has_body = false;
+        let mut fence: Option<&str> = None;
+
+        for line in text.lines() {
+            let trimmed = line.trim_start();
+            match fence {
+                Some(marker) => {
+                    if trimmed.starts_with(marker) {
+                        fence = None;
+                    }
+                    has_body = true;
+                }
+                None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                    fence = Some(&trimmed[..3]);
+                    has_body = true;
+                }
+                None => match atx_heading(line) {
+                    Some((level, title)) => {
+                        if has_body {
+                            self.push_markdown_section(&mut chunks, &section, &outline);
+                            section.clear();
+                            has_body = false;
+                        }
+                        outline.enter(level, title);
+                    }
+                    None => has_body |= !trimmed.is_empty(),
+                },
+            }
+            section.push_str(line);
+            section.push('\n');
+        }
+        self.push_markdown_section(&mut chunks, &section, &outline);
+        chunks
+    }
+
+    fn push_markdown_section(&self, chunks: &mut Vec<DocumentSection>, section: &str, outline: &Outline) {
+        let section = section.trim();
+        if section.is_empty() {
+            return;
+        }
+        let metadata = outline.label(DocumentSection::new("")).metadata;
+        chunks.extend(self.with_headers(self.split_plain(section), &metadata));
+    }
+
+    /// Groups sentences into chunks, breaking where the embedding distance to
+    /// the next sentence is above the configured percentile. Groups larger than
+    /// the chunk size are split further.
+    async fn split_semantic(
+        &self,
+        text: &str,
+        config: &SemanticSplitConfig,
+        embeddings: &dyn EmbeddingGenerator,
+    ) -> Result<Vec<DocumentSection>, AgentError> {
+        let sentences = sentence_spans(text);
+        if sentences.len() < 2 {
+            return Ok(self.split_sections(text));
+        }
+
+        let windows: Vec<String> = (0..sentences.len())
+            .map(|i| {
+                let first = i.saturating_sub(config.buffer_size);
+                let last = (i + config.buffer_size).min(sentences.len() - 1);
+                text[sentences[first].start..sentences[last].end].trim().to_string()
+            })
+            .collect();
+        let vectors = embeddings.generate_embeddings(&windows).await?;
+        let distances: Vec<f32> = vectors
+            .windows(2)
+            .map(|pair| 1.0 - cosine_similarity(&pair[0], &pair[1]))
+            .collect();
+        let threshold = percentile(&distances, config.breakpoint_percentile);
+
+        let metadata = DocumentMetadata::new();
+        let mut chunks = Vec::new();
+        let mut start = sentences[0].start;
+        for (i, distance) in distances.iter().enumerate() {
+            if *distance > threshold {
+                let group = text[start..sentences[i].end].trim();
+                chunks.extend(self.with_headers(self.split_plain(group), &metadata));
+                start = sentences[i + 1].start;
+            }
+        }
+        let group = text[start..].trim();
+        chunks.extend(self.with_headers(self.split_plain(group), &metadata));
+        Ok(chunks)
+    }
+}
+
+/// Byte offsets of every character start, and of the end of `text`
+fn char_boundaries(text: &str) -> Vec<usize> {
+    text.char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()))
+        .collect()
+}
+
+/// Level and title of an ATX heading such as `## Setup`
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let line = &line[indent..];
+    let level = line.len() - line.trim_start_matches('#').len();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let title = &line[level..];
+    if !title.is_empty() && !title.starts_with([' ', '\t']) {
+        return None;
+    }
+    let title = title.trim().trim_end_matches('#').trim_end();
+    (!title.is_empty()).then_some((level, title))
+}
+
+/// Spans of the sentences of `text`, ending at `.`, `!` or `?` followed by
+/// whitespace, or at a blank line
+fn sentence_spans(text: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+        let is_end = match c {
+            '.' | '!' | '?' => next.is_none_or(char::is_whitespace),
+            '\n' => next == Some('\n'),
+            _ => false,
+        };
+        if is_end {
+            let end = i + c.len_utf8();
+            if !text[start..end].trim().is_empty() {
+                spans.push(start..end);
+            }
+            start = end;
+        }
+    }
+    if !text[start..].trim().is_empty() {
+        spans.push(start..text.len());
+    }
+    spans
+}
+
+/// Linearly interpolated percentile of `values`
+fn percentile(values: &[f32], percentile: f32) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let rank = (percentile / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f32;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f32)
 }
 
 pub struct LanguageTextSplitter;
@@ -220,12 +626,15 @@ impl LanguageTextSplitter {
     }
 
     /// Create a text splitter optimized for a specific language
+    ///
+    /// Languages with a grammar are split at syntax boundaries, falling back to
+    /// the language's separators for definitions larger than a chunk.
     pub fn for_language(language: &str, chunk_size: usize, chunk_overlap: usize) -> TextSplitter {
         let separators = Self::get_separators_for_language(language);
-        TextSplitter {
-            chunk_size,
-            chunk_overlap,
-            separators,
+        let splitter = TextSplitter::new(chunk_size, chunk_overlap).with_separators(separators);
+        match CodeLanguage::from_name(language) {
+            Some(code_language) => splitter.with_strategy(SplitStrategy::Code(Some(code_language))),
+            None => splitter,
         }
     }
 }
@@ -234,9 +643,14 @@ impl LanguageTextSplitter {
 pub type DocumentMetadata = HashMap<String, String>;
 
 /// Options for chunk headers when splitting documents
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChunkHeaderOptions {
+    /// Prefixed to every chunk
+    #[serde(default)]
     pub chunk_header: String,
+    /// Prefixed, after `chunk_header`, to chunks that continue the text of the
+    /// chunk before them
+    #[serde(default)]
     pub chunk_overlap_header: Option<String>,
 }
 
@@ -342,4 +756,93 @@ More detailed content here.
             // Note: overlap might not always be present depending on split points
         }
     }
+
+    #[test]
+    fn test_markdown_splitter_records_heading_path() {
+        let splitter = TextSplitter::new(1000, 0).with_strategy(SplitStrategy::Markdown);
+        let markdown = "Intro text.\n\n# Setup\n\n## Linux\nUse the tarball.\n\n```sh\n# not a heading\n```\n\n## macOS ##\nUse brew.\n";
+        let chunks = splitter.split_sections(markdown);
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].text, "Intro text.");
+        assert!(chunks[0].metadata.is_empty());
+        // A heading with no content of its own stays with the next section
+        assert_eq!(
+            chunks[1].text,
+            "# Setup\n\n## Linux\nUse the tarball.\n\n```sh\n# not a heading\n```"
+        );
+        assert_eq!(chunks[1].metadata["section"], "Setup > Linux");
+        assert_eq!(chunks[2].metadata["section"], "Setup > macOS");
+        assert_eq!(chunks[2].metadata["section_number"], "1.2");
+    }
+
+    #[test]
+    fn test_chunk_headers() {
+        let splitter = TextSplitter::new(12, 0).with_header_options(
+            ChunkHeaderOptions::default()
+                .with_header("Guide: ")
+                .with_overlap_header("(cont.) "),
+        );
+        let chunks = splitter.split_text("first part second part");
+        assert_eq!(chunks, vec!["Guide: first part", "Guide: (cont.) second part"]);
+    }
+
+    #[test]
+    fn test_token_sized_chunks() {
+        let tokenizer = Arc::new(TiktokenTokenizer::cl100k().unwrap());
+        let splitter = TextSplitter::new(8, 2).with_tokenizer(tokenizer.clone());
+        let text = "The agent retries failed tool calls with exponential backoff. \
+                    Each retry waits twice as long as the one before it.";
+        let chunks = splitter.split_text(text);
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| tokenizer.count_tokens(chunk) <= 8));
+        // Far longer than 8 characters, since sizes are in tokens
+        assert!(chunks.iter().any(|chunk| chunk.len() > 20));
+    }
+
+    /// Embeds text by topic, so that sentences on the same topic are identical
+    struct TopicEmbeddings;
+
+    #[async_trait::async_trait]
+    impl EmbeddingGenerator for TopicEmbeddings {
+        async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>, AgentError> {
+            Ok(vec![
+                text.matches("cat").count() as f32,
+                text.matches("tax").count() as f32,
+            ])
+        }
+
+        fn embedding_dimension(&self) -> usize {
+            2
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_semantic_splitter_breaks_on_topic_change() {
+        let config = SemanticSplitConfig {
+            breakpoint_percentile: 50.0,
+            buffer_size: 0,
+        };
+        let splitter = TextSplitter::new(1000, 0).with_strategy(SplitStrategy::Semantic(config));
+        let text = "My cat sleeps all day. The cat likes fish! Income tax is due in April. \
+                    A tax form is needed.";
+        let chunks = splitter.split_document("notes.txt", text, &TopicEmbeddings).await.unwrap();
+
+        let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "My cat sleeps all day. The cat likes fish!",
+                "Income tax is due in April. A tax form is needed."
+            ]
+        );
+        // Without embeddings the text is split by size
+        assert_eq!(splitter.split_text(text).len(), 1);
+        assert_eq!(percentile(&[0.0, 1.0, 0.0], 95.0), 0.9);
+    }
 }
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::errors::AgentError;
+use crate::rag::embedding_file::{self, EmbeddingFile, EmbeddingFileHeader, Quantization};
 use crate::rag::embeddings::{cosine_similarity, euclidean_distance};
 
 #[derive(Debug, Clone)]
@@ -43,12 +44,36 @@ pub trait VectorStore: Send + Sync {
         top_k: usize,
     ) -> Result<Vec<SearchResult>, AgentError>;
 
+    /// Like [`VectorStore::search`], but only considers documents for which `filter`
+    /// returns true, so the `top_k` results all pass it
+    async fn search_filtered(
+        &self,
+        query_embedding: &[f32],
+        top_k: usize,
+        filter: &(dyn Fn(usize) -> bool + Send + Sync),
+    ) -> Result<Vec<SearchResult>, AgentError> {
+        let mut results = self.search(query_embedding, self.document_count()).await?;
+        results.retain(|result| filter(result.document_id));
+        results.truncate(top_k);
+        Ok(results)
+    }
+
     async fn remove_document(&mut self, document_id: usize) -> Result<(), AgentError>;
 
+    async fn remove_documents(&mut self, document_ids: &[usize]) -> Result<(), AgentError> {
+        for doc_id in document_ids {
+            self.remove_document(*doc_id).await?;
+        }
+        Ok(())
+    }
+
     async fn clear(&mut self) -> Result<(), AgentError>;
 
     fn document_count(&self) -> usize;
 
+    /// Whether an embedding is stored for `document_id`
+    fn contains(&self, document_id: usize) -> bool;
+
     fn embedding_dimension(&self) -> Option<usize>;
 }
 
@@ -109,37 +134,16 @@ impl VectorStore for InMemoryVectorStore {
         query_embedding: &[f32],
         top_k: usize,
     ) -> Result<Vec<SearchResult>, AgentError> {
-        if self.embeddings.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        if let Some(expected_dim) = self.embedding_dimension {
-            if query_embedding.len() != expected_dim {
-                return Err(AgentError::RagError(format!(
-                    "Query embedding dimension mismatch: expected {}, got {}",
-                    expected_dim,
-                    query_embedding.len()
-                )));
-            }
-        }
-
-        let mut results = Vec::new();
-
-        for (doc_id, embedding) in &self.embeddings {
-            let similarity = cosine_similarity(query_embedding, embedding);
-            let distance = euclidean_distance(query_embedding, embedding);
-
-            results.push(SearchResult::new(*doc_id, similarity, distance));
-        }
-
-        results.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        self.search_filtered(query_embedding, top_k, &|_| true).await
+    }
 
-        results.truncate(top_k);
-        Ok(results)
+    async fn search_filtered(
+        &self,
+        query_embedding: &[f32],
+        top_k: usize,
+        filter: &(dyn Fn(usize) -> bool + Send + Sync),
+    ) -> Result<Vec<SearchResult>, AgentError> {
+        rank_embeddings(&self.embeddings, self.embedding_dimension, query_embedding, top_k, filter)
     }
 
     async fn remove_document(&mut self, document_id: usize) -> Result<(), AgentError> {
@@ -157,24 +161,44 @@ impl VectorStore for InMemoryVectorStore {
         self.embeddings.len()
     }
 
+    fn contains(&self, document_id: usize) -> bool {
+        self.embeddings.contains_key(&document_id)
+    }
+
     fn embedding_dimension(&self) -> Option<usize> {
         self.embedding_dimension
     }
 }
 
+/// Brute-force store saved to a binary embedding file after every change
+///
+/// Loading also accepts the JSON files written by earlier versions and rewrites
+/// them in the binary format.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistentVectorStore {
     embeddings: HashMap<usize, Vec<f32>>,
     embedding_dimension: Option<usize>,
     #[serde(skip)]
+    embedding_model: String,
+    #[serde(skip)]
+    quantization: Quantization,
+    #[serde(skip)]
     file_path: Option<std::path::PathBuf>,
 }
 
+/// Per-row metadata of a saved vector store
+#[derive(Serialize, Deserialize)]
+struct StoredDocumentIds {
+    document_ids: Vec<usize>,
+}
+
 impl PersistentVectorStore {
     pub fn new() -> Self {
         Self {
             embeddings: HashMap::new(),
             embedding_dimension: None,
+            embedding_model: String::new(),
+            quantization: Quantization::default(),
             file_path: None,
         }
     }
@@ -184,31 +208,91 @@ impl PersistentVectorStore {
         self
     }
 
+    /// Records the model in the file header so a mismatch can be detected on load
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
+    /// Sets how embeddings are written by later saves
+    pub fn with_quantization(mut self, quantization: Quantization) -> Self {
+        self.quantization = quantization;
+        self
+    }
+
+    /// Model recorded in the loaded file, empty when unknown
+    pub fn embedding_model(&self) -> &str {
+        &self.embedding_model
+    }
+
     pub async fn save(&self) -> Result<(), AgentError> {
         if let Some(path) = &self.file_path {
-            let data = serde_json::to_string_pretty(self).map_err(|e| {
-                AgentError::RagError(format!("Failed to serialize vector store: {}", e))
-            })?;
-
-            tokio::fs::write(path, data).await.map_err(|e| {
-                AgentError::RagError(format!("Failed to write vector store to file: {}", e))
-            })?;
+            let mut document_ids: Vec<usize> = self.embeddings.keys().copied().collect();
+            document_ids.sort_unstable();
+            let rows: Vec<&[f32]> = document_ids
+                .iter()
+                .map(|id| self.embeddings[id].as_slice())
+                .collect();
+            let header = EmbeddingFileHeader {
+                embedding_model: self.embedding_model.clone(),
+                dimension: self.embedding_dimension.unwrap_or(0),
+                quantization: self.quantization,
+            };
+            let bytes = embedding_file::encode(&header, &rows, &StoredDocumentIds { document_ids })?;
+            embedding_file::write(path, bytes).await?;
         }
         Ok(())
     }
 
     pub async fn load<P: Into<std::path::PathBuf>>(path: P) -> Result<Self, AgentError> {
         let path = path.into();
-        let data = tokio::fs::read_to_string(&path).await.map_err(|e| {
-            AgentError::RagError(format!("Failed to read vector store file: {}", e))
-        })?;
+        let bytes = embedding_file::read(&path).await?;
 
-        let mut store: PersistentVectorStore = serde_json::from_str(&data).map_err(|e| {
-            AgentError::RagError(format!("Failed to deserialize vector store: {}", e))
-        })?;
+        if !embedding_file::is_embedding_file(&bytes) {
+            let mut store: PersistentVectorStore = serde_json::from_slice(&bytes).map_err(|e| {
+                AgentError::RagError(format!("Failed to deserialize vector store: {}", e))
+            })?;
+            log::info!(
+                "Migrating vector store {} from JSON to the binary format",
+                path.display()
+            );
+            store.file_path = Some(path);
+            store.save().await?;
+            return Ok(store);
+        }
+
+        let file: EmbeddingFile<StoredDocumentIds> = embedding_file::decode(&bytes)?;
+        let document_ids = file.metadata.document_ids;
+        if document_ids.len() != file.embeddings.len() {
+            return Err(AgentError::RagError(format!(
+                "Vector store file lists {} document ids for {} embeddings",
+                document_ids.len(),
+                file.embeddings.len()
+            )));
+        }
 
-        store.file_path = Some(path);
-        Ok(store)
+        Ok(Self {
+            embeddings: document_ids.into_iter().zip(file.embeddings).collect(),
+            embedding_dimension: (file.header.dimension > 0).then_some(file.header.dimension),
+            embedding_model: file.header.embedding_model,
+            quantization: file.header.quantization,
+            file_path: Some(path),
+        })
+    }
+
+    fn check_dimension(&mut self, embedding: &[f32]) -> Result<(), AgentError> {
+        if let Some(expected_dim) = self.embedding_dimension {
+            if embedding.len() != expected_dim {
+                return Err(AgentError::RagError(format!(
+                    "Embedding dimension mismatch: expected {}, got {}",
+                    expected_dim,
+                    embedding.len()
+                )));
+            }
+        } else {
+            self.embedding_dimension = Some(embedding.len());
+        }
+        Ok(())
     }
 }
 
@@ -225,19 +309,22 @@ impl VectorStore for PersistentVectorStore {
         document_id: usize,
         embedding: Vec<f32>,
     ) -> Result<(), AgentError> {
-        if let Some(expected_dim) = self.embedding_dimension {
-            if embedding.len() != expected_dim {
-                return Err(AgentError::RagError(format!(
-                    "Embedding dimension mismatch: expected {}, got {}",
-                    expected_dim,
-                    embedding.len()
-                )));
-            }
-        } else {
-            self.embedding_dimension = Some(embedding.len());
+        self.check_dimension(&embedding)?;
+        self.embeddings.insert(document_id, embedding);
+
+        if self.file_path.is_some() {
+            self.save().await?;
         }
 
-        self.embeddings.insert(document_id, embedding);
+        Ok(())
+    }
+
+    /// Saves once for the whole batch rather than after every document
+    async fn add_documents(&mut self, documents: Vec<(usize, Vec<f32>)>) -> Result<(), AgentError> {
+        for (document_id, embedding) in documents {
+            self.check_dimension(&embedding)?;
+            self.embeddings.insert(document_id, embedding);
+        }
 
         if self.file_path.is_some() {
             self.save().await?;
@@ -251,41 +338,33 @@ impl VectorStore for PersistentVectorStore {
         query_embedding: &[f32],
         top_k: usize,
     ) -> Result<Vec<SearchResult>, AgentError> {
-        if self.embeddings.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        if let Some(expected_dim) = self.embedding_dimension {
-            if query_embedding.len() != expected_dim {
-                return Err(AgentError::RagError(format!(
-                    "Query embedding dimension mismatch: expected {}, got {}",
-                    expected_dim,
-                    query_embedding.len()
-                )));
-            }
-        }
+        self.search_filtered(query_embedding, top_k, &|_| true).await
+    }
 
-        let mut results = Vec::new();
+    async fn search_filtered(
+        &self,
+        query_embedding: &[f32],
+        top_k: usize,
+        filter: &(dyn Fn(usize) -> bool + Send + Sync),
+    ) -> Result<Vec<SearchResult>, AgentError> {
+        rank_embeddings(&self.embeddings, self.embedding_dimension, query_embedding, top_k, filter)
+    }
 
-        for (doc_id, embedding) in &self.embeddings {
-            let similarity = cosine_similarity(query_embedding, embedding);
-            let distance = euclidean_distance(query_embedding, embedding);
+    async fn remove_document(&mut self, document_id: usize) -> Result<(), AgentError> {
+        self.embeddings.remove(&document_id);
 
-            results.push(SearchResult::new(*doc_id, similarity, distance));
+        if self.file_path.is_some() {
+            self.save().await?;
         }
 
-        results.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        results.truncate(top_k);
-        Ok(results)
+        Ok(())
     }
 
-    async fn remove_document(&mut self, document_id: usize) -> Result<(), AgentError> {
-        self.embeddings.remove(&document_id);
+    /// Saves once for the whole batch rather than after every document
+    async fn remove_documents(&mut self, document_ids: &[usize]) -> Result<(), AgentError> {
+        for document_id in document_ids {
+            self.embeddings.remove(document_id);
+        }
 
         if self.file_path.is_some() {
             self.save().await?;
@@ -309,11 +388,59 @@ impl VectorStore for PersistentVectorStore {
         self.embeddings.len()
     }
 
+    fn contains(&self, document_id: usize) -> bool {
+        self.embeddings.contains_key(&document_id)
+    }
+
     fn embedding_dimension(&self) -> Option<usize> {
         self.embedding_dimension
     }
 }
 
+/// Scores the embeddings that pass `filter` against the query, best first
+fn rank_embeddings(
+    embeddings: &HashMap<usize, Vec<f32>>,
+    embedding_dimension: Option<usize>,
+    query_embedding: &[f32],
+    top_k: usize,
+    filter: &(dyn Fn(usize) -> bool + Send + Sync),
+) -> Result<Vec<SearchResult>, AgentError> {
+    if embeddings.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if let Some(expected_dim) = embedding_dimension {
+        if query_embedding.len() != expected_dim {
+            return Err(AgentError::RagError(format!(
+                "Query embedding dimension mismatch: expected {}, got {}",
+                expected_dim,
+                query_embedding.len()
+            )));
+        }
+    }
+
+    let mut results = Vec::new();
+
+    for (doc_id, embedding) in embeddings {
+        if !filter(*doc_id) {
+            continue;
+        }
+        let similarity = cosine_similarity(query_embedding, embedding);
+        let distance = euclidean_distance(query_embedding, embedding);
+
+        results.push(SearchResult::new(*doc_id, similarity, distance));
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    results.truncate(top_k);
+    Ok(results)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -398,6 +525,21 @@ mod tests {
         assert!(results[1].score > results[2].score);
     }
 
+    #[tokio::test]
+    async fn test_filtered_search_fills_top_k() {
+        let mut store = InMemoryVectorStore::new();
+        store.add_document(1, vec![1.0, 0.0, 0.0]).await.unwrap();
+        store.add_document(2, vec![0.9, 0.1, 0.0]).await.unwrap();
+        store.add_document(3, vec![0.0, 1.0, 0.0]).await.unwrap();
+        store.add_document(4, vec![0.0, 0.0, 1.0]).await.unwrap();
+
+        let query = vec![1.0, 0.0, 0.0];
+        let odd_only = |id: usize| id % 2 == 1;
+        let results = store.search_filtered(&query, 2, &odd_only).await.unwrap();
+        let ids: Vec<usize> = results.iter().map(|r| r.document_id).collect();
+        assert_eq!(ids, vec![1, 3]);
+    }
+
     #[tokio::test]
     async fn test_batch_add_documents() {
         let mut store = InMemoryVectorStore::new();
@@ -411,4 +553,46 @@ mod tests {
         store.add_documents(documents).await.unwrap();
         assert_eq!(store.document_count(), 3);
     }
+
+    #[tokio::test]
+    async fn test_persistent_store_binary_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vectors.bin");
+
+        let mut store = PersistentVectorStore::new()
+            .with_file_path(&path)
+            .with_embedding_model("text-embedding-3-small")
+            .with_quantization(Quantization::Int8);
+        store
+            .add_documents(vec![(4, vec![1.0, 0.0, 0.0]), (9, vec![0.0, 0.6, 0.8])])
+            .await
+            .unwrap();
+
+        let loaded = PersistentVectorStore::load(&path).await.unwrap();
+        assert_eq!(loaded.embedding_model(), "text-embedding-3-small");
+        assert_eq!(loaded.embedding_dimension(), Some(3));
+        let results = loaded.search(&[0.0, 0.6, 0.8], 2).await.unwrap();
+        assert_eq!(results[0].document_id, 9);
+        assert!((results[0].score - 1.0).abs() < 1e-3);
+    }
+
+    #[tokio::test]
+    async fn test_json_store_is_migrated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vectors.json");
+        let legacy = serde_json::json!({
+            "embeddings": { "1": [1.0, 0.0], "2": [0.0, 1.0] },
+            "embedding_dimension": 2
+        });
+        std::fs::write(&path, serde_json::to_vec_pretty(&legacy).unwrap()).unwrap();
+
+        let store = PersistentVectorStore::load(&path).await.unwrap();
+        assert_eq!(store.document_count(), 2);
+        assert!(embedding_file::is_embedding_file(&std::fs::read(&path).unwrap()));
+
+        let reloaded = PersistentVectorStore::load(&path).await.unwrap();
+        let results = reloaded.search(&[0.0, 1.0], 1).await.unwrap();
+        assert_eq!(results[0].document_id, 2);
+        assert_eq!(reloaded.embedding_model(), "");
+    }
 }
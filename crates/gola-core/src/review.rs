@@ -0,0 +1,200 @@
+//! Reviewer pass over final answers
+//!
+//! Before a final answer is returned, the reviewer asks an LLM whether it fully
+//! addresses the original task and matches the output schema, if there is one.
+//! Only explicit final answers (`Final Answer:` or `assistant_done`) are reviewed;
+//! conversational replies go back to the user as they are.
+//! A rejection comes with a critique that the agent gets back as feedback. The
+//! reviewer never blocks a run on its own failures: an unreachable model or an
+//! unreadable verdict counts as an approval.
+
+use crate::config::ReviewConfig;
+use crate::core_types::{Message, Role};
+use crate::llm::LLM;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+const REVIEW_PROMPT: &str = "You review the final answer of an AI agent before it is \
+returned to the user. Check that the answer addresses every part of the task, does not \
+claim work that was not done, and follows the required output schema if one is given. \
+A question back to the user that is needed to make progress is acceptable. Reply with \
+only a JSON object: {\"approved\": true|false, \"critique\": \"what is missing or wrong, \
+empty when approved\"}.";
+
+/// Outcome of one review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewVerdict {
+    pub approved: bool,
+    #[serde(default)]
+    pub critique: String,
+}
+
+/// A review as recorded in the trace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRecord {
+    /// 1 for the first review of a task
+    pub round: usize,
+    pub approved: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub critique: String,
+}
+
+/// Checks final answers against the task they answer
+pub struct AnswerReviewer {
+    llm: Arc<dyn LLM>,
+    instructions: Option<String>,
+    output_schema: Option<Value>,
+    max_rounds: usize,
+}
+
+impl AnswerReviewer {
+    pub fn new(llm: Arc<dyn LLM>, config: &ReviewConfig) -> Self {
+        Self {
+            llm,
+            instructions: config.prompt.clone(),
+            output_schema: None,
+            max_rounds: config.max_rounds,
+        }
+    }
+
+    /// Also checks answers against `schema`
+    pub fn with_output_schema(mut self, schema: Value) -> Self {
+        self.output_schema = Some(schema);
+        self
+    }
+
+    /// Number of reviews per task, answers are accepted unreviewed after that
+    pub fn max_rounds(&self) -> usize {
+        self.max_rounds
+    }
+
+    /// Reviews `answer` as a response to `task`
+    pub async fn review(&self, task: &str, answer: &str) -> ReviewVerdict {
+        let mut system = REVIEW_PROMPT.to_string();
+        if let Some(instructions) = &self.instructions {
+            system.push_str("\n\nAdditional criteria:\n");
+            system.push_str(instructions);
+        }
+
+        let mut request = format!("Task:\n{}\n\n", task);
+        if let Some(schema) = &self.output_schema {
+            request.push_str(&format!("Required output schema:\n{}\n\n", schema));
+        }
+        request.push_str(&format!("Proposed answer:\n{}", answer));
+
+        let messages = vec![
+            Message {
+                role: Role::System,
+                content: system,
+                tool_call_id: None,
+                tool_calls: None,
+                reasoning: None,
+            },
+            Message {
+                role: Role::User,
+                content: request,
+                tool_call_id: None,
+                tool_calls: None,
+                reasoning: None,
+            },
+        ];
+
+        let reply = match self.llm.generate(messages, None).await {
+            Ok(response) => response.content.unwrap_or_default(),
+            Err(e) => {
+                log::warn!("Review failed, accepting the answer: {}", e);
+                return approved();
+            }
+        };
+        parse_verdict(&reply).unwrap_or_else(|| {
+            log::warn!("Unreadable review verdict, accepting the answer: {}", reply);
+            approved()
+        })
+    }
+}
+
+fn approved() -> ReviewVerdict {
+    ReviewVerdict {
+        approved: true,
+        critique: String::new(),
+    }
+}
+
+/// Reads the verdict object from the reviewer's reply, which may wrap it in prose
+/// or a code fence
+fn parse_verdict(reply: &str) -> Option<ReviewVerdict> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    serde_json::from_str(&reply[start..=end]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_types::LLMResponse;
+    use crate::errors::AgentError;
+    use crate::llm::ToolMetadata;
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    struct ReviewerLLM(&'static str);
+
+    #[async_trait]
+    impl LLM for ReviewerLLM {
+        async fn generate(
+            &self,
+            messages: Vec<Message>,
+            _tools: Option<Vec<ToolMetadata>>,
+        ) -> Result<LLMResponse, AgentError> {
+            assert!(messages[0].content.contains("Cite sources"));
+            assert!(messages[1].content.contains("Required output schema:"));
+            Ok(LLMResponse {
+                content: Some(self.0.to_string()),
+                tool_calls: None,
+                finish_reason: None,
+                usage: None,
+                reasoning: None,
+            })
+        }
+    }
+
+    fn reviewer(reply: &'static str) -> AnswerReviewer {
+        let config = ReviewConfig {
+            prompt: Some("Cite sources.".to_string()),
+            ..Default::default()
+        };
+        AnswerReviewer::new(Arc::new(ReviewerLLM(reply)), &config)
+            .with_output_schema(json!({ "type": "object" }))
+    }
+
+    #[test]
+    fn test_parse_verdict() {
+        assert_eq!(
+            parse_verdict("```json\n{\"approved\": false, \"critique\": \"Skipped part 2\"}\n```"),
+            Some(ReviewVerdict {
+                approved: false,
+                critique: "Skipped part 2".to_string()
+            })
+        );
+        assert_eq!(parse_verdict("{\"approved\": true}"), Some(approved()));
+        assert_eq!(parse_verdict("Looks good to me"), None);
+        assert_eq!(parse_verdict("} {"), None);
+    }
+
+    #[tokio::test]
+    async fn test_review_verdicts() {
+        let verdict = reviewer("{\"approved\": false, \"critique\": \"No sources\"}")
+            .review("Summarize the paper", "It is about cats.")
+            .await;
+        assert!(!verdict.approved);
+        assert_eq!(verdict.critique, "No sources");
+
+        // Unreadable replies do not hold the answer back
+        let verdict = reviewer("I cannot decide").review("Summarize the paper", "It is about cats.").await;
+        assert!(verdict.approved);
+    }
+}
@@ -42,6 +42,7 @@ mod rag_integration_tests {
                 tool_calls: None,
                 finish_reason: None,
                 usage: None,
+                reasoning: None,
             })
         }
     }
@@ -72,6 +73,8 @@ mod rag_integration_tests {
             system_prompt: None,
             memory_config: None,
             authorization_mode: AuthorizationMode::default(),
+            context_budget: None,
+            loop_detection: Default::default(),
         };
 
         let mut tools: HashMap<String, Arc<dyn crate::tools::Tool>> = HashMap::new();
@@ -161,6 +164,8 @@ mod rag_integration_tests {
             system_prompt: None,
             memory_config: None,
             authorization_mode: AuthorizationMode::default(),
+            context_budget: None,
+            loop_detection: Default::default(),
         };
 
         let mut agent = Agent::new(mock_llm, HashMap::new(), None, config);
@@ -9,6 +9,7 @@ use crate::core_types::{LLMResponse, Message};
 use crate::errors::AgentError;
 use crate::llm::ToolMetadata; // Assuming this is pub
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct MockLLMRequestPayload {
@@ -20,6 +21,7 @@ pub struct MockLLMRequestPayload {
 struct MockServerState {
     responses: Arc<Mutex<VecDeque<Result<LLMResponse, AgentError>>>>,
     requests: Arc<Mutex<Vec<MockLLMRequestPayload>>>,
+    raw_requests: Arc<Mutex<Vec<Value>>>,
 }
 
 impl MockServerState {
@@ -27,6 +29,7 @@ impl MockServerState {
         Self {
             responses: Arc::new(Mutex::new(VecDeque::from(responses))),
             requests: Arc::new(Mutex::new(Vec::new())),
+            raw_requests: Arc::new(Mutex::new(Vec::new())),
         }
     }
 }
@@ -64,19 +67,75 @@ async fn chat_completions_handler(
     }
 }
 
+/// Serves the queued responses in the Anthropic Messages format
+///
+/// Request bodies are recorded verbatim so tests can assert on provider-specific fields.
+async fn anthropic_messages_handler(
+    axum::extract::State(state): axum::extract::State<MockServerState>,
+    Json(body): Json<Value>,
+) -> Result<Json<Value>, axum::http::StatusCode> {
+    state.raw_requests.lock().unwrap().push(body);
+
+    let response = match state.responses.lock().unwrap().pop_front() {
+        Some(Ok(resp)) => resp,
+        Some(Err(e)) => {
+            log::error!("Mock LLM server simulating an error: {:?}", e);
+            return Err(axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        None => {
+            log::error!("Mock LLM server ran out of responses!");
+            return Err(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+        }
+    };
+
+    let mut content: Vec<Value> = response
+        .content
+        .iter()
+        .map(|text| json!({ "type": "text", "text": text }))
+        .collect();
+    for (i, call) in response.tool_calls.iter().flatten().enumerate() {
+        content.push(json!({
+            "type": "tool_use",
+            "id": call.id.clone().unwrap_or_else(|| format!("toolu_{}", i)),
+            "name": call.name,
+            "input": call.arguments
+        }));
+    }
+    let usage = response.usage.as_ref();
+
+    Ok(Json(json!({
+        "id": "msg_mock",
+        "type": "message",
+        "role": "assistant",
+        "model": "mock",
+        "content": content,
+        "stop_reason": response.finish_reason.unwrap_or_else(|| "end_turn".to_string()),
+        "stop_sequence": null,
+        "usage": {
+            "input_tokens": usage.map_or(0, |u| u.prompt_tokens),
+            "output_tokens": usage.map_or(0, |u| u.completion_tokens),
+            "cache_read_input_tokens": usage.and_then(|u| u.cache_read_tokens),
+            "cache_creation_input_tokens": usage.and_then(|u| u.cache_write_tokens)
+        }
+    })))
+}
+
 pub struct MockLLMServer {
     addr: SocketAddr,
     shutdown_tx: tokio::sync::oneshot::Sender<()>,
     pub recorded_requests: Arc<Mutex<Vec<MockLLMRequestPayload>>>,
+    pub recorded_raw_requests: Arc<Mutex<Vec<Value>>>,
 }
 
 impl MockLLMServer {
     pub async fn start(responses: Vec<Result<LLMResponse, AgentError>>) -> Self {
         let state = MockServerState::new(responses);
         let recorded_requests_clone = state.requests.clone();
+        let recorded_raw_requests = state.raw_requests.clone();
 
         let app = Router::new()
             .route("/v1/chat/completions", post(chat_completions_handler))
+            .route("/v1/messages", post(anthropic_messages_handler))
             .with_state(state);
 
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap_or_else(|e| {
@@ -106,6 +165,7 @@ impl MockLLMServer {
             addr,
             shutdown_tx,
             recorded_requests: recorded_requests_clone,
+            recorded_raw_requests,
         }
     }
 
@@ -127,5 +187,10 @@ impl MockLLMServer {
     pub fn get_requests(&self) -> Vec<MockLLMRequestPayload> {
         self.recorded_requests.lock().unwrap().clone()
     }
+
+    /// Raw bodies received on provider-specific routes such as `/v1/messages`
+    pub fn get_raw_requests(&self) -> Vec<Value> {
+        self.recorded_raw_requests.lock().unwrap().clone()
+    }
 }
 
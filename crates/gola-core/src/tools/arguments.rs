@@ -0,0 +1,203 @@
+//! Validation and repair of tool call arguments
+//!
+//! Arguments produced by the model are checked against the tool's `input_schema`
+//! before the tool runs. A few lossless coercions are applied first, so that
+//! near-misses like `"5"` for a number still go through, and anything left over is
+//! reported back as a list of violations the model can act on.
+
+use jsonschema::JSONSchema;
+use serde_json::{Map, Value};
+
+/// Coerces `arguments` towards `schema` and validates the result
+///
+/// Returns the arguments to pass to the tool, or one message per schema violation.
+/// Schemas that fail to compile are not enforced.
+pub fn prepare_arguments(schema: &Value, arguments: Value) -> Result<Value, Vec<String>> {
+    let arguments = coerce(schema, arguments);
+
+    let compiled = match JSONSchema::compile(schema) {
+        Ok(compiled) => compiled,
+        Err(e) => {
+            log::debug!("Skipping argument validation, tool schema does not compile: {}", e);
+            return Ok(arguments);
+        }
+    };
+
+    if let Err(errors) = compiled.validate(&arguments) {
+        return Err(errors
+            .map(|error| {
+                let path = error.instance_path.to_string();
+                let path = if path.is_empty() { "root".to_string() } else { path };
+                format!("At '{}': {}", path, error)
+            })
+            .collect());
+    }
+
+    Ok(arguments)
+}
+
+/// Formats violations as a tool observation asking the model to retry
+pub fn describe_violations(tool_name: &str, violations: &[String], schema: &Value) -> String {
+    let mut message = format!(
+        "Invalid arguments for tool '{}'. The call was not executed. Fix these problems and try again:\n",
+        tool_name
+    );
+    for violation in violations {
+        message.push_str(&format!("- {}\n", violation));
+    }
+    message.push_str(&format!("Expected input schema: {}", schema));
+    message
+}
+
+/// Applies safe coercions where the value doesn't already match the schema's type
+fn coerce(schema: &Value, value: Value) -> Value {
+    let types = schema_types(schema);
+    if types.is_empty() || types.iter().any(|t| matches_type(t, &value)) {
+        return coerce_children(schema, value);
+    }
+
+    for target in &types {
+        let coerced = match (target.as_str(), &value) {
+            ("number", Value::String(s)) => s.trim().parse::<f64>().ok().and_then(|n| {
+                serde_json::Number::from_f64(n).map(Value::Number)
+            }),
+            ("integer", Value::String(s)) => s.trim().parse::<i64>().ok().map(Value::from),
+            ("integer", Value::Number(n)) => n
+                .as_f64()
+                .filter(|f| f.fract() == 0.0)
+                .map(|f| Value::from(f as i64)),
+            ("boolean", Value::String(s)) => match s.trim() {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ("array", v) if !v.is_null() => Some(Value::Array(vec![v.clone()])),
+            ("object", Value::Null) => Some(Value::Object(Map::new())),
+            _ => None,
+        };
+        if let Some(coerced) = coerced {
+            return coerce_children(schema, coerced);
+        }
+    }
+
+    value
+}
+
+/// Recurses into object properties and array items
+fn coerce_children(schema: &Value, value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let strip_unknown = schema.get("additionalProperties") == Some(&Value::Bool(false));
+
+            let object = object
+                .into_iter()
+                .filter_map(|(key, value)| match properties.and_then(|p| p.get(&key)) {
+                    Some(property) => Some((key, coerce(property, value))),
+                    None if strip_unknown && properties.is_some() => {
+                        log::debug!("Dropping unknown tool argument '{}'", key);
+                        None
+                    }
+                    None => Some((key, value)),
+                })
+                .collect();
+            Value::Object(object)
+        }
+        Value::Array(items) => match schema.get("items") {
+            Some(item_schema) if item_schema.is_object() => {
+                Value::Array(items.into_iter().map(|item| coerce(item_schema, item)).collect())
+            }
+            _ => Value::Array(items),
+        },
+        other => other,
+    }
+}
+
+fn schema_types(schema: &Value) -> Vec<String> {
+    match schema.get("type") {
+        Some(Value::String(t)) => vec![t.clone()],
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(|t| t.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn matches_type(schema_type: &str, value: &Value) -> bool {
+    match schema_type {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn search_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string" },
+                "limit": { "type": "integer", "minimum": 1 },
+                "score": { "type": ["number", "null"] },
+                "exact": { "type": "boolean" },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["query"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_safe_coercions() {
+        let prepared = prepare_arguments(
+            &search_schema(),
+            json!({ "query": "rust", "limit": "5", "score": "0.5", "exact": "true", "tags": "lang", "verbose": true }),
+        )
+        .unwrap();
+
+        assert_eq!(
+            prepared,
+            json!({ "query": "rust", "limit": 5, "score": 0.5, "exact": true, "tags": ["lang"] })
+        );
+    }
+
+    #[test]
+    fn test_unknown_keys_kept_without_additional_properties_false() {
+        let schema = json!({ "type": "object", "properties": { "a": { "type": "number" } } });
+        let prepared = prepare_arguments(&schema, json!({ "a": 1, "b": 2 })).unwrap();
+        assert_eq!(prepared, json!({ "a": 1, "b": 2 }));
+
+        assert_eq!(prepare_arguments(&schema, Value::Null).unwrap(), json!({}));
+    }
+
+    #[test]
+    fn test_violations_are_reported() {
+        let violations =
+            prepare_arguments(&search_schema(), json!({ "limit": "many", "tags": [1] })).unwrap_err();
+
+        assert_eq!(violations.len(), 3, "{:?}", violations);
+        assert!(violations.iter().any(|v| v.starts_with("At 'root':") && v.contains("\"query\" is a required property")));
+        assert!(violations.iter().any(|v| v.starts_with("At '/limit':")));
+        assert!(violations.iter().any(|v| v.starts_with("At '/tags/0':")));
+
+        let message = describe_violations("search", &violations, &search_schema());
+        assert!(message.starts_with("Invalid arguments for tool 'search'."));
+        assert!(message.contains("Expected input schema:"));
+    }
+
+    #[test]
+    fn test_uncompilable_schema_is_not_enforced() {
+        let schema = json!({ "type": "not-a-type" });
+        assert_eq!(prepare_arguments(&schema, json!({ "x": 1 })).unwrap(), json!({ "x": 1 }));
+    }
+}
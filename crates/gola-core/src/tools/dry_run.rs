@@ -0,0 +1,347 @@
+//! Simulated tool execution for dry runs
+//!
+//! In a dry run the agent never calls a real tool, `execute_code` and `rag_search`
+//! included. Arguments are still validated and authorization is still asked for,
+//! and each call that gets that far is answered from a fixtures file when an entry
+//! matches, otherwise by asking the LLM for a plausible result from the tool's
+//! schema, otherwise with a placeholder. Every call is recorded and written to a
+//! report when the run ends.
+//!
+//! A fixtures file is a YAML (or JSON) list:
+//!
+//! ```yaml
+//! - tool: create_issue
+//!   arguments: { repo: "acme/*" }   # optional, `*` matches any text in strings
+//!   result: '{"number": 42}'
+//!   success: true                  # optional
+//! ```
+//!
+//! All listed arguments must match and unlisted arguments are ignored. The first
+//! matching entry wins.
+
+use crate::config::DryRunConfig;
+use crate::core_types::{Message, Role, ToolCall};
+use crate::errors::AgentError;
+use crate::llm::{ToolMetadata, LLM};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const SYNTHESIS_PROMPT: &str = "You simulate tool results for a dry run of an AI agent. \
+Given a tool and the arguments it was called with, reply with only the raw output the tool \
+would plausibly return, with no explanation or formatting around it.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolFixture {
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: Map<String, Value>,
+    pub result: Value,
+    #[serde(default = "default_success")]
+    pub success: bool,
+}
+
+fn default_success() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimulationSource {
+    Fixture,
+    Synthesized,
+    Placeholder,
+}
+
+/// One tool call the agent intended to make
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedCall {
+    pub step: usize,
+    pub tool: String,
+    pub arguments: Value,
+    pub source: SimulationSource,
+    pub result: String,
+    pub success: bool,
+}
+
+/// Answers tool calls without running the tools
+pub struct ToolSimulator {
+    fixtures: Vec<ToolFixture>,
+    llm: Option<Arc<dyn LLM>>,
+    report_path: Option<PathBuf>,
+    calls: Vec<SimulatedCall>,
+}
+
+impl ToolSimulator {
+    /// Creates a simulator with the given fixtures, synthesizing other results
+    /// with `llm` when one is given
+    pub fn new(fixtures: Vec<ToolFixture>, llm: Option<Arc<dyn LLM>>) -> Self {
+        Self {
+            fixtures,
+            llm,
+            report_path: None,
+            calls: Vec::new(),
+        }
+    }
+
+    pub fn from_config(config: &DryRunConfig, llm: Arc<dyn LLM>) -> Result<Self, AgentError> {
+        let fixtures = match &config.fixtures {
+            Some(path) => {
+                let content = std::fs::read_to_string(path).map_err(|e| {
+                    AgentError::ConfigError(format!("Failed to read dry-run fixtures {}: {}", path.display(), e))
+                })?;
+                serde_yaml::from_str(&content).map_err(|e| {
+                    AgentError::ConfigError(format!("Invalid dry-run fixtures {}: {}", path.display(), e))
+                })?
+            }
+            None => Vec::new(),
+        };
+
+        let mut simulator = Self::new(fixtures, config.synthesize.then_some(llm));
+        simulator.report_path = Some(config.report.clone());
+        Ok(simulator)
+    }
+
+    /// Produces a result for `call` and records it
+    pub async fn simulate(
+        &mut self,
+        step: usize,
+        call: &ToolCall,
+        metadata: Option<&ToolMetadata>,
+    ) -> SimulatedCall {
+        let (source, result, success) = if let Some(fixture) = self.find_fixture(call) {
+            let result = match &fixture.result {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            (SimulationSource::Fixture, result, fixture.success)
+        } else if let Some(result) = self.synthesize(call, metadata).await {
+            (SimulationSource::Synthesized, result, true)
+        } else {
+            let result = format!("[dry run] '{}' was not executed", call.name);
+            (SimulationSource::Placeholder, result, true)
+        };
+
+        log::info!("Dry run: simulated '{}' from {:?}", call.name, source);
+        let simulated = SimulatedCall {
+            step,
+            tool: call.name.clone(),
+            arguments: call.arguments.clone(),
+            source,
+            result,
+            success,
+        };
+        self.calls.push(simulated.clone());
+        simulated
+    }
+
+    /// Calls simulated so far
+    pub fn calls(&self) -> &[SimulatedCall] {
+        &self.calls
+    }
+
+    /// Writes the calls so far to the configured report file, if any
+    pub fn write_report(&self) -> Result<(), AgentError> {
+        let Some(path) = &self.report_path else {
+            return Ok(());
+        };
+        let report = serde_json::json!({ "calls": self.calls });
+        let content = serde_json::to_string_pretty(&report)
+            .map_err(|e| AgentError::ParsingError(e.to_string()))?;
+        std::fs::write(path, content).map_err(|e| {
+            AgentError::IoError(format!("Failed to write dry-run report {}: {}", path.display(), e))
+        })?;
+        log::info!(
+            "Dry run: {} intended tool call(s) written to {}",
+            self.calls.len(),
+            path.display()
+        );
+        Ok(())
+    }
+
+    fn find_fixture(&self, call: &ToolCall) -> Option<&ToolFixture> {
+        self.fixtures.iter().find(|fixture| {
+            fixture.tool == call.name
+                && fixture
+                    .arguments
+                    .iter()
+                    .all(|(key, pattern)| call.arguments.get(key).is_some_and(|v| value_matches(pattern, v)))
+        })
+    }
+
+    async fn synthesize(&self, call: &ToolCall, metadata: Option<&ToolMetadata>) -> Option<String> {
+        let llm = self.llm.as_ref()?;
+        let mut request = format!("Tool: {}\n", call.name);
+        if let Some(metadata) = metadata {
+            request.push_str(&format!(
+                "Description: {}\nInput schema: {}\n",
+                metadata.description, metadata.input_schema
+            ));
+        }
+        request.push_str(&format!("Arguments: {}", call.arguments));
+
+        let messages = vec![
+            Message {
+                role: Role::System,
+                content: SYNTHESIS_PROMPT.to_string(),
+                tool_call_id: None,
+                tool_calls: None,
+                reasoning: None,
+            },
+            Message {
+                role: Role::User,
+                content: request,
+                tool_call_id: None,
+                tool_calls: None,
+                reasoning: None,
+            },
+        ];
+        match llm.generate(messages, None).await {
+            Ok(response) => response.content.filter(|content| !content.trim().is_empty()),
+            Err(e) => {
+                log::warn!("Dry run: could not synthesize a result for '{}': {}", call.name, e);
+                None
+            }
+        }
+    }
+}
+
+/// Strings match with `*` as a wildcard, anything else must be equal
+fn value_matches(pattern: &Value, value: &Value) -> bool {
+    match (pattern, value) {
+        (Value::String(pattern), Value::String(text)) => wildcard_matches(pattern, text),
+        (Value::String(pattern), other) if pattern == "*" => !other.is_null(),
+        _ => pattern == value,
+    }
+}
+
+fn wildcard_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard at all
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_types::LLMResponse;
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    struct CannedLLM;
+
+    #[async_trait]
+    impl LLM for CannedLLM {
+        async fn generate(
+            &self,
+            messages: Vec<Message>,
+            _tools: Option<Vec<ToolMetadata>>,
+        ) -> Result<LLMResponse, AgentError> {
+            assert!(messages[1].content.contains("Input schema:"));
+            Ok(LLMResponse {
+                content: Some("[{\"title\": \"Plausible result\"}]".to_string()),
+                tool_calls: None,
+                finish_reason: None,
+                usage: None,
+                reasoning: None,
+            })
+        }
+    }
+
+    fn call(name: &str, arguments: Value) -> ToolCall {
+        ToolCall {
+            id: Some("call_1".to_string()),
+            name: name.to_string(),
+            arguments,
+        }
+    }
+
+    #[test]
+    fn test_wildcard_matching() {
+        assert!(wildcard_matches("acme/*", "acme/widgets"));
+        assert!(wildcard_matches("*widgets", "acme/widgets"));
+        assert!(wildcard_matches("a*m*s", "acme/widgets"));
+        assert!(wildcard_matches("exact", "exact"));
+        assert!(!wildcard_matches("exact", "exactly"));
+        assert!(!wildcard_matches("ab*ba", "aba"));
+        assert!(value_matches(&json!("*"), &json!(3)));
+        assert!(value_matches(&json!(3), &json!(3)));
+        assert!(!value_matches(&json!("3"), &json!(3)));
+    }
+
+    #[tokio::test]
+    async fn test_fixtures_then_synthesis_then_placeholder() {
+        let fixtures: Vec<ToolFixture> = serde_yaml::from_str(
+            r#"
+- tool: create_issue
+  arguments: { repo: "acme/*" }
+  result: { number: 42 }
+- tool: create_issue
+  result: permission denied
+  success: false
+"#,
+        )
+        .unwrap();
+        let metadata = ToolMetadata {
+            name: "search".to_string(),
+            description: "Searches the web".to_string(),
+            input_schema: json!({ "type": "object" }),
+        };
+
+        let mut simulator = ToolSimulator::new(fixtures.clone(), Some(Arc::new(CannedLLM)));
+        let hit = simulator
+            .simulate(0, &call("create_issue", json!({ "repo": "acme/widgets", "title": "Bug" })), None)
+            .await;
+        assert_eq!((hit.source, hit.result.as_str(), hit.success), (SimulationSource::Fixture, "{\"number\":42}", true));
+
+        let fallback = simulator
+            .simulate(1, &call("create_issue", json!({ "repo": "other/repo" })), None)
+            .await;
+        assert_eq!((fallback.result.as_str(), fallback.success), ("permission denied", false));
+
+        let synthesized = simulator.simulate(2, &call("search", json!({ "q": "rust" })), Some(&metadata)).await;
+        assert_eq!(synthesized.source, SimulationSource::Synthesized);
+        assert_eq!(synthesized.result, "[{\"title\": \"Plausible result\"}]");
+        assert_eq!(simulator.calls().len(), 3);
+
+        let mut offline = ToolSimulator::new(Vec::new(), None);
+        let placeholder = offline.simulate(0, &call("search", json!({})), None).await;
+        assert_eq!(placeholder.source, SimulationSource::Placeholder);
+        assert_eq!(placeholder.result, "[dry run] 'search' was not executed");
+    }
+
+    #[tokio::test]
+    async fn test_report_is_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DryRunConfig {
+            enabled: true,
+            fixtures: None,
+            synthesize: false,
+            report: dir.path().join("report.json"),
+        };
+        let mut simulator = ToolSimulator::from_config(&config, Arc::new(CannedLLM)).unwrap();
+        simulator.simulate(3, &call("delete_repo", json!({ "repo": "acme/widgets" })), None).await;
+        simulator.write_report().unwrap();
+
+        let report: Value = serde_json::from_str(&std::fs::read_to_string(&config.report).unwrap()).unwrap();
+        assert_eq!(report["calls"][0]["step"], 3);
+        assert_eq!(report["calls"][0]["tool"], "delete_repo");
+        assert_eq!(report["calls"][0]["source"], "placeholder");
+    }
+}
@@ -7,11 +7,12 @@
 //! the agent framework against tool evolution and enables community contributions.
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::errors::AgentError;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MCPToolInfo {
     pub name: String,
     pub description: String,
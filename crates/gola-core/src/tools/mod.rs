@@ -125,6 +125,12 @@ impl ToolFactory {
     }
 }
 
+// Validation of model-produced arguments against tool input schemas
+pub mod arguments;
+
+// Simulated execution for dry runs
+pub mod dry_run;
+
 // MCP (Model Context Protocol) client trait and implementations
 pub mod mcp_client;
 pub mod rmcp_client;
@@ -10,7 +10,7 @@ use std::sync::Arc;
 
 use crate::errors::AgentError;
 use crate::llm::ToolMetadata;
-use crate::rag::Rag;
+use crate::rag::{MetadataFilter, Rag};
 use crate::tools::Tool;
 pub struct RagSearchTool {
     rag_system: Arc<dyn Rag>,
@@ -34,7 +34,8 @@ impl RagSearchTool {
                         "description": "Maximum number of results to return (default: 5)",
                         "minimum": 1,
                         "maximum": 20
-                    }
+                    },
+                    "filter": MetadataFilter::json_schema()
                 },
                 "required": ["query"]
             }),
@@ -81,9 +82,14 @@ impl Tool for RagSearchTool {
             .and_then(|v| v.as_u64())
             .map(|v| v as usize);
 
-        log::info!("RAG search query: '{}', top_k: {:?}", query, top_k);
+        let filter = MetadataFilter::from_arguments(&arguments).map_err(|e| AgentError::ToolError {
+            tool_name: "rag_search".to_string(),
+            message: e.to_string(),
+        })?;
+
+        log::info!("RAG search query: '{}', top_k: {:?}, filter: {:?}", query, top_k, filter);
 
-        let results = self.rag_system.retrieve(query, top_k).await
+        let results = self.rag_system.retrieve_filtered(query, top_k, &filter).await
             .map_err(|e| AgentError::ToolError {
                 tool_name: "rag_search".to_string(),
                 message: format!("RAG search failed: {}", e),
@@ -171,7 +177,8 @@ impl RagSearchToolBuilder {
                         "description": format!("Maximum number of results to return (default: 5, max: {})", self.max_results),
                         "minimum": 1,
                         "maximum": self.max_results
-                    }
+                    },
+                    "filter": MetadataFilter::json_schema()
                 },
                 "required": ["query"]
             }),
@@ -290,6 +297,20 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_rag_search_tool_filter() {
+        let tool = RagSearchTool::new(Arc::new(DummyRag::new()));
+        assert!(tool.metadata().input_schema["properties"]["filter"].is_object());
+
+        // DummyRag answers from "dummy_source", which the filter excludes
+        let args = json!({ "query": "tokens", "filter": { "source": { "prefix": "docs/" } } });
+        let result = tool.execute(args).await.unwrap();
+        assert!(result.contains("No relevant documents found"));
+
+        let args = json!({ "query": "tokens", "filter": { "source": 42 } });
+        assert!(matches!(tool.execute(args).await, Err(AgentError::ToolError { .. })));
+    }
+
     #[tokio::test]
     async fn test_rag_search_tool_no_results() {
         let rag_system = Arc::new(DummyRag::new());
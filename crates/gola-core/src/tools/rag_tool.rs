@@ -9,7 +9,7 @@
 
 use crate::errors::AgentError;
 use crate::llm::ToolMetadata;
-use crate::rag::{Rag, RagDocument};
+use crate::rag::{MetadataFilter, Rag, RagDocument};
 use crate::tools::Tool;
 use async_trait::async_trait;
 use serde_json::{json, Value};
@@ -46,7 +46,8 @@ impl Tool for RagSearchTool {
                         "description": "Maximum number of documents to retrieve (default: 5)",
                         "minimum": 1,
                         "maximum": 20
-                    }
+                    },
+                    "filter": MetadataFilter::json_schema()
                 },
                 "required": ["query"]
             }),
@@ -67,9 +68,14 @@ impl Tool for RagSearchTool {
             .and_then(|v| v.as_u64())
             .map(|v| v as usize);
 
-        log::info!("RAG search: '{}' (top_k: {:?})", query, top_k);
+        let filter = MetadataFilter::from_arguments(&arguments).map_err(|e| AgentError::ToolError {
+            tool_name: "rag_search".to_string(),
+            message: e.to_string(),
+        })?;
+
+        log::info!("RAG search: '{}' (top_k: {:?}, filter: {:?})", query, top_k, filter);
 
-        let context = self.rag.retrieve(query, top_k).await?;
+        let context = self.rag.retrieve_filtered(query, top_k, &filter).await?;
         Ok(context.format_for_llm())
     }
 }
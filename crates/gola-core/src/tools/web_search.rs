@@ -30,10 +30,10 @@ pub enum SearchEngine {
 impl WebSearchTool {
     pub fn new() -> Self {
         Self {
-            client: Client::builder()
+            client: crate::http::builder()
                 .timeout(Duration::from_secs(30))
                 .build()
-                .unwrap_or_else(|_| Client::new()),
+                .unwrap_or_else(|_| crate::http::client()),
             api_key: None,
             search_engine: SearchEngine::DuckDuckGo,
         }
@@ -41,10 +41,10 @@ impl WebSearchTool {
 
     pub fn with_tavily_api_key(api_key: String) -> Self {
         Self {
-            client: Client::builder()
+            client: crate::http::builder()
                 .timeout(Duration::from_secs(30))
                 .build()
-                .unwrap_or_else(|_| Client::new()),
+                .unwrap_or_else(|_| crate::http::client()),
             api_key: Some(api_key),
             search_engine: SearchEngine::Tavily,
         }
@@ -52,10 +52,10 @@ impl WebSearchTool {
 
     pub fn with_serper_api_key(api_key: String) -> Self {
         Self {
-            client: Client::builder()
+            client: crate::http::builder()
                 .timeout(Duration::from_secs(30))
                 .build()
-                .unwrap_or_else(|_| Client::new()),
+                .unwrap_or_else(|_| crate::http::client()),
             api_key: Some(api_key),
             search_engine: SearchEngine::Serper,
         }
@@ -1,6 +1,8 @@
 // src/trace.rs
 
 use serde::{Deserialize, Serialize};
+use crate::context_builder::ContextReport;
+use crate::review::ReviewRecord;
 use crate::core_types::{ToolCall, Observation};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +11,15 @@ pub struct AgentStep {
     pub thought: Option<String>,
     pub tool_calls: Option<Vec<ToolCall>>,
     pub tool_results: Option<Vec<Observation>>,
+    /// Readable model reasoning for this step, if the provider returned any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    /// Token budget use for this step's request, including anything left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<ContextReport>,
+    /// Reviewer verdict on the final answer proposed in this step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review: Option<ReviewRecord>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
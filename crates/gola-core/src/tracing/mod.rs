@@ -81,6 +81,7 @@ Tool Result:
             content: prompt,
             tool_call_id: None,
             tool_calls: None,
+            reasoning: None,
         }];
 
         match llm_client.generate(messages, None).await {
@@ -44,6 +44,7 @@ mod tests {
                 tool_calls: None,
                 finish_reason: None,
                 usage: None,
+                reasoning: None,
             }),
         });
 
@@ -53,6 +54,9 @@ mod tests {
             thought: None,
             tool_calls: None,
             tool_results: None,
+            reasoning: None,
+            context: None,
+            review: None,
         };
 
         if let Some(handle) = handler.on_step_complete(&step) {
@@ -79,6 +83,7 @@ mod tests {
                 tool_calls: None,
                 finish_reason: None,
                 usage: None,
+                reasoning: None,
             }),
         });
 
@@ -96,6 +101,9 @@ mod tests {
                 content: "Success".to_string(),
                 success: true,
             }]),
+            reasoning: None,
+            context: None,
+            review: None,
         };
 
         if let Some(handle) = handler.on_step_complete(&step) {
@@ -141,6 +149,9 @@ mod tests {
                 content: "Success".to_string(),
                 success: true,
             }]),
+            reasoning: None,
+            context: None,
+            review: None,
         };
 
         if let Some(handle) = handler.on_step_complete(&step) {
@@ -170,6 +181,7 @@ mod tests {
                 tool_calls: None,
                 finish_reason: None,
                 usage: None,
+                reasoning: None,
             }),
         });
 
@@ -179,6 +191,9 @@ mod tests {
             thought: Some("Just a thought, no tools".to_string()),
             tool_calls: None,
             tool_results: None,
+            reasoning: None,
+            context: None,
+            review: None,
         };
 
         if let Some(handle) = handler.on_step_complete(&step) {
@@ -209,6 +224,7 @@ mod tests {
                 tool_calls: None,
                 finish_reason: None,
                 usage: None,
+                reasoning: None,
             }),
         });
 
@@ -231,6 +247,9 @@ mod tests {
                         content: "Success".to_string(),
                         success: true,
                     }]),
+                    reasoning: None,
+                    context: None,
+                    review: None,
                 };
                 let mut handler_guard = handler_clone.lock().await;
                 if let Some(handle) = handler_guard.on_step_complete(&step) {
@@ -264,6 +283,7 @@ mod tests {
                 tool_calls: None,
                 finish_reason: None,
                 usage: None,
+                reasoning: None,
             }),
         });
 
@@ -273,6 +293,9 @@ mod tests {
             thought: Some("This is a conversational response.".to_string()),
             tool_calls: None,
             tool_results: None,
+            reasoning: None,
+            context: None,
+            review: None,
         };
 
         if let Some(handle) = handler.on_step_complete(&step) {
@@ -48,6 +48,7 @@ impl LLM for MockLLM {
                         tool_calls: None,
                         finish_reason: None,
                         usage: None,
+                        reasoning: None,
                     });
                 } else {
                     return Ok(LLMResponse {
@@ -55,6 +56,7 @@ impl LLM for MockLLM {
                         tool_calls: None,
                         finish_reason: None,
                         usage: None,
+                        reasoning: None,
                     });
                 }
             }
@@ -67,6 +69,7 @@ impl LLM for MockLLM {
                 tool_calls: None,
                 finish_reason: None,
                 usage: None,
+                reasoning: None,
             })
         } else {
             Ok(LLMResponse {
@@ -74,6 +77,7 @@ impl LLM for MockLLM {
                 tool_calls: None,
                 finish_reason: None,
                 usage: None,
+                reasoning: None,
             })
         }
     }
@@ -168,6 +172,7 @@ async fn test_summary_generation() {
         content: "hello, this is the first message".to_string(),
         tool_call_id: None,
         tool_calls: None,
+        reasoning: None,
     };
     memory.add_message(message).await.unwrap();
 
@@ -186,6 +191,7 @@ async fn test_summary_evolution() {
         content: "hello, this is the first message".to_string(),
         tool_call_id: None,
         tool_calls: None,
+        reasoning: None,
     };
     memory.add_message(message1).await.unwrap();
 
@@ -194,6 +200,7 @@ async fn test_summary_evolution() {
         content: "this is the second message".to_string(),
         tool_call_id: None,
         tool_calls: None,
+        reasoning: None,
     };
     memory.add_message(message2).await.unwrap();
 
@@ -221,6 +228,7 @@ async fn test_clear_memory() {
         content: "hello, this is the first message".to_string(),
         tool_call_id: None,
         tool_calls: None,
+        reasoning: None,
     };
     memory.add_message(message).await.unwrap();
     memory.clear();
@@ -240,6 +248,7 @@ async fn test_complex_summary_evolution() {
         content: "hello, this is the first message".to_string(),
         tool_call_id: None,
         tool_calls: None,
+        reasoning: None,
     };
     memory.add_message(message1).await.unwrap();
 
@@ -248,6 +257,7 @@ async fn test_complex_summary_evolution() {
         content: "this is the second message".to_string(),
         tool_call_id: None,
         tool_calls: None,
+        reasoning: None,
     };
     memory.add_message(message2).await.unwrap();
 
@@ -256,6 +266,7 @@ async fn test_complex_summary_evolution() {
         content: "this is the third message".to_string(),
         tool_call_id: None,
         tool_calls: None,
+        reasoning: None,
     };
     memory.add_message(message3).await.unwrap();
 
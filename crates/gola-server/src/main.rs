@@ -110,6 +110,7 @@ async fn run_server(config: String, bind_addr: String, no_tool_auth: bool) -> Re
         gola_config,
         local_runtimes: true,
         non_interactive: false,
+        cassette: None,
     };
     let agent_handler = AgentFactory::create_from_config(factory_config).await?;
     log::info!("GolaAgentHandler created.");
@@ -29,6 +29,7 @@ use crate::domain::models::EditorName;
 use crate::domain::models::Event;
 use crate::domain::models::Loading;
 use crate::domain::models::Message;
+use crate::domain::models::MessageType;
 use crate::domain::models::SlashCommand;
 use crate::domain::models::TextArea;
 use crate::domain::services::events::EventsService;
@@ -152,6 +153,13 @@ pub async fn start_loop<B: ratatui::backend::Backend>(
                 app_state.add_message(msg);
                 app_state.waiting_for_backend = false;
             }
+            Event::AgentReasoning(text) => {
+                app_state.add_message(Message::new_with_type(
+                    Author::Gola,
+                    MessageType::Reasoning,
+                    &text,
+                ));
+            }
             Event::AgentPromptResponse(msg) => {
                 app_state.handle_agent_response(msg.clone());
                 if msg.done {
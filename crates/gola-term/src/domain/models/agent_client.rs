@@ -16,6 +16,7 @@ pub trait AgentClient: Send + Sync {
         event_tx: &mpsc::UnboundedSender<Event>,
     ) -> Result<()>;
     async fn clear_memory(&self) -> Result<()>;
+    async fn list_models(&self) -> Result<Vec<String>>;
 }
 
 pub type AgentClientBox = Box<dyn AgentClient>;
@@ -6,6 +6,7 @@ use tui_textarea::Input;
 pub enum Event {
     AgentMessage(Message),
     AgentPromptResponse(AgentResponse),
+    AgentReasoning(String),
     KeyboardCharInput(Input),
     KeyboardCTRLC,
     KeyboardCTRLO,
@@ -11,6 +11,7 @@ pub enum MessageType {
     #[default]
     Normal,
     Error,
+    Reasoning,
 }
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Default, Debug)]
@@ -19,6 +20,9 @@ pub struct Message {
     pub text: String,
     pub message_type: MessageType,
     pub code_blocks: Vec<String>,
+    /// Whether a reasoning message is shown in full rather than as its first line
+    #[serde(default)]
+    pub expanded: bool,
 }
 
 impl Message {
@@ -48,7 +52,31 @@ impl Message {
         self.text += &text.replace('\t', "  ");
     }
 
+    /// Text shown in the chat window. Reasoning is collapsed to its first line
+    /// unless it has been expanded.
+    pub fn display_text(&self) -> String {
+        if self.message_type != MessageType::Reasoning {
+            return self.text.clone();
+        }
+        if self.expanded {
+            return format!("▾ Reasoning:\n{}", self.text.trim());
+        }
+
+        let mut lines = self
+            .text
+            .lines()
+            .map(|line| return line.trim())
+            .filter(|line| return !line.is_empty());
+        let first = lines.next().unwrap_or_default();
+        let ellipsis = if lines.next().is_some() { "…" } else { "" };
+        return format!("▸ Reasoning: {first}{ellipsis}");
+    }
+
     pub fn codeblocks(&self) -> Vec<String> {
+        if self.message_type == MessageType::Reasoning {
+            return vec![];
+        }
+
         let mut codeblocks: Vec<String> = vec![];
         let mut current_codeblock: Vec<&str> = vec![];
         let mut in_codeblock = false;
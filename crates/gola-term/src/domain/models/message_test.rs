@@ -60,6 +60,37 @@ fn it_executes_append_with_tabs() {
     assert_eq!(msg.text, "Hi there!  It's me!");
 }
 
+#[test]
+fn it_collapses_reasoning_display_text() {
+    let msg = Message::new_with_type(
+        Author::Gola,
+        MessageType::Reasoning,
+        "\nThe user wants a sum.\nAdding 2 and 2 gives 4.",
+    );
+    assert_eq!(msg.display_text(), "▸ Reasoning: The user wants a sum.…");
+    assert_eq!(msg.text, "\nThe user wants a sum.\nAdding 2 and 2 gives 4.");
+}
+
+#[test]
+fn it_expands_reasoning_display_text() {
+    let mut msg = Message::new_with_type(
+        Author::Gola,
+        MessageType::Reasoning,
+        "\nThe user wants a sum.\nAdding 2 and 2 gives 4.",
+    );
+    msg.expanded = true;
+    assert_eq!(
+        msg.display_text(),
+        "▾ Reasoning:\nThe user wants a sum.\nAdding 2 and 2 gives 4."
+    );
+}
+
+#[test]
+fn it_skips_codeblocks_in_reasoning() {
+    let msg = Message::new_with_type(Author::Gola, MessageType::Reasoning, codeblock_fixture());
+    assert!(msg.codeblocks().is_empty());
+}
+
 #[test]
 fn it_executes_codeblocks() {
     let msg = Message::new(Author::Gola, codeblock_fixture());
@@ -31,6 +31,7 @@ impl SlashCommand {
             || cmd.is_help()
             || cmd.is_clear()
             || cmd.is_about()
+            || cmd.is_toggle_reasoning()
         {
             return Some(cmd);
         }
@@ -77,4 +78,8 @@ impl SlashCommand {
     pub fn is_about(&self) -> bool {
         return ["/about"].contains(&self.command.as_str());
     }
+
+    pub fn is_toggle_reasoning(&self) -> bool {
+        return ["/reasoning"].contains(&self.command.as_str());
+    }
 }
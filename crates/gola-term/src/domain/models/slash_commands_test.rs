@@ -134,6 +134,12 @@ fn it_is_not_help() {
     assert!(!cmd.is_help());
 }
 
+#[test]
+fn it_is_toggle_reasoning() {
+    let cmd = SlashCommand::parse("/reasoning").unwrap();
+    assert!(cmd.is_toggle_reasoning());
+}
+
 #[test]
 fn it_is_short_copy_chat() {
     let cmd = SlashCommand::parse("/c").unwrap();
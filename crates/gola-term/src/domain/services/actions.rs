@@ -25,6 +25,8 @@ COMMANDS:
 - /replace (/r) [CODE_BLOCK_NUMBER?] - Replaces selections with code blocks in an editor. See Code Actions for more details.
 - /copy (/c) [CODE_BLOCK_NUMBER?] - Copies the entire chat history to your clipboard. When a `CODE_BLOCK_NUMBER` is used, only the specified copy blocks are copied to clipboard. See Code Actions for more details.
 - /clear - Clears the current session's memory.
+- /modellist (/ml) - Lists the models the agent's LLM backend can serve.
+- /reasoning - Expands or collapses the model's reasoning.
 - /quit /exit (/q) - Exit Gola.
 - /help (/h) - Provides this help menu.
 - /about - Displays information about gola-term.
@@ -151,6 +153,27 @@ async fn send_prompt_to_agent(
     agent_client.send_prompt(prompt, event_tx).await
 }
 
+async fn list_models(
+    agent_client: &AgentClientBox,
+    event_tx: &mpsc::UnboundedSender<Event>,
+) -> Result<()> {
+    let models = agent_client.list_models().await?;
+    let text = if models.is_empty() {
+        "The agent did not report any models.".to_string()
+    } else {
+        let lines = models
+            .iter()
+            .map(|model| format!("- {model}"))
+            .collect::<Vec<String>>()
+            .join("\n");
+        format!("Available models:\n{lines}")
+    };
+
+    event_tx.send(Event::AgentMessage(Message::new(Author::Gola, &text)))?;
+
+    return Ok(());
+}
+
 fn help(event_tx: &mpsc::UnboundedSender<Event>) -> Result<()> {
     event_tx.send(Event::AgentMessage(Message::new(
         Author::Gola,
@@ -235,6 +258,12 @@ impl ActionsService {
                                 action_tx.send(Action::AgentClearMemory)?;
                                 continue;
                             }
+                            if command.is_model_list() {
+                                if let Err(err) = list_models(&agent_client_arc, &event_tx).await {
+                                    worker_error(err, &event_tx)?;
+                                }
+                                continue;
+                            }
                         }
 
                         let client_worker = agent_client_arc.clone();
@@ -262,6 +291,7 @@ mod tests {
 
     struct MockAgentClient {
         prompt_fn: Box<dyn Fn(AgentPrompt) -> Result<()> + Send + Sync>,
+        models: Vec<String>,
     }
 
     #[async_trait]
@@ -285,6 +315,10 @@ mod tests {
         async fn clear_memory(&self) -> Result<()> {
             Ok(())
         }
+
+        async fn list_models(&self) -> Result<Vec<String>> {
+            return Ok(self.models.clone());
+        }
     }
 
     #[tokio::test]
@@ -299,6 +333,7 @@ mod tests {
                 completion_tx.send(prompt.text).unwrap();
                 Ok(())
             }),
+            models: vec![],
         };
 
         tokio::spawn(async move {
@@ -310,4 +345,45 @@ mod tests {
         let received_prompt = completion_rx.recv().await.unwrap();
         assert_eq!(received_prompt, GOLA_CONNECT_MESSAGE);
     }
+
+    #[tokio::test]
+    async fn test_model_list_reports_agent_models() {
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let (action_tx, mut action_rx) = mpsc::unbounded_channel::<Action>();
+
+        let agent_client = MockAgentClient {
+            prompt_fn: Box::new(|_| return Ok(())),
+            models: vec!["llama3.2:latest".to_string(), "qwen3:8b".to_string()],
+        };
+
+        let service_action_tx = action_tx.clone();
+        tokio::spawn(async move {
+            ActionsService::start(
+                Box::new(agent_client),
+                service_action_tx,
+                event_tx,
+                &mut action_rx,
+            )
+            .await
+            .unwrap();
+        });
+
+        action_tx
+            .send(Action::AgentRequest(AgentPrompt {
+                text: "/modellist".to_string(),
+                ..Default::default()
+            }))
+            .unwrap();
+
+        let event = event_rx.recv().await.unwrap();
+        match event {
+            Event::AgentMessage(message) => {
+                assert_eq!(
+                    message.text,
+                    "Available models:\n- llama3.2:latest\n- qwen3:8b"
+                );
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
 }
@@ -157,7 +157,9 @@ impl<'a> AppState<'a> {
 
     pub fn handle_agent_response(&mut self, msg: AgentResponse) {
         if let Some(last_message) = self.messages.last_mut() {
-            if last_message.author != Author::User {
+            if last_message.author != Author::User
+                && last_message.message_type() != MessageType::Reasoning
+            {
                 last_message.append(&msg.text);
             } else {
                 self.messages.push(Message::new(msg.author, &msg.text));
@@ -252,11 +254,32 @@ impl<'a> AppState<'a> {
                 tx.send(Action::AgentClearMemory)?;
                 self.waiting_for_backend = true;
             }
+
+            if command.is_toggle_reasoning() {
+                should_continue = true;
+                self.toggle_reasoning();
+            }
         }
 
         return Ok((should_break, should_continue));
     }
 
+    /// Expands every reasoning message if any is collapsed, otherwise collapses them
+    pub fn toggle_reasoning(&mut self) {
+        let expand = self
+            .messages
+            .iter()
+            .any(|message| return message.message_type() == MessageType::Reasoning && !message.expanded);
+        for message in self.messages.iter_mut() {
+            if message.message_type() == MessageType::Reasoning {
+                message.expanded = expand;
+            }
+        }
+
+        self.bubble_list.clear_cache();
+        self.sync_dependants();
+    }
+
     pub fn set_rect(&mut self, rect: Rect) {
         self.last_known_width = rect.width.into();
         self.last_known_height = rect.height.into();
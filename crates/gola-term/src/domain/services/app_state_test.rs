@@ -45,6 +45,9 @@ impl AgentClient for MockAgentClient {
     async fn clear_memory(&self) -> Result<()> {
         return Ok(());
     }
+    async fn list_models(&self) -> Result<Vec<String>> {
+        return Ok(vec![]);
+    }
 }
 
 impl Default for AppState<'static> {
@@ -190,6 +193,27 @@ mod handle_slash_commands {
         return Ok(());
     }
 
+    #[test]
+    fn it_toggles_reasoning() -> Result<()> {
+        let (tx, _rx) = mpsc::unbounded_channel::<Action>();
+        let mut app_state = AppState::default();
+        app_state.add_message(Message::new_with_type(
+            Author::Model,
+            MessageType::Reasoning,
+            "Thinking it over.\nStill thinking.",
+        ));
+
+        let (should_break, should_continue) = app_state.handle_slash_commands("/reasoning", &tx)?;
+        assert!(!should_break);
+        assert!(should_continue);
+        assert!(app_state.messages[0].expanded);
+
+        app_state.handle_slash_commands("/reasoning", &tx)?;
+        assert!(!app_state.messages[0].expanded);
+
+        return Ok(());
+    }
+
     #[test]
     fn it_returns_error_message_on_invalid_codeblock() -> Result<()> {
         let (tx, _rx) = mpsc::unbounded_channel::<Action>();
@@ -258,6 +282,31 @@ mod handle_agent_response {
         );
     }
 
+    #[test]
+    fn it_starts_new_message_after_reasoning() {
+        let mut app_state = AppState::default();
+        app_state
+            .messages
+            .push(Message::new(Author::User, "Do something for me!"));
+        app_state.messages.push(Message::new_with_type(
+            Author::Gola,
+            MessageType::Reasoning,
+            "Thinking it over",
+        ));
+        let agent_response = AgentResponse {
+            author: Author::Gola,
+            text: "All done!".to_string(),
+            done: false,
+            context: None,
+        };
+        app_state.handle_agent_response(agent_response);
+
+        assert_eq!(app_state.messages.len(), 3);
+        assert_eq!(app_state.messages[1].text, "Thinking it over");
+        assert_eq!(app_state.messages[2].text, "All done!");
+        assert_eq!(app_state.messages[2].message_type(), MessageType::Normal);
+    }
+
     #[test]
     fn it_handles_icebreaker_message() {
         let mut app_state = AppState::default();
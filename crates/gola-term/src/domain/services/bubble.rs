@@ -85,8 +85,9 @@ impl<'a> Bubble<'a> {
         let mut lines: Vec<Line> = vec![];
 
         let max_line_length = self.get_max_line_length();
+        let text = self.message.display_text();
 
-        for line in self.message.text.lines() {
+        for line in text.lines() {
             let mut spans = vec![];
 
             if line.trim().starts_with("```") {
@@ -207,7 +208,7 @@ impl<'a> Bubble<'a> {
 
         let mut max_line_length = self
             .message
-            .text
+            .display_text()
             .lines()
             .map(|line| {
                 return line.len();
@@ -275,6 +276,11 @@ impl<'a> Bubble<'a> {
                 text,
                 Style::default().fg(Color::Red),
             );
+        } else if self.message.message_type() == MessageType::Reasoning {
+            return Span::styled(
+                text,
+                Style::default().fg(Color::DarkGray),
+            );
         } else if self.message.author == Author::Gola {
             return Span::styled(
                 text,
@@ -92,6 +92,11 @@ impl<'a> BubbleList<'a> {
             .sum();
     }
 
+    /// Forces every message to be rendered again on the next update
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
     pub fn len(&self) -> usize {
         return self.lines_len;
     }
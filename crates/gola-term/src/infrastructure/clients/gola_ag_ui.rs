@@ -124,6 +124,7 @@ impl AgentClient for GolaAgUI {
         let mut current_event_name = "message".to_string();
         let mut current_event_data = String::new();
         let mut assistant_message = String::new();
+        let mut reasoning = String::new();
         let mut updated_messages = request_payload.messages.clone();
 
         while let Ok(line) = lines_reader.next_line().await {
@@ -137,7 +138,7 @@ impl AgentClient for GolaAgUI {
                 if !current_event_data.is_empty() {
                     if let Ok(event) = GolaEvent::from_sse(&current_event_name, &current_event_data)
                     {
-                        self.handle_gola_event(event, &mut assistant_message, tx)
+                        self.handle_gola_event(event, &mut assistant_message, &mut reasoning, tx)
                             .await?;
                     }
                 }
@@ -176,6 +177,22 @@ impl AgentClient for GolaAgUI {
         Ok(())
     }
 
+    async fn list_models(&self) -> Result<Vec<String>> {
+        let models_url = format!("{}/models", self.url);
+        let response = reqwest::Client::new()
+            .get(&models_url)
+            .timeout(Duration::from_millis(self.timeout.parse::<u64>()?))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            tracing::error!(status = response.status().as_u16(), "GolaAgUI model listing failed");
+            bail!("GolaAgUI model listing failed");
+        }
+
+        return Ok(response.json::<Vec<String>>().await?);
+    }
+
     async fn clear_memory(&self) -> Result<()> {
         let clear_url = format!("{}/memory/clear", self.url);
         let res = reqwest::Client::new()
@@ -210,9 +227,19 @@ impl GolaAgUI {
         &self,
         event: GolaEvent,
         assistant_message: &mut String,
+        reasoning: &mut String,
         tx: &mpsc::UnboundedSender<Event>,
     ) -> Result<()> {
         match event {
+            GolaEvent::ThinkingTextMessageContent(thinking_event) => {
+                reasoning.push_str(&thinking_event.delta);
+            }
+            GolaEvent::ThinkingTextMessageEnd(_) => {
+                if !reasoning.trim().is_empty() {
+                    tx.send(Event::AgentReasoning(reasoning.clone()))?;
+                }
+                reasoning.clear();
+            }
             GolaEvent::TextMessageContent(msg_event) => {
                 assistant_message.push_str(&msg_event.delta);
                 let response = AgentResponse {
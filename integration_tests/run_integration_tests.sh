@@ -89,6 +89,13 @@ else
   else
     DOCUMENTS_MOUNT=""
   fi
+
+  # Mount recorded cassettes for offline replay scenarios
+  if [ -d "$SCENARIO_DIR/cassettes" ]; then
+    CASSETTES_MOUNT="-v $(realpath $SCENARIO_DIR)/cassettes:/test/cassettes:ro"
+  else
+    CASSETTES_MOUNT=""
+  fi
   
   docker run --rm --platform linux/amd64 \
     -e OPENAI_API_KEY \
@@ -98,6 +105,7 @@ else
     -v "$(realpath $SCENARIO_DIR)/$GOLA_CONFIG:/test/gola.yaml:ro" \
     -v "$(realpath $SCENARIO_DIR)/prompts:/test/prompts:ro" \
     $DOCUMENTS_MOUNT \
+    $CASSETTES_MOUNT \
     $DOCKER_IMAGE_NAME \
     "/usr/local/bin/harness" "/test/scenario.yaml"
 fi